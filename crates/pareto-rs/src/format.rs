@@ -14,12 +14,16 @@ pub fn round4(v: f64) -> f64 {
 mod tests {
     use super::*;
 
+    #[allow(clippy::approx_constant)]
+    const PI_ROUNDED_2: f64 = 3.14;
+    #[allow(clippy::approx_constant)]
+    const PI_ROUNDED_4: f64 = 3.1416;
     const TOL_2: f64 = 0.001;
     const TOL_4: f64 = 0.00001;
 
     #[test]
     fn test_round2_basic() {
-        assert!((round2(std::f64::consts::PI) - 3.14).abs() < TOL_2);
+        assert!((round2(std::f64::consts::PI) - PI_ROUNDED_2).abs() < TOL_2);
         assert_eq!(round2(1.234), 1.23);
         assert_eq!(round2(1.999), 2.00);
     }
@@ -31,7 +35,7 @@ mod tests {
 
     #[test]
     fn test_round4_basic() {
-        assert!((round4(std::f64::consts::PI) - 3.1416).abs() < TOL_4);
+        assert!((round4(std::f64::consts::PI) - PI_ROUNDED_4).abs() < TOL_4);
         assert_eq!(round4(1.23456), 1.2346);
     }
 
@@ -2,6 +2,8 @@
 //!
 //! No I/O, no CLI, no external API calls. Just pure business logic.
 
+#![allow(non_snake_case)]
+
 pub mod models;
 pub mod cost;
 pub mod pricing;
@@ -0,0 +1,577 @@
+// Subscription allocation: prorates provider seats per calendar month (and over the part of it a
+// report's range covers) and spreads the result over a reference population of events.
+// Allocation rates are fixed by that population, so filtered views (one model, one day) carry
+// exactly the share they carry in the unfiltered report.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::cli::AllocationStrategy;
+use crate::cost::{calc_variable_cost, event_pricing};
+use crate::models::*;
+use crate::money::*;
+use crate::period::TimeRange;
+use crate::utils::resolve_provider_alias;
+
+type Month = (i32, u32);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum AllocScope {
+    Month(Month),
+    Day(NaiveDate),
+    Session(Month, String),
+}
+
+// The unit subscription shares are split over: events of one provider, allocation scope, model,
+// service tier and day. A cell's share is converted to the report currency before it is split
+// between its events, so a view priced event by event and the same view aggregated while
+// streaming are charged the same.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AllocCell {
+    pub provider: String,
+    scope: AllocScope,
+    pub model: String,
+    pub service_tier: String,
+    pub day: NaiveDate,
+}
+
+impl AllocCell {
+    pub fn new(strategy: AllocationStrategy, evt: &UsageEvent) -> Self {
+        Self {
+            provider: evt.provider.clone(),
+            scope: scope_for(strategy, evt),
+            model: evt.model.clone(),
+            service_tier: evt.service_tier_name().to_string(),
+            day: evt.timestamp.date_naive(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SubscriptionAllocator {
+    strategies: HashMap<String, AllocationStrategy>,
+    // Micro-units charged to each provider and scope, with the reference population's total
+    // weight (tokens, or micro-units of variable cost) in that scope.
+    shares: HashMap<(String, AllocScope), (Micros, u128)>,
+    idle: BTreeMap<String, Micros>,
+}
+
+impl SubscriptionAllocator {
+    // `events` is the reference population, normally every event in the reporting month
+    // before provider/model filters are applied.
+    pub fn build(
+        events: &[UsageEvent],
+        pricing: &PricingBook,
+        strategy_override: Option<AllocationStrategy>,
+    ) -> Self {
+        Self::build_over(events, pricing, strategy_override, &TimeRange::default())
+    }
+
+    // Like `build`, charging each month only for the part of it inside `range`.
+    pub fn build_over(
+        events: &[UsageEvent],
+        pricing: &PricingBook,
+        strategy_override: Option<AllocationStrategy>,
+        range: &TimeRange,
+    ) -> Self {
+        let mut cells: BTreeMap<AllocCell, u128> = BTreeMap::new();
+        for evt in events {
+            let Some((provider, rate, multiplier)) = event_pricing(evt, pricing) else {
+                continue;
+            };
+            let strategy = resolve_strategy(provider, strategy_override);
+            let variable_cost = scale_micros(calc_variable_cost(&evt.usage, rate), multiplier);
+            *cells.entry(AllocCell::new(strategy, evt)).or_default() +=
+                event_weight(strategy, evt, variable_cost);
+        }
+        Self::from_cells(&cells, pricing, strategy_override, range)
+    }
+
+    // Like `build`, from the reference population's weight per cell; cells must be priced. Each
+    // month is charged for the part of it inside `range`.
+    pub fn from_cells(
+        cells: &BTreeMap<AllocCell, u128>,
+        pricing: &PricingBook,
+        strategy_override: Option<AllocationStrategy>,
+        range: &TimeRange,
+    ) -> Self {
+        let mut allocator = Self::default();
+        let mut weights: HashMap<(String, AllocScope), u128> = HashMap::new();
+        let mut months: BTreeSet<Month> = BTreeSet::new();
+        let mut providers: BTreeMap<String, &ProviderPricing> = BTreeMap::new();
+
+        for (cell, weight) in cells {
+            let Some(provider) = pricing
+                .providers
+                .get(&resolve_provider_alias(&cell.provider, pricing))
+            else {
+                continue;
+            };
+            allocator.strategies.insert(
+                cell.provider.clone(),
+                resolve_strategy(provider, strategy_override),
+            );
+            providers.insert(cell.provider.clone(), provider);
+            months.insert(month_of(cell.day));
+            *weights
+                .entry((cell.provider.clone(), cell.scope.clone()))
+                .or_default() += weight;
+        }
+
+        for (provider_name, provider) in &providers {
+            let strategy = allocator.strategies[provider_name];
+            for &month in &months {
+                let prorated = to_micros(prorated_subscription_over(provider, month, range));
+                if prorated <= 0 {
+                    continue;
+                }
+                let shares =
+                    scope_shares(strategy, provider, month, range, &weights, provider_name);
+                let mut allocated = 0;
+                for (scope, share) in shares {
+                    let key = (provider_name.clone(), scope);
+                    let weight = weights.get(&key).copied().unwrap_or(0);
+                    if weight > 0 && share > 0 {
+                        allocator.shares.insert(key, (share, weight));
+                        allocated += share;
+                    }
+                }
+                let idle = prorated - allocated;
+                if idle > 0 {
+                    *allocator.idle.entry(provider_name.clone()).or_default() += idle;
+                }
+            }
+        }
+        allocator
+    }
+
+    pub fn strategy_for(&self, provider: &str) -> AllocationStrategy {
+        self.strategies.get(provider).copied().unwrap_or_default()
+    }
+
+    // Subscription share of each cell in a view, given its weight. A view's cells in one provider
+    // scope carry that scope's share in proportion to their weight, split between them by largest
+    // remainder, so the whole reference population is charged the exact subscription.
+    pub fn allocate_cells(&self, cells: &BTreeMap<AllocCell, u128>) -> BTreeMap<AllocCell, Micros> {
+        let mut groups: BTreeMap<(&str, &AllocScope), Vec<(&AllocCell, u128)>> = BTreeMap::new();
+        for (cell, weight) in cells {
+            groups
+                .entry((cell.provider.as_str(), &cell.scope))
+                .or_default()
+                .push((cell, *weight));
+        }
+
+        let mut allocated = BTreeMap::new();
+        for ((provider, scope), members) in groups {
+            let Some(&(share, reference_weight)) =
+                self.shares.get(&(provider.to_string(), scope.clone()))
+            else {
+                continue;
+            };
+            let member_weights: Vec<u128> = members.iter().map(|(_, weight)| *weight).collect();
+            let view_weight: u128 = member_weights.iter().sum();
+            let view_share = if view_weight >= reference_weight {
+                share
+            } else {
+                div_round(
+                    share as i128 * view_weight as i128,
+                    reference_weight as i128,
+                ) as Micros
+            };
+            for ((cell, _), part) in members
+                .into_iter()
+                .zip(largest_remainder(view_share, &member_weights))
+            {
+                allocated.insert(cell.clone(), part);
+            }
+        }
+        allocated
+    }
+
+    // Subscription share of each priced event, given its variable cost, in the provider's
+    // currency.
+    pub fn allocate_events(&self, events: &[(&UsageEvent, Micros)]) -> Vec<Micros> {
+        self.allocate_events_converted(events, &vec![1.0; events.len()])
+    }
+
+    // Like `allocate_events`, with each cell's share converted by its events' FX factor (the same
+    // for every event in a cell) before it is split between them by weight.
+    pub fn allocate_events_converted(
+        &self,
+        events: &[(&UsageEvent, Micros)],
+        factors: &[f64],
+    ) -> Vec<Micros> {
+        let mut cells: BTreeMap<AllocCell, (u128, Vec<usize>)> = BTreeMap::new();
+        let mut weights = Vec::with_capacity(events.len());
+        for (idx, (evt, variable_cost)) in events.iter().enumerate() {
+            let strategy = self.strategy_for(&evt.provider);
+            let weight = event_weight(strategy, evt, *variable_cost);
+            weights.push(weight);
+            let cell = cells.entry(AllocCell::new(strategy, evt)).or_default();
+            cell.0 += weight;
+            cell.1.push(idx);
+        }
+        let shares = self.allocate_cells(
+            &cells
+                .iter()
+                .map(|(cell, (weight, _))| (cell.clone(), *weight))
+                .collect(),
+        );
+
+        let mut allocated = vec![0; events.len()];
+        for (cell, (_, members)) in cells {
+            let Some(&share) = shares.get(&cell) else {
+                continue;
+            };
+            let share = scale_micros(share, factors[members[0]]);
+            let member_weights: Vec<u128> = members.iter().map(|idx| weights[*idx]).collect();
+            for (idx, part) in members
+                .iter()
+                .zip(largest_remainder(share, &member_weights))
+            {
+                allocated[*idx] = part;
+            }
+        }
+        allocated
+    }
+
+    pub fn idle_micros_for<'a>(&self, providers: impl IntoIterator<Item = &'a str>) -> Micros {
+        providers
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|provider| self.idle.get(provider))
+            .sum()
+    }
+}
+
+pub fn resolve_strategy(
+    provider: &ProviderPricing,
+    strategy_override: Option<AllocationStrategy>,
+) -> AllocationStrategy {
+    strategy_override
+        .or(provider.allocation)
+        .unwrap_or_default()
+}
+
+pub fn event_weight(strategy: AllocationStrategy, evt: &UsageEvent, variable_cost: Micros) -> u128 {
+    match strategy {
+        AllocationStrategy::VariableCost => variable_cost.max(0) as u128,
+        _ => evt.usage.total() as u128,
+    }
+}
+
+fn month_of(day: NaiveDate) -> Month {
+    (day.year(), day.month())
+}
+
+fn scope_for(strategy: AllocationStrategy, evt: &UsageEvent) -> AllocScope {
+    let day = evt.timestamp.date_naive();
+    match strategy {
+        AllocationStrategy::Tokens | AllocationStrategy::VariableCost => {
+            AllocScope::Month(month_of(day))
+        }
+        AllocationStrategy::ActiveDays | AllocationStrategy::PerSeat => AllocScope::Day(day),
+        AllocationStrategy::PerSession => {
+            AllocScope::Session(month_of(day), evt.session_id.clone())
+        }
+    }
+}
+
+// How the month's prorated subscription is divided between scopes that saw usage.
+fn scope_shares(
+    strategy: AllocationStrategy,
+    provider: &ProviderPricing,
+    month: Month,
+    range: &TimeRange,
+    weights: &HashMap<(String, AllocScope), u128>,
+    provider_name: &str,
+) -> Vec<(AllocScope, Micros)> {
+    let prorated = to_micros(prorated_subscription_over(provider, month, range));
+    let mut active: Vec<AllocScope> = weights
+        .iter()
+        .filter(|((name, scope), weight)| {
+            name == provider_name && **weight > 0 && scope_month(scope) == month
+        })
+        .map(|((_, scope), _)| scope.clone())
+        .collect();
+    active.sort();
+    match strategy {
+        AllocationStrategy::Tokens | AllocationStrategy::VariableCost => {
+            vec![(AllocScope::Month(month), prorated)]
+        }
+        AllocationStrategy::ActiveDays | AllocationStrategy::PerSession => {
+            let shares = largest_remainder(prorated, &vec![1; active.len()]);
+            active.into_iter().zip(shares).collect()
+        }
+        AllocationStrategy::PerSeat => active
+            .into_iter()
+            .map(|scope| {
+                let share = match &scope {
+                    AllocScope::Day(day) => {
+                        to_micros(daily_seat_cost(provider, *day) * range.day_coverage(*day))
+                    }
+                    _ => 0,
+                };
+                (scope, share)
+            })
+            .collect(),
+    }
+}
+
+fn scope_month(scope: &AllocScope) -> Month {
+    match scope {
+        AllocScope::Month(month) | AllocScope::Session(month, _) => *month,
+        AllocScope::Day(day) => month_of(*day),
+    }
+}
+
+pub fn month_days(month: Month) -> impl Iterator<Item = NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(month.0, month.1, 1).expect("valid month");
+    (0..31)
+        .map(move |offset| first + Duration::days(offset))
+        .take_while(move |day| day.month() == month.1)
+}
+
+fn seat_usd_month(provider: &ProviderPricing, seat: &SubscriptionSeat) -> f64 {
+    provider.seat_usd_month(Some(seat)) * seat.count as f64
+}
+
+fn daily_seat_cost(provider: &ProviderPricing, day: NaiveDate) -> f64 {
+    let days_in_month = month_days(month_of(day)).count() as f64;
+    if provider.seats.is_empty() {
+        return provider.seat_usd_month(None) / days_in_month;
+    }
+    provider
+        .seats
+        .iter()
+        .filter(|seat| seat.is_active_on(day))
+        .map(|seat| seat_usd_month(provider, seat) / days_in_month)
+        .sum()
+}
+
+// Subscription cost billed for a calendar month, prorated by seat start/end dates.
+pub fn prorated_subscription(provider: &ProviderPricing, month: (i32, u32)) -> f64 {
+    if provider.seats.is_empty() {
+        return provider.seat_usd_month(None);
+    }
+    let days: Vec<NaiveDate> = month_days(month).collect();
+    provider
+        .seats
+        .iter()
+        .map(|seat| {
+            let active = days.iter().filter(|day| seat.is_active_on(**day)).count();
+            seat_usd_month(provider, seat) * active as f64 / days.len() as f64
+        })
+        .sum()
+}
+
+// `prorated_subscription` for the part of the month inside `range`, by the share of each day
+// the range covers.
+pub fn prorated_subscription_over(
+    provider: &ProviderPricing,
+    month: Month,
+    range: &TimeRange,
+) -> f64 {
+    if range.covers_month(month) {
+        return prorated_subscription(provider, month);
+    }
+    let days: Vec<NaiveDate> = month_days(month).collect();
+    let covered = |active: &dyn Fn(NaiveDate) -> bool| -> f64 {
+        days.iter()
+            .filter(|day| active(**day))
+            .map(|day| range.day_coverage(*day))
+            .sum::<f64>()
+            / days.len() as f64
+    };
+    if provider.seats.is_empty() {
+        return provider.seat_usd_month(None) * covered(&|_| true);
+    }
+    provider
+        .seats
+        .iter()
+        .map(|seat| seat_usd_month(provider, seat) * covered(&|day| seat.is_active_on(day)))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn event(model: &str, session: &str, day: u32, tokens: u64) -> UsageEvent {
+        UsageEvent {
+            provider: "claude".to_string(),
+            model: model.to_string(),
+            session_id: session.to_string(),
+            timestamp: Utc.with_ymd_and_hms(2026, 2, day, 12, 0, 0).unwrap(),
+            usage: TokenUsage {
+                input_tokens: tokens,
+                output_tokens: 0,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 0,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn rate(input: f64) -> ModelRate {
+        ModelRate {
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: 0.0,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: None,
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        }
+    }
+
+    fn pricing(seats: Vec<SubscriptionSeat>) -> PricingBook {
+        PricingBook {
+            providers: HashMap::from([(
+                "claude".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 28.0,
+                    models: HashMap::from([
+                        ("sonnet".to_string(), rate(3.0).into()),
+                        ("opus".to_string(), rate(15.0).into()),
+                    ]),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::new(),
+                    allocation: None,
+                    seats,
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        }
+    }
+
+    fn seat(start: Option<u32>, end: Option<u32>) -> SubscriptionSeat {
+        SubscriptionSeat {
+            name: None,
+            usd_month: None,
+            count: 1,
+            start: start.map(|day| NaiveDate::from_ymd_opt(2026, 2, day).unwrap()),
+            end: end.map(|day| NaiveDate::from_ymd_opt(2026, 2, day).unwrap()),
+            plan: None,
+        }
+    }
+
+    #[test]
+    fn test_prorated_subscription_by_seat_dates() {
+        let book = pricing(vec![seat(None, None), seat(Some(15), None)]);
+        let provider = &book.providers["claude"];
+        // February 2026 has 28 days: one full seat plus 14 of 28 days of a second seat.
+        assert!((prorated_subscription(provider, (2026, 2)) - 42.0).abs() < 1e-9);
+        assert!((prorated_subscription(provider, (2026, 1)) - 28.0).abs() < 1e-9);
+
+        // Feb 8-21 covers 14 days of the first seat and 7 of the second.
+        let range = TimeRange {
+            from: Some(Utc.with_ymd_and_hms(2026, 2, 8, 0, 0, 0).unwrap()),
+            to: Some(Utc.with_ymd_and_hms(2026, 2, 22, 0, 0, 0).unwrap()),
+        };
+        assert!((prorated_subscription_over(provider, (2026, 2), &range) - 21.0).abs() < 1e-9);
+        assert!(
+            (prorated_subscription_over(provider, (2026, 2), &TimeRange::month((2026, 2))) - 42.0)
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_filtered_subset_keeps_its_share() {
+        let book = pricing(Vec::new());
+        let events = vec![
+            event("sonnet", "a", 1, 3_000_000),
+            event("opus", "b", 2, 1_000_000),
+        ];
+        let allocator = SubscriptionAllocator::build(&events, &book, None);
+        assert_eq!(
+            allocator.allocate_events(&[(&events[0], 9_000_000), (&events[1], 15_000_000)]),
+            vec![21_000_000, 7_000_000]
+        );
+        assert_eq!(
+            allocator.allocate_events(&[(&events[1], 15_000_000)]),
+            vec![7_000_000]
+        );
+
+        let by_cost =
+            SubscriptionAllocator::build(&events, &book, Some(AllocationStrategy::VariableCost));
+        assert_eq!(
+            by_cost.allocate_events(&[(&events[0], 9_000_000), (&events[1], 15_000_000)]),
+            vec![10_500_000, 17_500_000]
+        );
+    }
+
+    #[test]
+    fn test_active_days_and_sessions_split_evenly() {
+        let book = pricing(Vec::new());
+        let events = vec![
+            event("sonnet", "a", 1, 3_000_000),
+            event("sonnet", "a", 1, 1_000_000),
+            event("sonnet", "b", 3, 1_000),
+        ];
+        let priced: Vec<(&UsageEvent, Micros)> = events.iter().map(|evt| (evt, 0)).collect();
+        let by_day =
+            SubscriptionAllocator::build(&events, &book, Some(AllocationStrategy::ActiveDays));
+        assert_eq!(
+            by_day.allocate_events(&priced),
+            vec![10_500_000, 3_500_000, 14_000_000]
+        );
+        assert_eq!(by_day.idle_micros_for(["claude"]), 0);
+
+        let by_session =
+            SubscriptionAllocator::build(&events, &book, Some(AllocationStrategy::PerSession));
+        assert_eq!(
+            by_session.allocate_events(&priced),
+            vec![10_500_000, 3_500_000, 14_000_000]
+        );
+    }
+
+    #[test]
+    fn test_uneven_split_sums_to_subscription() {
+        let book = pricing(Vec::new());
+        let events = vec![
+            event("sonnet", "a", 1, 1),
+            event("sonnet", "b", 2, 1),
+            event("opus", "c", 3, 1),
+        ];
+        let priced: Vec<(&UsageEvent, Micros)> = events.iter().map(|evt| (evt, 0)).collect();
+        let allocator = SubscriptionAllocator::build(&events, &book, None);
+        let parts = allocator.allocate_events(&priced);
+        // Leftover micro-units go to cells in key order, so not to whichever event came first.
+        assert_eq!(parts, vec![9_333_333, 9_333_333, 9_333_334]);
+        assert_eq!(parts.iter().sum::<Micros>(), 28_000_000);
+    }
+
+    #[test]
+    fn test_per_seat_reports_idle_days() {
+        let book = pricing(vec![seat(None, None)]);
+        let events = vec![
+            event("sonnet", "a", 1, 1_000),
+            event("sonnet", "b", 2, 1_000),
+        ];
+        let allocator =
+            SubscriptionAllocator::build(&events, &book, Some(AllocationStrategy::PerSeat));
+        assert_eq!(
+            allocator.allocate_events(&[(&events[0], 0)]),
+            vec![1_000_000]
+        );
+        assert_eq!(allocator.idle_micros_for(["claude", "claude"]), 26_000_000);
+        assert_eq!(allocator.idle_micros_for(["codex"]), 0);
+    }
+}
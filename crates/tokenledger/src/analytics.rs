@@ -1,35 +1,66 @@
 use anyhow::{anyhow, Result};
-use chrono::Datelike;
-use std::collections::BTreeMap;
 
-use crate::cli::{CoverageArgs, DailyArgs, MonthlyArgs, OutputMode, QueryArgs};
-use crate::models::{CostBreakdown, DailyEntry, DailyReport, UsageEvent};
+use chrono::Utc;
+
+use crate::cli::{
+    CoverageArgs, DailyArgs, ExportArgs, Granularity, MonthlyArgs, OutputMode, QueryArgs,
+    SeriesArgs,
+};
+use crate::export::{coverage_tables, pivot_tables, timeseries_tables, write_export};
+use crate::fx::{load_fx_table, CurrencyConverter};
+use crate::html::{cost_report_html, series_title, write_html};
+use crate::ingest::aggregation::GroupBy;
+use crate::models::{CostBreakdown, PivotReport, SessionReport, TimeSeriesReport};
+use crate::period::TimeRange;
+use crate::stream::{CostStream, ReportView};
 use crate::utils::{
-    build_coverage_report, collect_unpriced_events, compute_costs, filter_month,
-    filter_provider_model, load_events, load_pricing, maybe_write_unpriced_outputs,
-    print_coverage_table, print_daily_markdown, print_daily_table, render_cost_breakdown,
+    build_coverage_report, collect_unpriced_events, filter_month, load_events, load_pricing,
+    maybe_write_unpriced_outputs, print_coverage_table, print_pivot_markdown, print_pivot_table,
+    print_timeseries_markdown, print_timeseries_table, render_cost_breakdown,
 };
 
 pub fn run_monthly(args: MonthlyArgs) -> Result<()> {
-    let report = build_monthly_report(&args.query, args.month.as_deref())?;
+    let range = TimeRange::from_args(&args.period, Utc::now())?;
+    if !args.group_by.is_empty() {
+        let group = GroupBy::new(&args.group_by)?;
+        let report = build_pivot_report(&args.query, &range, &group, args.nested)?;
+        return render_pivot_report(&report, args.query.output, &args.query.export);
+    }
+    if let OutputMode::Html = args.query.output {
+        // The HTML report charts the month by day, so it is built from the daily series.
+        let report = build_timeseries_report(&args.query, &range, Granularity::Day)?;
+        return write_html(&cost_report_html("Monthly", &report), &args.query.export);
+    }
+    let report = build_monthly_report(&args.query, &range)?;
     render_cost_breakdown(
         "Monthly",
         &report,
         args.query.output,
         args.query.top_providers,
         args.query.top_models,
+        &args.query.export,
     )?;
 
     Ok(())
 }
 
 pub fn run_daily(args: DailyArgs) -> Result<()> {
-    let report = build_daily_report(&args.query, args.month.as_deref())?;
-    render_daily_report(
+    run_series(SeriesArgs {
+        query: args.query,
+        period: args.period,
+        granularity: Granularity::Day,
+    })
+}
+
+pub fn run_series(args: SeriesArgs) -> Result<()> {
+    let range = TimeRange::from_args(&args.period, Utc::now())?;
+    let report = build_timeseries_report(&args.query, &range, args.granularity)?;
+    render_timeseries_report(
         &report,
         args.query.output,
         args.query.top_providers,
         args.query.top_models,
+        &args.query.export,
     )
 }
 
@@ -52,86 +83,140 @@ pub fn run_coverage(args: CoverageArgs) -> Result<()> {
         args.write_unpriced_events.as_deref(),
     )?;
 
-    if args.json_output {
-        println!("{}", serde_json::to_string_pretty(&report)?);
+    let output = if args.json_output {
+        OutputMode::Json
     } else {
-        print_coverage_table(&report);
+        args.output
+    };
+    match output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_coverage_table(&report),
+        OutputMode::Csv | OutputMode::Xlsx => {
+            write_export(&coverage_tables(&report), output, &args.export)?
+        }
+        OutputMode::Markdown | OutputMode::Html => {
+            return Err(anyhow!(
+                "coverage supports table, json, csv and xlsx output"
+            ))
+        }
     }
     Ok(())
 }
 
-pub fn build_monthly_report(query: &QueryArgs, month: Option<&str>) -> Result<CostBreakdown> {
-    let pricing = load_pricing(&query.pricing)?;
+pub fn build_monthly_report(query: &QueryArgs, range: &TimeRange) -> Result<CostBreakdown> {
+    let (stream, view) = stream_query(query, range, false, false)?;
+    stream.totals(&view)
+}
 
-    let events = load_events(&query.events)?;
-    let normalized = crate::utils::normalize_events(events, &pricing);
-    let month_filtered = filter_month(normalized, month)?;
-    let filtered = filter_provider_model(month_filtered, &pricing, &query.providers, &query.models);
-    if filtered.is_empty() {
-        return Err(anyhow!(
-            "no events matched selected month/provider/model filters"
-        ));
-    }
+pub fn build_pivot_report(
+    query: &QueryArgs,
+    range: &TimeRange,
+    group: &GroupBy,
+    nested: bool,
+) -> Result<PivotReport> {
+    let (stream, view) = stream_query(query, range, false, group.needs_sessions())?;
+    stream.pivot(&view, group, nested)
+}
 
-    compute_costs(&filtered, &pricing, query.on_unpriced)
+pub fn build_session_report(query: &QueryArgs, range: &TimeRange) -> Result<SessionReport> {
+    let (stream, view) = stream_query(query, range, false, true)?;
+    stream.sessions(&view)
 }
 
-pub fn build_daily_report(query: &QueryArgs, month: Option<&str>) -> Result<DailyReport> {
-    let pricing = load_pricing(&query.pricing)?;
+pub fn build_timeseries_report(
+    query: &QueryArgs,
+    range: &TimeRange,
+    granularity: Granularity,
+) -> Result<TimeSeriesReport> {
+    let (stream, view) = stream_query(query, range, granularity == Granularity::Hour, false)?;
+    stream.series(&view, granularity)
+}
 
-    let events = load_events(&query.events)?;
-    let normalized = crate::utils::normalize_events(events, &pricing);
-    let month_filtered = filter_month(normalized, month)?;
-    let filtered = filter_provider_model(month_filtered, &pricing, &query.providers, &query.models);
-    if filtered.is_empty() {
-        return Err(anyhow!(
-            "no events matched selected month/provider/model filters"
-        ));
+// Reads the query's event files once into a `CostStream`; nothing is kept per event.
+pub fn stream_query(
+    query: &QueryArgs,
+    range: &TimeRange,
+    hourly: bool,
+    sessions: bool,
+) -> Result<(CostStream, ReportView)> {
+    let (mut stream, view) = open_stream(query, range, hourly)?;
+    if sessions {
+        stream.track_sessions();
     }
-
-    let totals = compute_costs(&filtered, &pricing, query.on_unpriced)?;
-    let month = format!(
-        "{:04}-{:02}",
-        filtered[0].timestamp.year(),
-        filtered[0].timestamp.month()
-    );
-
-    let mut by_day: BTreeMap<chrono::NaiveDate, Vec<UsageEvent>> = BTreeMap::new();
-    for event in filtered {
-        by_day
-            .entry(event.timestamp.date_naive())
-            .or_default()
-            .push(event);
+    for path in &query.events {
+        stream.read_jsonl(path)?;
     }
+    Ok((stream, view))
+}
 
-    let mut days = Vec::with_capacity(by_day.len());
-    for (day, day_events) in by_day {
-        let breakdown = compute_costs(&day_events, &pricing, query.on_unpriced)?;
-        days.push(DailyEntry {
-            day: day.format("%Y-%m-%d").to_string(),
-            breakdown,
-        });
+// An empty `CostStream` for the query, for callers that read its events themselves.
+pub fn open_stream(
+    query: &QueryArgs,
+    range: &TimeRange,
+    hourly: bool,
+) -> Result<(CostStream, ReportView)> {
+    let pricing = load_pricing(&query.pricing)?;
+    let view = ReportView::new(&pricing, &query.providers, &query.models, query.on_unpriced);
+    let mut stream = CostStream::new(
+        pricing,
+        currency_converter(query)?,
+        query.allocation,
+        *range,
+        hourly,
+    );
+    if query.no_session_counts {
+        stream.skip_session_counts();
     }
+    Ok((stream, view))
+}
 
-    let report = DailyReport {
-        month,
-        totals,
-        days,
-    };
-    Ok(report)
+pub fn currency_converter(query: &QueryArgs) -> Result<CurrencyConverter> {
+    let table = query.fx_rates.as_deref().map(load_fx_table).transpose()?;
+    Ok(CurrencyConverter::new(
+        &query.currency,
+        query.fx_date,
+        table,
+    ))
 }
 
-pub fn render_daily_report(
-    report: &DailyReport,
+pub fn render_timeseries_report(
+    report: &TimeSeriesReport,
     output: OutputMode,
     top_providers: Option<usize>,
     top_models: Option<usize>,
+    export: &ExportArgs,
 ) -> Result<()> {
     match output {
         OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
-        OutputMode::Table => print_daily_table(report, top_providers, top_models),
-        OutputMode::Markdown => print_daily_markdown(report, top_providers, top_models),
+        OutputMode::Table => print_timeseries_table(report, top_providers, top_models),
+        OutputMode::Markdown => print_timeseries_markdown(report, top_providers, top_models),
+        OutputMode::Csv | OutputMode::Xlsx => {
+            write_export(&timeseries_tables(report), output, export)?
+        }
+        OutputMode::Html => write_html(
+            &cost_report_html(series_title(report.granularity), report),
+            export,
+        )?,
     }
 
     Ok(())
 }
+
+pub fn render_pivot_report(
+    report: &PivotReport,
+    output: OutputMode,
+    export: &ExportArgs,
+) -> Result<()> {
+    match output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(report)?),
+        OutputMode::Table => print_pivot_table(report),
+        OutputMode::Markdown => print_pivot_markdown(report),
+        OutputMode::Csv | OutputMode::Xlsx => write_export(&pivot_tables(report), output, export)?,
+        OutputMode::Html => {
+            return Err(anyhow!(
+                "--group-by supports table, markdown, json, csv and xlsx output"
+            ))
+        }
+    }
+    Ok(())
+}
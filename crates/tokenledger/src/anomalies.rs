@@ -0,0 +1,415 @@
+// Spend anomalies: buckets of a daily or hourly series whose provider or model variable cost sits
+// well above that series' typical spend. A bucket's baseline is the median of the other buckets in
+// its season (weekday for daily series, hour of day for hourly ones). Its score is a robust z-score
+// against the relative spread of every other bucket around the median of the rest of its season,
+// so a handful of same-season points cannot collapse the spread and one spike cannot inflate it.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+
+use crate::analytics::build_timeseries_report;
+use crate::cli::{AnomaliesArgs, Granularity, OutputMode};
+use crate::cost::NO_ANOMALIES_TIP;
+use crate::export::{anomaly_tables, write_export};
+use crate::forecast::range_or_latest_month;
+use crate::fx::currency_symbol;
+use crate::models::*;
+use crate::money::{cents_to_units, units_to_cents};
+use crate::utils::{print_anomaly_table, round2};
+
+pub const DEFAULT_ANOMALY_THRESHOLD: f64 = 3.5;
+pub const DEFAULT_ANOMALY_MIN_USD: f64 = 1.0;
+// Series shorter than this have no baseline worth scoring against.
+const MIN_SERIES_POINTS: usize = 7;
+// Seasons need this many buckets besides the scored one, or the series is scored unseasoned.
+const MIN_SEASON_POINTS: usize = 3;
+// Scale the MAD and mean absolute deviation so both estimate a normal standard deviation.
+const MAD_SCALE: f64 = 1.4826;
+const MEAN_AD_SCALE: f64 = 1.2533;
+// Spread floors for flat baselines: a share of the median, and a hundredth of a unit.
+const SPREAD_FLOOR_SHARE: f64 = 0.05;
+const SPREAD_FLOOR: f64 = 0.01;
+// Anomalies turned into suggestions, largest first.
+const SUGGESTED_ANOMALIES: usize = 3;
+
+pub fn run_anomalies(args: AnomaliesArgs) -> Result<()> {
+    let range = range_or_latest_month(&args.query, &args.period)?;
+    let series = build_timeseries_report(&args.query, &range, args.granularity)?;
+    let report = detect_anomalies(&series, args.threshold, args.min_usd)?;
+    match args.query.output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_anomaly_table(&report),
+        OutputMode::Csv | OutputMode::Xlsx => write_export(
+            &anomaly_tables(&report),
+            args.query.output,
+            &args.query.export,
+        )?,
+        OutputMode::Markdown | OutputMode::Html => {
+            return Err(anyhow!(
+                "anomalies supports table, json, csv and xlsx output"
+            ))
+        }
+    }
+    Ok(())
+}
+
+// Scores every provider and model series of `series` from its first bucket with events to its
+// last, counting buckets without events as zero spend. Only spikes are reported: a bucket must
+// score at least `threshold` and exceed its baseline by at least `min_usd`.
+pub fn detect_anomalies(
+    series: &TimeSeriesReport,
+    threshold: f64,
+    min_usd: f64,
+) -> Result<AnomalyReport> {
+    let step = match series.granularity {
+        Granularity::Day => Duration::days(1),
+        Granularity::Hour => Duration::hours(1),
+        other => {
+            return Err(anyhow!(
+                "anomaly detection needs a daily or hourly series, not {}",
+                other.as_str()
+            ))
+        }
+    };
+    let mut slots: Vec<DateTime<Utc>> = Vec::new();
+    if let (Some(first), Some(last)) = (series.buckets.first(), series.buckets.last()) {
+        let mut at = first.start;
+        while at <= last.start {
+            slots.push(at);
+            at += step;
+        }
+    }
+    let seasons: Vec<u32> = slots
+        .iter()
+        .map(|at| match series.granularity {
+            Granularity::Hour => at.hour(),
+            _ => at.weekday().num_days_from_monday(),
+        })
+        .collect();
+    let index: BTreeMap<DateTime<Utc>, usize> = slots
+        .iter()
+        .enumerate()
+        .map(|(idx, at)| (*at, idx))
+        .collect();
+
+    let mut spend: BTreeMap<(AnomalyScope, &str), Vec<f64>> = BTreeMap::new();
+    let mut labels: Vec<&str> = vec![""; slots.len()];
+    for bucket in &series.buckets {
+        let idx = index[&bucket.start];
+        labels[idx] = bucket.period.as_str();
+        let scoped = [
+            (AnomalyScope::Provider, &bucket.breakdown.provider_breakdown),
+            (AnomalyScope::Model, &bucket.breakdown.model_breakdown),
+        ];
+        for (scope, rows) in scoped {
+            for row in rows {
+                spend
+                    .entry((scope, row.name.as_str()))
+                    .or_insert_with(|| vec![0.0; slots.len()])[idx] = row.variable_cost_usd;
+            }
+        }
+    }
+
+    // A provider with a single model would be reported twice for the same spike.
+    let providers: Vec<Vec<f64>> = spend
+        .iter()
+        .filter(|((scope, _), _)| *scope == AnomalyScope::Provider)
+        .map(|(_, values)| values.clone())
+        .collect();
+    spend.retain(|(scope, _), values| {
+        *scope == AnomalyScope::Provider || !providers.contains(values)
+    });
+
+    let mut anomalies = Vec::new();
+    for ((scope, name), values) in &spend {
+        for spike in find_spikes(values, &seasons, threshold, min_usd) {
+            anomalies.push(Anomaly {
+                bucket: labels[spike.index].to_string(),
+                start: slots[spike.index],
+                scope: *scope,
+                name: name.to_string(),
+                spend_usd: values[spike.index],
+                baseline_usd: cents_to_units(units_to_cents(spike.baseline)),
+                z_score: round2(spike.z_score),
+                excess_usd: cents_to_units(units_to_cents(values[spike.index] - spike.baseline)),
+            });
+        }
+    }
+    anomalies.sort_by(|a, b| {
+        b.z_score
+            .total_cmp(&a.z_score)
+            .then(b.excess_usd.total_cmp(&a.excess_usd))
+            .then(a.start.cmp(&b.start))
+    });
+    Ok(AnomalyReport {
+        period: series.period.clone(),
+        currency: series.totals.currency.clone(),
+        granularity: series.granularity,
+        threshold,
+        min_usd,
+        buckets: slots.len(),
+        anomalies,
+    })
+}
+
+// A bucket of one series flagged by `find_spikes`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spike {
+    pub index: usize,
+    pub baseline: f64,
+    pub z_score: f64,
+}
+
+// Buckets of `values` that spike above the other buckets of their season; `seasons` holds each
+// bucket's season. Each bucket is left out of its own baseline and of the spread it is scored by.
+pub fn find_spikes(values: &[f64], seasons: &[u32], threshold: f64, min_usd: f64) -> Vec<Spike> {
+    if values.len() < MIN_SERIES_POINTS {
+        return Vec::new();
+    }
+    let mut sizes: BTreeMap<u32, usize> = BTreeMap::new();
+    for season in seasons {
+        *sizes.entry(*season).or_default() += 1;
+    }
+    let seasonal = sizes.values().all(|size| *size > MIN_SEASON_POINTS);
+    let season_of = |idx: usize| if seasonal { seasons[idx] } else { 0 };
+
+    let mut spikes = Vec::new();
+    for (idx, value) in values.iter().enumerate() {
+        let mut by_season: BTreeMap<u32, Vec<f64>> = BTreeMap::new();
+        for (other, value) in values.iter().enumerate() {
+            if other != idx {
+                by_season.entry(season_of(other)).or_default().push(*value);
+            }
+        }
+        for season in by_season.values_mut() {
+            season.sort_by(f64::total_cmp);
+        }
+        // Spend varies in proportion to its level, so each other bucket contributes its relative
+        // distance from the median of the rest of its season; seasons without spend have no scale.
+        let mut residuals: Vec<f64> = values
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != idx)
+            .filter_map(|(other, value)| {
+                let season = &by_season[&season_of(other)];
+                let level = median_skipping(season, season.partition_point(|x| x < value))?;
+                (level > 0.0).then(|| (value - level).abs() / level)
+            })
+            .collect();
+        let season = &by_season[&season_of(idx)];
+        let baseline = median_skipping(season, season.len()).unwrap_or(0.0);
+        let spread = if residuals.is_empty() {
+            SPREAD_FLOOR
+        } else {
+            (robust_spread(&mut residuals, 1.0) * baseline).max(SPREAD_FLOOR)
+        };
+        let z_score = (value - baseline) / spread;
+        if z_score >= threshold && value - baseline >= min_usd {
+            spikes.push(Spike {
+                index: idx,
+                baseline,
+                z_score,
+            });
+        }
+    }
+    spikes
+}
+
+// The baseline's median and `value`'s distance above it in robust standard deviations: the
+// scaled MAD, or the scaled mean absolute deviation when more than half the baseline is flat.
+pub fn robust_z(value: f64, baseline: &mut [f64]) -> (f64, f64) {
+    if baseline.is_empty() {
+        return (0.0, 0.0);
+    }
+    let median = median_of(baseline);
+    let mut deviations: Vec<f64> = baseline.iter().map(|x| (x - median).abs()).collect();
+    (
+        median,
+        (value - median) / robust_spread(&mut deviations, median),
+    )
+}
+
+// A robust standard deviation from absolute `deviations` off a baseline of `level`, floored for
+// flat baselines.
+fn robust_spread(deviations: &mut [f64], level: f64) -> f64 {
+    let mad = median_of(deviations);
+    let spread = if mad > 0.0 {
+        MAD_SCALE * mad
+    } else {
+        MEAN_AD_SCALE * deviations.iter().sum::<f64>() / deviations.len() as f64
+    };
+    spread.max(SPREAD_FLOOR_SHARE * level).max(SPREAD_FLOOR)
+}
+
+fn median_of(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+// The median of `sorted` without its element at `skip`, which may be past the end to keep all.
+fn median_skipping(sorted: &[f64], skip: usize) -> Option<f64> {
+    let len = sorted.len() - usize::from(skip < sorted.len());
+    let at = |k: usize| sorted[if k < skip { k } else { k + 1 }];
+    let mid = len / 2;
+    match len {
+        0 => None,
+        _ if len.is_multiple_of(2) => Some((at(mid - 1) + at(mid)) / 2.0),
+        _ => Some(at(mid)),
+    }
+}
+
+// Suggestions for the report's largest spikes; they replace the "no obvious anomalies" tip.
+pub fn add_anomaly_suggestions(suggestions: &mut Vec<String>, report: &AnomalyReport) {
+    if report.anomalies.is_empty() {
+        return;
+    }
+    suggestions.retain(|tip| tip != NO_ANOMALIES_TIP);
+    let sym = currency_symbol(&report.currency);
+    for anomaly in report.anomalies.iter().take(SUGGESTED_ANOMALIES) {
+        suggestions.push(format!(
+            "Spend spike: {} {} spent {}{:.2} in {} against a typical {}{:.2} (z {:.1}): look for a looping agent or an alias now resolving to a pricier model.",
+            anomaly.scope.as_str(),
+            anomaly.name,
+            sym,
+            anomaly.spend_usd,
+            anomaly.bucket,
+            sym,
+            anomaly.baseline_usd,
+            anomaly.z_score
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{ExportArgs, FxDateMode, QueryArgs};
+    use crate::fx::USD;
+    use crate::period::TimeRange;
+
+    // Four weeks from Monday: $10 on weekdays and $2 at weekends.
+    fn weeks() -> (Vec<f64>, Vec<u32>) {
+        let seasons: Vec<u32> = (0..28).map(|day| day % 7).collect();
+        let values = seasons
+            .iter()
+            .map(|weekday| if *weekday >= 5 { 2.0 } else { 10.0 })
+            .collect();
+        (values, seasons)
+    }
+
+    #[test]
+    fn test_spikes_are_scored_against_their_weekday() {
+        let (mut values, seasons) = weeks();
+        // A Wednesday spike, a Saturday at weekday spend and a Monday a little over.
+        values[9] = 40.0;
+        values[19] = 10.0;
+        values[21] = 10.5;
+        let spikes = find_spikes(&values, &seasons, 3.5, 1.0);
+        let flagged: Vec<usize> = spikes.iter().map(|spike| spike.index).collect();
+        assert_eq!(flagged, [9, 19]);
+        assert_eq!(spikes[0].baseline, 10.0);
+        assert_eq!(spikes[1].baseline, 2.0);
+
+        // Without weekday history every other day is the baseline, where $10 is typical.
+        let flat = vec![0; 28];
+        let flagged: Vec<usize> = find_spikes(&values, &flat, 3.5, 1.0)
+            .iter()
+            .map(|spike| spike.index)
+            .collect();
+        assert_eq!(flagged, [9]);
+
+        // A week is too short to judge.
+        assert!(find_spikes(&values[..6], &seasons[..6], 3.5, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_robust_z_is_not_masked_by_an_outlier() {
+        // One earlier spike in the baseline barely moves the median or the MAD.
+        let mut baseline = [4.0, 5.0, 6.0, 5.0, 500.0];
+        let (median, z) = robust_z(20.0, &mut baseline);
+        assert_eq!(median, 5.0);
+        assert!(z > 10.0);
+
+        // A mostly flat baseline falls back to the mean absolute deviation (0.8 here).
+        let mut baseline = [5.0, 5.0, 5.0, 5.0, 9.0];
+        let (_, z) = robust_z(8.0, &mut baseline);
+        assert!(z > 2.9 && z < 3.0);
+        assert_eq!(robust_z(1.0, &mut []), (0.0, 0.0));
+    }
+
+    // `weeks()` with up to 30% of uniform noise on every day.
+    fn noisy_weeks(seed: u64) -> (Vec<f64>, Vec<u32>) {
+        let mut state = seed;
+        let (mut values, seasons) = weeks();
+        for value in &mut values {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let noise = (state >> 33) as f64 / (1u64 << 31) as f64 * 2.0 - 1.0;
+            *value += 0.3 * noise * *value;
+        }
+        (values, seasons)
+    }
+
+    #[test]
+    fn test_ordinary_noise_is_not_flagged() {
+        let mut false_alarms = 0;
+        for seed in 1..=40 {
+            let (mut values, seasons) = noisy_weeks(seed);
+            // A Wednesday at three times its noisy spend.
+            values[16] *= 3.0;
+            let spikes = find_spikes(&values, &seasons, 3.5, 1.0);
+            assert!(spikes.iter().any(|spike| spike.index == 16), "seed {seed}");
+            false_alarms += spikes.iter().filter(|spike| spike.index != 16).count();
+        }
+        // Forty months of noise, 1,080 ordinary days.
+        assert!(false_alarms <= 4, "{false_alarms} ordinary days flagged");
+    }
+
+    #[test]
+    fn test_single_model_providers_are_reported_once() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events.jsonl");
+        let lines: Vec<String> = (1..=14)
+            .map(|day| {
+                let input = if day == 10 { 3_000_000 } else { 300_000 };
+                format!(
+                    r#"{{"provider":"claude","model":"claude-sonnet-4-5","session_id":"s{day}","timestamp":"2026-02-{day:02}T12:00:00Z","usage":{{"input_tokens":{input},"output_tokens":0,"cache_write_tokens":0,"cache_read_tokens":0,"tool_input_tokens":0,"tool_output_tokens":0}}}}"#
+                )
+            })
+            .collect();
+        std::fs::write(&events, lines.join("\n")).unwrap();
+        let query = QueryArgs {
+            events: vec![events],
+            pricing: root.join("pricing.example.json"),
+            providers: Vec::new(),
+            models: Vec::new(),
+            top_models: None,
+            top_providers: None,
+            output: OutputMode::Json,
+            on_unpriced: ParetoRs::OnUnpricedAction::Error,
+            allocation: None,
+            currency: USD.to_string(),
+            fx_rates: None,
+            fx_date: FxDateMode::EventDay,
+            no_session_counts: false,
+            export: ExportArgs::default(),
+        };
+        let range = TimeRange::month((2026, 2));
+        let series = build_timeseries_report(&query, &range, Granularity::Day).unwrap();
+        let report = detect_anomalies(&series, 3.5, 1.0).unwrap();
+
+        assert_eq!(report.anomalies.len(), 1);
+        assert_eq!(report.anomalies[0].scope, AnomalyScope::Provider);
+        assert_eq!(report.anomalies[0].name, "claude");
+        assert_eq!(report.anomalies[0].bucket, "2026-02-10");
+    }
+}
@@ -8,9 +8,13 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 
-use crate::cli::{BenchArgs, BenchScenario, OnUnpricedAction};
+use crate::cli::{BenchArgs, BenchScenario, Granularity, OnUnpricedAction};
+use crate::fx::CurrencyConverter;
 use crate::ingest::source_mtime_unix;
 use crate::models::*;
+use crate::money::{cents_to_units, units_to_cents};
+use crate::period::TimeRange;
+use crate::stream::{CostStream, ReportView};
 use crate::utils::*;
 
 pub const PERF_GATES_PATH: &str = "benchmarks/perf-gates.json";
@@ -49,6 +53,18 @@ pub fn execute_bench(args: BenchArgs) -> Result<BenchExecution> {
     }
     let pricing = load_pricing(&args.pricing)?;
 
+    // Streaming runs before anything loads events into memory so its peak RSS is its own.
+    if matches!(args.scenario, BenchScenario::Streaming) {
+        let (result, month) = run_bench_streaming(
+            &args.events,
+            pricing,
+            args.month.as_deref(),
+            args.stream_events,
+            args.on_unpriced,
+        )?;
+        return finish_bench(&args, month, vec![result]);
+    }
+
     let baseline_events = load_events(&args.events)?;
     let baseline_normalized = normalize_events(baseline_events, &pricing);
     let baseline_filtered = filter_month(baseline_normalized, args.month.as_deref())?;
@@ -109,8 +125,17 @@ pub fn execute_bench(args: BenchArgs) -> Result<BenchExecution> {
                 args.on_unpriced,
             )?);
         }
+        BenchScenario::Streaming => unreachable!("streaming scenario runs before loading events"),
     }
 
+    finish_bench(&args, month, results)
+}
+
+fn finish_bench(
+    args: &BenchArgs,
+    month: String,
+    results: Vec<BenchScenarioResult>,
+) -> Result<BenchExecution> {
     let baseline_used = args.baseline.is_some();
     let report = BenchReport {
         scenario: bench_scenario_name(args.scenario).to_string(),
@@ -372,6 +397,102 @@ pub fn run_bench_burst(
     ))
 }
 
+// Events per generated session, and the most template events `stream_events` generation keeps.
+const STREAM_SESSION_EVENTS: u64 = 50;
+const STREAM_TEMPLATE_EVENTS: usize = 1_024;
+
+// Prices `paths` through a `CostStream` that keeps no session ids, as `--no-session-counts` does,
+// so peak RSS stays flat however many events are read. With `stream_events` set it streams that
+// many events generated from them instead, with a fresh session every few events.
+pub fn run_bench_streaming(
+    paths: &[PathBuf],
+    pricing: PricingBook,
+    month: Option<&str>,
+    stream_events: u64,
+    on_unpriced: OnUnpricedAction,
+) -> Result<(BenchScenarioResult, String)> {
+    let view = ReportView::new(&pricing, &[], &[], on_unpriced);
+    let range = month
+        .map(parse_month)
+        .transpose()?
+        .map_or_else(TimeRange::default, TimeRange::month);
+    let generated = if stream_events > 0 {
+        Some(generate_stream_events(paths, &range, stream_events)?)
+    } else {
+        None
+    };
+    let start = Instant::now();
+    let mut stream = CostStream::new(pricing, CurrencyConverter::usd(), None, range, false);
+    stream.skip_session_counts();
+    match &generated {
+        Some(file) => stream.read_jsonl(file.path())?,
+        None => {
+            for path in paths {
+                stream.read_jsonl(path)?;
+            }
+        }
+    }
+    let report = stream.series(&view, Granularity::Day)?;
+    let mut result = build_bench_result(
+        BenchScenario::Streaming,
+        start.elapsed(),
+        stream.events_seen() as usize,
+        bench_correctness_from_breakdown(&report.totals),
+    );
+    result.peak_rss_kb = peak_rss_kb();
+    Ok((result, report.period))
+}
+
+// Writes `count` events to a temporary JSONL file, cycling through the first events of `paths`
+// within `range`. Every `STREAM_SESSION_EVENTS` events start a new session, and sessions move
+// across the days of the template event's month.
+pub fn generate_stream_events(
+    paths: &[PathBuf],
+    range: &TimeRange,
+    count: u64,
+) -> Result<tempfile::NamedTempFile> {
+    let mut templates = Vec::new();
+    for path in paths {
+        for_each_jsonl_event(path, |evt| {
+            if templates.len() < STREAM_TEMPLATE_EVENTS && range.contains(evt.timestamp) {
+                templates.push(evt);
+            }
+            Ok(())
+        })?;
+    }
+    if templates.is_empty() {
+        return Err(anyhow!("no events matched selected month filters"));
+    }
+    let mut file = tempfile::NamedTempFile::new().context("creating streaming bench events")?;
+    {
+        let mut out = std::io::BufWriter::new(file.as_file_mut());
+        for idx in 0..count {
+            let mut evt = templates[(idx % templates.len() as u64) as usize].clone();
+            let session = idx / STREAM_SESSION_EVENTS;
+            let day = (session % 28) as u32 + 1;
+            evt.session_id = format!("bench-stream-{session}");
+            if let Some(timestamp) = evt.timestamp.with_day(day) {
+                if range.contains(timestamp) {
+                    evt.timestamp = timestamp;
+                }
+            }
+            serde_json::to_writer(&mut out, &evt)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()?;
+    }
+    Ok(file)
+}
+
+// High-water resident set size of this process, where the platform reports one.
+pub fn peak_rss_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
 pub fn build_bench_result(
     scenario: BenchScenario,
     elapsed: std::time::Duration,
@@ -389,6 +510,7 @@ pub fn build_bench_result(
         elapsed_ms: round4(elapsed_s * 1000.0),
         events_processed,
         events_per_sec: round4(events_per_sec),
+        peak_rss_kb: None,
         correctness: Some(correctness),
         elapsed_ms_delta: None,
         events_per_sec_delta: None,
@@ -399,9 +521,9 @@ pub fn build_bench_result(
 
 pub fn bench_correctness_from_breakdown(breakdown: &CostBreakdown) -> BenchScenarioCorrectness {
     bench_correctness_from_accumulator(&BenchCorrectnessAccumulator {
-        variable_cost_usd: breakdown.variable_cost_usd,
-        subscription_allocated_usd: breakdown.subscription_allocated_usd,
-        monthly_total_usd: breakdown.monthly_total_usd,
+        variable_cost_cents: units_to_cents(breakdown.variable_cost_usd),
+        subscription_allocated_cents: units_to_cents(breakdown.subscription_allocated_usd),
+        monthly_total_cents: units_to_cents(breakdown.monthly_total_usd),
         total_tokens: breakdown.total_tokens,
         input_tokens: breakdown.input_tokens,
         output_tokens: breakdown.output_tokens,
@@ -417,9 +539,10 @@ pub fn bench_correctness_add_breakdown(
     accumulator: &mut BenchCorrectnessAccumulator,
     breakdown: &CostBreakdown,
 ) {
-    accumulator.variable_cost_usd += breakdown.variable_cost_usd;
-    accumulator.subscription_allocated_usd += breakdown.subscription_allocated_usd;
-    accumulator.monthly_total_usd += breakdown.monthly_total_usd;
+    accumulator.variable_cost_cents += units_to_cents(breakdown.variable_cost_usd);
+    accumulator.subscription_allocated_cents +=
+        units_to_cents(breakdown.subscription_allocated_usd);
+    accumulator.monthly_total_cents += units_to_cents(breakdown.monthly_total_usd);
     accumulator.total_tokens += breakdown.total_tokens;
     accumulator.input_tokens += breakdown.input_tokens;
     accumulator.output_tokens += breakdown.output_tokens;
@@ -435,11 +558,11 @@ pub fn bench_correctness_from_accumulator(
 ) -> BenchScenarioCorrectness {
     let total_mtok = accumulator.total_tokens as f64 / MTOK;
     BenchScenarioCorrectness {
-        variable_cost_usd: round2(accumulator.variable_cost_usd),
-        subscription_allocated_usd: round2(accumulator.subscription_allocated_usd),
-        monthly_total_usd: round2(accumulator.monthly_total_usd),
+        variable_cost_usd: cents_to_units(accumulator.variable_cost_cents),
+        subscription_allocated_usd: cents_to_units(accumulator.subscription_allocated_cents),
+        monthly_total_usd: cents_to_units(accumulator.monthly_total_cents),
         blended_usd_per_mtok: round4(if total_mtok > 0.0 {
-            accumulator.monthly_total_usd / total_mtok
+            cents_to_units(accumulator.monthly_total_cents) / total_mtok
         } else {
             0.0
         }),
@@ -524,29 +647,26 @@ pub fn verify_bench_golden(report: &BenchReport, golden_path: &Path, epsilon: f6
 
         match (&result.correctness, &expected.correctness) {
             (Some(actual), Some(expected_correctness)) => {
-                compare_golden_f64(
+                compare_golden_cents(
                     &mut mismatches,
                     &result.scenario,
                     "variable_cost_usd",
                     actual.variable_cost_usd,
                     expected_correctness.variable_cost_usd,
-                    epsilon,
                 );
-                compare_golden_f64(
+                compare_golden_cents(
                     &mut mismatches,
                     &result.scenario,
                     "subscription_allocated_usd",
                     actual.subscription_allocated_usd,
                     expected_correctness.subscription_allocated_usd,
-                    epsilon,
                 );
-                compare_golden_f64(
+                compare_golden_cents(
                     &mut mismatches,
                     &result.scenario,
                     "monthly_total_usd",
                     actual.monthly_total_usd,
                     expected_correctness.monthly_total_usd,
-                    epsilon,
                 );
                 compare_golden_f64(
                     &mut mismatches,
@@ -663,6 +783,22 @@ pub fn compare_golden_f64(
     }
 }
 
+// Money fields are whole cents, so they must match exactly whatever the epsilon.
+pub fn compare_golden_cents(
+    mismatches: &mut Vec<String>,
+    scenario: &str,
+    field: &str,
+    actual: f64,
+    expected: f64,
+) {
+    if units_to_cents(actual) != units_to_cents(expected) {
+        mismatches.push(format!(
+            "{scenario}: {field} {:.2} != {:.2}",
+            actual, expected
+        ));
+    }
+}
+
 pub fn compare_golden_u64(
     mismatches: &mut Vec<String>,
     scenario: &str,
@@ -696,3 +832,24 @@ pub fn load_perf_gate_config(path: &Path) -> Result<PerfGateConfig> {
     serde_json::from_reader(File::open(path).with_context(|| format!("opening {:?}", path))?)
         .with_context(|| format!("parsing perf gate config {:?}", path))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_stream_events_spread_over_sessions_and_days() {
+        let file = generate_stream_events(
+            &[Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples/events.jsonl")],
+            &TimeRange::default(),
+            STREAM_SESSION_EVENTS * 40,
+        )
+        .unwrap();
+        let events = load_events(&[file.path().to_path_buf()]).unwrap();
+        assert_eq!(events.len() as u64, STREAM_SESSION_EVENTS * 40);
+        let sessions: HashSet<&str> = events.iter().map(|evt| evt.session_id.as_str()).collect();
+        let days: HashSet<u32> = events.iter().map(|evt| evt.timestamp.day()).collect();
+        assert_eq!(sessions.len(), 40);
+        assert_eq!(days.len(), 28);
+    }
+}
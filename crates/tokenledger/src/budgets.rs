@@ -0,0 +1,600 @@
+// Budgets: spending limits over the calendar month or a trailing window, for every event or one
+// provider, model or tag. Monthly budgets also warn when the month-end forecast runs over; alerts
+// go to a JSON webhook and/or a local command.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration as StdDuration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+
+use crate::allocation::SubscriptionAllocator;
+use crate::cli::{
+    AllocationStrategy, BudgetArgs, BudgetCheckArgs, BudgetCommand, BudgetStatus, ForecastMethod,
+    OnUnpricedAction, OutputMode,
+};
+use crate::cost::{price_events, summarize_priced, PricedEvents};
+use crate::forecast::{days, forecast_series};
+use crate::fx::{month_end, CurrencyConverter, USD};
+use crate::models::*;
+use crate::money::from_micros;
+use crate::period::TimeRange;
+use crate::utils::{
+    load_events, load_pricing, normalize_events, parse_month, print_budget_table,
+    resolve_model_alias, resolve_provider_alias, round4,
+};
+
+const WEBHOOK_TIMEOUT: StdDuration = StdDuration::from_secs(10);
+
+pub fn run_budget(args: BudgetArgs) -> Result<()> {
+    match args.command {
+        BudgetCommand::Check(check) => run_budget_check(check),
+    }
+}
+
+fn run_budget_check(args: BudgetCheckArgs) -> Result<()> {
+    let config = load_budget_config(&args.budgets)?;
+    let pricing = load_pricing(&args.pricing)?;
+    let events = normalize_events(load_events(&args.events)?, &pricing);
+    let as_of = budget_as_of(args.month.as_deref(), Utc::now())?;
+    let report = evaluate_budgets(
+        &events,
+        &pricing,
+        &config,
+        args.on_unpriced,
+        args.allocation,
+        as_of,
+    )?;
+    let output = if args.json_output {
+        OutputMode::Json
+    } else {
+        args.output
+    };
+    match output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_budget_table(&report),
+        _ => return Err(anyhow!("budget check supports table and json output")),
+    }
+    if args.alert {
+        let sent = send_budget_alerts(&config.alerts, &report)?;
+        eprintln!("budget alerts sent: {}", sent);
+    }
+    if report.status != BudgetStatus::Ok && report.status >= args.fail_on {
+        io::stdout().flush()?;
+        std::process::exit(report.status.exit_code());
+    }
+    Ok(())
+}
+
+pub fn load_budget_config(path: &Path) -> Result<BudgetConfig> {
+    let config: BudgetConfig =
+        serde_json::from_reader(File::open(path).with_context(|| format!("opening {:?}", path))?)
+            .with_context(|| format!("parsing budgets {:?}", path))?;
+    for budget in &config.budgets {
+        if budget.amount_usd <= 0.0 {
+            return Err(anyhow!(
+                "budget '{}' needs a positive amount_usd",
+                budget.name
+            ));
+        }
+        if !(budget.warn_at > 0.0 && budget.warn_at <= 1.0) {
+            return Err(anyhow!(
+                "budget '{}' warn_at must be in (0, 1], got {}",
+                budget.name,
+                budget.warn_at
+            ));
+        }
+        match (budget.period, budget.window.as_deref()) {
+            (BudgetPeriod::Rolling, Some(window)) => {
+                TimeRange::trailing(window, Utc::now())
+                    .with_context(|| format!("budget '{}' window", budget.name))?;
+            }
+            (BudgetPeriod::Rolling, None) => {
+                return Err(anyhow!("rolling budget '{}' needs a window", budget.name))
+            }
+            (BudgetPeriod::Monthly, Some(_)) => {
+                return Err(anyhow!(
+                    "budget '{}' has a window but is not rolling",
+                    budget.name
+                ))
+            }
+            (BudgetPeriod::Monthly, None) => {}
+        }
+    }
+    Ok(config)
+}
+
+// Budgets are checked now, or with `--month` at the end of that month if it is over.
+pub fn budget_as_of(month: Option<&str>, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let Some(month) = month else {
+        return Ok(now);
+    };
+    let end = TimeRange::month(parse_month(month)?)
+        .to
+        .expect("months have an end");
+    Ok(end.min(now))
+}
+
+// Each distinct period is priced once, with subscriptions charged for the part of each month
+// inside it, and sliced per budget scope.
+pub fn evaluate_budgets(
+    events: &[UsageEvent],
+    pricing: &PricingBook,
+    config: &BudgetConfig,
+    on_unpriced: OnUnpricedAction,
+    allocation: Option<AllocationStrategy>,
+    as_of: DateTime<Utc>,
+) -> Result<BudgetReport> {
+    let last_day = (as_of - Duration::seconds(1)).date_naive();
+    let month = TimeRange::month((last_day.year(), last_day.month()));
+    let mut periods: BTreeMap<String, TimeRange> = BTreeMap::new();
+    for budget in &config.budgets {
+        let (label, range) = budget_period(budget, month, as_of)?;
+        periods.insert(label, range);
+    }
+    let in_period: BTreeMap<&str, Vec<UsageEvent>> = periods
+        .iter()
+        .map(|(label, range)| {
+            let events = events
+                .iter()
+                .filter(|evt| range.contains(evt.timestamp))
+                .cloned()
+                .collect();
+            (label.as_str(), events)
+        })
+        .collect();
+    let money = CurrencyConverter::usd();
+    let mut priced: BTreeMap<&str, PricedEvents> = BTreeMap::new();
+    for (label, events) in &in_period {
+        let allocator =
+            SubscriptionAllocator::build_over(events, pricing, allocation, &periods[*label]);
+        priced.insert(
+            label,
+            price_events(events, pricing, on_unpriced, &allocator, &money)?,
+        );
+    }
+
+    let mut results = Vec::with_capacity(config.budgets.len());
+    for budget in &config.budgets {
+        let (label, _) = budget_period(budget, month, as_of)?;
+        let scope = ResolvedScope::new(&budget.scope, pricing);
+        let slice = priced[label.as_str()].slice(|evt| scope.matches(evt));
+        let spent = summarize_priced(&slice, &money, None).monthly_total_usd;
+        let projected = (budget.period == BudgetPeriod::Monthly)
+            .then(|| projected_month_total(&slice, spent, last_day));
+        results.push(budget_result(
+            budget,
+            scope.label(),
+            label,
+            spent,
+            projected,
+        ));
+    }
+    Ok(BudgetReport {
+        as_of,
+        currency: USD.to_string(),
+        status: results
+            .iter()
+            .map(|result| result.status)
+            .max()
+            .unwrap_or_default(),
+        budgets: results,
+    })
+}
+
+// The budget's period label and range: the month holding `as_of`, or the trailing window.
+fn budget_period(
+    budget: &Budget,
+    month: TimeRange,
+    as_of: DateTime<Utc>,
+) -> Result<(String, TimeRange)> {
+    match (budget.period, budget.window.as_deref()) {
+        (BudgetPeriod::Rolling, Some(window)) => {
+            Ok((window.to_string(), TimeRange::trailing(window, as_of)?))
+        }
+        (BudgetPeriod::Rolling, None) => {
+            Err(anyhow!("rolling budget '{}' needs a window", budget.name))
+        }
+        (BudgetPeriod::Monthly, _) => {
+            let from = month.from.expect("months have a start");
+            Ok((format!("{:04}-{:02}", from.year(), from.month()), month))
+        }
+    }
+}
+
+// Spend so far plus the weekday-aware projection of the month's remaining variable cost.
+fn projected_month_total(slice: &PricedEvents, spent: f64, last_day: NaiveDate) -> f64 {
+    let mut daily_spend: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for priced in &slice.events {
+        *daily_spend
+            .entry(priced.event.timestamp.date_naive())
+            .or_default() += from_micros(priced.variable_micros);
+    }
+    let start = last_day.with_day(1).expect("first of month");
+    let daily: Vec<(NaiveDate, f64)> = days(start, last_day)
+        .into_iter()
+        .map(|day| (day, daily_spend.get(&day).copied().unwrap_or(0.0)))
+        .collect();
+    let remaining = days(last_day + Duration::days(1), month_end(last_day));
+    let series = forecast_series("budget", &daily, &remaining, ForecastMethod::Weekday);
+    round4(spent + series.projected_usd - series.spent_usd)
+}
+
+fn budget_result(
+    budget: &Budget,
+    scope: String,
+    period: String,
+    spent: f64,
+    projected: Option<f64>,
+) -> BudgetResult {
+    let status = if spent >= budget.amount_usd {
+        BudgetStatus::Exceeded
+    } else if spent >= budget.amount_usd * budget.warn_at
+        || projected.is_some_and(|projected| projected > budget.amount_usd)
+    {
+        BudgetStatus::Warn
+    } else {
+        BudgetStatus::Ok
+    };
+    BudgetResult {
+        name: budget.name.clone(),
+        scope,
+        period,
+        amount_usd: budget.amount_usd,
+        spent_usd: spent,
+        used_ratio: round4(spent / budget.amount_usd),
+        projected_usd: projected.map(|projected| (projected * 100.0).round() / 100.0),
+        status,
+    }
+}
+
+// A budget scope with provider and model names resolved through the pricing aliases, the way
+// events are normalized.
+struct ResolvedScope<'a> {
+    provider: Option<String>,
+    model: Option<String>,
+    tag: Option<&'a str>,
+}
+
+impl<'a> ResolvedScope<'a> {
+    fn new(scope: &'a BudgetScope, pricing: &PricingBook) -> Self {
+        let provider = scope
+            .provider
+            .as_deref()
+            .map(|provider| resolve_provider_alias(provider, pricing));
+        let model = scope.model.as_deref().map(|model| match &provider {
+            Some(provider) => resolve_model_alias(provider, model, pricing),
+            None => model.to_string(),
+        });
+        Self {
+            provider,
+            model,
+            tag: scope.tag.as_deref(),
+        }
+    }
+
+    fn matches(&self, evt: &UsageEvent) -> bool {
+        self.provider
+            .as_ref()
+            .is_none_or(|provider| *provider == evt.provider)
+            && self.model.as_ref().is_none_or(|model| *model == evt.model)
+            && self
+                .tag
+                .is_none_or(|tag| evt.tags.iter().any(|have| have == tag))
+    }
+
+    fn label(&self) -> String {
+        let parts: Vec<String> = [
+            ("provider", self.provider.as_deref()),
+            ("model", self.model.as_deref()),
+            ("tag", self.tag),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| format!("{}={}", key, value)))
+        .collect();
+        if parts.is_empty() {
+            "all".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+// Sends one alert with every budget at or above `alerts.on` to each configured target; returns
+// the number of targets alerted.
+pub fn send_budget_alerts(alerts: &BudgetAlertConfig, report: &BudgetReport) -> Result<usize> {
+    let budgets: Vec<BudgetResult> = report
+        .budgets
+        .iter()
+        .filter(|budget| budget.status >= alerts.on)
+        .cloned()
+        .collect();
+    if budgets.is_empty() {
+        return Ok(0);
+    }
+    let alert = BudgetAlert {
+        generated_at: Utc::now(),
+        as_of: report.as_of,
+        status: report.status,
+        budgets,
+    };
+    let body = serde_json::to_vec(&alert)?;
+    let mut sent = 0;
+    if let Some(url) = alerts.webhook_url.as_deref() {
+        post_webhook(url, &body)?;
+        sent += 1;
+    }
+    if let Some(command) = alerts.command.as_deref() {
+        run_alert_command(command, &body)?;
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+fn post_webhook(url: &str, body: &[u8]) -> Result<()> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("creating webhook runtime")?;
+    rt.block_on(async {
+        reqwest::Client::new()
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec())
+            .timeout(WEBHOOK_TIMEOUT)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("posting budget alert to {}", url))?;
+        Ok(())
+    })
+}
+
+fn run_alert_command(command: &str, body: &[u8]) -> Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let mut child = shell
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("running budget alert command '{}'", command))?;
+    let written = child.stdin.take().expect("piped stdin").write_all(body);
+    // Commands that ignore the payload may exit before reading it.
+    if let Err(err) = written {
+        if err.kind() != io::ErrorKind::BrokenPipe {
+            return Err(err).context("writing budget alert to command");
+        }
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!(
+            "budget alert command '{}' failed: {}",
+            command,
+            status
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+
+    fn event(provider: &str, day: u32, mtok: u64, tags: &[&str]) -> UsageEvent {
+        UsageEvent {
+            provider: provider.to_string(),
+            model: "m".to_string(),
+            session_id: format!("{}-{}", provider, day),
+            timestamp: Utc.with_ymd_and_hms(2026, 2, day, 12, 0, 0).unwrap(),
+            usage: TokenUsage {
+                input_tokens: mtok * 1_000_000,
+                output_tokens: 0,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 0,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    fn pricing() -> PricingBook {
+        let provider = |subscription: f64| ProviderPricing {
+            currency: None,
+            subscription_usd_month: subscription,
+            models: HashMap::from([(
+                "m".to_string(),
+                ModelRate {
+                    input_usd_per_mtok: 1.0,
+                    output_usd_per_mtok: 1.0,
+                    cache_write_usd_per_mtok: None,
+                    cache_write_1h_usd_per_mtok: None,
+                    cache_read_usd_per_mtok: None,
+                    tool_input_usd_per_mtok: None,
+                    tool_output_usd_per_mtok: None,
+                    effective_from: None,
+                    effective_to: None,
+                    context_tiers: Vec::new(),
+                }
+                .into(),
+            )]),
+            model_aliases: HashMap::new(),
+            service_tiers: HashMap::new(),
+            allocation: None,
+            seats: Vec::new(),
+            plan: None,
+            plans: HashMap::new(),
+            contract: None,
+        };
+        PricingBook {
+            providers: HashMap::from([
+                ("claude".to_string(), provider(20.0)),
+                ("codex".to_string(), provider(0.0)),
+            ]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        }
+    }
+
+    fn budget(name: &str, amount_usd: f64, scope: BudgetScope) -> Budget {
+        Budget {
+            name: name.to_string(),
+            amount_usd,
+            scope,
+            period: BudgetPeriod::Monthly,
+            window: None,
+            warn_at: 0.8,
+        }
+    }
+
+    #[test]
+    fn test_budgets_report_spend_forecast_and_status() {
+        // $1/day on codex for Feb 1-14, $5 of claude usage tagged for project api.
+        let mut events: Vec<UsageEvent> = (1..=14).map(|day| event("codex", day, 1, &[])).collect();
+        events.push(event("claude", 14, 5, &["project:api"]));
+        let scope = |provider: Option<&str>, tag: Option<&str>| BudgetScope {
+            provider: provider.map(str::to_string),
+            model: None,
+            tag: tag.map(str::to_string),
+        };
+        let config = BudgetConfig {
+            budgets: vec![
+                budget("all", 30.0, scope(None, None)),
+                budget("codex", 20.0, scope(Some("codex"), None)),
+                budget("api", 100.0, scope(None, Some("project:api"))),
+                Budget {
+                    period: BudgetPeriod::Rolling,
+                    window: Some("3d".to_string()),
+                    ..budget("codex-3d", 4.0, scope(Some("codex"), None))
+                },
+            ],
+            alerts: BudgetAlertConfig::default(),
+        };
+        let as_of = Utc.with_ymd_and_hms(2026, 2, 15, 0, 0, 0).unwrap();
+        let report = evaluate_budgets(
+            &events,
+            &pricing(),
+            &config,
+            OnUnpricedAction::Error,
+            None,
+            as_of,
+        )
+        .unwrap();
+
+        let status: Vec<(&str, BudgetStatus)> = report
+            .budgets
+            .iter()
+            .map(|budget| (budget.name.as_str(), budget.status))
+            .collect();
+        assert_eq!(
+            status,
+            [
+                ("all", BudgetStatus::Exceeded),
+                ("codex", BudgetStatus::Warn),
+                ("api", BudgetStatus::Ok),
+                ("codex-3d", BudgetStatus::Ok),
+            ]
+        );
+        assert_eq!(report.status, BudgetStatus::Exceeded);
+        // $14 codex, $5 claude usage and its $20 subscription.
+        assert_eq!(report.budgets[0].spent_usd, 39.0);
+        // $14 so far at $1 a day, 14 days to go: under budget now, over by month end.
+        let codex = &report.budgets[1];
+        assert_eq!(codex.spent_usd, 14.0);
+        assert_eq!(codex.projected_usd, Some(28.0));
+        assert_eq!(report.budgets[2].spent_usd, 25.0);
+        assert_eq!(report.budgets[2].scope, "tag=project:api");
+        assert_eq!(report.budgets[3].period, "3d");
+        assert_eq!(report.budgets[3].spent_usd, 3.0);
+        assert_eq!(report.budgets[3].projected_usd, None);
+    }
+
+    #[test]
+    fn test_budget_alerts_reach_webhook_and_command() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("alert.json");
+        let report = BudgetReport {
+            as_of: Utc.with_ymd_and_hms(2026, 2, 15, 0, 0, 0).unwrap(),
+            currency: USD.to_string(),
+            status: BudgetStatus::Warn,
+            budgets: [("quiet", BudgetStatus::Ok), ("loud", BudgetStatus::Warn)]
+                .into_iter()
+                .map(|(name, status)| BudgetResult {
+                    name: name.to_string(),
+                    scope: "all".to_string(),
+                    period: "2026-02".to_string(),
+                    amount_usd: 10.0,
+                    spent_usd: 8.0,
+                    used_ratio: 0.8,
+                    projected_usd: None,
+                    status,
+                })
+                .collect(),
+        };
+        let alerts = BudgetAlertConfig {
+            webhook_url: Some(url),
+            command: cfg!(unix).then(|| format!("cat > '{}'", out.display())),
+            on: BudgetStatus::Warn,
+        };
+        let sent = send_budget_alerts(&alerts, &report).unwrap();
+
+        let body: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!(body["status"], "warn");
+        assert_eq!(body["budgets"].as_array().unwrap().len(), 1);
+        assert_eq!(body["budgets"][0]["name"], "loud");
+        if cfg!(unix) {
+            assert_eq!(sent, 2);
+            let written: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+            assert_eq!(written, body);
+        }
+
+        let quiet = BudgetAlertConfig {
+            on: BudgetStatus::Exceeded,
+            ..alerts
+        };
+        assert_eq!(send_budget_alerts(&quiet, &report).unwrap(), 0);
+    }
+}
@@ -30,7 +30,11 @@ pub fn build_coverage_report(events: &[UsageEvent], pricing: &PricingBook) -> Co
 
         let provider = &pricing.providers[&provider_name];
         let model_name = resolve_model_alias(&provider_name, &event.model, pricing);
-        if !provider.models.contains_key(&model_name) {
+        let has_rate = provider
+            .models
+            .get(&model_name)
+            .is_some_and(|schedule| schedule.rate_at(event.timestamp).is_some());
+        if !has_rate {
             missing_models_by_provider
                 .entry(provider_name.clone())
                 .or_default()
@@ -96,6 +100,9 @@ pub fn build_coverage_report(events: &[UsageEvent], pricing: &PricingBook) -> Co
         totals_tokens += event.usage.total();
     }
 
+    let long_context_by_model = crate::cost::collect_long_context_usage(events, pricing);
+    let long_context_events = long_context_by_model.iter().map(|usage| usage.events).sum();
+
     CoverageReport {
         month,
         totals: crate::models::CoverageTotals {
@@ -108,6 +115,8 @@ pub fn build_coverage_report(events: &[UsageEvent], pricing: &PricingBook) -> Co
         missing_models_by_provider: missing_models_by_provider_vecs,
         suggested_provider_aliases,
         suggested_model_aliases_by_provider,
+        long_context_events,
+        long_context_by_model,
     }
 }
 
@@ -121,7 +130,10 @@ pub fn collect_unpriced_events(events: &[UsageEvent], pricing: &PricingBook) ->
             }
             let provider = &pricing.providers[&provider_name];
             let model_name = resolve_model_alias(&provider_name, &event.model, pricing);
-            !provider.models.contains_key(&model_name)
+            provider
+                .models
+                .get(&model_name)
+                .is_none_or(|schedule| schedule.rate_at(event.timestamp).is_none())
         })
         .cloned()
         .collect()
@@ -246,9 +258,16 @@ mod tests {
         providers.insert(
             "openai".to_string(),
             ProviderPricing {
+                currency: None,
                 subscription_usd_month: 0.0,
                 models: std::collections::HashMap::new(),
                 model_aliases: std::collections::HashMap::new(),
+                service_tiers: std::collections::HashMap::new(),
+                allocation: None,
+                seats: Vec::new(),
+                plan: None,
+                plans: std::collections::HashMap::new(),
+                contract: None,
             },
         );
         let pricing = PricingBook {
@@ -267,9 +286,16 @@ mod tests {
         providers.insert(
             "openai".to_string(),
             ProviderPricing {
+                currency: None,
                 subscription_usd_month: 0.0,
                 models: std::collections::HashMap::new(),
                 model_aliases: std::collections::HashMap::new(),
+                service_tiers: std::collections::HashMap::new(),
+                allocation: None,
+                seats: Vec::new(),
+                plan: None,
+                plans: std::collections::HashMap::new(),
+                contract: None,
             },
         );
         let mut provider_aliases = std::collections::HashMap::new();
@@ -293,18 +319,30 @@ mod tests {
                 input_usd_per_mtok: 0.5,
                 output_usd_per_mtok: 1.0,
                 cache_write_usd_per_mtok: None,
+                cache_write_1h_usd_per_mtok: None,
                 cache_read_usd_per_mtok: None,
                 tool_input_usd_per_mtok: None,
                 tool_output_usd_per_mtok: None,
-            },
+                effective_from: None,
+                effective_to: None,
+                context_tiers: Vec::new(),
+            }
+            .into(),
         );
         let mut providers = std::collections::HashMap::new();
         providers.insert(
             "openai".to_string(),
             ProviderPricing {
+                currency: None,
                 subscription_usd_month: 0.0,
                 models,
                 model_aliases: std::collections::HashMap::new(),
+                service_tiers: std::collections::HashMap::new(),
+                allocation: None,
+                seats: Vec::new(),
+                plan: None,
+                plans: std::collections::HashMap::new(),
+                contract: None,
             },
         );
         let pricing = PricingBook {
@@ -326,10 +364,15 @@ mod tests {
                 input_usd_per_mtok: 0.5,
                 output_usd_per_mtok: 1.0,
                 cache_write_usd_per_mtok: None,
+                cache_write_1h_usd_per_mtok: None,
                 cache_read_usd_per_mtok: None,
                 tool_input_usd_per_mtok: None,
                 tool_output_usd_per_mtok: None,
-            },
+                effective_from: None,
+                effective_to: None,
+                context_tiers: Vec::new(),
+            }
+            .into(),
         );
         let mut model_aliases = std::collections::HashMap::new();
         model_aliases.insert("gpt-4-turbo".to_string(), "gpt-4".to_string());
@@ -337,9 +380,16 @@ mod tests {
         providers.insert(
             "openai".to_string(),
             ProviderPricing {
+                currency: None,
                 subscription_usd_month: 0.0,
                 models,
                 model_aliases,
+                service_tiers: std::collections::HashMap::new(),
+                allocation: None,
+                seats: Vec::new(),
+                plan: None,
+                plans: std::collections::HashMap::new(),
+                contract: None,
             },
         );
         let pricing = PricingBook {
@@ -383,10 +433,13 @@ mod tests {
                 input_tokens: 100,
                 output_tokens: 100,
                 cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
                 cache_read_tokens: 0,
                 tool_input_tokens: 0,
                 tool_output_tokens: 0,
             },
+            service_tier: None,
+            tags: Vec::new(),
         }];
 
         let report = build_coverage_report(&events, &pricing);
@@ -1,8 +1,10 @@
 use chrono::{DateTime, Utc};
 use clap::{Args, Parser, Subcommand, ValueEnum};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::anomalies::{DEFAULT_ANOMALY_MIN_USD, DEFAULT_ANOMALY_THRESHOLD};
+
 #[derive(Parser, Debug)]
 #[command(name = "tokenledger")]
 #[command(about = "Fast token/session usage and blended cost analytics")]
@@ -15,7 +17,19 @@ pub struct Cli {
 pub enum Command {
     Monthly(MonthlyArgs),
     Daily(DailyArgs),
+    Series(SeriesArgs),
     Coverage(CoverageArgs),
+    PlanAnalysis(PlanAnalysisArgs),
+    Windows(WindowsArgs),
+    Price(PriceArgs),
+    Whatif(WhatifArgs),
+    Cache(CacheArgs),
+    Sessions(SessionsArgs),
+    Forecast(ForecastArgs),
+    Anomalies(AnomaliesArgs),
+    Compare(CompareArgs),
+    Budget(BudgetArgs),
+    Tui(TuiArgs),
     PricingCheck(PricingCheckArgs),
     PricingApply(PricingApplyArgs),
     PricingReconcile(PricingReconcileArgs),
@@ -25,6 +39,7 @@ pub enum Command {
     Bench(BenchArgs),
     Orchestrate(OrchestrateArgs),
     Benchmarks(BenchmarksArgs),
+    Ledger(LedgerArgs),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -50,22 +65,113 @@ pub struct QueryArgs {
         help = "Behavior when events reference provider/model entries missing from pricing"
     )]
     pub on_unpriced: OnUnpricedAction,
+    #[arg(
+        long,
+        value_enum,
+        help = "Subscription allocation strategy; overrides per-provider `allocation` in pricing"
+    )]
+    pub allocation: Option<AllocationStrategy>,
+    #[arg(
+        long,
+        default_value = "USD",
+        help = "Report currency (ISO 4217 code); non-USD amounts need --fx-rates"
+    )]
+    pub currency: String,
+    #[arg(long, help = "FX rate table JSON with dated rates per currency")]
+    pub fx_rates: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = FxDateMode::EventDay,
+        help = "Which day's FX rate converts each event"
+    )]
+    pub fx_date: FxDateMode,
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Keep no session ids so memory stays flat on very large event logs; session counts read 0"
+    )]
+    pub no_session_counts: bool,
+    #[command(flatten)]
+    pub export: ExportArgs,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct ExportArgs {
+    #[arg(
+        long,
+        help = "Write csv/xlsx/html output to this path (csv and html default to stdout, xlsx requires a path)"
+    )]
+    pub output_path: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CsvLayout::Flat,
+        help = "CSV layout: flat (one table with a section column) or sections (one table per section)"
+    )]
+    pub csv_layout: CsvLayout,
+}
+
+// The time range a report covers; without flags, every event.
+#[derive(Args, Debug, Clone, Default)]
+pub struct PeriodArgs {
+    #[arg(long, help = "Month in YYYY-MM", conflicts_with_all = ["from", "to", "last"])]
+    pub month: Option<String>,
+    #[arg(
+        long,
+        help = "Range start: RFC3339 timestamp, or YYYY-MM-DD / YYYY-MM from its first day"
+    )]
+    pub from: Option<String>,
+    #[arg(
+        long,
+        help = "Range end: RFC3339 timestamp (exclusive), or YYYY-MM-DD / YYYY-MM through its last day"
+    )]
+    pub to: Option<String>,
+    #[arg(
+        long,
+        help = "Rolling window ending now, e.g. 24h, 30d or 12w",
+        conflicts_with_all = ["from", "to"]
+    )]
+    pub last: Option<String>,
 }
 
 #[derive(Parser, Debug)]
 pub struct MonthlyArgs {
     #[command(flatten)]
     pub query: QueryArgs,
-    #[arg(long, help = "Month in YYYY-MM")]
-    pub month: Option<String>,
+    #[command(flatten)]
+    pub period: PeriodArgs,
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "Report a pivot grouped by these dimensions, e.g. provider,model,day,session"
+    )]
+    pub group_by: Vec<GroupDimension>,
+    #[arg(
+        long,
+        requires = "group_by",
+        help = "Nest each --group-by level under the previous one instead of listing leaf groups"
+    )]
+    pub nested: bool,
 }
 
 #[derive(Parser, Debug)]
 pub struct DailyArgs {
     #[command(flatten)]
     pub query: QueryArgs,
-    #[arg(long, help = "Month in YYYY-MM")]
-    pub month: Option<String>,
+    #[command(flatten)]
+    pub period: PeriodArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct SeriesArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+    #[command(flatten)]
+    pub period: PeriodArgs,
+    #[arg(long, value_enum, default_value_t = Granularity::Day)]
+    pub granularity: Granularity,
 }
 
 #[derive(Parser, Debug)]
@@ -76,14 +182,367 @@ pub struct CoverageArgs {
     pub pricing: PathBuf,
     #[arg(long, help = "Month in YYYY-MM")]
     pub month: Option<String>,
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, conflicts_with = "output")]
     pub json_output: bool,
+    #[arg(
+        long,
+        default_value = "table",
+        help = "Output format (table, json, csv, xlsx); --json-output is shorthand for json"
+    )]
+    pub output: OutputMode,
+    #[command(flatten)]
+    pub export: ExportArgs,
     #[arg(long, help = "Write suggested pricing patch JSON to this path")]
     pub write_patch: Option<PathBuf>,
     #[arg(long, help = "Write unpriced events JSONL to this path")]
     pub write_unpriced_events: Option<PathBuf>,
 }
 
+#[derive(Parser, Debug)]
+pub struct PlanAnalysisArgs {
+    #[arg(long = "events", required = true)]
+    pub events: Vec<PathBuf>,
+    #[arg(long, default_value = "pricing.example.json")]
+    pub pricing: PathBuf,
+    #[arg(
+        long,
+        help = "Month in YYYY-MM; defaults to the month of the latest event"
+    )]
+    pub month: Option<String>,
+    #[arg(long = "provider")]
+    pub providers: Vec<String>,
+    #[arg(long, default_value_t = false, conflicts_with = "output")]
+    pub json_output: bool,
+    #[arg(
+        long,
+        default_value = "table",
+        help = "Output format (table, json, csv, xlsx); --json-output is shorthand for json"
+    )]
+    pub output: OutputMode,
+    #[command(flatten)]
+    pub export: ExportArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct WindowsArgs {
+    #[arg(long = "events", required = true)]
+    pub events: Vec<PathBuf>,
+    #[arg(long, default_value = "pricing.example.json")]
+    pub pricing: PathBuf,
+    #[arg(long = "provider")]
+    pub providers: Vec<String>,
+    #[arg(long, help = "RFC3339 time to evaluate windows at; defaults to now")]
+    pub now: Option<DateTime<Utc>>,
+    #[arg(
+        long,
+        help = "Window checkpoint path; only events appended since the last run are read"
+    )]
+    pub checkpoint: Option<PathBuf>,
+    #[arg(long, default_value_t = false, conflicts_with = "output")]
+    pub json_output: bool,
+    #[arg(
+        long,
+        default_value = "table",
+        help = "Output format (table, json); --json-output is shorthand for json"
+    )]
+    pub output: OutputMode,
+}
+
+#[derive(Parser, Debug)]
+pub struct CacheArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+    #[command(flatten)]
+    pub period: PeriodArgs,
+}
+
+#[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct SessionsArgs {
+    #[command(subcommand)]
+    pub command: Option<SessionsCommand>,
+    #[command(flatten)]
+    pub query: QueryArgs,
+    #[command(flatten)]
+    pub period: PeriodArgs,
+    #[arg(long, value_enum, default_value_t = SessionSort::Cost)]
+    pub sort: SessionSort,
+    #[arg(long, help = "Only list sessions costing at least this much")]
+    pub min_cost: Option<f64>,
+    #[arg(long, help = "Limit the number of sessions listed")]
+    pub top: Option<usize>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SessionsCommand {
+    /// Event-by-event timeline of one session
+    Show(Box<SessionShowArgs>),
+}
+
+#[derive(Parser, Debug)]
+pub struct SessionShowArgs {
+    pub session_id: String,
+    #[command(flatten)]
+    pub query: QueryArgs,
+    #[command(flatten)]
+    pub period: PeriodArgs,
+}
+
+// Order of `sessions` rows; every order but `start` lists the largest first.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum SessionSort {
+    #[default]
+    Cost,
+    Tokens,
+    Events,
+    Duration,
+    Start,
+}
+
+#[derive(Parser, Debug)]
+pub struct ForecastArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+    #[command(flatten)]
+    pub period: PeriodArgs,
+    #[arg(long, value_enum, default_value_t = ForecastMethod::Weekday)]
+    pub method: ForecastMethod,
+    #[arg(
+        long,
+        help = "Last day of history in YYYY-MM-DD; defaults to the last day with events"
+    )]
+    pub as_of: Option<String>,
+}
+
+// How `forecast` extends daily history to the end of the period.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ForecastMethod {
+    // Mean daily spend.
+    RunRate,
+    // Mean spend of the same weekday.
+    #[default]
+    Weekday,
+    // Exponentially weighted recent spend.
+    Ewma,
+}
+
+impl ForecastMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RunRate => "run-rate",
+            Self::Weekday => "weekday",
+            Self::Ewma => "ewma",
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct AnomaliesArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+    #[command(flatten)]
+    pub period: PeriodArgs,
+    #[arg(long, value_enum, default_value_t = Granularity::Day, help = "day or hour")]
+    pub granularity: Granularity,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_ANOMALY_THRESHOLD,
+        help = "Robust z-score at or above which a bucket is flagged"
+    )]
+    pub threshold: f64,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_ANOMALY_MIN_USD,
+        help = "Minimum spend above the baseline for a bucket to be flagged"
+    )]
+    pub min_usd: f64,
+}
+
+#[derive(Parser, Debug)]
+pub struct CompareArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+    #[command(flatten)]
+    pub period: PeriodArgs,
+    #[arg(
+        long,
+        help = "Baseline month in YYYY-MM; defaults to the period just before",
+        conflicts_with_all = ["against_from", "against_to"]
+    )]
+    pub against: Option<String>,
+    #[arg(long, help = "Baseline range start, in the forms --from accepts")]
+    pub against_from: Option<String>,
+    #[arg(long, help = "Baseline range end, in the forms --to accepts")]
+    pub against_to: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct BudgetArgs {
+    #[command(subcommand)]
+    pub command: BudgetCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BudgetCommand {
+    /// Evaluate budgets; exits 2 when one is exceeded, or 3 on warnings with --fail-on warn
+    Check(BudgetCheckArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct BudgetCheckArgs {
+    #[arg(long = "events", required = true)]
+    pub events: Vec<PathBuf>,
+    #[arg(long, default_value = "pricing.example.json")]
+    pub pricing: PathBuf,
+    #[arg(long, default_value = "budgets.json")]
+    pub budgets: PathBuf,
+    #[arg(
+        long,
+        help = "Evaluate as of the end of this month (YYYY-MM) instead of now"
+    )]
+    pub month: Option<String>,
+    #[arg(long, value_enum, default_value_t = OnUnpricedAction::Error)]
+    pub on_unpriced: OnUnpricedAction,
+    #[arg(
+        long,
+        value_enum,
+        help = "Subscription allocation strategy; overrides per-provider `allocation` in pricing"
+    )]
+    pub allocation: Option<AllocationStrategy>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = BudgetStatus::Exceeded,
+        help = "Lowest budget status that fails the check"
+    )]
+    pub fail_on: BudgetStatus,
+    #[arg(long, help = "Send the alerts configured in the budgets file")]
+    pub alert: bool,
+    #[arg(long, default_value_t = false, conflicts_with = "output")]
+    pub json_output: bool,
+    #[arg(
+        long,
+        default_value = "table",
+        help = "Output format (table, json); --json-output is shorthand for json"
+    )]
+    pub output: OutputMode,
+}
+
+#[derive(Parser, Debug)]
+pub struct TuiArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+    #[arg(long, help = "Month in YYYY-MM; defaults to the current month")]
+    pub month: Option<String>,
+    #[arg(long, help = "Budgets file to show as gauges")]
+    pub budgets: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Aggregate cache path shared with `orchestrate --aggregate-cache-path`"
+    )]
+    pub aggregate_cache_path: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value_t = 2,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Seconds between checks of the events file for new events"
+    )]
+    pub refresh_secs: u64,
+}
+
+// A budget's state, from best to worst.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetStatus {
+    #[default]
+    Ok,
+    Warn,
+    Exceeded,
+}
+
+impl BudgetStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Warn => "warn",
+            Self::Exceeded => "exceeded",
+        }
+    }
+
+    // Process exit code of a failed `budget check`.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::Warn => 3,
+            Self::Exceeded => 2,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct WhatifArgs {
+    #[arg(long = "events", required = true)]
+    pub events: Vec<PathBuf>,
+    #[arg(long, default_value = "pricing.example.json")]
+    pub pricing: PathBuf,
+    #[arg(long, help = "Pricing book for simulated costs; defaults to --pricing")]
+    pub target_pricing: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Month in YYYY-MM; defaults to the month of the latest event"
+    )]
+    pub month: Option<String>,
+    #[arg(
+        long = "rule",
+        required = true,
+        help = "Substitution FROM=TO[,option=value...]: FROM and TO are provider[/model]; options are cache_hit_ratio, input_ratio and output_ratio"
+    )]
+    pub rules: Vec<String>,
+    #[arg(long, value_enum, default_value_t = OnUnpricedAction::Error)]
+    pub on_unpriced: OnUnpricedAction,
+    #[arg(long, default_value_t = false, conflicts_with = "output")]
+    pub json_output: bool,
+    #[arg(
+        long,
+        default_value = "table",
+        help = "Output format (table, json, csv, xlsx); --json-output is shorthand for json"
+    )]
+    pub output: OutputMode,
+    #[command(flatten)]
+    pub export: ExportArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct PriceArgs {
+    #[arg(long = "events", required = true)]
+    pub events: Vec<PathBuf>,
+    #[arg(long, default_value = "pricing.example.json")]
+    pub pricing: PathBuf,
+    #[arg(long, help = "Output path for priced JSONL; defaults to stdout")]
+    pub output: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OnUnpricedAction::Error,
+        help = "Behavior for events missing from pricing; skip and warn still write them without costs"
+    )]
+    pub on_unpriced: OnUnpricedAction,
+    #[arg(
+        long,
+        help = "Include each event's subscription share; reads all events into memory first"
+    )]
+    pub with_subscription: bool,
+    #[arg(
+        long,
+        value_enum,
+        help = "Subscription allocation strategy; overrides per-provider `allocation` in pricing"
+    )]
+    pub allocation: Option<AllocationStrategy>,
+}
+
 #[derive(Parser, Debug)]
 pub struct PricingCheckArgs {
     #[arg(long = "events", required = true)]
@@ -120,6 +579,11 @@ pub struct PricingApplyArgs {
         help = "Allow overwriting existing model rates/alias mappings"
     )]
     pub allow_overwrite_model_rates: bool,
+    #[arg(
+        long,
+        help = "RFC3339 start for patched rates without effective_from; existing models get a new period appended"
+    )]
+    pub effective_from: Option<DateTime<Utc>>,
 }
 
 #[derive(Parser, Debug)]
@@ -172,8 +636,16 @@ pub struct PricingAuditArgs {
     pub allow_stale: bool,
     #[arg(long, default_value_t = false)]
     pub allow_missing_source: bool,
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, conflicts_with = "output")]
     pub json_output: bool,
+    #[arg(
+        long,
+        default_value = "table",
+        help = "Output format (table, json, csv, xlsx); --json-output is shorthand for json"
+    )]
+    pub output: OutputMode,
+    #[command(flatten)]
+    pub export: ExportArgs,
 }
 
 #[derive(Parser, Debug)]
@@ -233,6 +705,12 @@ pub struct BenchArgs {
     pub warm_tail_events: usize,
     #[arg(long, default_value_t = 2_000)]
     pub burst_batch_events: usize,
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Streaming scenario: stream this many events generated from --events, spread over new sessions and days; 0 streams --events as is"
+    )]
+    pub stream_events: u64,
     #[arg(long, default_value_t = false)]
     pub json_output: bool,
     #[arg(long, value_enum, default_value_t = OnUnpricedAction::Error)]
@@ -248,8 +726,8 @@ pub struct BenchArgs {
     pub golden: Option<PathBuf>,
     #[arg(
         long,
-        default_value_t = 0.0001,
-        help = "Absolute epsilon for floating-point golden correctness comparisons"
+        default_value_t = 0.0,
+        help = "Absolute epsilon for golden blended $/MTok and MTok comparisons; money fields always match to the cent"
     )]
     pub golden_epsilon: f64,
     #[arg(
@@ -289,6 +767,12 @@ pub struct OrchestrateArgs {
     pub pricing: PathBuf,
     #[arg(long, value_enum, default_value_t = OnUnpricedAction::Error)]
     pub on_unpriced: OnUnpricedAction,
+    #[arg(
+        long,
+        value_enum,
+        help = "Subscription allocation strategy for the UI snapshot"
+    )]
+    pub allocation: Option<AllocationStrategy>,
     #[arg(long)]
     pub skip_ingest: bool,
     #[arg(long)]
@@ -379,8 +863,24 @@ pub struct OrchestrateArgs {
         help = "UI snapshot verbosity mode: compact (top lists) or extended (full provider/model breakdowns)"
     )]
     pub ui_snapshot_mode: UiSnapshotMode,
+    #[arg(
+        long,
+        help = "Write a self-contained HTML cost report for the month to this path"
+    )]
+    pub html_report_path: Option<PathBuf>,
     #[arg(long, help = "Write orchestrate pipeline summary JSON to this path")]
     pub pipeline_summary_path: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Budgets file to evaluate; status goes to the pipeline summary and UI snapshot"
+    )]
+    pub budgets: Option<PathBuf>,
+    #[arg(
+        long,
+        requires = "budgets",
+        help = "Send the alerts configured in the budgets file"
+    )]
+    pub budget_alerts: bool,
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -389,6 +889,8 @@ pub enum BenchScenario {
     WarmTail,
     Burst,
     All,
+    /// Monthly report over a streamed read of --events; not part of `all`
+    Streaming,
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -396,6 +898,22 @@ pub enum OutputMode {
     Table,
     Markdown,
     Json,
+    Csv,
+    Xlsx,
+    Html,
+}
+
+impl OutputMode {
+    pub fn is_export(self) -> bool {
+        matches!(self, OutputMode::Csv | OutputMode::Xlsx)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, ValueEnum)]
+pub enum CsvLayout {
+    #[default]
+    Flat,
+    Sections,
 }
 
 pub use ParetoRs::OnUnpricedAction;
@@ -407,6 +925,91 @@ pub enum UiSnapshotMode {
     Extended,
 }
 
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FxDateMode {
+    /// Rate in effect on each event's day
+    #[default]
+    EventDay,
+    /// Rate in effect on the last day of each event's month
+    MonthEnd,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AllocationStrategy {
+    /// Proportional to tokens
+    #[default]
+    Tokens,
+    /// Proportional to list-price variable cost
+    VariableCost,
+    /// Even split per active day, then by tokens within the day
+    ActiveDays,
+    /// Even split per session, then by tokens within the session
+    PerSession,
+    /// Each active seat-day is charged to that day's usage; idle seat-days stay unallocated
+    PerSeat,
+}
+
+impl AllocationStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Tokens => "tokens",
+            Self::VariableCost => "variable-cost",
+            Self::ActiveDays => "active-days",
+            Self::PerSession => "per-session",
+            Self::PerSeat => "per-seat",
+        }
+    }
+}
+
+// Bucket size of a time-series report.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Hour,
+    #[default]
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Granularity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Granularity::Hour => "hour",
+            Granularity::Day => "day",
+            Granularity::Week => "week",
+            Granularity::Month => "month",
+            Granularity::Quarter => "quarter",
+            Granularity::Year => "year",
+        }
+    }
+}
+
+// A dimension of a `--group-by` pivot.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupDimension {
+    Provider,
+    Model,
+    Day,
+    Session,
+}
+
+impl GroupDimension {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GroupDimension::Provider => "provider",
+            GroupDimension::Model => "model",
+            GroupDimension::Day => "day",
+            GroupDimension::Session => "session",
+        }
+    }
+}
+
 // =============================================================================
 // BENCHMARKS CLI
 // =============================================================================
@@ -476,3 +1079,73 @@ pub struct ValidateBenchmarksArgs {
     #[arg(long, help = "Config file to validate")]
     pub config: Option<PathBuf>,
 }
+
+// =============================================================================
+// LEDGER CLI
+// =============================================================================
+
+#[derive(Parser, Debug)]
+pub struct LedgerArgs {
+    #[command(subcommand)]
+    pub command: LedgerCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum LedgerCommand {
+    /// Build unified model/provider ledger CSV and SQL artifacts
+    Build(LedgerBuildArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct LedgerBuildArgs {
+    #[arg(long, default_value = "pricing.example.json")]
+    pub pricing: PathBuf,
+    #[arg(
+        long,
+        default_value = "models_normalized.csv",
+        help = "Normalized benchmark rows (model, benchmark, value_primary, is_missing)"
+    )]
+    pub models_csv: PathBuf,
+    #[arg(
+        long,
+        help = "BenchmarkData JSON array merged into the benchmark store (price fallback and runtime metrics)"
+    )]
+    pub benchmarks: Option<PathBuf>,
+    #[arg(
+        long = "runtime-snapshot",
+        help = "CLIProxyAPI runtime metrics snapshot JSON (repeatable)"
+    )]
+    pub runtime_snapshots: Vec<PathBuf>,
+    #[arg(
+        long,
+        default_value = "ledger",
+        help = "Output directory for generated ledger artifacts"
+    )]
+    pub ledger_dir: PathBuf,
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Skip runtime metrics and Pareto view artifacts"
+    )]
+    pub skip_pareto: bool,
+    #[arg(long, default_value_t = false)]
+    pub json_output: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_json_output_conflicts_with_explicit_output() {
+        Cli::command().debug_assert();
+        let parse = |extra: &[&str]| {
+            let base = ["tokenledger", "coverage", "--events", "events.jsonl"];
+            Cli::try_parse_from(base.iter().chain(extra))
+        };
+        assert!(parse(&["--json-output"]).is_ok());
+        assert!(parse(&["--output", "csv"]).is_ok());
+        assert!(parse(&["--json-output", "--output", "csv"]).is_err());
+    }
+}
@@ -0,0 +1,309 @@
+// Period-over-period comparison: two ranges' cost breakdowns side by side, per provider and model,
+// with each cost change attributed to volume (tokens) and price/mix ($/MTok).
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+
+use crate::analytics::{currency_converter, stream_query};
+use crate::cli::{CompareArgs, OutputMode, PeriodArgs, QueryArgs};
+use crate::cost::BreakdownAcc;
+use crate::export::{compare_tables, write_export};
+use crate::forecast::range_or_latest_month;
+use crate::html::{compare_html, write_html};
+use crate::models::*;
+use crate::money::{cents_to_units, units_to_cents};
+use crate::period::TimeRange;
+use crate::utils::{print_compare_markdown, print_compare_table, round2, round4};
+
+pub fn run_compare(args: CompareArgs) -> Result<()> {
+    let range = range_or_latest_month(&args.query, &args.period)?;
+    let against = baseline_range(&args, &range)?;
+    let current = build_compare_period(&args.query, &range)?;
+    let baseline = build_compare_period(&args.query, &against)?;
+    let report = build_compare(&current, &baseline);
+    match args.query.output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_compare_table(&report),
+        OutputMode::Markdown => print_compare_markdown(&report),
+        OutputMode::Html => write_html(&compare_html(&report), &args.query.export)?,
+        OutputMode::Csv | OutputMode::Xlsx => write_export(
+            &compare_tables(&report),
+            args.query.output,
+            &args.query.export,
+        )?,
+    }
+    Ok(())
+}
+
+// `--against` or `--against-from`/`--against-to`, or else the period just before `range`.
+fn baseline_range(args: &CompareArgs, range: &TimeRange) -> Result<TimeRange> {
+    if args.against.is_none() && args.against_from.is_none() && args.against_to.is_none() {
+        return range.previous().ok_or_else(|| {
+            anyhow!("open-ended periods need --against or --against-from/--against-to")
+        });
+    }
+    let baseline = PeriodArgs {
+        month: args.against.clone(),
+        from: args.against_from.clone(),
+        to: args.against_to.clone(),
+        last: None,
+    };
+    TimeRange::from_args(&baseline, Utc::now())
+}
+
+// One side of a comparison: the period's label, totals and per-model rows.
+pub struct ComparePeriod {
+    pub label: String,
+    pub totals: CostBreakdown,
+    // Keyed "provider/model", as `--group-by model` keys them.
+    pub models: Vec<NamedMetric>,
+}
+
+// The range's side of a comparison. A range without events compares as an empty breakdown, so
+// every row in the other period compares against zero.
+pub fn build_compare_period(query: &QueryArgs, range: &TimeRange) -> Result<ComparePeriod> {
+    let (stream, view) = stream_query(query, range, false, false)?;
+    if stream.is_empty(&view) {
+        let day = range
+            .from
+            .or(range.to)
+            .unwrap_or_else(Utc::now)
+            .date_naive();
+        return Ok(ComparePeriod {
+            label: range.label(day, day),
+            totals: BreakdownAcc::default().finish(&currency_converter(query)?, None),
+            models: Vec::new(),
+        });
+    }
+    let (label, totals) = stream.labelled_totals(&view)?;
+    Ok(ComparePeriod {
+        label,
+        totals,
+        models: stream.model_rows(&view)?,
+    })
+}
+
+pub fn build_compare(current: &ComparePeriod, baseline: &ComparePeriod) -> CompareReport {
+    let totals = compare_row(
+        "total",
+        Some(&Side::totals(&baseline.totals)),
+        Some(&Side::totals(&current.totals)),
+    );
+    CompareReport {
+        period: current.label.clone(),
+        against: baseline.label.clone(),
+        currency: current.totals.currency.clone(),
+        totals,
+        providers: compare_rows(
+            &baseline.totals.provider_breakdown,
+            &current.totals.provider_breakdown,
+        ),
+        models: compare_rows(&baseline.models, &current.models),
+    }
+}
+
+// The figures compared for one row in one period.
+struct Side {
+    cost_usd: f64,
+    tokens: u64,
+    blended_usd_per_mtok: f64,
+    sessions: usize,
+}
+
+impl Side {
+    fn totals(breakdown: &CostBreakdown) -> Self {
+        Self {
+            cost_usd: breakdown.monthly_total_usd,
+            tokens: breakdown.total_tokens,
+            blended_usd_per_mtok: breakdown.blended_usd_per_mtok,
+            sessions: breakdown.session_count,
+        }
+    }
+
+    fn metric(row: &NamedMetric) -> Self {
+        Self {
+            cost_usd: row.total_cost_usd,
+            tokens: row.tokens,
+            blended_usd_per_mtok: row.blended_usd_per_mtok,
+            sessions: row.session_count,
+        }
+    }
+}
+
+// Rows in either period, largest cost change first.
+fn compare_rows(baseline: &[NamedMetric], current: &[NamedMetric]) -> Vec<CompareRow> {
+    let mut sides: BTreeMap<&str, (Option<Side>, Option<Side>)> = BTreeMap::new();
+    for row in baseline {
+        sides.entry(&row.name).or_default().0 = Some(Side::metric(row));
+    }
+    for row in current {
+        sides.entry(&row.name).or_default().1 = Some(Side::metric(row));
+    }
+    let mut rows: Vec<CompareRow> = sides
+        .iter()
+        .map(|(name, (baseline, current))| compare_row(name, baseline.as_ref(), current.as_ref()))
+        .collect();
+    rows.sort_by(|a, b| {
+        b.cost_delta_usd
+            .abs()
+            .total_cmp(&a.cost_delta_usd.abs())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    rows
+}
+
+fn compare_row(name: &str, baseline: Option<&Side>, current: Option<&Side>) -> CompareRow {
+    let empty = Side {
+        cost_usd: 0.0,
+        tokens: 0,
+        blended_usd_per_mtok: 0.0,
+        sessions: 0,
+    };
+    let baseline = baseline.unwrap_or(&empty);
+    let current = current.unwrap_or(&empty);
+    let baseline_cents = units_to_cents(baseline.cost_usd);
+    let delta_cents = units_to_cents(current.cost_usd) - baseline_cents;
+    // Volume at the baseline's cost per token; without tokens on both sides the change is all
+    // volume.
+    let volume_cents = if baseline.tokens == 0 || current.tokens == 0 {
+        delta_cents
+    } else {
+        let per_token = baseline_cents as f64 / baseline.tokens as f64;
+        ((current.tokens as f64 - baseline.tokens as f64) * per_token).round() as i64
+    };
+    CompareRow {
+        name: name.to_string(),
+        baseline_cost_usd: baseline.cost_usd,
+        cost_usd: current.cost_usd,
+        cost_delta_usd: cents_to_units(delta_cents),
+        cost_delta_pct: (baseline_cents != 0)
+            .then(|| round2(delta_cents as f64 / baseline_cents as f64 * 100.0)),
+        baseline_tokens: baseline.tokens,
+        tokens: current.tokens,
+        tokens_delta: current.tokens as i64 - baseline.tokens as i64,
+        baseline_blended_usd_per_mtok: baseline.blended_usd_per_mtok,
+        blended_usd_per_mtok: current.blended_usd_per_mtok,
+        blended_delta_usd_per_mtok: round4(
+            current.blended_usd_per_mtok - baseline.blended_usd_per_mtok,
+        ),
+        baseline_sessions: baseline.sessions,
+        sessions: current.sessions,
+        sessions_delta: current.sessions as i64 - baseline.sessions as i64,
+        volume_effect_usd: cents_to_units(volume_cents),
+        price_mix_effect_usd: cents_to_units(delta_cents - volume_cents),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side(cost_usd: f64, tokens: u64, sessions: usize) -> Side {
+        Side {
+            cost_usd,
+            tokens,
+            blended_usd_per_mtok: round4(cost_usd / (tokens as f64 / 1_000_000.0)),
+            sessions,
+        }
+    }
+
+    #[test]
+    fn test_cost_change_splits_into_volume_and_price_mix() {
+        // Twice the tokens at a quarter less per token: $100 -> $150.
+        let row = compare_row(
+            "claude",
+            Some(&side(100.0, 10_000_000, 4)),
+            Some(&side(150.0, 20_000_000, 6)),
+        );
+        assert_eq!(row.cost_delta_usd, 50.0);
+        assert_eq!(row.cost_delta_pct, Some(50.0));
+        assert_eq!(row.volume_effect_usd, 100.0);
+        assert_eq!(row.price_mix_effect_usd, -50.0);
+        assert_eq!(row.tokens_delta, 10_000_000);
+        assert_eq!(row.sessions_delta, 2);
+        assert_eq!(row.blended_delta_usd_per_mtok, -2.5);
+
+        // Effects add up to the delta to the cent even when the split does not divide evenly.
+        let row = compare_row(
+            "codex",
+            Some(&side(10.0, 3_000_000, 1)),
+            Some(&side(7.77, 1_000_001, 1)),
+        );
+        assert_eq!(
+            units_to_cents(row.volume_effect_usd) + units_to_cents(row.price_mix_effect_usd),
+            units_to_cents(row.cost_delta_usd)
+        );
+    }
+
+    #[test]
+    fn test_rows_in_one_period_compare_against_zero() {
+        let metric = |name: &str, cost: f64, tokens: u64| NamedMetric {
+            name: name.to_string(),
+            tokens,
+            mtok: tokens as f64 / 1_000_000.0,
+            variable_cost_usd: cost,
+            subscription_allocated_usd: 0.0,
+            total_cost_usd: cost,
+            blended_usd_per_mtok: 0.0,
+            session_count: 1,
+            tool_share: 0.0,
+        };
+        let rows = compare_rows(
+            &[metric("claude", 5.0, 100), metric("cursor", 2.0, 10)],
+            &[metric("claude", 6.0, 100), metric("codex", 9.0, 50)],
+        );
+        let names: Vec<&str> = rows.iter().map(|row| row.name.as_str()).collect();
+        assert_eq!(names, ["codex", "cursor", "claude"]);
+        assert_eq!(rows[0].cost_delta_pct, None);
+        assert_eq!(rows[0].volume_effect_usd, 9.0);
+        assert_eq!(rows[1].cost_delta_usd, -2.0);
+        assert_eq!(rows[1].volume_effect_usd, -2.0);
+        // Same tokens, higher cost: all price/mix.
+        assert_eq!(rows[2].volume_effect_usd, 0.0);
+        assert_eq!(rows[2].price_mix_effect_usd, 1.0);
+    }
+
+    fn month_query(month: &str) -> (QueryArgs, TimeRange) {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let query = QueryArgs {
+            events: vec![root.join("examples/events.jsonl")],
+            pricing: root.join("pricing.example.json"),
+            providers: Vec::new(),
+            models: Vec::new(),
+            top_models: None,
+            top_providers: None,
+            output: OutputMode::Json,
+            on_unpriced: ParetoRs::OnUnpricedAction::Error,
+            allocation: None,
+            currency: "USD".to_string(),
+            fx_rates: None,
+            fx_date: crate::cli::FxDateMode::EventDay,
+            no_session_counts: false,
+            export: crate::cli::ExportArgs::default(),
+        };
+        let range = TimeRange::month(crate::utils::parse_month(month).unwrap());
+        (query, range)
+    }
+
+    #[test]
+    fn test_period_without_events_compares_as_empty() {
+        let (query, current) = month_query("2026-02");
+        let (_, baseline) = month_query("2026-01");
+        let current = build_compare_period(&query, &current).unwrap();
+        let baseline = build_compare_period(&query, &baseline).unwrap();
+        assert_eq!(baseline.label, "2026-01");
+        assert_eq!(baseline.totals.monthly_total_usd, 0.0);
+
+        let report = build_compare(&current, &baseline);
+        assert_eq!(report.against, "2026-01");
+        assert_eq!(report.totals.baseline_cost_usd, 0.0);
+        assert_eq!(report.totals.cost_usd, current.totals.monthly_total_usd);
+        // Model rows carry their provider, as `--group-by model` rows do.
+        assert!(report
+            .models
+            .iter()
+            .any(|row| row.name == "claude/claude-sonnet-4-5" && row.baseline_cost_usd == 0.0));
+    }
+}
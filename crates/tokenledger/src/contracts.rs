@@ -0,0 +1,466 @@
+// Contract terms applied after variable cost. Discounts come off each event in `event_cost`;
+// credits and commits depend on spend outside the reported events, so `ContractLedger` replays a
+// reference population (normally every loaded event, across months) day by day.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+
+use crate::cost::{event_cost, PricedEvents};
+use crate::fx::{month_end, CurrencyConverter};
+use crate::models::*;
+use crate::money::*;
+use crate::utils::resolve_provider_alias;
+
+type Month = (i32, u32);
+
+#[derive(Debug, Default, Clone)]
+pub struct ContractLedger {
+    contracts: BTreeMap<String, ProviderContract>,
+    // Discounted variable spend and the credit that paid for it, per provider and day, in the
+    // provider's currency.
+    days: BTreeMap<(String, NaiveDate), (Micros, Micros)>,
+    months: HashMap<(String, Month), Micros>,
+    // Credit burned per provider as (credit index, day, amount).
+    burns: HashMap<String, Vec<(usize, NaiveDate, Micros)>>,
+}
+
+impl ContractLedger {
+    pub fn build(events: &[UsageEvent], pricing: &PricingBook) -> Self {
+        let mut spend: BTreeMap<(String, NaiveDate), Micros> = BTreeMap::new();
+        if pricing
+            .providers
+            .values()
+            .any(|provider| provider.contract.is_some())
+        {
+            for evt in events {
+                if let Some((_, amount)) = contract_spend(evt, pricing) {
+                    *spend
+                        .entry((evt.provider.clone(), evt.timestamp.date_naive()))
+                        .or_default() += amount;
+                }
+            }
+        }
+        Self::from_spend(spend, pricing)
+    }
+
+    // Like `build`, from discounted spend already summed per provider and day.
+    pub fn from_spend(
+        spend: impl IntoIterator<Item = ((String, NaiveDate), Micros)>,
+        pricing: &PricingBook,
+    ) -> Self {
+        let mut ledger = Self::default();
+        for ((provider, day), amount) in spend {
+            let Some(contract) = pricing
+                .providers
+                .get(&resolve_provider_alias(&provider, pricing))
+                .and_then(|pricing| pricing.contract.as_ref())
+            else {
+                continue;
+            };
+            ledger
+                .contracts
+                .entry(provider.clone())
+                .or_insert_with(|| contract.clone());
+            *ledger
+                .months
+                .entry((provider.clone(), month_of(day)))
+                .or_default() += amount;
+            ledger.days.entry((provider, day)).or_default().0 += amount;
+        }
+
+        for (provider, contract) in &ledger.contracts {
+            let credits = &contract.credits;
+            let mut balances: Vec<Micros> =
+                credits.iter().map(|credit| to_micros(credit.usd)).collect();
+            let mut order: Vec<usize> = (0..credits.len()).collect();
+            order.sort_by_key(|idx| {
+                (
+                    credits[*idx].expires.unwrap_or(NaiveDate::MAX),
+                    credits[*idx].granted,
+                )
+            });
+            let range = (provider.clone(), NaiveDate::MIN)..=(provider.clone(), NaiveDate::MAX);
+            for ((_, day), (spend, credited)) in ledger.days.range_mut(range) {
+                let mut due = *spend;
+                for idx in &order {
+                    if due <= 0 {
+                        break;
+                    }
+                    if !credits[*idx].is_active_on(*day) || balances[*idx] <= 0 {
+                        continue;
+                    }
+                    let burned = due.min(balances[*idx]);
+                    balances[*idx] -= burned;
+                    due -= burned;
+                    ledger
+                        .burns
+                        .entry(provider.clone())
+                        .or_default()
+                        .push((*idx, *day, burned));
+                }
+                *credited = *spend - due;
+            }
+        }
+        ledger
+    }
+
+    // Credit applied to `spend` of the provider's spend on `day`; a view holding part of the
+    // day's usage gets the matching part of its credit.
+    pub fn credit_applied(&self, provider: &str, day: NaiveDate, spend: Micros) -> Micros {
+        let Some(&(day_spend, credited)) = self.days.get(&(provider.to_string(), day)) else {
+            return 0;
+        };
+        if spend >= day_spend {
+            credited
+        } else {
+            div_round(credited as i128 * spend as i128, day_spend as i128) as Micros
+        }
+    }
+
+    pub fn commit_shortfall(&self, provider: &str, month: Month) -> Micros {
+        let Some(commit) = self
+            .contracts
+            .get(provider)
+            .and_then(|contract| contract.commit_usd_month)
+        else {
+            return 0;
+        };
+        let spend = self
+            .months
+            .get(&(provider.to_string(), month))
+            .copied()
+            .unwrap_or(0);
+        (to_micros(commit) - spend).max(0)
+    }
+
+    // Unburned balance of the provider's credits still usable on `day`.
+    pub fn remaining_credit(&self, provider: &str, day: NaiveDate) -> Micros {
+        let Some(contract) = self.contracts.get(provider) else {
+            return 0;
+        };
+        let burns = self.burns.get(provider);
+        contract
+            .credits
+            .iter()
+            .enumerate()
+            .filter(|(_, credit)| credit.is_active_on(day))
+            .map(|(idx, credit)| {
+                let burned: Micros = burns
+                    .into_iter()
+                    .flatten()
+                    .filter(|(burn_idx, burn_day, _)| *burn_idx == idx && *burn_day <= day)
+                    .map(|(_, _, amount)| amount)
+                    .sum();
+                to_micros(credit.usd) - burned
+            })
+            .sum()
+    }
+}
+
+// Discounted variable spend of a priced event whose provider has a contract, in the provider's
+// currency.
+fn contract_spend<'a>(
+    evt: &UsageEvent,
+    pricing: &'a PricingBook,
+) -> Option<(&'a ProviderContract, Micros)> {
+    let cost = event_cost(evt, pricing)?;
+    let contract = cost.provider.contract.as_ref()?;
+    Some((contract, cost.variable - cost.discount))
+}
+
+// A view's usage of contract providers per provider and day: discounts in the report currency
+// and discounted spend in the provider's currency.
+#[derive(Debug, Default, Clone)]
+pub struct ContractUsage {
+    pub days: BTreeMap<(String, NaiveDate), (Micros, Micros)>,
+    // Latest day with priced usage in the view, contract or not.
+    pub last_day: Option<NaiveDate>,
+}
+
+impl ContractUsage {
+    pub fn from_priced(priced: &PricedEvents, pricing: &PricingBook) -> Self {
+        let mut usage = Self::default();
+        for item in &priced.events {
+            let day = item.event.timestamp.date_naive();
+            usage.last_day = usage.last_day.max(Some(day));
+            if let Some((_, spend)) = contract_spend(item.event, pricing) {
+                usage.add(&item.event.provider, day, item.discount_micros, spend);
+            }
+        }
+        usage
+    }
+
+    pub fn add(&mut self, provider: &str, day: NaiveDate, discount: Micros, spend: Micros) {
+        let entry = self.days.entry((provider.to_string(), day)).or_default();
+        entry.0 += discount;
+        entry.1 += spend;
+    }
+}
+
+#[derive(Default)]
+struct ProviderTotals {
+    currency: String,
+    discount: Micros,
+    credits: Micros,
+    shortfall: Micros,
+    remaining: Micros,
+}
+
+// List vs net cost for the providers in `usage` that have a contract, with money converted like
+// the rest of `breakdown`. Commit shortfalls are charged whole to any view holding the provider.
+pub fn contract_summary(
+    usage: &ContractUsage,
+    breakdown: &CostBreakdown,
+    pricing: &PricingBook,
+    ledger: &ContractLedger,
+    money: &CurrencyConverter,
+) -> Result<Option<ContractSummary>> {
+    let mut totals: BTreeMap<String, ProviderTotals> = BTreeMap::new();
+    let mut months: BTreeSet<(String, Month)> = BTreeSet::new();
+    for ((provider, day), (discount, _)) in &usage.days {
+        totals.entry(provider.clone()).or_default().discount += discount;
+        months.insert((provider.clone(), month_of(*day)));
+    }
+    if totals.is_empty() {
+        return Ok(None);
+    }
+    let Some(last_day) = usage.last_day else {
+        return Ok(None);
+    };
+
+    for (name, entry) in totals.iter_mut() {
+        entry.currency = pricing
+            .providers
+            .get(name)
+            .map(|provider| provider.currency().to_string())
+            .unwrap_or_else(|| crate::fx::USD.to_string());
+        entry.remaining = scale_micros(
+            ledger.remaining_credit(name, last_day),
+            money.factor_on(&entry.currency, last_day)?,
+        );
+    }
+    for ((name, day), (_, spend)) in &usage.days {
+        let entry = totals.get_mut(name).expect("provider totals");
+        entry.credits += scale_micros(
+            ledger.credit_applied(name, *day, *spend),
+            money.factor_on(&entry.currency, *day)?,
+        );
+    }
+    for (name, month) in months {
+        let entry = totals.get_mut(&name).expect("provider totals");
+        let month_last_day =
+            month_end(NaiveDate::from_ymd_opt(month.0, month.1, 1).expect("month"));
+        entry.shortfall += scale_micros(
+            ledger.commit_shortfall(&name, month),
+            money.factor_on(&entry.currency, month_last_day)?,
+        );
+    }
+
+    let mut summary = ContractSummary {
+        list_cost_usd: breakdown.monthly_total_usd,
+        ..ContractSummary::default()
+    };
+    let mut discount_cents = 0;
+    let mut shortfall_cents = 0;
+    let mut credit_cents = 0;
+    let mut remaining_cents = 0;
+    for (name, entry) in totals {
+        let list_cents = breakdown
+            .provider_breakdown
+            .iter()
+            .find(|row| row.name == name)
+            .map_or(0, |row| units_to_cents(row.total_cost_usd));
+        let discount = to_cents(entry.discount);
+        let shortfall = to_cents(entry.shortfall);
+        let credits = to_cents(entry.credits);
+        let remaining = to_cents(entry.remaining);
+        discount_cents += discount;
+        shortfall_cents += shortfall;
+        credit_cents += credits;
+        remaining_cents += remaining;
+        summary.providers.push(ProviderContractSummary {
+            provider: name,
+            list_cost_usd: cents_to_units(list_cents),
+            discount_usd: cents_to_units(discount),
+            commit_shortfall_usd: cents_to_units(shortfall),
+            credits_applied_usd: cents_to_units(credits),
+            net_cost_usd: cents_to_units(list_cents - discount - credits + shortfall),
+            remaining_credit_usd: cents_to_units(remaining),
+        });
+    }
+    let list_cents = units_to_cents(breakdown.monthly_total_usd);
+    summary.discount_usd = cents_to_units(discount_cents);
+    summary.commit_shortfall_usd = cents_to_units(shortfall_cents);
+    summary.credits_applied_usd = cents_to_units(credit_cents);
+    summary.net_cost_usd =
+        cents_to_units(list_cents - discount_cents - credit_cents + shortfall_cents);
+    summary.remaining_credit_usd = cents_to_units(remaining_cents);
+    Ok(Some(summary))
+}
+
+fn month_of(day: NaiveDate) -> Month {
+    (day.year(), day.month())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocation::SubscriptionAllocator;
+    use crate::cost::{price_events, summarize_priced};
+    use chrono::{TimeZone, Utc};
+    use ParetoRs::OnUnpricedAction;
+
+    fn event(model: &str, month: u32, day: u32, input_tokens: u64) -> UsageEvent {
+        UsageEvent {
+            provider: "codex".to_string(),
+            model: model.to_string(),
+            session_id: format!("s{}", day),
+            timestamp: Utc.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap(),
+            usage: TokenUsage {
+                input_tokens,
+                output_tokens: 0,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 0,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn credit(usd: f64, granted: (u32, u32), expires: Option<(u32, u32)>) -> ContractCredit {
+        let date = |(month, day): (u32, u32)| NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+        ContractCredit {
+            name: None,
+            usd,
+            granted: date(granted),
+            expires: expires.map(date),
+        }
+    }
+
+    fn pricing(contract: ProviderContract) -> PricingBook {
+        let rate = |input: f64| ModelRate {
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: 0.0,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: None,
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        };
+        PricingBook {
+            providers: HashMap::from([(
+                "codex".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 0.0,
+                    models: HashMap::from([
+                        ("gpt-5".to_string(), rate(10.0).into()),
+                        ("gpt-5-mini".to_string(), rate(2.0).into()),
+                    ]),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::new(),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: Some(contract),
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        }
+    }
+
+    fn summarize(
+        events: &[UsageEvent],
+        reference: &[UsageEvent],
+        book: &PricingBook,
+    ) -> CostBreakdown {
+        let money = CurrencyConverter::usd();
+        let allocator = SubscriptionAllocator::build(events, book, None);
+        let priced =
+            price_events(events, book, OnUnpricedAction::Error, &allocator, &money).unwrap();
+        let mut breakdown = summarize_priced(&priced, &money, None);
+        let ledger = ContractLedger::build(reference, book);
+        breakdown.contract = contract_summary(
+            &ContractUsage::from_priced(&priced, book),
+            &breakdown,
+            book,
+            &ledger,
+            &money,
+        )
+        .unwrap();
+        breakdown
+    }
+
+    #[test]
+    fn test_discounts_and_commit_shortfall() {
+        let book = pricing(ProviderContract {
+            discount_pct: 20.0,
+            model_discounts: HashMap::from([("gpt-5-mini".to_string(), 50.0)]),
+            credits: Vec::new(),
+            commit_usd_month: Some(20.0),
+        });
+        let events = vec![
+            event("gpt-5", 2, 3, 1_000_000),
+            event("gpt-5-mini", 2, 4, 1_000_000),
+        ];
+        let report = summarize(&events, &events, &book);
+        let contract = report.contract.expect("contract summary");
+        // List 12.00; 2.00 + 1.00 off; 9.00 of discounted spend against a 20.00 commit.
+        assert_eq!(contract.list_cost_usd, 12.0);
+        assert_eq!(contract.discount_usd, 3.0);
+        assert_eq!(contract.commit_shortfall_usd, 11.0);
+        assert_eq!(contract.net_cost_usd, 20.0);
+        assert_eq!(contract.providers.len(), 1);
+        assert_eq!(contract.providers[0].net_cost_usd, 20.0);
+    }
+
+    #[test]
+    fn test_credits_burn_across_months_and_expire() {
+        let book = pricing(ProviderContract {
+            discount_pct: 0.0,
+            model_discounts: HashMap::new(),
+            credits: vec![
+                credit(15.0, (1, 1), None),
+                credit(4.0, (1, 1), Some((2, 10))),
+            ],
+            commit_usd_month: None,
+        });
+        let reference = vec![
+            event("gpt-5", 1, 20, 1_000_000),
+            event("gpt-5", 2, 5, 1_000_000),
+            event("gpt-5", 2, 20, 1_000_000),
+        ];
+        // January: 10.00, paid by the expiring credit first (4.00) then 6.00 of the other.
+        let january = summarize(&reference[..1], &reference, &book);
+        let contract = january.contract.expect("contract summary");
+        assert_eq!(contract.credits_applied_usd, 10.0);
+        assert_eq!(contract.net_cost_usd, 0.0);
+        assert_eq!(contract.remaining_credit_usd, 9.0);
+
+        // February: 9.00 of credit left pays for the first event; the second is billed in full.
+        let february = summarize(&reference[1..], &reference, &book);
+        let contract = february.contract.expect("contract summary");
+        assert_eq!(contract.list_cost_usd, 20.0);
+        assert_eq!(contract.credits_applied_usd, 9.0);
+        assert_eq!(contract.net_cost_usd, 11.0);
+        assert_eq!(contract.remaining_credit_usd, 0.0);
+    }
+
+    #[test]
+    fn test_no_contract_providers_have_no_summary() {
+        let mut book = pricing(ProviderContract::default());
+        book.providers.get_mut("codex").unwrap().contract = None;
+        let events = vec![event("gpt-5", 2, 3, 1_000_000)];
+        assert!(summarize(&events, &events, &book).contract.is_none());
+    }
+}
@@ -1,13 +1,19 @@
 // Cost calculation utilities
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
 
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 
-use ParetoRs::OnUnpricedAction;
-use crate::format::{round2, round4};
+use crate::allocation::{AllocCell, SubscriptionAllocator};
+use crate::contracts::{contract_summary, ContractLedger, ContractUsage};
+use crate::format::round4;
+use crate::fx::CurrencyConverter;
 use crate::models::*;
+use crate::money::*;
+use crate::prompt_cache::{build_cache_report, cache_suggestions, CacheSession, CacheSessionKey};
+use ParetoRs::OnUnpricedAction;
 
 pub const MTOK: f64 = 1_000_000.0;
 
@@ -16,24 +22,165 @@ pub fn compute_costs(
     pricing: &PricingBook,
     on_unpriced: OnUnpricedAction,
 ) -> Result<CostBreakdown> {
-    let mut global = Acc::default();
-    let mut by_provider: BTreeMap<String, Acc> = BTreeMap::new();
-    let mut by_model: BTreeMap<String, Acc> = BTreeMap::new();
-    let mut provider_token_totals: HashMap<String, u64> = HashMap::new();
+    let allocator = SubscriptionAllocator::build(events, pricing, None);
+    let money = CurrencyConverter::usd();
+    let priced = price_events(events, pricing, on_unpriced, &allocator, &money)?;
+    let mut breakdown = summarize_priced(&priced, &money, None);
+    breakdown.idle_subscription_usd = cents_to_units(to_cents(
+        allocator.idle_micros_for(events.iter().map(|evt| evt.provider.as_str())),
+    ));
+    breakdown.contract = contract_summary(
+        &ContractUsage::from_priced(&priced, pricing),
+        &breakdown,
+        pricing,
+        &ContractLedger::build(events, pricing),
+        &money,
+    )?;
+    Ok(breakdown)
+}
+
+// Prices `events` and charges each its subscription share from `allocator`, which may have
+// been built from a wider population (e.g. the whole month when `events` is one model or day).
+pub fn compute_costs_with_allocator(
+    events: &[UsageEvent],
+    pricing: &PricingBook,
+    on_unpriced: OnUnpricedAction,
+    allocator: &SubscriptionAllocator,
+) -> Result<CostBreakdown> {
+    compute_costs_in_currency(
+        events,
+        pricing,
+        on_unpriced,
+        allocator,
+        &CurrencyConverter::usd(),
+    )
+}
+
+// Like `compute_costs_with_allocator`, with every event's cost converted from its provider's
+// pricing currency into `money.currency`.
+pub fn compute_costs_in_currency(
+    events: &[UsageEvent],
+    pricing: &PricingBook,
+    on_unpriced: OnUnpricedAction,
+    allocator: &SubscriptionAllocator,
+    money: &CurrencyConverter,
+) -> Result<CostBreakdown> {
+    let priced = price_events(events, pricing, on_unpriced, allocator, money)?;
+    Ok(summarize_priced(&priced, money, None))
+}
+
+// Per-event costs in integer micro-units of the report currency. Reports that split one
+// population several ways (days of a month) price it once and summarize each slice, so the
+// slices add up to the whole exactly.
+#[derive(Debug, Clone)]
+pub struct PricedEvents<'a> {
+    pub events: Vec<PricedEvent<'a>>,
+    pub unpriced: Vec<&'a UsageEvent>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PricedEvent<'a> {
+    pub event: &'a UsageEvent,
+    pub variable_micros: Micros,
+    pub subscription_micros: Micros,
+    pub batch_savings_micros: Micros,
+    // Contract discount off `variable_micros`, which stays at list rates.
+    pub discount_micros: Micros,
+    pub cache_read_savings_micros: Micros,
+    pub cache_write_premium_micros: Micros,
+}
+
+impl<'a> PricedEvents<'a> {
+    pub fn slice(&self, keep: impl Fn(&UsageEvent) -> bool) -> PricedEvents<'a> {
+        PricedEvents {
+            events: self
+                .events
+                .iter()
+                .filter(|priced| keep(priced.event))
+                .cloned()
+                .collect(),
+            unpriced: self
+                .unpriced
+                .iter()
+                .copied()
+                .filter(|evt| keep(evt))
+                .collect(),
+        }
+    }
+
+    pub fn variable_micros(&self) -> Micros {
+        self.events
+            .iter()
+            .map(|priced| priced.variable_micros)
+            .sum()
+    }
+
+    pub fn subscription_micros(&self) -> Micros {
+        self.events
+            .iter()
+            .map(|priced| priced.subscription_micros)
+            .sum()
+    }
+}
+
+pub fn price_events<'a>(
+    events: &'a [UsageEvent],
+    pricing: &PricingBook,
+    on_unpriced: OnUnpricedAction,
+    allocator: &SubscriptionAllocator,
+    money: &CurrencyConverter,
+) -> Result<PricedEvents<'a>> {
     let mut missing: BTreeMap<String, usize> = BTreeMap::new();
+    let mut unpriced = Vec::new();
 
     for evt in events {
-        if event_pricing(evt, pricing).is_some() {
-            *provider_token_totals
-                .entry(evt.provider.clone())
-                .or_default() += evt.usage.total();
-        } else {
+        if event_pricing(evt, pricing).is_none() {
+            unpriced.push(evt);
             *missing
                 .entry(format!("{}:{}", evt.provider, evt.model))
                 .or_default() += 1;
         }
     }
+    check_unpriced(&missing, on_unpriced)?;
+
+    // Costs in the provider's currency first: the allocator weighs in that currency.
+    let mut native = Vec::with_capacity(events.len());
+    let mut factors = Vec::with_capacity(events.len());
+    for evt in events {
+        let Some(cost) = event_cost(evt, pricing) else {
+            continue;
+        };
+        factors.push(money.factor(cost.provider.currency(), evt.timestamp)?);
+        native.push((evt, cost));
+    }
+    let subscriptions = allocator.allocate_events_converted(
+        &native
+            .iter()
+            .map(|(evt, cost)| (*evt, cost.variable))
+            .collect::<Vec<_>>(),
+        &factors,
+    );
 
+    let events = native
+        .into_iter()
+        .zip(factors.into_iter().zip(subscriptions))
+        .map(|((evt, cost), (fx, subscription))| PricedEvent {
+            event: evt,
+            variable_micros: scale_micros(cost.variable, fx),
+            subscription_micros: subscription,
+            batch_savings_micros: scale_micros(cost.batch_savings, fx),
+            discount_micros: scale_micros(cost.discount, fx),
+            cache_read_savings_micros: scale_micros(cost.cache_read_savings, fx),
+            cache_write_premium_micros: scale_micros(cost.cache_write_premium, fx),
+        })
+        .collect();
+    Ok(PricedEvents { events, unpriced })
+}
+
+pub fn check_unpriced(
+    missing: &BTreeMap<String, usize>,
+    on_unpriced: OnUnpricedAction,
+) -> Result<()> {
     if on_unpriced == OnUnpricedAction::Error && !missing.is_empty() {
         let details = missing
             .iter()
@@ -45,113 +192,286 @@ pub fn compute_costs(
             details
         ));
     }
+    Ok(())
+}
 
-    for evt in events {
-        let Some((provider, rate)) = event_pricing(evt, pricing) else {
-            continue;
-        };
+// An event's costs in its provider's currency, before subscription allocation.
+pub struct EventCost<'a> {
+    pub provider: &'a ProviderPricing,
+    pub variable: Micros,
+    // What the same usage would have saved on batch pricing; standard-tier events only.
+    pub batch_savings: Micros,
+    pub discount: Micros,
+    pub cache_read_savings: Micros,
+    pub cache_write_premium: Micros,
+}
 
-        let variable_cost = calc_variable_cost(&evt.usage, rate);
-        let provider_total_tokens = *provider_token_totals
-            .get(&evt.provider)
-            .ok_or_else(|| anyhow!("missing token totals for provider {}", evt.provider))?;
-        let event_sub_alloc = allocate_subscription(
-            evt.usage.total(),
-            provider_total_tokens,
-            provider.subscription_usd_month,
-        );
+pub fn event_cost<'a>(evt: &UsageEvent, pricing: &'a PricingBook) -> Option<EventCost<'a>> {
+    let (provider, rate, multiplier) = event_pricing(evt, pricing)?;
+    let model = resolved_model(evt, pricing);
+    let variable = scale_micros(calc_variable_cost(&evt.usage, rate), multiplier);
+    let mut batch_savings = 0;
+    if evt.service_tier.is_none() {
+        if let Some((batch_rate, batch_multiplier)) =
+            service_tier_rate(provider, &model, "batch", evt.timestamp)
+        {
+            batch_savings = variable
+                - scale_micros(calc_variable_cost(&evt.usage, batch_rate), batch_multiplier);
+        }
+    }
+    let discount = provider
+        .contract
+        .as_ref()
+        .map_or(0, |contract| contract_discount(contract, &model, variable));
+    let (cache_read_savings, cache_write_premium) =
+        calc_cache_savings(&evt.usage, rate, multiplier);
+    Some(EventCost {
+        provider,
+        variable,
+        batch_savings,
+        discount,
+        cache_read_savings,
+        cache_write_premium,
+    })
+}
 
-        merge_acc(&mut global, evt, variable_cost, event_sub_alloc);
-        merge_acc(
-            by_provider.entry(evt.provider.clone()).or_default(),
-            evt,
-            variable_cost,
-            event_sub_alloc,
-        );
-        merge_acc(
-            by_model.entry(evt.model.clone()).or_default(),
-            evt,
-            variable_cost,
-            event_sub_alloc,
-        );
+// Builds the report for `priced`. Money columns are rounded to cents with largest remainders so
+// provider, model and tier rows sum to the totals; `cents` pins the (variable, subscription)
+// totals when this report is itself one row of a larger one.
+pub fn summarize_priced(
+    priced: &PricedEvents,
+    money: &CurrencyConverter,
+    cents: Option<(i64, i64)>,
+) -> CostBreakdown {
+    let mut acc = BreakdownAcc::default();
+    for item in &priced.events {
+        acc.add_event(item);
     }
+    acc.skipped_unpriced += priced.unpriced.len();
+    acc.finish(money, cents)
+}
 
-    let total_subscription = global.subscription_allocated_usd;
-    let provider_breakdown = build_breakdown(&by_provider);
-    let model_breakdown = build_breakdown(&by_model);
+// Report rows being summed. Priced events and streamed cells fold into the same accumulators, so
+// a report is the same whichever way its events were read.
+#[derive(Debug, Default)]
+pub struct BreakdownAcc {
+    global: Acc,
+    by_provider: BTreeMap<String, Acc>,
+    by_model: BTreeMap<String, Acc>,
+    by_service_tier: BTreeMap<String, Acc>,
+    // None when the events were folded in by cell without their sessions.
+    cache_sessions: Option<BTreeMap<CacheSessionKey, CacheSession>>,
+    batch_savings: Micros,
+    pub skipped_unpriced: usize,
+}
 
-    let sub_alloc = global.subscription_allocated_usd;
-    let monthly_total = global.variable_cost_usd + sub_alloc;
-    let mtok = global.tokens as f64 / MTOK;
-    let blended = if mtok > 0.0 {
-        monthly_total / mtok
-    } else {
-        0.0
-    };
+impl BreakdownAcc {
+    pub fn add_event(&mut self, item: &PricedEvent) {
+        let evt = item.event;
+        self.batch_savings += item.batch_savings_micros;
+        for acc in [
+            &mut self.global,
+            self.by_provider.entry(evt.provider.clone()).or_default(),
+            self.by_model.entry(evt.model.clone()).or_default(),
+            self.by_service_tier
+                .entry(evt.service_tier_name().to_string())
+                .or_default(),
+        ] {
+            merge_acc(acc, evt, item.variable_micros, item.subscription_micros);
+            acc.cache_read_savings_micros += item.cache_read_savings_micros;
+            acc.cache_write_premium_micros += item.cache_write_premium_micros;
+        }
+        if let Some(session) = CacheSession::of_event(evt, item.cache_write_premium_micros) {
+            self.add_cache_session(CacheSession::key(evt), &session);
+        }
+    }
 
-    Ok(CostBreakdown {
-        variable_cost_usd: round2(global.variable_cost_usd),
-        subscription_allocated_usd: round2(sub_alloc),
-        monthly_total_usd: round2(monthly_total),
-        blended_usd_per_mtok: round4(blended),
-        total_tokens: global.tokens,
-        total_mtok: round4(mtok),
-        input_tokens: global.input_tokens,
-        output_tokens: global.output_tokens,
-        cache_write_tokens: global.cache_write_tokens,
-        cache_read_tokens: global.cache_read_tokens,
-        tool_input_tokens: global.tool_input_tokens,
-        tool_output_tokens: global.tool_output_tokens,
-        session_count: global.sessions.len(),
-        skipped_unpriced_count: missing.values().copied().sum(),
-        provider_breakdown,
-        model_breakdown,
-        suggestions: make_suggestions(&global, total_subscription),
-    })
+    pub fn add_cache_session(&mut self, key: CacheSessionKey, session: &CacheSession) {
+        self.cache_sessions
+            .get_or_insert_with(BTreeMap::new)
+            .entry(key)
+            .or_default()
+            .merge(session);
+    }
+
+    // Folds in a cell's events, summed in `usage`, and the cell's subscription share.
+    pub fn add_cell(
+        &mut self,
+        cell: &AllocCell,
+        usage: &Acc,
+        subscription: Micros,
+        batch_savings: Micros,
+    ) {
+        self.batch_savings += batch_savings;
+        for acc in [
+            &mut self.global,
+            self.by_provider.entry(cell.provider.clone()).or_default(),
+            self.by_model.entry(cell.model.clone()).or_default(),
+            self.by_service_tier
+                .entry(cell.service_tier.clone())
+                .or_default(),
+        ] {
+            merge_partial(acc, usage);
+            acc.subscription_allocated_micros += subscription;
+        }
+    }
+
+    pub fn variable_micros(&self) -> Micros {
+        self.global.variable_cost_micros
+    }
+
+    pub fn subscription_micros(&self) -> Micros {
+        self.global.subscription_allocated_micros
+    }
+
+    pub fn finish(self, money: &CurrencyConverter, cents: Option<(i64, i64)>) -> CostBreakdown {
+        let global = self.global;
+        let (variable_cents, subscription_cents) = cents.unwrap_or((
+            to_cents(global.variable_cost_micros),
+            to_cents(global.subscription_allocated_micros),
+        ));
+        let provider_breakdown =
+            build_breakdown(&self.by_provider, variable_cents, subscription_cents);
+        let model_breakdown = build_breakdown(&self.by_model, variable_cents, subscription_cents);
+        let service_tier_breakdown =
+            build_breakdown(&self.by_service_tier, variable_cents, subscription_cents);
+        let cache = build_cache_report(
+            &global,
+            &self.by_provider,
+            &self.by_model,
+            self.cache_sessions.as_ref(),
+        );
+        let mut suggestions = make_suggestions(&global);
+        if let Some(cache) = &cache {
+            suggestions.extend(cache_suggestions(cache, money));
+        }
+        if self.batch_savings >= MICROS_PER_CENT {
+            suggestions.push(format!(
+                "Standard-tier traffic would cost {}{:.2} less on batch pricing; move latency-tolerant jobs (evals, backfills, bulk summarization) to batch.",
+                money.symbol(),
+                cents_to_units(to_cents(self.batch_savings))
+            ));
+        }
+
+        let monthly_total_cents = variable_cents + subscription_cents;
+        let mtok = global.tokens as f64 / MTOK;
+        let blended = if mtok > 0.0 {
+            cents_to_units(monthly_total_cents) / mtok
+        } else {
+            0.0
+        };
+
+        CostBreakdown {
+            currency: money.currency.clone(),
+            variable_cost_usd: cents_to_units(variable_cents),
+            subscription_allocated_usd: cents_to_units(subscription_cents),
+            monthly_total_usd: cents_to_units(monthly_total_cents),
+            blended_usd_per_mtok: round4(blended),
+            total_tokens: global.tokens,
+            total_mtok: round4(mtok),
+            input_tokens: global.input_tokens,
+            output_tokens: global.output_tokens,
+            cache_write_tokens: global.cache_write_tokens,
+            cache_write_1h_tokens: global.cache_write_1h_tokens,
+            cache_read_tokens: global.cache_read_tokens,
+            tool_input_tokens: global.tool_input_tokens,
+            tool_output_tokens: global.tool_output_tokens,
+            session_count: global.sessions.len(),
+            skipped_unpriced_count: self.skipped_unpriced,
+            provider_breakdown,
+            model_breakdown,
+            service_tier_breakdown,
+            batch_savings_estimate_usd: cents_to_units(to_cents(self.batch_savings)),
+            idle_subscription_usd: 0.0,
+            contract: None,
+            cache,
+            suggestions,
+        }
+    }
 }
 
-pub fn build_breakdown(items: &BTreeMap<String, Acc>) -> Vec<NamedMetric> {
+// Rows whose cent-rounded variable and subscription columns sum to the given totals; each row's
+// total is its own variable plus subscription, so every column adds up.
+pub fn build_breakdown(
+    items: &BTreeMap<String, Acc>,
+    variable_cents: i64,
+    subscription_cents: i64,
+) -> Vec<NamedMetric> {
+    let variable: Vec<Micros> = items.values().map(|acc| acc.variable_cost_micros).collect();
+    let subscription: Vec<Micros> = items
+        .values()
+        .map(|acc| acc.subscription_allocated_micros)
+        .collect();
+    let variable = round_parts(&variable, MICROS_PER_CENT, variable_cents);
+    let subscription = round_parts(&subscription, MICROS_PER_CENT, subscription_cents);
     items
         .iter()
-        .map(|(name, acc)| {
-            let sub = acc.subscription_allocated_usd;
-            let total = acc.variable_cost_usd + sub;
-            let mtok = acc.tokens as f64 / MTOK;
-            let tool_tokens = acc.tool_input_tokens + acc.tool_output_tokens;
-            let tool_share = if acc.tokens == 0 {
-                0.0
-            } else {
-                tool_tokens as f64 / acc.tokens as f64
-            };
-            NamedMetric {
-                name: name.clone(),
-                tokens: acc.tokens,
-                mtok: round4(mtok),
-                variable_cost_usd: round2(acc.variable_cost_usd),
-                subscription_allocated_usd: round2(sub),
-                total_cost_usd: round2(total),
-                blended_usd_per_mtok: round4(if mtok > 0.0 { total / mtok } else { 0.0 }),
-                session_count: acc.sessions.len(),
-                tool_share: round4(tool_share),
-            }
+        .zip(variable.into_iter().zip(subscription))
+        .map(|((name, acc), (variable_cents, subscription_cents))| {
+            named_metric(name, acc, variable_cents, subscription_cents)
         })
         .collect()
 }
 
-pub fn merge_acc(acc: &mut Acc, evt: &UsageEvent, variable_cost: f64, sub_alloc: f64) {
+// A row from its accumulator and its already-rounded cents.
+pub fn named_metric(
+    name: &str,
+    acc: &Acc,
+    variable_cents: i64,
+    subscription_cents: i64,
+) -> NamedMetric {
+    let total = cents_to_units(variable_cents + subscription_cents);
+    let mtok = acc.tokens as f64 / MTOK;
+    let tool_tokens = acc.tool_input_tokens + acc.tool_output_tokens;
+    let tool_share = if acc.tokens == 0 {
+        0.0
+    } else {
+        tool_tokens as f64 / acc.tokens as f64
+    };
+    NamedMetric {
+        name: name.to_string(),
+        tokens: acc.tokens,
+        mtok: round4(mtok),
+        variable_cost_usd: cents_to_units(variable_cents),
+        subscription_allocated_usd: cents_to_units(subscription_cents),
+        total_cost_usd: total,
+        blended_usd_per_mtok: round4(if mtok > 0.0 { total / mtok } else { 0.0 }),
+        session_count: acc.sessions.len(),
+        tool_share: round4(tool_share),
+    }
+}
+
+pub fn merge_acc(acc: &mut Acc, evt: &UsageEvent, variable_cost: Micros, sub_alloc: Micros) {
     acc.tokens += evt.usage.total();
     acc.input_tokens += evt.usage.input_tokens;
     acc.output_tokens += evt.usage.output_tokens;
     acc.cache_write_tokens += evt.usage.cache_write_tokens;
+    acc.cache_write_1h_tokens += evt.usage.cache_write_1h_tokens;
     acc.cache_read_tokens += evt.usage.cache_read_tokens;
     acc.tool_input_tokens += evt.usage.tool_input_tokens;
     acc.tool_output_tokens += evt.usage.tool_output_tokens;
-    acc.variable_cost_usd += variable_cost;
-    acc.subscription_allocated_usd += sub_alloc;
+    acc.variable_cost_micros += variable_cost;
+    acc.subscription_allocated_micros += sub_alloc;
     acc.sessions
         .insert(session_hash(&evt.provider, &evt.session_id));
 }
 
+pub fn merge_partial(acc: &mut Acc, part: &Acc) {
+    acc.tokens += part.tokens;
+    acc.input_tokens += part.input_tokens;
+    acc.output_tokens += part.output_tokens;
+    acc.cache_write_tokens += part.cache_write_tokens;
+    acc.cache_write_1h_tokens += part.cache_write_1h_tokens;
+    acc.cache_read_tokens += part.cache_read_tokens;
+    acc.tool_input_tokens += part.tool_input_tokens;
+    acc.tool_output_tokens += part.tool_output_tokens;
+    acc.variable_cost_micros += part.variable_cost_micros;
+    acc.subscription_allocated_micros += part.subscription_allocated_micros;
+    acc.cache_read_savings_micros += part.cache_read_savings_micros;
+    acc.cache_write_premium_micros += part.cache_write_premium_micros;
+    acc.sessions.extend(&part.sessions);
+}
+
 pub fn session_hash(provider: &str, session_id: &str) -> u64 {
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
     provider.hash(&mut hasher);
@@ -159,37 +479,164 @@ pub fn session_hash(provider: &str, session_id: &str) -> u64 {
     hasher.finish()
 }
 
-pub fn calc_variable_cost(usage: &TokenUsage, rate: &ModelRate) -> f64 {
-    let cache_write = rate
-        .cache_write_usd_per_mtok
-        .unwrap_or(rate.input_usd_per_mtok);
-    let cache_read = rate
-        .cache_read_usd_per_mtok
-        .unwrap_or(rate.input_usd_per_mtok * 0.1);
-    let tool_in = rate
-        .tool_input_usd_per_mtok
-        .unwrap_or(rate.input_usd_per_mtok);
-    let tool_out = rate
-        .tool_output_usd_per_mtok
-        .unwrap_or(rate.output_usd_per_mtok);
-
-    (usage.input_tokens as f64 / MTOK) * rate.input_usd_per_mtok
-        + (usage.output_tokens as f64 / MTOK) * rate.output_usd_per_mtok
-        + (usage.cache_write_tokens as f64 / MTOK) * cache_write
-        + (usage.cache_read_tokens as f64 / MTOK) * cache_read
-        + (usage.tool_input_tokens as f64 / MTOK) * tool_in
-        + (usage.tool_output_tokens as f64 / MTOK) * tool_out
-}
-
-pub fn allocate_subscription(item_tokens: u64, total_tokens: u64, subscription: f64) -> f64 {
-    if total_tokens == 0 {
-        0.0
-    } else {
-        subscription * (item_tokens as f64 / total_tokens as f64)
+// Exact cost in micro-units of the rate card's currency.
+pub fn calc_variable_cost(usage: &TokenUsage, rate: &ModelRate) -> Micros {
+    token_cost_micros(component_terms(usage, rate).iter().sum())
+}
+
+// `calc_variable_cost` scaled by `multiplier` and split into token components, in `TokenUsage`
+// field order, by largest remainder so the components sum to the event's variable cost.
+pub fn calc_component_costs(usage: &TokenUsage, rate: &ModelRate, multiplier: f64) -> [Micros; 7] {
+    let terms = component_terms(usage, rate);
+    let total = scale_micros(token_cost_micros(terms.iter().sum()), multiplier);
+    let weights: Vec<u128> = terms.iter().map(|term| (*term).max(0) as u128).collect();
+    let mut components = [0; 7];
+    components.copy_from_slice(&largest_remainder(total, &weights));
+    components
+}
+
+// What prompt caching saved (reads billed below the input rate) and cost (writes billed above
+// it) against sending the same tokens as uncached input, at the rates the event is priced at.
+pub fn calc_cache_savings(
+    usage: &TokenUsage,
+    rate: &ModelRate,
+    multiplier: f64,
+) -> (Micros, Micros) {
+    let [input, _, cache_write, cache_write_1h, cache_read, _, _] =
+        component_rates(usage, rate).map(rate_micros);
+    let savings = usage.cache_read_tokens as i128 * (input - cache_read);
+    let premium = usage.cache_write_tokens as i128 * (cache_write - input)
+        + usage.cache_write_1h_tokens as i128 * (cache_write_1h - input);
+    (
+        scale_micros(token_cost_micros(savings), multiplier),
+        scale_micros(token_cost_micros(premium), multiplier),
+    )
+}
+
+// `tokens * rate_micros` for each token component of `usage`, in `TokenUsage` field order.
+fn component_terms(usage: &TokenUsage, rate: &ModelRate) -> [i128; 7] {
+    let tokens = [
+        usage.input_tokens,
+        usage.output_tokens,
+        usage.cache_write_tokens,
+        usage.cache_write_1h_tokens,
+        usage.cache_read_tokens,
+        usage.tool_input_tokens,
+        usage.tool_output_tokens,
+    ];
+    let rates = component_rates(usage, rate);
+    std::array::from_fn(|i| tokens[i] as i128 * rate_micros(rates[i]))
+}
+
+// `rate` as applied to `usage`: a long-context tier the prompt crossed is resolved into plain
+// per-component rates, so the result prices `usage` the same way without any tiers.
+pub fn applied_rate(usage: &TokenUsage, rate: &ModelRate) -> ModelRate {
+    if rate.context_tier(usage.prompt_tokens()).is_none() {
+        return rate.clone();
+    }
+    let [input, output, cache_write, cache_write_1h, cache_read, tool_in, tool_out] =
+        component_rates(usage, rate);
+    ModelRate {
+        input_usd_per_mtok: input,
+        output_usd_per_mtok: output,
+        cache_write_usd_per_mtok: Some(cache_write),
+        cache_write_1h_usd_per_mtok: Some(cache_write_1h),
+        cache_read_usd_per_mtok: Some(cache_read),
+        tool_input_usd_per_mtok: Some(tool_in),
+        tool_output_usd_per_mtok: Some(tool_out),
+        effective_from: rate.effective_from,
+        effective_to: rate.effective_to,
+        context_tiers: Vec::new(),
+    }
+}
+
+// $/MTok applied to each usage component, in `component_terms` order.
+fn component_rates(usage: &TokenUsage, rate: &ModelRate) -> [f64; 7] {
+    let tier = rate.context_tier(usage.prompt_tokens());
+    let input = tier.map_or(rate.input_usd_per_mtok, |tier| tier.input_usd_per_mtok);
+    let output = tier.map_or(rate.output_usd_per_mtok, |tier| tier.output_usd_per_mtok);
+    let cache_write = tier
+        .and_then(|tier| tier.cache_write_usd_per_mtok)
+        .or(rate.cache_write_usd_per_mtok)
+        .unwrap_or(input);
+    // Books with a single cache-write rate price 1-hour writes the same as 5-minute ones.
+    let cache_write_1h = tier
+        .and_then(|tier| tier.cache_write_1h_usd_per_mtok)
+        .or(rate.cache_write_1h_usd_per_mtok)
+        .unwrap_or(cache_write);
+    let cache_read = tier
+        .and_then(|tier| tier.cache_read_usd_per_mtok)
+        .or(rate.cache_read_usd_per_mtok)
+        .unwrap_or(input * 0.1);
+    let tool_in = tier
+        .and_then(|tier| tier.tool_input_usd_per_mtok)
+        .or(rate.tool_input_usd_per_mtok)
+        .unwrap_or(input);
+    let tool_out = tier
+        .and_then(|tier| tier.tool_output_usd_per_mtok)
+        .or(rate.tool_output_usd_per_mtok)
+        .unwrap_or(output);
+
+    [
+        input,
+        output,
+        cache_write,
+        cache_write_1h,
+        cache_read,
+        tool_in,
+        tool_out,
+    ]
+}
+
+// Priced events whose prompt crossed a long-context tier, grouped by model and tier.
+pub fn collect_long_context_usage(
+    events: &[UsageEvent],
+    pricing: &PricingBook,
+) -> Vec<LongContextUsage> {
+    let mut grouped: BTreeMap<(String, String, u64), (usize, u64)> = BTreeMap::new();
+    for evt in events {
+        let Some((_, rate, _)) = event_pricing(evt, pricing) else {
+            continue;
+        };
+        let prompt_tokens = evt.usage.prompt_tokens();
+        let Some(tier) = rate.context_tier(prompt_tokens) else {
+            continue;
+        };
+        let provider = crate::utils::resolve_provider_alias(&evt.provider, pricing);
+        let model = crate::utils::resolve_model_alias(&provider, &evt.model, pricing);
+        let entry = grouped
+            .entry((provider, model, tier.above_prompt_tokens))
+            .or_default();
+        entry.0 += 1;
+        entry.1 += prompt_tokens;
     }
+    grouped
+        .into_iter()
+        .map(
+            |((provider, model, above_prompt_tokens), (events, prompt_tokens))| LongContextUsage {
+                provider,
+                model,
+                above_prompt_tokens,
+                events,
+                prompt_tokens,
+            },
+        )
+        .collect()
 }
 
-pub fn make_suggestions(global: &Acc, total_subscription: f64) -> Vec<String> {
+// Splits a subscription over items by token count; the parts always sum to `subscription`.
+pub fn allocate_subscription(subscription: Micros, item_tokens: &[u64]) -> Vec<Micros> {
+    let weights: Vec<u128> = item_tokens.iter().map(|tokens| *tokens as u128).collect();
+    largest_remainder(subscription, &weights)
+}
+
+// Replaced by spend anomaly tips when a daily series is at hand.
+pub const NO_ANOMALIES_TIP: &str =
+    "No obvious anomalies detected; keep collecting session-level data and compare 4-week trend deltas.";
+
+pub fn make_suggestions(global: &Acc) -> Vec<String> {
+    let variable_cost = from_micros(global.variable_cost_micros);
+    let total_subscription = from_micros(global.subscription_allocated_micros);
     let mut tips = Vec::new();
     let total_tokens = global.tokens as f64;
     if total_tokens > 0.0 {
@@ -202,36 +649,73 @@ pub fn make_suggestions(global: &Acc, total_subscription: f64) -> Vec<String> {
         if cache_share < 0.10 {
             tips.push("Cache-read share is low (<10%): improve prompt prefix reuse and session stickiness for Claude-style caching.".to_string());
         }
-        let var_per_mtok = global.variable_cost_usd / (total_tokens / MTOK);
+        let var_per_mtok = variable_cost / (total_tokens / MTOK);
         if var_per_mtok > 12.0 {
             tips.push("Blended variable $/MTok is high: route low-complexity jobs to cheaper models/providers via policy rules.".to_string());
         }
     }
-    let total_monthly = global.variable_cost_usd + total_subscription;
+    let total_monthly = variable_cost + total_subscription;
     if total_monthly > 0.0 && total_subscription / total_monthly > 0.7 {
         tips.push("Subscriptions dominate monthly cost (>70%): consolidate seats/plans or increase utilization with shared routing.".to_string());
     }
     if tips.is_empty() {
-        tips.push("No obvious anomalies detected; keep collecting session-level data and compare 4-week trend deltas.".to_string());
+        tips.push(NO_ANOMALIES_TIP.to_string());
     }
     tips
 }
 
-// Helper function to get event pricing - uses utils functions
+// Helper function to get event pricing - uses utils functions. Returns the provider, the rate
+// for the event's service tier and the multiplier to apply to that rate's cost.
 pub fn event_pricing<'a>(
     evt: &UsageEvent,
     pricing: &'a PricingBook,
-) -> Option<(&'a ProviderPricing, &'a ModelRate)> {
+) -> Option<(&'a ProviderPricing, &'a ModelRate, f64)> {
     let provider_name = crate::utils::resolve_provider_alias(&evt.provider, pricing);
     let provider = pricing.providers.get(&provider_name)?;
     let model_name = crate::utils::resolve_model_alias(&provider_name, &evt.model, pricing);
-    let rate = provider.models.get(&model_name)?;
-    Some((provider, rate))
+    if let Some(tier) = evt.service_tier.as_deref() {
+        if let Some((rate, multiplier)) =
+            service_tier_rate(provider, &model_name, tier, evt.timestamp)
+        {
+            return Some((provider, rate, multiplier));
+        }
+    }
+    let rate = provider.models.get(&model_name)?.rate_at(evt.timestamp)?;
+    Some((provider, rate, 1.0))
+}
+
+// Tiers the provider does not price fall back to standard rates in event_pricing.
+pub fn service_tier_rate<'a>(
+    provider: &'a ProviderPricing,
+    model: &str,
+    tier: &str,
+    at: DateTime<Utc>,
+) -> Option<(&'a ModelRate, f64)> {
+    let tier = provider.service_tiers.get(tier)?;
+    if let Some(rate) = tier
+        .models
+        .get(model)
+        .and_then(|schedule| schedule.rate_at(at))
+    {
+        return Some((rate, 1.0));
+    }
+    let rate = provider.models.get(model)?.rate_at(at)?;
+    Some((rate, tier.multiplier))
+}
+
+pub fn contract_discount(contract: &ProviderContract, model: &str, variable: Micros) -> Micros {
+    scale_micros(variable, contract.discount_pct_for(model) / 100.0)
+}
+
+pub fn resolved_model(evt: &UsageEvent, pricing: &PricingBook) -> String {
+    let provider_name = crate::utils::resolve_provider_alias(&evt.provider, pricing);
+    crate::utils::resolve_model_alias(&provider_name, &evt.model, pricing)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_calc_variable_cost_basic() {
@@ -239,6 +723,7 @@ mod tests {
             input_tokens: 1_000_000,
             output_tokens: 1_000_000,
             cache_write_tokens: 0,
+            cache_write_1h_tokens: 0,
             cache_read_tokens: 0,
             tool_input_tokens: 0,
             tool_output_tokens: 0,
@@ -247,12 +732,16 @@ mod tests {
             input_usd_per_mtok: 0.5,
             output_usd_per_mtok: 1.0,
             cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
             cache_read_usd_per_mtok: None,
             tool_input_usd_per_mtok: None,
             tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
         };
         let cost = calc_variable_cost(&usage, &rate);
-        assert!((cost - 1.5).abs() < 0.0001);
+        assert_eq!(cost, 1_500_000);
     }
 
     #[test]
@@ -261,6 +750,7 @@ mod tests {
             input_tokens: 1_000_000,
             output_tokens: 1_000_000,
             cache_write_tokens: 1_000_000,
+            cache_write_1h_tokens: 0,
             cache_read_tokens: 1_000_000,
             tool_input_tokens: 0,
             tool_output_tokens: 0,
@@ -269,37 +759,41 @@ mod tests {
             input_usd_per_mtok: 0.5,
             output_usd_per_mtok: 1.0,
             cache_write_usd_per_mtok: Some(0.1),
+            cache_write_1h_usd_per_mtok: None,
             cache_read_usd_per_mtok: Some(0.05),
             tool_input_usd_per_mtok: None,
             tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
         };
         let cost = calc_variable_cost(&usage, &rate);
         // 0.5 (input) + 1.0 (output) + 0.1 (cache_write) + 0.05 (cache_read) = 1.65
-        assert!((cost - 1.65).abs() < 0.0001);
+        assert_eq!(cost, 1_650_000);
     }
 
     #[test]
     fn test_allocate_subscription_full() {
-        let allocated = allocate_subscription(1_000_000, 1_000_000, 100.0);
-        assert!((allocated - 100.0).abs() < 0.01);
+        let allocated = allocate_subscription(100_000_000, &[1_000_000]);
+        assert_eq!(allocated, vec![100_000_000]);
     }
 
     #[test]
-    fn test_allocate_subscription_half() {
-        let allocated = allocate_subscription(500_000, 1_000_000, 100.0);
-        assert!((allocated - 50.0).abs() < 0.01);
+    fn test_allocate_subscription_thirds_sum_exactly() {
+        let allocated = allocate_subscription(100_000_000, &[1_000, 1_000, 1_000]);
+        assert_eq!(allocated, vec![33_333_334, 33_333_333, 33_333_333]);
     }
 
     #[test]
     fn test_allocate_subscription_zero_total() {
-        let allocated = allocate_subscription(1_000, 0, 100.0);
-        assert_eq!(allocated, 0.0);
+        let allocated = allocate_subscription(100_000_000, &[0, 0]);
+        assert_eq!(allocated, vec![0, 0]);
     }
 
     #[test]
     fn test_allocate_subscription_zero_monthly() {
-        let allocated = allocate_subscription(1_000, 2_000, 0.0);
-        assert_eq!(allocated, 0.0);
+        let allocated = allocate_subscription(0, &[1_000, 2_000]);
+        assert_eq!(allocated, vec![0, 0]);
     }
 
     #[test]
@@ -331,6 +825,7 @@ mod tests {
             input_tokens: 0,
             output_tokens: 0,
             cache_write_tokens: 0,
+            cache_write_1h_tokens: 0,
             cache_read_tokens: 0,
             tool_input_tokens: 0,
             tool_output_tokens: 0,
@@ -339,12 +834,16 @@ mod tests {
             input_usd_per_mtok: 0.5,
             output_usd_per_mtok: 1.0,
             cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
             cache_read_usd_per_mtok: None,
             tool_input_usd_per_mtok: None,
             tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
         };
         let cost = calc_variable_cost(&usage, &rate);
-        assert_eq!(cost, 0.0);
+        assert_eq!(cost, 0);
     }
 
     #[test]
@@ -353,6 +852,7 @@ mod tests {
             input_tokens: 1_000_000,
             output_tokens: 0,
             cache_write_tokens: 0,
+            cache_write_1h_tokens: 0,
             cache_read_tokens: 0,
             tool_input_tokens: 1_000_000,
             tool_output_tokens: 1_000_000,
@@ -361,12 +861,417 @@ mod tests {
             input_usd_per_mtok: 0.5,
             output_usd_per_mtok: 1.0,
             cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
             cache_read_usd_per_mtok: None,
             tool_input_usd_per_mtok: Some(0.2),
             tool_output_usd_per_mtok: Some(0.3),
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
         };
         let cost = calc_variable_cost(&usage, &rate);
         // 0.5 (input) + 0.2 (tool_input) + 0.3 (tool_output) = 1.0
-        assert!((cost - 1.0).abs() < 0.0001);
+        assert_eq!(cost, 1_000_000);
+    }
+
+    #[test]
+    fn test_event_pricing_uses_rate_in_effect_at_timestamp() {
+        let rate = |input: f64| ModelRate {
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: 10.0,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: None,
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        };
+        let cutover = chrono::DateTime::parse_from_rfc3339("2026-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let mut schedule = ModelRateSchedule::from(rate(3.0));
+        schedule.append_period(rate(2.0), cutover).unwrap();
+        let pricing = PricingBook {
+            providers: HashMap::from([(
+                "claude".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 0.0,
+                    models: HashMap::from([("claude-sonnet-4-5".to_string(), schedule)]),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::new(),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        };
+        let event_at = |timestamp| UsageEvent {
+            provider: "claude".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            session_id: "s1".to_string(),
+            timestamp,
+            usage: TokenUsage {
+                input_tokens: 1_000_000,
+                output_tokens: 0,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 0,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        };
+
+        let before = event_at(cutover - chrono::Duration::seconds(1));
+        let (_, old_rate, _) = event_pricing(&before, &pricing).unwrap();
+        assert_eq!(old_rate.input_usd_per_mtok, 3.0);
+        let (_, new_rate, _) = event_pricing(&event_at(cutover), &pricing).unwrap();
+        assert_eq!(new_rate.input_usd_per_mtok, 2.0);
+    }
+
+    #[test]
+    fn test_calc_variable_cost_long_context_tier() {
+        let rate = ModelRate {
+            input_usd_per_mtok: 3.0,
+            output_usd_per_mtok: 15.0,
+            cache_write_usd_per_mtok: Some(3.75),
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: Some(0.3),
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: vec![ContextTier {
+                above_prompt_tokens: 200_000,
+                input_usd_per_mtok: 6.0,
+                output_usd_per_mtok: 22.5,
+                cache_write_usd_per_mtok: None,
+                cache_write_1h_usd_per_mtok: None,
+                cache_read_usd_per_mtok: Some(0.6),
+                tool_input_usd_per_mtok: None,
+                tool_output_usd_per_mtok: None,
+            }],
+        };
+        let usage = |input_tokens: u64, cache_read_tokens: u64| TokenUsage {
+            input_tokens,
+            output_tokens: 100_000,
+            cache_write_tokens: 0,
+            cache_write_1h_tokens: 0,
+            cache_read_tokens,
+            tool_input_tokens: 0,
+            tool_output_tokens: 0,
+        };
+
+        // Exactly at the threshold stays on base rates: 0.6 + 1.5
+        let base = calc_variable_cost(&usage(200_000, 0), &rate);
+        assert_eq!(base, 2_100_000);
+
+        // Cache reads count toward the prompt: 0.6 + 2.25 + 0.0600006, rounded to the micro
+        let tiered = calc_variable_cost(&usage(100_000, 100_001), &rate);
+        assert_eq!(tiered, 2_910_001);
+        assert_eq!(
+            rate.context_tier(300_000)
+                .map(|tier| tier.above_prompt_tokens),
+            Some(200_000)
+        );
+        assert!(rate.context_tier(200_000).is_none());
+
+        // Tool tokens take the tier's input/output rates when no tool rate is set: below the
+        // threshold 0.3 + 1.5 + 0.3 + 1.5, above it 1.8 + 2.25 + 0.6 + 2.25
+        let tools = |input_tokens: u64| TokenUsage {
+            tool_input_tokens: 100_000,
+            tool_output_tokens: 100_000,
+            ..usage(input_tokens, 0)
+        };
+        assert_eq!(calc_variable_cost(&tools(100_000), &rate), 3_600_000);
+        assert_eq!(calc_variable_cost(&tools(300_000), &rate), 6_900_000);
+    }
+
+    #[test]
+    fn test_calc_variable_cost_cache_write_ttl_classes() {
+        let usage = TokenUsage {
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_write_tokens: 1_000_000,
+            cache_write_1h_tokens: 1_000_000,
+            cache_read_tokens: 0,
+            tool_input_tokens: 0,
+            tool_output_tokens: 0,
+        };
+        let mut rate = ModelRate {
+            input_usd_per_mtok: 3.0,
+            output_usd_per_mtok: 15.0,
+            cache_write_usd_per_mtok: Some(3.75),
+            cache_write_1h_usd_per_mtok: Some(6.0),
+            cache_read_usd_per_mtok: Some(0.3),
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        };
+        assert_eq!(calc_variable_cost(&usage, &rate), 9_750_000);
+
+        // Without a 1h rate both TTL classes use the single cache-write rate
+        rate.cache_write_1h_usd_per_mtok = None;
+        assert_eq!(calc_variable_cost(&usage, &rate), 7_500_000);
+    }
+
+    #[test]
+    fn test_compute_costs_applies_service_tier_pricing() {
+        let rate = |input: f64, output: f64| ModelRate {
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: output,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: None,
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        };
+        let pricing = PricingBook {
+            providers: HashMap::from([(
+                "codex".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 0.0,
+                    models: HashMap::from([("gpt-5".to_string(), rate(1.0, 10.0).into())]),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::from([
+                        (
+                            "batch".to_string(),
+                            ServiceTierPricing {
+                                multiplier: 0.5,
+                                models: HashMap::new(),
+                            },
+                        ),
+                        (
+                            "priority".to_string(),
+                            ServiceTierPricing {
+                                multiplier: 1.0,
+                                models: HashMap::from([(
+                                    "gpt-5".to_string(),
+                                    rate(2.5, 20.0).into(),
+                                )]),
+                            },
+                        ),
+                    ]),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        };
+        let event = |service_tier: Option<&str>| UsageEvent {
+            provider: "codex".to_string(),
+            model: "gpt-5".to_string(),
+            session_id: "s1".to_string(),
+            timestamp: chrono::Utc::now(),
+            usage: TokenUsage {
+                input_tokens: 1_000_000,
+                output_tokens: 100_000,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 0,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: service_tier.map(str::to_string),
+            tags: Vec::new(),
+        };
+        let events = vec![event(None), event(Some("batch")), event(Some("priority"))];
+
+        let report = compute_costs(&events, &pricing, OnUnpricedAction::Error).unwrap();
+        // standard 2.0 + batch 1.0 + priority 4.5
+        assert!((report.variable_cost_usd - 7.5).abs() < 0.0001);
+        let tiers: Vec<(&str, f64)> = report
+            .service_tier_breakdown
+            .iter()
+            .map(|row| (row.name.as_str(), row.variable_cost_usd))
+            .collect();
+        assert_eq!(
+            tiers,
+            vec![("batch", 1.0), ("priority", 4.5), ("standard", 2.0)]
+        );
+        assert!((report.batch_savings_estimate_usd - 1.0).abs() < 0.0001);
+        assert!(report.suggestions.iter().any(|tip| tip.contains("batch")));
+    }
+
+    #[test]
+    fn test_compute_costs_in_currency_converts_from_provider_currency() {
+        use crate::cli::FxDateMode;
+        use crate::fx::{FxRate, FxTable};
+        use chrono::{NaiveDate, TimeZone};
+
+        let pricing = PricingBook {
+            providers: HashMap::from([(
+                "mistral".to_string(),
+                ProviderPricing {
+                    currency: Some("EUR".to_string()),
+                    subscription_usd_month: 30.0,
+                    models: HashMap::from([(
+                        "mistral-large".to_string(),
+                        ModelRate {
+                            input_usd_per_mtok: 1.0,
+                            output_usd_per_mtok: 10.0,
+                            cache_write_usd_per_mtok: None,
+                            cache_write_1h_usd_per_mtok: None,
+                            cache_read_usd_per_mtok: None,
+                            tool_input_usd_per_mtok: None,
+                            tool_output_usd_per_mtok: None,
+                            effective_from: None,
+                            effective_to: None,
+                            context_tiers: Vec::new(),
+                        }
+                        .into(),
+                    )]),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::new(),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        };
+        let events = vec![UsageEvent {
+            provider: "mistral".to_string(),
+            model: "mistral-large".to_string(),
+            session_id: "s1".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap(),
+            usage: TokenUsage {
+                input_tokens: 1_000_000,
+                output_tokens: 100_000,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 0,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        }];
+        let rate = |rate: f64| {
+            vec![FxRate {
+                date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+                rate,
+            }]
+        };
+        let table = FxTable {
+            base: "USD".to_string(),
+            rates: HashMap::from([
+                ("EUR".to_string(), rate(0.8)),
+                ("GBP".to_string(), rate(0.6)),
+            ]),
+        };
+        let money = CurrencyConverter::new("GBP", FxDateMode::EventDay, Some(table));
+        let allocator = SubscriptionAllocator::build(&events, &pricing, None);
+
+        let report = compute_costs_in_currency(
+            &events,
+            &pricing,
+            OnUnpricedAction::Error,
+            &allocator,
+            &money,
+        )
+        .unwrap();
+        // 2 EUR of usage and the 30 EUR subscription at 0.75 GBP per EUR.
+        assert_eq!(report.currency, "GBP");
+        assert!((report.variable_cost_usd - 1.5).abs() < 0.0001);
+        assert!((report.subscription_allocated_usd - 22.5).abs() < 0.0001);
+        assert!((report.monthly_total_usd - 24.0).abs() < 0.0001);
+
+        let err = compute_costs(&events, &pricing, OnUnpricedAction::Error).unwrap_err();
+        assert!(err.to_string().contains("--fx-rates"));
+    }
+
+    #[test]
+    fn test_breakdown_rows_sum_exactly_to_totals() {
+        let rate = ModelRate {
+            input_usd_per_mtok: 0.3,
+            output_usd_per_mtok: 0.0,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: None,
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        };
+        let pricing = PricingBook {
+            providers: HashMap::from([(
+                "claude".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 20.0,
+                    models: HashMap::from([
+                        ("haiku".to_string(), rate.clone().into()),
+                        ("sonnet".to_string(), rate.clone().into()),
+                        ("opus".to_string(), rate.into()),
+                    ]),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::new(),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        };
+        let event = |model: &str| UsageEvent {
+            provider: "claude".to_string(),
+            model: model.to_string(),
+            session_id: model.to_string(),
+            timestamp: chrono::Utc::now(),
+            usage: TokenUsage {
+                input_tokens: 11_111,
+                output_tokens: 0,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 0,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        };
+        let events: Vec<UsageEvent> = ["haiku", "sonnet", "opus"]
+            .into_iter()
+            .flat_map(|model| std::iter::repeat_with(move || event(model)).take(5))
+            .collect();
+
+        let report = compute_costs(&events, &pricing, OnUnpricedAction::Error).unwrap();
+        // 15 events of 3333.3 micro-dollars and a 20.00 subscription split three ways.
+        assert_eq!(report.variable_cost_usd, 0.05);
+        assert_eq!(report.subscription_allocated_usd, 20.0);
+        let cents = |rows: &[NamedMetric], field: fn(&NamedMetric) -> f64| -> i64 {
+            rows.iter().map(|row| units_to_cents(field(row))).sum()
+        };
+        let rows = &report.model_breakdown;
+        assert_eq!(cents(rows, |row| row.variable_cost_usd), 5);
+        assert_eq!(cents(rows, |row| row.subscription_allocated_usd), 2_000);
+        assert_eq!(cents(rows, |row| row.total_cost_usd), 2_005);
+        assert_eq!(units_to_cents(report.monthly_total_usd), 2_005);
     }
 }
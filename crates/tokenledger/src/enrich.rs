@@ -0,0 +1,346 @@
+// Per-event cost enrichment (`tokenledger price`). Events are streamed line by line so large
+// ledgers price in constant memory; subscription shares need the whole population and so are
+// only computed when asked for.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use anyhow::{anyhow, Context, Result};
+use ParetoRs::OnUnpricedAction;
+
+use crate::allocation::SubscriptionAllocator;
+use crate::cli::{AllocationStrategy, PriceArgs};
+use crate::cost::{
+    applied_rate, calc_component_costs, calc_variable_cost, event_pricing, resolved_model,
+};
+use crate::models::*;
+use crate::money::*;
+use crate::utils::{
+    for_each_jsonl_event, load_events, load_pricing, normalize_events, resolve_provider_alias,
+};
+
+pub fn run_price(args: PriceArgs) -> Result<()> {
+    let pricing = load_pricing(&args.pricing)?;
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).with_context(|| format!("creating {:?}", path))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut unpriced: BTreeMap<String, usize> = BTreeMap::new();
+    let mut written = 0;
+
+    if args.with_subscription {
+        let events = load_events(&args.events)?;
+        let shares = subscription_shares(&events, &pricing, args.allocation);
+        for (evt, share) in events.iter().zip(shares) {
+            let mut record = price_event(evt, &pricing);
+            if let (Some(costs), Some(share)) = (record.costs.as_mut(), share) {
+                costs.subscription_usd = Some(from_micros(share));
+            }
+            write_record(&mut out, record, args.on_unpriced, &mut unpriced)?;
+            written += 1;
+        }
+    } else {
+        for path in &args.events {
+            for_each_jsonl_event(path, |evt| {
+                let record = price_event(&evt, &pricing);
+                write_record(&mut out, record, args.on_unpriced, &mut unpriced)?;
+                written += 1;
+                Ok(())
+            })?;
+        }
+    }
+    out.flush()?;
+
+    if !unpriced.is_empty() && args.on_unpriced == OnUnpricedAction::Warn {
+        let details = unpriced
+            .iter()
+            .map(|(key, count)| format!("{key} (events={count})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        eprintln!("warning: wrote unpriced events without costs: {}", details);
+    }
+    if let Some(path) = &args.output {
+        eprintln!("priced {} events -> {:?}", written, path);
+    }
+    Ok(())
+}
+
+// Each event's subscription share, allocated as reports allocate it: over events with aliases
+// resolved, so an aliased provider shares its canonical provider's subscription. None for
+// unpriced events.
+pub fn subscription_shares(
+    events: &[UsageEvent],
+    pricing: &PricingBook,
+    allocation: Option<AllocationStrategy>,
+) -> Vec<Option<Micros>> {
+    let normalized = normalize_events(events.to_vec(), pricing);
+    let allocator = SubscriptionAllocator::build(&normalized, pricing, allocation);
+    let mut priced_idx = Vec::new();
+    let mut priced: Vec<(&UsageEvent, Micros)> = Vec::new();
+    for (idx, evt) in normalized.iter().enumerate() {
+        if let Some((_, rate, multiplier)) = event_pricing(evt, pricing) {
+            priced_idx.push(idx);
+            priced.push((
+                evt,
+                scale_micros(calc_variable_cost(&evt.usage, rate), multiplier),
+            ));
+        }
+    }
+    let mut shares = vec![None; events.len()];
+    for (idx, share) in priced_idx
+        .into_iter()
+        .zip(allocator.allocate_events(&priced))
+    {
+        shares[idx] = Some(share);
+    }
+    shares
+}
+
+// The rate and per-component costs reports use for `evt`, without a subscription share.
+pub fn price_event(evt: &UsageEvent, pricing: &PricingBook) -> PricedEventRecord {
+    let canonical_provider = resolve_provider_alias(&evt.provider, pricing);
+    let canonical_model = resolved_model(evt, pricing);
+    let Some((provider, rate, multiplier)) = event_pricing(evt, pricing) else {
+        return PricedEventRecord {
+            event: evt.clone(),
+            currency: pricing
+                .providers
+                .get(&canonical_provider)
+                .map_or(crate::fx::USD, |provider| provider.currency())
+                .to_string(),
+            canonical_provider,
+            canonical_model,
+            rate: None,
+            service_tier_multiplier: 1.0,
+            costs: None,
+        };
+    };
+    let [input, output, cache_write, cache_write_1h, cache_read, tool_input, tool_output] =
+        calc_component_costs(&evt.usage, rate, multiplier);
+    PricedEventRecord {
+        event: evt.clone(),
+        canonical_provider,
+        canonical_model,
+        currency: provider.currency().to_string(),
+        rate: Some(applied_rate(&evt.usage, rate)),
+        service_tier_multiplier: multiplier,
+        costs: Some(EventCosts {
+            input_usd: from_micros(input),
+            output_usd: from_micros(output),
+            cache_write_usd: from_micros(cache_write),
+            cache_write_1h_usd: from_micros(cache_write_1h),
+            cache_read_usd: from_micros(cache_read),
+            tool_input_usd: from_micros(tool_input),
+            tool_output_usd: from_micros(tool_output),
+            variable_usd: from_micros(
+                input
+                    + output
+                    + cache_write
+                    + cache_write_1h
+                    + cache_read
+                    + tool_input
+                    + tool_output,
+            ),
+            subscription_usd: None,
+        }),
+    }
+}
+
+fn write_record(
+    out: &mut dyn Write,
+    record: PricedEventRecord,
+    on_unpriced: OnUnpricedAction,
+    unpriced: &mut BTreeMap<String, usize>,
+) -> Result<()> {
+    if record.costs.is_none() {
+        let key = format!("{}:{}", record.event.provider, record.event.model);
+        if on_unpriced == OnUnpricedAction::Error {
+            return Err(anyhow!(
+                "unpriced event found: {}. Re-run with --on-unpriced skip to write it without costs",
+                key
+            ));
+        }
+        *unpriced.entry(key).or_default() += 1;
+    }
+    serde_json::to_writer(&mut *out, &record)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn pricing() -> PricingBook {
+        PricingBook {
+            providers: HashMap::from([(
+                "codex".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 0.0,
+                    models: HashMap::from([(
+                        "gpt-5".to_string(),
+                        ModelRate {
+                            input_usd_per_mtok: 1.25,
+                            output_usd_per_mtok: 10.0,
+                            cache_write_usd_per_mtok: None,
+                            cache_write_1h_usd_per_mtok: None,
+                            cache_read_usd_per_mtok: Some(0.125),
+                            tool_input_usd_per_mtok: None,
+                            tool_output_usd_per_mtok: None,
+                            effective_from: None,
+                            effective_to: None,
+                            context_tiers: Vec::new(),
+                        }
+                        .into(),
+                    )]),
+                    model_aliases: HashMap::from([(
+                        "gpt-5-latest".to_string(),
+                        "gpt-5".to_string(),
+                    )]),
+                    service_tiers: HashMap::from([(
+                        "flex".to_string(),
+                        ServiceTierPricing {
+                            multiplier: 0.5,
+                            models: HashMap::new(),
+                        },
+                    )]),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::from([("openai".to_string(), "codex".to_string())]),
+            meta: None,
+        }
+    }
+
+    fn event(provider: &str, model: &str, service_tier: Option<&str>) -> UsageEvent {
+        UsageEvent {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            session_id: "s1".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2026, 2, 3, 12, 0, 0).unwrap(),
+            usage: TokenUsage {
+                input_tokens: 333_333,
+                output_tokens: 7,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 1_000_000,
+                tool_input_tokens: 1,
+                tool_output_tokens: 0,
+            },
+            service_tier: service_tier.map(str::to_string),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_price_event_resolves_aliases_and_splits_components() {
+        let book = pricing();
+        let evt = event("openai", "gpt-5-latest", Some("flex"));
+        let record = price_event(&evt, &book);
+        assert_eq!(record.canonical_provider, "codex");
+        assert_eq!(record.canonical_model, "gpt-5");
+        assert_eq!(record.service_tier_multiplier, 0.5);
+        assert_eq!(
+            record.rate.as_ref().map(|rate| rate.input_usd_per_mtok),
+            Some(1.25)
+        );
+
+        let costs = record.costs.expect("priced");
+        let (_, rate, multiplier) = event_pricing(&evt, &book).unwrap();
+        let variable = scale_micros(calc_variable_cost(&evt.usage, rate), multiplier);
+        assert_eq!(costs.variable_usd, from_micros(variable));
+        let components = [
+            costs.input_usd,
+            costs.output_usd,
+            costs.cache_write_usd,
+            costs.cache_write_1h_usd,
+            costs.cache_read_usd,
+            costs.tool_input_usd,
+            costs.tool_output_usd,
+        ];
+        assert_eq!(
+            components.iter().map(|usd| to_micros(*usd)).sum::<Micros>(),
+            variable
+        );
+        assert_eq!(costs.cache_read_usd, 0.0625);
+        assert!(costs.subscription_usd.is_none());
+    }
+
+    #[test]
+    fn test_price_event_reports_the_long_context_rate_it_applied() {
+        let mut book = pricing();
+        let codex = book.providers.get_mut("codex").unwrap();
+        let mut tiered = codex.models["gpt-5"].latest().unwrap().clone();
+        tiered.context_tiers.push(ContextTier {
+            above_prompt_tokens: 1_000_000,
+            input_usd_per_mtok: 2.5,
+            output_usd_per_mtok: 15.0,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: Some(0.25),
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+        });
+        codex.models.insert("gpt-5".to_string(), tiered.into());
+
+        // 1,333,333 prompt tokens cross the tier.
+        let evt = event("codex", "gpt-5", None);
+        let record = price_event(&evt, &book);
+        let rate = record.rate.expect("priced");
+        assert_eq!(rate.input_usd_per_mtok, 2.5);
+        assert_eq!(rate.cache_read_usd_per_mtok, Some(0.25));
+        assert_eq!(rate.tool_input_usd_per_mtok, Some(2.5));
+        assert!(rate.context_tiers.is_empty());
+        assert_eq!(
+            from_micros(calc_variable_cost(&evt.usage, &rate)),
+            record.costs.expect("priced").variable_usd
+        );
+
+        let mut short = evt.clone();
+        short.usage.cache_read_tokens = 0;
+        let rate = price_event(&short, &book).rate.expect("priced");
+        assert_eq!(rate.input_usd_per_mtok, 1.25);
+        assert_eq!(rate.context_tiers.len(), 1);
+    }
+
+    #[test]
+    fn test_subscription_shares_follow_provider_aliases() {
+        let mut book = pricing();
+        book.providers
+            .get_mut("codex")
+            .unwrap()
+            .subscription_usd_month = 100.0;
+        let events = [
+            event("openai", "gpt-5-latest", None),
+            event("codex", "gpt-5", None),
+            event("codex", "gpt-9", None),
+        ];
+        let shares = subscription_shares(&events, &book, None);
+        assert_eq!(shares[2], None);
+        // The alias shares the one $100 subscription with its canonical provider.
+        let total: Micros = shares.iter().flatten().sum();
+        assert_eq!(from_micros(total), 100.0);
+        assert_eq!(shares[0], shares[1]);
+    }
+
+    #[test]
+    fn test_price_event_keeps_unpriced_events() {
+        let record = price_event(&event("codex", "gpt-9", None), &pricing());
+        assert!(record.rate.is_none());
+        assert!(record.costs.is_none());
+        let line = serde_json::to_value(&record).unwrap();
+        assert_eq!(line["model"], "gpt-9");
+        assert_eq!(line["canonical_provider"], "codex");
+        assert!(line["costs"].is_null());
+    }
+}
@@ -0,0 +1,1274 @@
+// Spreadsheet exports (CSV and XLSX) for report, coverage and audit outputs
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use rust_xlsxwriter::{Format, Workbook};
+
+use crate::cli::{CsvLayout, ExportArgs, OutputMode};
+use crate::cost::MTOK;
+use crate::format::{round2, round4};
+use crate::models::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportCell {
+    Text(String),
+    Int(u64),
+    Number(f64),
+    Empty,
+}
+
+impl ExportCell {
+    fn to_csv_field(&self) -> String {
+        match self {
+            ExportCell::Text(value) => value.clone(),
+            ExportCell::Int(value) => value.to_string(),
+            ExportCell::Number(value) => value.to_string(),
+            ExportCell::Empty => String::new(),
+        }
+    }
+}
+
+impl From<&str> for ExportCell {
+    fn from(value: &str) -> Self {
+        ExportCell::Text(value.to_string())
+    }
+}
+
+impl From<String> for ExportCell {
+    fn from(value: String) -> Self {
+        ExportCell::Text(value)
+    }
+}
+
+impl From<u64> for ExportCell {
+    fn from(value: u64) -> Self {
+        ExportCell::Int(value)
+    }
+}
+
+impl From<usize> for ExportCell {
+    fn from(value: usize) -> Self {
+        ExportCell::Int(value as u64)
+    }
+}
+
+// Signed counts such as deltas; exact well past any token count.
+impl From<i64> for ExportCell {
+    fn from(value: i64) -> Self {
+        ExportCell::Number(value as f64)
+    }
+}
+
+impl From<f64> for ExportCell {
+    fn from(value: f64) -> Self {
+        ExportCell::Number(value)
+    }
+}
+
+impl From<bool> for ExportCell {
+    fn from(value: bool) -> Self {
+        ExportCell::Text(value.to_string())
+    }
+}
+
+/// One named table of an export; becomes a CSV section or an XLSX sheet.
+#[derive(Debug, Clone)]
+pub struct ExportTable {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<ExportCell>>,
+    pub totals: Option<Vec<ExportCell>>,
+}
+
+impl ExportTable {
+    pub fn new(name: &str, headers: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+            totals: None,
+        }
+    }
+}
+
+const METRIC_HEADERS: &[&str] = &[
+    "tokens",
+    "mtok",
+    "variable_cost_usd",
+    "subscription_allocated_usd",
+    "total_cost_usd",
+    "blended_usd_per_mtok",
+    "session_count",
+    "tool_share",
+];
+
+const SUMMARY_HEADERS: &[&str] = &[
+    "variable_cost_usd",
+    "subscription_allocated_usd",
+    "monthly_total_usd",
+    "blended_usd_per_mtok",
+    "total_tokens",
+    "total_mtok",
+    "input_tokens",
+    "output_tokens",
+    "cache_write_tokens",
+    "cache_write_1h_tokens",
+    "cache_read_tokens",
+    "tool_input_tokens",
+    "tool_output_tokens",
+    "session_count",
+    "skipped_unpriced_count",
+    "batch_savings_estimate_usd",
+    "idle_subscription_usd",
+    "currency",
+];
+
+fn headers_with(prefix: &[&str], rest: &[&str]) -> Vec<String> {
+    prefix
+        .iter()
+        .chain(rest.iter())
+        .map(|h| h.to_string())
+        .collect()
+}
+
+fn metric_cells(row: &NamedMetric) -> Vec<ExportCell> {
+    vec![
+        row.tokens.into(),
+        row.mtok.into(),
+        row.variable_cost_usd.into(),
+        row.subscription_allocated_usd.into(),
+        row.total_cost_usd.into(),
+        row.blended_usd_per_mtok.into(),
+        row.session_count.into(),
+        row.tool_share.into(),
+    ]
+}
+
+fn metric_total_cells(report: &CostBreakdown) -> Vec<ExportCell> {
+    let tool_tokens = report.tool_input_tokens + report.tool_output_tokens;
+    let tool_share = if report.total_tokens == 0 {
+        0.0
+    } else {
+        tool_tokens as f64 / report.total_tokens as f64
+    };
+    vec![
+        report.total_tokens.into(),
+        round4(report.total_tokens as f64 / MTOK).into(),
+        report.variable_cost_usd.into(),
+        report.subscription_allocated_usd.into(),
+        report.monthly_total_usd.into(),
+        report.blended_usd_per_mtok.into(),
+        report.session_count.into(),
+        round4(tool_share).into(),
+    ]
+}
+
+fn summary_cells(report: &CostBreakdown) -> Vec<ExportCell> {
+    vec![
+        report.variable_cost_usd.into(),
+        report.subscription_allocated_usd.into(),
+        report.monthly_total_usd.into(),
+        report.blended_usd_per_mtok.into(),
+        report.total_tokens.into(),
+        report.total_mtok.into(),
+        report.input_tokens.into(),
+        report.output_tokens.into(),
+        report.cache_write_tokens.into(),
+        report.cache_write_1h_tokens.into(),
+        report.cache_read_tokens.into(),
+        report.tool_input_tokens.into(),
+        report.tool_output_tokens.into(),
+        report.session_count.into(),
+        report.skipped_unpriced_count.into(),
+        report.batch_savings_estimate_usd.into(),
+        report.idle_subscription_usd.into(),
+        report.currency.as_str().into(),
+    ]
+}
+
+fn breakdown_table(
+    name: &str,
+    key: &str,
+    rows: &[NamedMetric],
+    report: &CostBreakdown,
+) -> ExportTable {
+    let mut table = ExportTable::new(name, &[]);
+    table.headers = headers_with(&[key], METRIC_HEADERS);
+    for row in rows {
+        let mut cells = vec![ExportCell::from(row.name.as_str())];
+        cells.extend(metric_cells(row));
+        table.rows.push(cells);
+    }
+    let mut totals = vec![ExportCell::from("TOTAL")];
+    totals.extend(metric_total_cells(report));
+    table.totals = Some(totals);
+    table
+}
+
+pub fn cost_breakdown_tables(report: &CostBreakdown) -> Vec<ExportTable> {
+    let mut summary = ExportTable::new("summary", SUMMARY_HEADERS);
+    summary.rows.push(summary_cells(report));
+    let mut tables = vec![
+        summary,
+        breakdown_table("providers", "provider", &report.provider_breakdown, report),
+        breakdown_table("models", "model", &report.model_breakdown, report),
+        breakdown_table(
+            "service_tiers",
+            "service_tier",
+            &report.service_tier_breakdown,
+            report,
+        ),
+    ];
+    if let Some(contract) = &report.contract {
+        tables.push(contract_table(contract));
+    }
+    tables
+}
+
+const CONTRACT_HEADERS: &[&str] = &[
+    "provider",
+    "list_cost",
+    "discount",
+    "commit_shortfall",
+    "credits_applied",
+    "net_cost",
+    "remaining_credit",
+];
+
+fn contract_table(contract: &ContractSummary) -> ExportTable {
+    let mut table = ExportTable::new("contract", CONTRACT_HEADERS);
+    for row in &contract.providers {
+        table.rows.push(vec![
+            ExportCell::from(row.provider.as_str()),
+            ExportCell::from(row.list_cost_usd),
+            ExportCell::from(row.discount_usd),
+            ExportCell::from(row.commit_shortfall_usd),
+            ExportCell::from(row.credits_applied_usd),
+            ExportCell::from(row.net_cost_usd),
+            ExportCell::from(row.remaining_credit_usd),
+        ]);
+    }
+    table.totals = Some(vec![
+        ExportCell::from("TOTAL"),
+        ExportCell::from(contract.list_cost_usd),
+        ExportCell::from(contract.discount_usd),
+        ExportCell::from(contract.commit_shortfall_usd),
+        ExportCell::from(contract.credits_applied_usd),
+        ExportCell::from(contract.net_cost_usd),
+        ExportCell::from(contract.remaining_credit_usd),
+    ]);
+    table
+}
+
+pub fn timeseries_tables(report: &TimeSeriesReport) -> Vec<ExportTable> {
+    let mut summary = ExportTable::new("summary", &[]);
+    summary.headers = headers_with(&["period", "granularity"], SUMMARY_HEADERS);
+    let mut cells = vec![
+        ExportCell::from(report.period.as_str()),
+        ExportCell::from(report.granularity.as_str()),
+    ];
+    cells.extend(summary_cells(&report.totals));
+    summary.rows.push(cells);
+
+    let mut buckets = ExportTable::new("buckets", &[]);
+    buckets.headers = headers_with(&["period"], METRIC_HEADERS);
+    let mut bucket_providers = ExportTable::new("bucket_providers", &[]);
+    bucket_providers.headers = headers_with(&["period", "provider"], METRIC_HEADERS);
+    let mut bucket_models = ExportTable::new("bucket_models", &[]);
+    bucket_models.headers = headers_with(&["period", "model"], METRIC_HEADERS);
+
+    for bucket in &report.buckets {
+        let mut cells = vec![ExportCell::from(bucket.period.as_str())];
+        cells.extend(metric_total_cells(&bucket.breakdown));
+        buckets.rows.push(cells);
+        for row in &bucket.breakdown.provider_breakdown {
+            let mut cells = vec![
+                ExportCell::from(bucket.period.as_str()),
+                ExportCell::from(row.name.as_str()),
+            ];
+            cells.extend(metric_cells(row));
+            bucket_providers.rows.push(cells);
+        }
+        for row in &bucket.breakdown.model_breakdown {
+            let mut cells = vec![
+                ExportCell::from(bucket.period.as_str()),
+                ExportCell::from(row.name.as_str()),
+            ];
+            cells.extend(metric_cells(row));
+            bucket_models.rows.push(cells);
+        }
+    }
+    let mut totals = vec![ExportCell::from("TOTAL")];
+    totals.extend(metric_total_cells(&report.totals));
+    buckets.totals = Some(totals);
+
+    vec![
+        summary,
+        breakdown_table(
+            "providers",
+            "provider",
+            &report.totals.provider_breakdown,
+            &report.totals,
+        ),
+        breakdown_table(
+            "models",
+            "model",
+            &report.totals.model_breakdown,
+            &report.totals,
+        ),
+        breakdown_table(
+            "service_tiers",
+            "service_tier",
+            &report.totals.service_tier_breakdown,
+            &report.totals,
+        ),
+        buckets,
+        bucket_providers,
+        bucket_models,
+    ]
+}
+
+// One column per dimension, rows depth first; nested subtotal rows leave deeper columns empty.
+pub fn pivot_tables(report: &PivotReport) -> Vec<ExportTable> {
+    let dimensions: Vec<&str> = report
+        .dimensions
+        .iter()
+        .map(|dimension| dimension.as_str())
+        .collect();
+    let mut table = ExportTable::new("pivot", &[]);
+    table.headers = headers_with(&dimensions, METRIC_HEADERS);
+    let key_cells = |keys: &[String]| -> Vec<ExportCell> {
+        (0..dimensions.len())
+            .map(|idx| {
+                keys.get(idx)
+                    .map_or(ExportCell::Empty, |key| key.as_str().into())
+            })
+            .collect()
+    };
+    let mut stack: Vec<&PivotRow> = report.rows.iter().rev().collect();
+    while let Some(row) = stack.pop() {
+        let mut cells = key_cells(&row.keys);
+        cells.extend(metric_cells(&row.metric));
+        table.rows.push(cells);
+        stack.extend(row.children.iter().rev());
+    }
+    let mut totals = key_cells(&["TOTAL".to_string()]);
+    totals.extend(metric_cells(&report.totals));
+    table.totals = Some(totals);
+    vec![table]
+}
+
+const SESSION_HEADERS: &[&str] = &[
+    "provider",
+    "session_id",
+    "start",
+    "end",
+    "duration_secs",
+    "event_count",
+    "total_tokens",
+    "input_tokens",
+    "output_tokens",
+    "cache_write_tokens",
+    "cache_write_1h_tokens",
+    "cache_read_tokens",
+    "tool_input_tokens",
+    "tool_output_tokens",
+    "variable_cost_usd",
+    "subscription_allocated_usd",
+    "total_cost_usd",
+    "cache_hit_ratio",
+];
+
+// Session rows and their model mix.
+fn session_summary_tables(sessions: &[SessionSummary]) -> Vec<ExportTable> {
+    let mut table = ExportTable::new("sessions", SESSION_HEADERS);
+    let mut models = ExportTable::new("session_models", &[]);
+    models.headers = headers_with(&["provider", "session_id", "model"], METRIC_HEADERS);
+    for session in sessions {
+        table.rows.push(vec![
+            session.provider.as_str().into(),
+            session.session_id.as_str().into(),
+            session.start.to_rfc3339().into(),
+            session.end.to_rfc3339().into(),
+            ExportCell::Number(session.duration_secs as f64),
+            session.event_count.into(),
+            session.total_tokens.into(),
+            session.input_tokens.into(),
+            session.output_tokens.into(),
+            session.cache_write_tokens.into(),
+            session.cache_write_1h_tokens.into(),
+            session.cache_read_tokens.into(),
+            session.tool_input_tokens.into(),
+            session.tool_output_tokens.into(),
+            session.variable_cost_usd.into(),
+            session.subscription_allocated_usd.into(),
+            session.total_cost_usd.into(),
+            session.cache_hit_ratio.into(),
+        ]);
+        for row in &session.models {
+            let mut cells = vec![
+                ExportCell::from(session.provider.as_str()),
+                ExportCell::from(session.session_id.as_str()),
+                ExportCell::from(row.name.as_str()),
+            ];
+            cells.extend(metric_cells(row));
+            models.rows.push(cells);
+        }
+    }
+    vec![table, models]
+}
+
+pub fn session_tables(report: &SessionReport) -> Vec<ExportTable> {
+    session_summary_tables(&report.sessions)
+}
+
+pub fn session_timeline_tables(timeline: &SessionTimeline) -> Vec<ExportTable> {
+    let mut events = ExportTable::new(
+        "events",
+        &[
+            "timestamp",
+            "gap_secs",
+            "provider",
+            "model",
+            "service_tier",
+            "input_tokens",
+            "output_tokens",
+            "cache_write_tokens",
+            "cache_write_1h_tokens",
+            "cache_read_tokens",
+            "tool_input_tokens",
+            "tool_output_tokens",
+            "variable_cost_usd",
+            "cumulative_cost_usd",
+        ],
+    );
+    for event in &timeline.events {
+        events.rows.push(vec![
+            event.timestamp.to_rfc3339().into(),
+            ExportCell::Number(event.gap_secs as f64),
+            event.provider.as_str().into(),
+            event.model.as_str().into(),
+            event.service_tier.as_str().into(),
+            event.usage.input_tokens.into(),
+            event.usage.output_tokens.into(),
+            event.usage.cache_write_tokens.into(),
+            event.usage.cache_write_1h_tokens.into(),
+            event.usage.cache_read_tokens.into(),
+            event.usage.tool_input_tokens.into(),
+            event.usage.tool_output_tokens.into(),
+            event
+                .variable_cost_usd
+                .map_or(ExportCell::Empty, ExportCell::Number),
+            event.cumulative_cost_usd.into(),
+        ]);
+    }
+    let mut tables = session_summary_tables(&timeline.sessions);
+    tables.push(events);
+    tables
+}
+
+pub fn coverage_tables(report: &CoverageReport) -> Vec<ExportTable> {
+    let mut summary = ExportTable::new(
+        "summary",
+        &[
+            "month",
+            "events",
+            "tokens",
+            "priced_count",
+            "unpriced_count",
+            "missing_provider_count",
+            "long_context_events",
+        ],
+    );
+    summary.rows.push(vec![
+        report.month.as_str().into(),
+        report.totals.events.into(),
+        report.totals.tokens.into(),
+        report.priced_count.into(),
+        report.unpriced_count.into(),
+        report.missing_providers.len().into(),
+        report.long_context_events.into(),
+    ]);
+
+    let mut providers = ExportTable::new("missing_providers", &["provider", "suggested_aliases"]);
+    for provider in &report.missing_providers {
+        let suggestions = report
+            .suggested_provider_aliases
+            .get(provider)
+            .map(|items| items.join(" "))
+            .unwrap_or_default();
+        providers
+            .rows
+            .push(vec![provider.as_str().into(), suggestions.into()]);
+    }
+
+    let mut models = ExportTable::new("missing_models", &["provider", "model", "events"]);
+    for (provider, suggestions) in &report.suggested_model_aliases_by_provider {
+        for suggestion in suggestions {
+            models.rows.push(vec![
+                provider.as_str().into(),
+                suggestion.model.as_str().into(),
+                suggestion.count.into(),
+            ]);
+        }
+    }
+    let unpriced_model_events: usize = models
+        .rows
+        .iter()
+        .filter_map(|row| match row.get(2) {
+            Some(ExportCell::Int(count)) => Some(*count as usize),
+            _ => None,
+        })
+        .sum();
+    models.totals = Some(vec![
+        "TOTAL".into(),
+        ExportCell::Empty,
+        unpriced_model_events.into(),
+    ]);
+
+    let mut long_context = ExportTable::new(
+        "long_context",
+        &[
+            "provider",
+            "model",
+            "above_prompt_tokens",
+            "events",
+            "prompt_tokens",
+        ],
+    );
+    for usage in &report.long_context_by_model {
+        long_context.rows.push(vec![
+            usage.provider.as_str().into(),
+            usage.model.as_str().into(),
+            usage.above_prompt_tokens.into(),
+            usage.events.into(),
+            usage.prompt_tokens.into(),
+        ]);
+    }
+
+    vec![summary, providers, models, long_context]
+}
+
+pub fn plan_analysis_tables(report: &PlanAnalysisReport) -> Vec<ExportTable> {
+    let mut providers = ExportTable::new(
+        "providers",
+        &[
+            "month",
+            "provider",
+            "currency",
+            "plan",
+            "tokens",
+            "api_list_cost_usd",
+            "subscription_usd",
+            "overage_usd",
+            "plan_cost_usd",
+            "net_savings_usd",
+        ],
+    );
+    let mut seats = ExportTable::new(
+        "seats",
+        &[
+            "provider",
+            "seat",
+            "plan",
+            "count",
+            "active_days",
+            "tokens",
+            "included_tokens",
+            "utilization_pct",
+            "quota_exceeded",
+            "break_even_tokens",
+            "subscription_usd",
+            "overage_usd",
+            "plan_cost_usd",
+            "api_list_cost_usd",
+            "savings_usd",
+            "best_option",
+            "best_option_usd",
+            "recommendation",
+        ],
+    );
+    for provider in &report.providers {
+        providers.rows.push(vec![
+            report.month.as_str().into(),
+            provider.provider.as_str().into(),
+            provider.currency.as_str().into(),
+            optional_text(provider.plan.as_deref()),
+            provider.tokens.into(),
+            provider.api_list_cost_usd.into(),
+            provider.subscription_usd.into(),
+            provider.overage_usd.into(),
+            provider.plan_cost_usd.into(),
+            provider.net_savings_usd.into(),
+        ]);
+        for seat in &provider.seats {
+            seats.rows.push(vec![
+                provider.provider.as_str().into(),
+                seat.seat.as_str().into(),
+                optional_text(seat.plan.as_deref()),
+                (seat.count as u64).into(),
+                (seat.active_days as u64).into(),
+                seat.tokens.into(),
+                seat.included_tokens
+                    .map(ExportCell::Int)
+                    .unwrap_or(ExportCell::Empty),
+                seat.utilization_pct
+                    .map(ExportCell::Number)
+                    .unwrap_or(ExportCell::Empty),
+                seat.quota_exceeded.into(),
+                seat.break_even_tokens
+                    .map(ExportCell::Int)
+                    .unwrap_or(ExportCell::Empty),
+                seat.subscription_usd.into(),
+                seat.overage_usd.into(),
+                seat.plan_cost_usd.into(),
+                seat.api_list_cost_usd.into(),
+                seat.savings_usd.into(),
+                seat.best_option.as_str().into(),
+                seat.best_option_usd.into(),
+                seat.recommendation.as_str().into(),
+            ]);
+        }
+    }
+    let sum = |field: fn(&ProviderPlanAnalysis) -> f64| -> ExportCell {
+        round2(report.providers.iter().map(field).sum()).into()
+    };
+    providers.totals = Some(vec![
+        "TOTAL".into(),
+        ExportCell::Empty,
+        ExportCell::Empty,
+        ExportCell::Empty,
+        report
+            .providers
+            .iter()
+            .map(|provider| provider.tokens)
+            .sum::<u64>()
+            .into(),
+        sum(|provider| provider.api_list_cost_usd),
+        sum(|provider| provider.subscription_usd),
+        sum(|provider| provider.overage_usd),
+        sum(|provider| provider.plan_cost_usd),
+        sum(|provider| provider.net_savings_usd),
+    ]);
+
+    vec![providers, seats]
+}
+
+pub fn whatif_tables(report: &WhatifReport) -> Vec<ExportTable> {
+    let mut rules = ExportTable::new(
+        "rules",
+        &["rule", "events", "tokens_before", "tokens_after"],
+    );
+    for rule in &report.rules {
+        rules.rows.push(vec![
+            rule.rule.as_str().into(),
+            rule.events.into(),
+            rule.tokens_before.into(),
+            rule.tokens_after.into(),
+        ]);
+    }
+    let mut summary = ExportTable::new(
+        "summary",
+        &[
+            "month",
+            "metric",
+            "actual_usd",
+            "simulated_usd",
+            "delta_usd",
+        ],
+    );
+    for (metric, actual, simulated) in [
+        (
+            "variable_cost",
+            report.actual.variable_cost_usd,
+            report.simulated.variable_cost_usd,
+        ),
+        (
+            "subscription_allocated",
+            report.actual.subscription_allocated_usd,
+            report.simulated.subscription_allocated_usd,
+        ),
+        (
+            "idle_subscription",
+            report.actual.idle_subscription_usd,
+            report.simulated.idle_subscription_usd,
+        ),
+        ("total", report.actual_total_usd, report.simulated_total_usd),
+    ] {
+        summary.rows.push(vec![
+            report.month.as_str().into(),
+            metric.into(),
+            actual.into(),
+            simulated.into(),
+            round2(simulated - actual).into(),
+        ]);
+    }
+    let delta_table = |name: &str, key: &str, deltas: &[WhatifDelta]| {
+        let mut table = ExportTable::new(name, &[key, "actual_usd", "simulated_usd", "delta_usd"]);
+        for row in deltas {
+            table.rows.push(vec![
+                row.name.as_str().into(),
+                row.actual_usd.into(),
+                row.simulated_usd.into(),
+                row.delta_usd.into(),
+            ]);
+        }
+        table
+    };
+    vec![
+        summary,
+        rules,
+        delta_table("providers", "provider", &report.providers),
+        delta_table("models", "model", &report.models),
+    ]
+}
+
+pub fn cache_tables(report: &CacheReport) -> Vec<ExportTable> {
+    let columns = [
+        "scope",
+        "name",
+        "input_tokens",
+        "cache_read_tokens",
+        "cache_write_tokens",
+        "hit_ratio",
+        "write_amplification",
+        "read_savings_usd",
+        "write_premium_usd",
+        "net_savings_usd",
+        "unread_sessions",
+    ];
+    let mut metrics = ExportTable::new("metrics", &columns);
+    let scoped = std::iter::once(("total", &report.totals))
+        .chain(report.providers.iter().map(|metric| ("provider", metric)))
+        .chain(report.models.iter().map(|metric| ("model", metric)));
+    for (scope, metric) in scoped {
+        metrics.rows.push(vec![
+            scope.into(),
+            metric.name.as_str().into(),
+            metric.input_tokens.into(),
+            metric.cache_read_tokens.into(),
+            metric.cache_write_tokens.into(),
+            metric.hit_ratio.into(),
+            metric
+                .write_amplification
+                .map(ExportCell::from)
+                .unwrap_or(ExportCell::Empty),
+            metric.read_savings_usd.into(),
+            metric.write_premium_usd.into(),
+            metric.net_savings_usd.into(),
+            metric
+                .unread_sessions
+                .map(|count| ExportCell::Int(count as u64))
+                .unwrap_or(ExportCell::Empty),
+        ]);
+    }
+    let mut unread = ExportTable::new(
+        "unread_sessions",
+        &[
+            "provider",
+            "model",
+            "session_id",
+            "cache_write_tokens",
+            "write_premium_usd",
+        ],
+    );
+    for session in &report.unread_sessions {
+        unread.rows.push(vec![
+            session.provider.as_str().into(),
+            session.model.as_str().into(),
+            session.session_id.as_str().into(),
+            session.cache_write_tokens.into(),
+            session.write_premium_usd.into(),
+        ]);
+    }
+    vec![metrics, unread]
+}
+
+pub fn forecast_tables(report: &ForecastReport) -> Vec<ExportTable> {
+    let mut summary = ExportTable::new(
+        "summary",
+        &[
+            "period",
+            "currency",
+            "as_of",
+            "method",
+            "confidence",
+            "elapsed_days",
+            "remaining_days",
+            "spent_total_usd",
+            "projected_total_usd",
+        ],
+    );
+    summary.rows.push(vec![
+        report.period.as_str().into(),
+        report.currency.as_str().into(),
+        report.as_of.to_string().into(),
+        report.method.as_str().into(),
+        report.confidence.into(),
+        report.elapsed_days.into(),
+        report.remaining_days.into(),
+        report.spent_total_usd.into(),
+        report.projected_total_usd.into(),
+    ]);
+    let mut series = ExportTable::new(
+        "series",
+        &[
+            "scope",
+            "name",
+            "spent_usd",
+            "daily_average_usd",
+            "projected_usd",
+            "low_usd",
+            "high_usd",
+            "run_rate_usd",
+            "weekday_usd",
+            "ewma_usd",
+        ],
+    );
+    let scoped = std::iter::once(("total", &report.overall))
+        .chain(report.providers.iter().map(|row| ("provider", row)));
+    for (scope, row) in scoped {
+        series.rows.push(vec![
+            scope.into(),
+            row.name.as_str().into(),
+            row.spent_usd.into(),
+            row.daily_average_usd.into(),
+            row.projected_usd.into(),
+            row.low_usd.into(),
+            row.high_usd.into(),
+            row.run_rate_usd.into(),
+            row.weekday_usd.into(),
+            row.ewma_usd.into(),
+        ]);
+    }
+    vec![summary, series]
+}
+
+pub fn compare_tables(report: &CompareReport) -> Vec<ExportTable> {
+    let mut rows = ExportTable::new(
+        "rows",
+        &[
+            "period",
+            "against",
+            "scope",
+            "name",
+            "baseline_cost_usd",
+            "cost_usd",
+            "cost_delta_usd",
+            "cost_delta_pct",
+            "baseline_tokens",
+            "tokens",
+            "tokens_delta",
+            "baseline_blended_usd_per_mtok",
+            "blended_usd_per_mtok",
+            "baseline_sessions",
+            "sessions",
+            "sessions_delta",
+            "volume_effect_usd",
+            "price_mix_effect_usd",
+        ],
+    );
+    let scoped = std::iter::once(("total", &report.totals))
+        .chain(report.providers.iter().map(|row| ("provider", row)))
+        .chain(report.models.iter().map(|row| ("model", row)));
+    for (scope, row) in scoped {
+        rows.rows.push(vec![
+            report.period.as_str().into(),
+            report.against.as_str().into(),
+            scope.into(),
+            row.name.as_str().into(),
+            row.baseline_cost_usd.into(),
+            row.cost_usd.into(),
+            row.cost_delta_usd.into(),
+            row.cost_delta_pct
+                .map_or(ExportCell::Empty, ExportCell::from),
+            row.baseline_tokens.into(),
+            row.tokens.into(),
+            row.tokens_delta.into(),
+            row.baseline_blended_usd_per_mtok.into(),
+            row.blended_usd_per_mtok.into(),
+            row.baseline_sessions.into(),
+            row.sessions.into(),
+            row.sessions_delta.into(),
+            row.volume_effect_usd.into(),
+            row.price_mix_effect_usd.into(),
+        ]);
+    }
+    vec![rows]
+}
+
+pub fn anomaly_tables(report: &AnomalyReport) -> Vec<ExportTable> {
+    let mut anomalies = ExportTable::new(
+        "anomalies",
+        &[
+            "period",
+            "granularity",
+            "bucket",
+            "start",
+            "scope",
+            "name",
+            "spend_usd",
+            "baseline_usd",
+            "excess_usd",
+            "z_score",
+        ],
+    );
+    for anomaly in &report.anomalies {
+        anomalies.rows.push(vec![
+            report.period.as_str().into(),
+            report.granularity.as_str().into(),
+            anomaly.bucket.as_str().into(),
+            anomaly.start.to_rfc3339().into(),
+            anomaly.scope.as_str().into(),
+            anomaly.name.as_str().into(),
+            anomaly.spend_usd.into(),
+            anomaly.baseline_usd.into(),
+            anomaly.excess_usd.into(),
+            anomaly.z_score.into(),
+        ]);
+    }
+    vec![anomalies]
+}
+
+fn optional_text(value: Option<&str>) -> ExportCell {
+    value.map(ExportCell::from).unwrap_or(ExportCell::Empty)
+}
+
+pub fn pricing_audit_tables(report: &PricingAuditReport) -> Vec<ExportTable> {
+    let mut summary = ExportTable::new(
+        "summary",
+        &[
+            "pricing_path",
+            "checked_at",
+            "metadata_present",
+            "source_present",
+            "updated_at_present",
+            "age_days",
+            "stale",
+            "pass",
+        ],
+    );
+    summary.rows.push(vec![
+        report.pricing_path.as_str().into(),
+        report.checked_at.as_str().into(),
+        report.metadata_present.into(),
+        report.source_present.into(),
+        report.updated_at_present.into(),
+        report
+            .age_days
+            .map(|days| ExportCell::Number(days as f64))
+            .unwrap_or(ExportCell::Empty),
+        report.stale.into(),
+        report.pass.into(),
+    ]);
+
+    let mut findings = ExportTable::new("findings", &["severity", "message"]);
+    for violation in &report.violations {
+        findings
+            .rows
+            .push(vec!["violation".into(), violation.as_str().into()]);
+    }
+    for warning in &report.warnings {
+        findings
+            .rows
+            .push(vec!["warning".into(), warning.as_str().into()]);
+    }
+
+    vec![summary, findings]
+}
+
+pub fn write_export(tables: &[ExportTable], output: OutputMode, export: &ExportArgs) -> Result<()> {
+    match output {
+        OutputMode::Csv => match export.output_path.as_deref() {
+            Some(path) => {
+                let file = create_output_file(path)?;
+                write_csv(file, tables, export.csv_layout)
+                    .with_context(|| format!("writing csv {:?}", path))
+            }
+            None => write_csv(io::stdout().lock(), tables, export.csv_layout),
+        },
+        OutputMode::Xlsx => {
+            let path = export
+                .output_path
+                .as_deref()
+                .ok_or_else(|| anyhow!("--output xlsx requires --output-path"))?;
+            write_xlsx(path, tables)
+        }
+        OutputMode::Table | OutputMode::Markdown | OutputMode::Json | OutputMode::Html => Err(
+            anyhow!("write_export only supports csv and xlsx output modes"),
+        ),
+    }
+}
+
+fn create_output_file(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("creating output directory {:?}", parent))?;
+        }
+    }
+    File::create(path).with_context(|| format!("creating {:?}", path))
+}
+
+fn table_rows(table: &ExportTable) -> impl Iterator<Item = &Vec<ExportCell>> {
+    table.rows.iter().chain(table.totals.iter())
+}
+
+pub fn write_csv<W: Write>(writer: W, tables: &[ExportTable], layout: CsvLayout) -> Result<()> {
+    match layout {
+        CsvLayout::Flat => write_csv_flat(writer, tables),
+        CsvLayout::Sections => write_csv_sections(writer, tables),
+    }
+}
+
+fn write_csv_flat<W: Write>(writer: W, tables: &[ExportTable]) -> Result<()> {
+    let mut columns: Vec<&str> = Vec::new();
+    for table in tables {
+        for header in &table.headers {
+            if !columns.contains(&header.as_str()) {
+                columns.push(header);
+            }
+        }
+    }
+
+    let mut csv_writer = csv::WriterBuilder::new().flexible(true).from_writer(writer);
+    let mut header_row = vec!["section"];
+    header_row.extend(columns.iter().copied());
+    csv_writer.write_record(&header_row)?;
+    for table in tables {
+        for row in table_rows(table) {
+            let mut record = vec![table.name.clone()];
+            for column in &columns {
+                let value = table
+                    .headers
+                    .iter()
+                    .position(|header| header == column)
+                    .and_then(|idx| row.get(idx))
+                    .map(ExportCell::to_csv_field)
+                    .unwrap_or_default();
+                record.push(value);
+            }
+            csv_writer.write_record(&record)?;
+        }
+    }
+    csv_writer.flush()?;
+    Ok(())
+}
+
+fn write_csv_sections<W: Write>(mut writer: W, tables: &[ExportTable]) -> Result<()> {
+    for (idx, table) in tables.iter().enumerate() {
+        if idx > 0 {
+            writer.write_all(b"\n")?;
+        }
+        let mut csv_writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_writer(&mut writer);
+        csv_writer.write_record([format!("# {}", table.name)])?;
+        csv_writer.write_record(&table.headers)?;
+        for row in table_rows(table) {
+            csv_writer.write_record(row.iter().map(ExportCell::to_csv_field))?;
+        }
+        csv_writer.flush()?;
+    }
+    Ok(())
+}
+
+fn xlsx_number_format(header: &str) -> Option<&'static str> {
+    if header == "tool_share" {
+        Some("0.00%")
+    } else if header.ends_with("_per_mtok") || header.ends_with("mtok") {
+        Some("#,##0.0000")
+    } else if header.ends_with("_usd") {
+        Some("#,##0.00")
+    } else {
+        None
+    }
+}
+
+pub fn write_xlsx(path: &Path, tables: &[ExportTable]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("creating output directory {:?}", parent))?;
+        }
+    }
+    let mut workbook = Workbook::new();
+    let bold = Format::new().set_bold();
+    for table in tables {
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(&table.name)?;
+        for (col, header) in table.headers.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, header, &bold)?;
+        }
+        let column_formats: Vec<Format> = table
+            .headers
+            .iter()
+            .map(|header| match xlsx_number_format(header) {
+                Some(num_format) => Format::new().set_num_format(num_format),
+                None => Format::new(),
+            })
+            .collect();
+        for (row_idx, row) in table_rows(table).enumerate() {
+            let is_total = table.totals.is_some() && row_idx == table.rows.len();
+            let xlsx_row = (row_idx + 1) as u32;
+            for (col, cell) in row.iter().enumerate() {
+                let mut format = column_formats.get(col).cloned().unwrap_or_else(Format::new);
+                if is_total {
+                    format = format.set_bold();
+                }
+                let col = col as u16;
+                match cell {
+                    ExportCell::Text(value) => {
+                        sheet.write_string_with_format(xlsx_row, col, value, &format)?;
+                    }
+                    ExportCell::Int(value) => {
+                        sheet.write_number_with_format(xlsx_row, col, *value as f64, &format)?;
+                    }
+                    ExportCell::Number(value) => {
+                        sheet.write_number_with_format(xlsx_row, col, *value, &format)?;
+                    }
+                    ExportCell::Empty => {}
+                }
+            }
+        }
+        sheet.autofit();
+    }
+    workbook
+        .save(path)
+        .with_context(|| format!("writing xlsx {:?}", path))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(name: &str, tokens: u64, total: f64) -> NamedMetric {
+        NamedMetric {
+            name: name.to_string(),
+            tokens,
+            mtok: tokens as f64 / MTOK,
+            variable_cost_usd: total,
+            subscription_allocated_usd: 0.0,
+            total_cost_usd: total,
+            blended_usd_per_mtok: 0.0,
+            session_count: 1,
+            tool_share: 0.0,
+        }
+    }
+
+    fn breakdown() -> CostBreakdown {
+        CostBreakdown {
+            currency: "USD".to_string(),
+            variable_cost_usd: 3.5,
+            subscription_allocated_usd: 0.0,
+            monthly_total_usd: 3.5,
+            blended_usd_per_mtok: 1.75,
+            total_tokens: 2_000_000,
+            total_mtok: 2.0,
+            input_tokens: 2_000_000,
+            output_tokens: 0,
+            cache_write_tokens: 0,
+            cache_write_1h_tokens: 0,
+            cache_read_tokens: 0,
+            tool_input_tokens: 0,
+            tool_output_tokens: 0,
+            session_count: 2,
+            skipped_unpriced_count: 0,
+            provider_breakdown: vec![
+                metric("claude", 1_500_000, 3.0),
+                metric("codex", 500_000, 0.5),
+            ],
+            model_breakdown: vec![metric("claude-sonnet-4-5", 2_000_000, 3.5)],
+            service_tier_breakdown: vec![metric("standard", 2_000_000, 3.5)],
+            batch_savings_estimate_usd: 0.0,
+            idle_subscription_usd: 0.0,
+            contract: None,
+            cache: None,
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_cost_breakdown_tables_include_totals_rows() {
+        let tables = cost_breakdown_tables(&breakdown());
+        let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["summary", "providers", "models", "service_tiers"]
+        );
+
+        let providers = &tables[1];
+        assert_eq!(providers.rows.len(), 2);
+        let totals = providers.totals.as_ref().expect("providers totals row");
+        assert_eq!(totals[0], ExportCell::Text("TOTAL".to_string()));
+        assert_eq!(totals[1], ExportCell::Int(2_000_000));
+        assert_eq!(totals[5], ExportCell::Number(3.5));
+    }
+
+    #[test]
+    fn test_write_csv_sections_layout() {
+        let tables = cost_breakdown_tables(&breakdown());
+        let mut out = Vec::new();
+        write_csv(&mut out, &tables, CsvLayout::Sections).expect("csv");
+        let text = String::from_utf8(out).expect("utf8");
+        assert!(text.starts_with("# summary\n"));
+        assert!(text.contains("\n\n# providers\nprovider,tokens,"));
+        assert!(text.contains("claude,1500000,1.5,3,0,3,0,1,0\n"));
+        assert!(text.contains("TOTAL,2000000,2,3.5,0,3.5,1.75,2,0\n"));
+    }
+
+    #[test]
+    fn test_write_csv_flat_layout_unions_columns() {
+        let tables = cost_breakdown_tables(&breakdown());
+        let mut out = Vec::new();
+        write_csv(&mut out, &tables, CsvLayout::Flat).expect("csv");
+        let text = String::from_utf8(out).expect("utf8");
+        let mut lines = text.lines();
+        let header = lines.next().expect("header");
+        assert!(header.starts_with("section,variable_cost_usd,"));
+        let columns: Vec<&str> = header.split(',').collect();
+        assert!(columns.contains(&"provider"));
+        assert!(columns.contains(&"model"));
+        let widths: Vec<usize> = text.lines().map(|line| line.split(',').count()).collect();
+        assert!(widths.iter().all(|w| *w == widths[0]));
+    }
+
+    #[test]
+    fn test_write_xlsx_creates_workbook() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("monthly.xlsx");
+        write_xlsx(&path, &cost_breakdown_tables(&breakdown())).expect("xlsx");
+        let bytes = fs::read(&path).expect("read xlsx");
+        assert_eq!(&bytes[..2], b"PK");
+    }
+
+    #[test]
+    fn test_render_cost_breakdown_writes_xlsx_to_output_path() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let export = ExportArgs {
+            output_path: Some(dir.path().join("monthly.xlsx")),
+            ..ExportArgs::default()
+        };
+        crate::utils::render_cost_breakdown(
+            "Monthly",
+            &breakdown(),
+            OutputMode::Xlsx,
+            None,
+            None,
+            &export,
+        )
+        .expect("xlsx through render_cost_breakdown");
+        assert!(dir.path().join("monthly.xlsx").is_file());
+    }
+
+    #[test]
+    fn test_write_export_xlsx_requires_path() {
+        let err = write_export(&[], OutputMode::Xlsx, &ExportArgs::default())
+            .expect_err("xlsx without path should fail");
+        assert!(err.to_string().contains("--output-path"));
+    }
+}
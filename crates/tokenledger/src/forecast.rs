@@ -0,0 +1,289 @@
+// Spend forecasts: daily variable cost through an as-of day, extended to the end of the period by
+// run rate, same-weekday averages or an exponentially weighted average, with a confidence band
+// from the spread of daily spend.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+
+use crate::analytics::build_timeseries_report;
+use crate::cli::{ForecastArgs, ForecastMethod, Granularity, OutputMode, PeriodArgs, QueryArgs};
+use crate::export::{forecast_tables, write_export};
+use crate::fx::month_end;
+use crate::models::*;
+use crate::money::{cents_to_units, units_to_cents};
+use crate::period::TimeRange;
+use crate::utils::{for_each_jsonl_event, print_forecast_table};
+
+// Weight of each new day in the EWMA level.
+const EWMA_ALPHA: f64 = 0.3;
+// Two-sided 90% normal quantile; the band is projection +/- z * daily stddev * sqrt(days left).
+const BAND_Z: f64 = 1.645;
+pub const FORECAST_CONFIDENCE: f64 = 0.9;
+
+pub fn run_forecast(args: ForecastArgs) -> Result<()> {
+    let range = range_or_latest_month(&args.query, &args.period)?;
+    let as_of = args
+        .as_of
+        .as_deref()
+        .map(|raw| {
+            NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .with_context(|| format!("invalid --as-of '{}', expected YYYY-MM-DD", raw))
+        })
+        .transpose()?;
+    let series = build_timeseries_report(&args.query, &range, Granularity::Day)?;
+    let report = build_forecast(&series, &range, as_of, args.method)?;
+    match args.query.output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_forecast_table(&report),
+        OutputMode::Csv | OutputMode::Xlsx => write_export(
+            &forecast_tables(&report),
+            args.query.output,
+            &args.query.export,
+        )?,
+        OutputMode::Markdown | OutputMode::Html => {
+            return Err(anyhow!(
+                "forecast supports table, json, csv and xlsx output"
+            ))
+        }
+    }
+    Ok(())
+}
+
+// The period flags' range, or without any the month of the latest event.
+pub fn range_or_latest_month(query: &QueryArgs, period: &PeriodArgs) -> Result<TimeRange> {
+    if period.month.is_some()
+        || period.from.is_some()
+        || period.to.is_some()
+        || period.last.is_some()
+    {
+        return TimeRange::from_args(period, Utc::now());
+    }
+    let mut latest = None;
+    for path in &query.events {
+        for_each_jsonl_event(path, |evt| {
+            latest = latest.max(Some(evt.timestamp));
+            Ok(())
+        })?;
+    }
+    let latest = latest.ok_or_else(|| anyhow!("no events in {:?}", query.events))?;
+    Ok(TimeRange::month((latest.year(), latest.month())))
+}
+
+// Projects `range` from a daily series of it. History runs from the range start (or the first day
+// with events) through `as_of`, which defaults to the last day with events; the projection runs to
+// the range end, or for open ranges to the end of the as-of month.
+pub fn build_forecast(
+    series: &TimeSeriesReport,
+    range: &TimeRange,
+    as_of: Option<NaiveDate>,
+    method: ForecastMethod,
+) -> Result<ForecastReport> {
+    if series.granularity != Granularity::Day {
+        return Err(anyhow!("forecasts need a daily series"));
+    }
+    let (Some(first), Some(last)) = (series.buckets.first(), series.buckets.last()) else {
+        return Err(anyhow!("no events to forecast from"));
+    };
+    let start = range
+        .from
+        .map_or(first.start.date_naive(), |from| from.date_naive());
+    let as_of = as_of.unwrap_or(last.start.date_naive());
+    if as_of < start {
+        return Err(anyhow!(
+            "--as-of {} is before the period starts on {}",
+            as_of,
+            start
+        ));
+    }
+    let end = range.to.map_or_else(
+        || month_end(as_of),
+        |to| (to - Duration::seconds(1)).date_naive(),
+    );
+    let as_of = as_of.min(end);
+    let history: Vec<NaiveDate> = days(start, as_of);
+    let remaining: Vec<NaiveDate> = days(as_of + Duration::days(1), end);
+
+    let mut overall: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    let mut providers: BTreeMap<&str, BTreeMap<NaiveDate, f64>> = BTreeMap::new();
+    let mut spent_total_cents = 0;
+    for bucket in &series.buckets {
+        let day = bucket.start.date_naive();
+        if day > as_of {
+            continue;
+        }
+        overall.insert(day, bucket.breakdown.variable_cost_usd);
+        spent_total_cents += units_to_cents(bucket.breakdown.monthly_total_usd);
+        for provider in &bucket.breakdown.provider_breakdown {
+            providers
+                .entry(provider.name.as_str())
+                .or_default()
+                .insert(day, provider.variable_cost_usd);
+        }
+    }
+
+    let project = |name: &str, spend: &BTreeMap<NaiveDate, f64>| {
+        let daily: Vec<(NaiveDate, f64)> = history
+            .iter()
+            .map(|day| (*day, spend.get(day).copied().unwrap_or(0.0)))
+            .collect();
+        forecast_series(name, &daily, &remaining, method)
+    };
+    let overall = project("total", &overall);
+    let providers = providers
+        .iter()
+        .map(|(name, spend)| project(name, spend))
+        .collect();
+    let remaining_usd = overall.projected_usd - overall.spent_usd;
+    Ok(ForecastReport {
+        period: range.label(start, end),
+        currency: series.totals.currency.clone(),
+        as_of,
+        method,
+        confidence: FORECAST_CONFIDENCE,
+        elapsed_days: history.len(),
+        remaining_days: remaining.len(),
+        spent_total_usd: cents_to_units(spent_total_cents),
+        projected_total_usd: cents_to_units(spent_total_cents + units_to_cents(remaining_usd)),
+        overall,
+        providers,
+    })
+}
+
+// The snapshot's month-end total: cost so far plus the projected remaining variable cost.
+pub fn ui_snapshot_forecast(report: &ForecastReport) -> UiSnapshotForecast {
+    let spent = units_to_cents(report.spent_total_usd);
+    let at = |projected: f64| {
+        cents_to_units(spent + units_to_cents(projected) - units_to_cents(report.overall.spent_usd))
+    };
+    UiSnapshotForecast {
+        as_of: report.as_of,
+        method: report.method,
+        projected_cost_usd: report.projected_total_usd,
+        low_usd: at(report.overall.low_usd),
+        high_usd: at(report.overall.high_usd),
+    }
+}
+
+pub fn days(from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+    from.iter_days().take_while(|day| *day <= to).collect()
+}
+
+// One series' month-end figures from its daily spend (`daily`, one entry per elapsed day).
+pub fn forecast_series(
+    name: &str,
+    daily: &[(NaiveDate, f64)],
+    remaining: &[NaiveDate],
+    method: ForecastMethod,
+) -> ForecastSeries {
+    let spent: f64 = daily.iter().map(|(_, spend)| spend).sum();
+    let mean = if daily.is_empty() {
+        0.0
+    } else {
+        spent / daily.len() as f64
+    };
+    let run_rate = mean * remaining.len() as f64;
+
+    let mut weekdays = [(0.0, 0usize); 7];
+    for (day, spend) in daily {
+        let slot = &mut weekdays[day.weekday().num_days_from_monday() as usize];
+        slot.0 += spend;
+        slot.1 += 1;
+    }
+    // Weekdays without history fall back to the overall mean.
+    let weekday_mean =
+        |day: &NaiveDate| match weekdays[day.weekday().num_days_from_monday() as usize] {
+            (_, 0) => mean,
+            (sum, count) => sum / count as f64,
+        };
+    let weekday: f64 = remaining.iter().map(weekday_mean).sum();
+
+    let level = daily
+        .iter()
+        .map(|(_, spend)| *spend)
+        .reduce(|level, spend| EWMA_ALPHA * spend + (1.0 - EWMA_ALPHA) * level)
+        .unwrap_or(0.0);
+    let ewma = level * remaining.len() as f64;
+
+    let stddev = if daily.len() < 2 {
+        0.0
+    } else {
+        let variance = daily
+            .iter()
+            .map(|(_, spend)| (spend - mean).powi(2))
+            .sum::<f64>()
+            / (daily.len() - 1) as f64;
+        variance.sqrt()
+    };
+    let projected = match method {
+        ForecastMethod::RunRate => run_rate,
+        ForecastMethod::Weekday => weekday,
+        ForecastMethod::Ewma => ewma,
+    };
+    let band = BAND_Z * stddev * (remaining.len() as f64).sqrt();
+    let usd = |remaining: f64| cents_to_units(units_to_cents(spent + remaining));
+    ForecastSeries {
+        name: name.to_string(),
+        spent_usd: usd(0.0),
+        daily_average_usd: cents_to_units(units_to_cents(mean)),
+        projected_usd: usd(projected),
+        low_usd: usd((projected - band).max(0.0)),
+        high_usd: usd(projected + band),
+        run_rate_usd: usd(run_rate),
+        weekday_usd: usd(weekday),
+        ewma_usd: usd(ewma),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, d).unwrap()
+    }
+
+    #[test]
+    fn test_forecast_methods_extend_daily_spend() {
+        // Two weeks from Monday 2026-02-02: $10 on weekdays, $2 on weekends.
+        let daily: Vec<(NaiveDate, f64)> = (2..=15)
+            .map(|d| {
+                let spend = if day(d).weekday().number_from_monday() > 5 {
+                    2.0
+                } else {
+                    10.0
+                };
+                (day(d), spend)
+            })
+            .collect();
+        // Monday 16th through Saturday 21st.
+        let remaining = days(day(16), day(21));
+        let series = forecast_series("total", &daily, &remaining, ForecastMethod::Weekday);
+
+        assert_eq!(series.spent_usd, 108.0);
+        assert_eq!(series.daily_average_usd, 7.71);
+        // Five weekdays and a Saturday at their own averages.
+        assert_eq!(series.weekday_usd, 160.0);
+        assert_eq!(series.projected_usd, series.weekday_usd);
+        assert_eq!(series.run_rate_usd, 154.29);
+        // The last day seen was a Sunday, which pulls the EWMA level below the mean.
+        assert!(series.ewma_usd < series.run_rate_usd);
+        assert!(series.low_usd < series.projected_usd && series.projected_usd < series.high_usd);
+
+        let flat: Vec<(NaiveDate, f64)> = (2..=8).map(|d| (day(d), 5.0)).collect();
+        let series = forecast_series("total", &flat, &remaining, ForecastMethod::RunRate);
+        assert_eq!(series.projected_usd, 65.0);
+        assert_eq!(series.ewma_usd, 65.0);
+        assert_eq!(series.low_usd, series.high_usd);
+    }
+
+    #[test]
+    fn test_forecast_without_remaining_days_is_spend() {
+        let daily = vec![(day(1), 3.0), (day(2), 0.0)];
+        let series = forecast_series("claude", &daily, &[], ForecastMethod::Ewma);
+        assert_eq!(series.projected_usd, 3.0);
+        assert_eq!(series.low_usd, 3.0);
+        assert_eq!(series.high_usd, 3.0);
+    }
+}
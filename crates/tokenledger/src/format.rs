@@ -1,5 +1,7 @@
 // Output formatting functions for tables, markdown, and audit reports
 
+use crate::cli::Granularity;
+use crate::fx::currency_symbol;
 use crate::models::*;
 
 pub fn print_coverage_table(report: &CoverageReport) {
@@ -8,6 +10,7 @@ pub fn print_coverage_table(report: &CoverageReport) {
     println!("  Total Tokens:             {}", report.totals.tokens);
     println!("  Priced Events:            {}", report.priced_count);
     println!("  Unpriced Events:          {}", report.unpriced_count);
+    println!("  Long-Context Events:      {}", report.long_context_events);
     println!();
 
     println!("Missing Providers");
@@ -53,6 +56,483 @@ pub fn print_coverage_table(report: &CoverageReport) {
             }
         }
     }
+    println!();
+
+    println!("Long-Context Tier Usage");
+    if report.long_context_by_model.is_empty() {
+        println!("  (none)");
+    } else {
+        for usage in &report.long_context_by_model {
+            println!(
+                "  {} / {} (>{} prompt tokens): {} events, {} prompt tokens",
+                usage.provider,
+                usage.model,
+                usage.above_prompt_tokens,
+                usage.events,
+                usage.prompt_tokens
+            );
+        }
+    }
+}
+
+pub fn print_plan_analysis_table(report: &PlanAnalysisReport) {
+    println!("Plan Analysis ({})", report.month);
+    for provider in &report.providers {
+        let sym = currency_symbol(&provider.currency);
+        println!();
+        println!(
+            "{} (plan: {})",
+            provider.provider,
+            provider.plan.as_deref().unwrap_or("(none)")
+        );
+        println!("  Tokens:                   {}", provider.tokens);
+        println!(
+            "  API List Cost:            {}{:.2}",
+            sym, provider.api_list_cost_usd
+        );
+        println!(
+            "  Subscription:             {}{:.2}",
+            sym, provider.subscription_usd
+        );
+        println!(
+            "  Overage:                  {}{:.2}",
+            sym, provider.overage_usd
+        );
+        println!(
+            "  Plan Cost:                {}{:.2}",
+            sym, provider.plan_cost_usd
+        );
+        println!(
+            "  Net Savings vs API:       {}",
+            signed_amount(&sym, provider.net_savings_usd)
+        );
+        for seat in &provider.seats {
+            let utilization = seat
+                .utilization_pct
+                .map(|pct| format!("{:.1}%", pct))
+                .unwrap_or_else(|| "unlimited".to_string());
+            let break_even = seat
+                .break_even_tokens
+                .map(|tokens| tokens.to_string())
+                .unwrap_or_else(|| "n/a".to_string());
+            println!(
+                "  {:<16} x{} days={} tokens={} utilization={} break_even_tokens={} plan={}{:.2} api={}{:.2} -> {}",
+                seat.seat,
+                seat.count,
+                seat.active_days,
+                seat.tokens,
+                utilization,
+                break_even,
+                sym,
+                seat.plan_cost_usd,
+                sym,
+                seat.api_list_cost_usd,
+                seat.recommendation
+            );
+        }
+    }
+}
+
+pub fn print_whatif_table(report: &WhatifReport) {
+    println!("What-if ({})", report.month);
+    for rule in &report.rules {
+        println!(
+            "  rule {:<40} events={} tokens={} -> {}",
+            rule.rule, rule.events, rule.tokens_before, rule.tokens_after
+        );
+    }
+    println!();
+    println!(
+        "  {:<24} {:>12} {:>12} {:>12}",
+        "", "Actual", "Simulated", "Delta"
+    );
+    let rows = [
+        (
+            "Variable Cost",
+            report.actual.variable_cost_usd,
+            report.simulated.variable_cost_usd,
+        ),
+        (
+            "Subscription Allocated",
+            report.actual.subscription_allocated_usd,
+            report.simulated.subscription_allocated_usd,
+        ),
+        (
+            "Idle Subscription",
+            report.actual.idle_subscription_usd,
+            report.simulated.idle_subscription_usd,
+        ),
+        ("Total", report.actual_total_usd, report.simulated_total_usd),
+    ];
+    for (label, actual, simulated) in rows {
+        println!(
+            "  {:<24} {:>12} {:>12} {:>12}",
+            label,
+            format!("${:.2}", actual),
+            format!("${:.2}", simulated),
+            signed_usd(round2(simulated - actual))
+        );
+    }
+    for (title, deltas) in [
+        ("Per Provider", &report.providers),
+        ("Per Model", &report.models),
+    ] {
+        println!();
+        println!("{}", title);
+        for row in deltas {
+            println!(
+                "  {:<32} actual=${:.2} simulated=${:.2} delta={}",
+                row.name,
+                row.actual_usd,
+                row.simulated_usd,
+                signed_usd(row.delta_usd)
+            );
+        }
+    }
+}
+
+fn signed_usd(amount: f64) -> String {
+    if amount < 0.0 {
+        format!("-${:.2}", -amount)
+    } else {
+        format!("+${:.2}", amount)
+    }
+}
+
+pub fn print_cache_table(report: &CacheReport, currency: &str) {
+    println!("Prompt Cache ({})", currency);
+    let header = || {
+        println!(
+            "  {:<28} {:>8} {:>10} {:>12} {:>12} {:>12} {:>7}",
+            "", "Hit", "Write/Read", "Read Saved", "Write Prem.", "Net Saved", "Unread"
+        )
+    };
+    let row = |metric: &CacheMetric| {
+        println!(
+            "  {:<28} {:>7.1}% {:>10} {:>12.2} {:>12.2} {:>12.2} {:>7}",
+            metric.name,
+            metric.hit_ratio * 100.0,
+            metric
+                .write_amplification
+                .map_or("-".to_string(), |ratio| format!("{:.2}x", ratio)),
+            metric.read_savings_usd,
+            metric.write_premium_usd,
+            metric.net_savings_usd,
+            metric
+                .unread_sessions
+                .map_or("-".to_string(), |count| count.to_string())
+        )
+    };
+    header();
+    row(&report.totals);
+    for (title, metrics) in [
+        ("Per Provider", &report.providers),
+        ("Per Model", &report.models),
+    ] {
+        println!();
+        println!("{}", title);
+        header();
+        for metric in metrics {
+            row(metric);
+        }
+    }
+    if !report.unread_sessions.is_empty() {
+        println!();
+        println!("Sessions With Unread Cache Writes");
+        for session in &report.unread_sessions {
+            println!(
+                "  {:<12} {:<28} {:<24} writes={} premium={:.2}",
+                session.provider,
+                session.model,
+                session.session_id,
+                session.cache_write_tokens,
+                session.write_premium_usd
+            );
+        }
+    }
+}
+
+pub fn print_budget_table(report: &BudgetReport) {
+    let sym = currency_symbol(&report.currency);
+    println!(
+        "Budgets as of {}: {}",
+        report.as_of.to_rfc3339(),
+        report.status.as_str()
+    );
+    println!(
+        "  {:<20} {:<28} {:<8} {:>10} {:>10} {:>7} {:>10}  Status",
+        "Budget", "Scope", "Period", "Limit", "Spent", "Used", "Projected"
+    );
+    for budget in &report.budgets {
+        println!(
+            "  {:<20} {:<28} {:<8} {:>10} {:>10} {:>6.1}% {:>10}  {}",
+            budget.name,
+            budget.scope,
+            budget.period,
+            format!("{}{:.2}", sym, budget.amount_usd),
+            format!("{}{:.2}", sym, budget.spent_usd),
+            budget.used_ratio * 100.0,
+            budget
+                .projected_usd
+                .map_or("-".to_string(), |projected| format!(
+                    "{}{:.2}",
+                    sym, projected
+                )),
+            budget.status.as_str()
+        );
+    }
+}
+
+pub fn print_forecast_table(report: &ForecastReport) {
+    let sym = currency_symbol(&report.currency);
+    println!(
+        "Forecast ({}) as of {}: {} days elapsed, {} remaining",
+        report.period, report.as_of, report.elapsed_days, report.remaining_days
+    );
+    println!(
+        "  Spent so far: {}{:.2}  Projected: {}{:.2} ({} variable {}{:.2}, {:.0}% band {}{:.2}..{}{:.2})",
+        sym,
+        report.spent_total_usd,
+        sym,
+        report.projected_total_usd,
+        report.method.as_str(),
+        sym,
+        report.overall.projected_usd,
+        report.confidence * 100.0,
+        sym,
+        report.overall.low_usd,
+        sym,
+        report.overall.high_usd
+    );
+    println!(
+        "  {:<20} {:>10} {:>9} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "Variable Cost",
+        "Spent",
+        "Avg/Day",
+        "Projected",
+        "Low",
+        "High",
+        "Run Rate",
+        "Weekday",
+        "EWMA"
+    );
+    for series in std::iter::once(&report.overall).chain(&report.providers) {
+        println!(
+            "  {:<20} {:>10.2} {:>9.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2}",
+            series.name,
+            series.spent_usd,
+            series.daily_average_usd,
+            series.projected_usd,
+            series.low_usd,
+            series.high_usd,
+            series.run_rate_usd,
+            series.weekday_usd,
+            series.ewma_usd
+        );
+    }
+}
+
+pub fn print_compare_table(report: &CompareReport) {
+    let sym = currency_symbol(&report.currency);
+    let totals = &report.totals;
+    println!("Compare {} against {}", report.period, report.against);
+    println!(
+        "  Total: {}{:.2} -> {}{:.2} ({}{}), volume {}, price/mix {}",
+        sym,
+        totals.baseline_cost_usd,
+        sym,
+        totals.cost_usd,
+        signed_amount(&sym, totals.cost_delta_usd),
+        totals
+            .cost_delta_pct
+            .map_or(String::new(), |pct| format!(", {:+.2}%", pct)),
+        signed_amount(&sym, totals.volume_effect_usd),
+        signed_amount(&sym, totals.price_mix_effect_usd)
+    );
+    println!(
+        "  Tokens: {} -> {} ({:+})  Blended/MTok: {}{:.4} -> {}{:.4}  Sessions: {} -> {}",
+        totals.baseline_tokens,
+        totals.tokens,
+        totals.tokens_delta,
+        sym,
+        totals.baseline_blended_usd_per_mtok,
+        sym,
+        totals.blended_usd_per_mtok,
+        totals.baseline_sessions,
+        totals.sessions
+    );
+    for (title, rows) in [
+        ("Per Provider", &report.providers),
+        ("Per Model", &report.models),
+    ] {
+        println!();
+        println!("{}", title);
+        println!(
+            "  {:<28} {:>10} {:>10} {:>11} {:>9} {:>14} {:>10} {:>10} {:>9} {:>11} {:>11}",
+            "",
+            "Before",
+            "After",
+            "Delta",
+            "Delta %",
+            "Tokens Delta",
+            "$/MTok Bef",
+            "$/MTok Aft",
+            "Sessions",
+            "Volume",
+            "Price/Mix"
+        );
+        for row in rows {
+            println!(
+                "  {:<28} {:>10.2} {:>10.2} {:>11} {:>9} {:>+14} {:>10.4} {:>10.4} {:>+9} {:>11} {:>11}",
+                row.name,
+                row.baseline_cost_usd,
+                row.cost_usd,
+                signed_amount(&sym, row.cost_delta_usd),
+                row.cost_delta_pct
+                    .map_or("-".to_string(), |pct| format!("{:+.2}%", pct)),
+                row.tokens_delta,
+                row.baseline_blended_usd_per_mtok,
+                row.blended_usd_per_mtok,
+                row.sessions_delta,
+                signed_amount(&sym, row.volume_effect_usd),
+                signed_amount(&sym, row.price_mix_effect_usd)
+            );
+        }
+    }
+}
+
+// Pasteable into a review doc: the headline movement, then provider and model tables.
+pub fn print_compare_markdown(report: &CompareReport) {
+    let sym = currency_symbol(&report.currency);
+    let totals = &report.totals;
+    println!(
+        "## Cost Comparison: {} vs {}",
+        report.period, report.against
+    );
+    println!();
+    println!(
+        "- Total Cost: `{}{:.2}` → `{}{:.2}` (**{}**{})",
+        sym,
+        totals.baseline_cost_usd,
+        sym,
+        totals.cost_usd,
+        signed_amount(&sym, totals.cost_delta_usd),
+        totals
+            .cost_delta_pct
+            .map_or(String::new(), |pct| format!(", {:+.2}%", pct))
+    );
+    println!(
+        "- Volume Effect: `{}`",
+        signed_amount(&sym, totals.volume_effect_usd)
+    );
+    println!(
+        "- Price/Mix Effect: `{}`",
+        signed_amount(&sym, totals.price_mix_effect_usd)
+    );
+    println!(
+        "- Tokens: `{}` → `{}` (`{:+}`)",
+        totals.baseline_tokens, totals.tokens, totals.tokens_delta
+    );
+    println!(
+        "- Blended Cost / MTok: `{}{:.4}` → `{}{:.4}`",
+        sym, totals.baseline_blended_usd_per_mtok, sym, totals.blended_usd_per_mtok
+    );
+    println!(
+        "- Sessions: `{}` → `{}` (`{:+}`)",
+        totals.baseline_sessions, totals.sessions, totals.sessions_delta
+    );
+    for (title, column, rows) in [
+        ("By Provider", "Provider", &report.providers),
+        ("By Model", "Model", &report.models),
+    ] {
+        println!();
+        println!("### {}", title);
+        println!(
+            "| {0} | {1} {3} | {2} {3} | Δ {3} | Δ % | Δ Tokens | {1} {3}/MTok | {2} {3}/MTok | Δ Sessions | Volume {3} | Price/Mix {3} |",
+            column, report.against, report.period, report.currency
+        );
+        println!("|---|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|");
+        for row in rows {
+            println!(
+                "| {} | {:.2} | {:.2} | {:+.2} | {} | {:+} | {:.4} | {:.4} | {:+} | {:+.2} | {:+.2} |",
+                row.name,
+                row.baseline_cost_usd,
+                row.cost_usd,
+                row.cost_delta_usd,
+                row.cost_delta_pct
+                    .map_or("-".to_string(), |pct| format!("{:+.2}%", pct)),
+                row.tokens_delta,
+                row.baseline_blended_usd_per_mtok,
+                row.blended_usd_per_mtok,
+                row.sessions_delta,
+                row.volume_effect_usd,
+                row.price_mix_effect_usd
+            );
+        }
+    }
+}
+
+// "+$1.20" or "-$0.35".
+fn signed_amount(sym: &str, amount: f64) -> String {
+    if amount < 0.0 {
+        format!("-{}{:.2}", sym, -amount)
+    } else {
+        format!("+{}{:.2}", sym, amount)
+    }
+}
+
+pub fn print_anomaly_table(report: &AnomalyReport) {
+    let sym = currency_symbol(&report.currency);
+    println!(
+        "Spend Anomalies ({}, {} buckets of a {} series, z >= {}, excess >= {}{:.2})",
+        report.period,
+        report.buckets,
+        report.granularity.as_str(),
+        report.threshold,
+        sym,
+        report.min_usd
+    );
+    if report.anomalies.is_empty() {
+        println!("  (none)");
+        return;
+    }
+    println!(
+        "  {:<20} {:<9} {:<28} {:>10} {:>10} {:>10} {:>8}",
+        "Bucket", "Scope", "Name", "Spend", "Baseline", "Excess", "Z"
+    );
+    for anomaly in &report.anomalies {
+        println!(
+            "  {:<20} {:<9} {:<28} {:>10.2} {:>10.2} {:>10.2} {:>8.2}",
+            anomaly.bucket,
+            anomaly.scope.as_str(),
+            anomaly.name,
+            anomaly.spend_usd,
+            anomaly.baseline_usd,
+            anomaly.excess_usd,
+            anomaly.z_score
+        );
+    }
+}
+
+pub fn print_window_table(report: &WindowReport) {
+    println!("Usage Windows (as of {})", report.as_of.to_rfc3339());
+    if report.windows.is_empty() {
+        println!("  (none)");
+    }
+    for window in &report.windows {
+        let used = match (window.limit_tokens, window.used_pct) {
+            (Some(limit), Some(pct)) => format!("{}/{} ({:.1}%)", window.tokens, limit, pct),
+            _ => format!("{} (no limit)", window.tokens),
+        };
+        let resets = window
+            .resets_at
+            .map(|at| at.to_rfc3339())
+            .unwrap_or_else(|| "(idle)".to_string());
+        println!(
+            "  {:<10} {:<8} {:<10} {}h {:?} used={} resets_at={}",
+            window.provider, window.plan, window.window, window.hours, window.kind, used, resets
+        );
+    }
 }
 
 pub fn print_pricing_audit_report(report: &PricingAuditReport) {
@@ -89,22 +569,23 @@ pub fn print_table(
     top_providers: Option<usize>,
     top_models: Option<usize>,
 ) {
+    let sym = currency_symbol(&report.currency);
     println!("{} Cost Summary", label);
     println!(
-        "  Variable Cost:            ${:.2}",
-        report.variable_cost_usd
+        "  Variable Cost:            {}{:.2}",
+        sym, report.variable_cost_usd
     );
     println!(
-        "  Subscription Allocated:   ${:.2}",
-        report.subscription_allocated_usd
+        "  Subscription Allocated:   {}{:.2}",
+        sym, report.subscription_allocated_usd
     );
     println!(
-        "  Monthly Total:            ${:.2}",
-        report.monthly_total_usd
+        "  Monthly Total:            {}{:.2}",
+        sym, report.monthly_total_usd
     );
     println!(
-        "  Blended Cost / MTok:      ${:.4}",
-        report.blended_usd_per_mtok
+        "  Blended Cost / MTok:      {}{:.4}",
+        sym, report.blended_usd_per_mtok
     );
     println!("  Total Tokens:             {}", report.total_tokens);
     println!("  Total MTok:               {:.4}", report.total_mtok);
@@ -113,15 +594,70 @@ pub fn print_table(
         "  Skipped Unpriced Events:  {}",
         report.skipped_unpriced_count
     );
+    if report.idle_subscription_usd > 0.0 {
+        println!(
+            "  Idle Subscription:        {}{:.2} (not allocated to usage)",
+            sym, report.idle_subscription_usd
+        );
+    }
     println!();
 
+    if let Some(contract) = &report.contract {
+        println!("Contract");
+        println!(
+            "  List Cost:                {}{:.2}",
+            sym, contract.list_cost_usd
+        );
+        println!(
+            "  Discounts:                {}{:.2}",
+            sym, contract.discount_usd
+        );
+        println!(
+            "  Commit Shortfall:         {}{:.2}",
+            sym, contract.commit_shortfall_usd
+        );
+        println!(
+            "  Credits Applied:          {}{:.2}",
+            sym, contract.credits_applied_usd
+        );
+        println!(
+            "  Net Cost:                 {}{:.2}",
+            sym, contract.net_cost_usd
+        );
+        println!(
+            "  Remaining Credit:         {}{:.2}",
+            sym, contract.remaining_credit_usd
+        );
+        for row in &contract.providers {
+            println!(
+                "  {:<16} list={}{:.2} discount={}{:.2} shortfall={}{:.2} credits={}{:.2} net={}{:.2} remaining_credit={}{:.2}",
+                row.provider,
+                sym,
+                row.list_cost_usd,
+                sym,
+                row.discount_usd,
+                sym,
+                row.commit_shortfall_usd,
+                sym,
+                row.credits_applied_usd,
+                sym,
+                row.net_cost_usd,
+                sym,
+                row.remaining_credit_usd
+            );
+        }
+        println!();
+    }
+
     println!("Per Provider");
     for row in top_rows(&report.provider_breakdown, top_providers) {
         println!(
-            "  {:<16} tokens={} total=${:.2} blended=${:.4}/MTok sessions={} tool_share={:.2}%",
+            "  {:<16} tokens={} total={}{:.2} blended={}{:.4}/MTok sessions={} tool_share={:.2}%",
             row.name,
             row.tokens,
+            sym,
             row.total_cost_usd,
+            sym,
             row.blended_usd_per_mtok,
             row.session_count,
             row.tool_share * 100.0
@@ -132,10 +668,12 @@ pub fn print_table(
     println!("Per Model");
     for row in top_rows(&report.model_breakdown, top_models) {
         println!(
-            "  {:<24} tokens={} total=${:.2} blended=${:.4}/MTok sessions={} tool_share={:.2}%",
+            "  {:<24} tokens={} total={}{:.2} blended={}{:.4}/MTok sessions={} tool_share={:.2}%",
             row.name,
             row.tokens,
+            sym,
             row.total_cost_usd,
+            sym,
             row.blended_usd_per_mtok,
             row.session_count,
             row.tool_share * 100.0
@@ -143,6 +681,27 @@ pub fn print_table(
     }
     println!();
 
+    if report
+        .service_tier_breakdown
+        .iter()
+        .any(|row| row.name != STANDARD_SERVICE_TIER)
+    {
+        println!("Per Service Tier");
+        for row in &report.service_tier_breakdown {
+            println!(
+                "  {:<16} tokens={} total={}{:.2} blended={}{:.4}/MTok sessions={}",
+                row.name,
+                row.tokens,
+                sym,
+                row.total_cost_usd,
+                sym,
+                row.blended_usd_per_mtok,
+                row.session_count
+            );
+        }
+        println!();
+    }
+
     println!("Suggestions");
     for tip in &report.suggestions {
         println!("  - {}", tip);
@@ -155,17 +714,18 @@ pub fn print_markdown(
     top_providers: Option<usize>,
     top_models: Option<usize>,
 ) {
+    let sym = currency_symbol(&report.currency);
     println!("## {} Cost Summary", label);
     println!();
-    println!("- Variable Cost: `${:.2}`", report.variable_cost_usd);
+    println!("- Variable Cost: `{}{:.2}`", sym, report.variable_cost_usd);
     println!(
-        "- Subscription Allocated: `${:.2}`",
-        report.subscription_allocated_usd
+        "- Subscription Allocated: `{}{:.2}`",
+        sym, report.subscription_allocated_usd
     );
-    println!("- Monthly Total: `${:.2}`", report.monthly_total_usd);
+    println!("- Monthly Total: `{}{:.2}`", sym, report.monthly_total_usd);
     println!(
-        "- Blended Cost / MTok: `${:.4}`",
-        report.blended_usd_per_mtok
+        "- Blended Cost / MTok: `{}{:.4}`",
+        sym, report.blended_usd_per_mtok
     );
     println!("- Total Tokens: `{}`", report.total_tokens);
     println!("- Total MTok: `{:.4}`", report.total_mtok);
@@ -174,10 +734,50 @@ pub fn print_markdown(
         "- Skipped Unpriced Events: `{}`",
         report.skipped_unpriced_count
     );
+    if report.idle_subscription_usd > 0.0 {
+        println!(
+            "- Idle Subscription (not allocated to usage): `{}{:.2}`",
+            sym, report.idle_subscription_usd
+        );
+    }
     println!();
 
+    if let Some(contract) = &report.contract {
+        println!("### Contract");
+        println!(
+            "| Provider | List {0} | Discounts {0} | Commit Shortfall {0} | Credits Applied {0} | Net {0} | Remaining Credit {0} |",
+            report.currency
+        );
+        println!("|---|---:|---:|---:|---:|---:|---:|");
+        for row in &contract.providers {
+            println!(
+                "| {} | {:.2} | {:.2} | {:.2} | {:.2} | {:.2} | {:.2} |",
+                row.provider,
+                row.list_cost_usd,
+                row.discount_usd,
+                row.commit_shortfall_usd,
+                row.credits_applied_usd,
+                row.net_cost_usd,
+                row.remaining_credit_usd
+            );
+        }
+        println!(
+            "| **Total** | {:.2} | {:.2} | {:.2} | {:.2} | {:.2} | {:.2} |",
+            contract.list_cost_usd,
+            contract.discount_usd,
+            contract.commit_shortfall_usd,
+            contract.credits_applied_usd,
+            contract.net_cost_usd,
+            contract.remaining_credit_usd
+        );
+        println!();
+    }
+
     println!("### Per Provider");
-    println!("| Provider | Tokens | Total USD | Blended USD/MTok | Sessions | Tool Share |",);
+    println!(
+        "| Provider | Tokens | Total {0} | Blended {0}/MTok | Sessions | Tool Share |",
+        report.currency
+    );
     println!("|---|---:|---:|---:|---:|---:|");
     for row in top_rows(&report.provider_breakdown, top_providers) {
         println!(
@@ -193,7 +793,10 @@ pub fn print_markdown(
     println!();
 
     println!("### Per Model");
-    println!("| Model | Tokens | Total USD | Blended USD/MTok | Sessions | Tool Share |",);
+    println!(
+        "| Model | Tokens | Total {0} | Blended {0}/MTok | Sessions | Tool Share |",
+        report.currency
+    );
     println!("|---|---:|---:|---:|---:|---:|");
     for row in top_rows(&report.model_breakdown, top_models) {
         println!(
@@ -208,46 +811,276 @@ pub fn print_markdown(
     }
     println!();
 
+    if report
+        .service_tier_breakdown
+        .iter()
+        .any(|row| row.name != STANDARD_SERVICE_TIER)
+    {
+        println!("### Per Service Tier");
+        println!(
+            "| Service Tier | Tokens | Total {0} | Blended {0}/MTok | Sessions |",
+            report.currency
+        );
+        println!("|---|---:|---:|---:|---:|");
+        for row in &report.service_tier_breakdown {
+            println!(
+                "| {} | {} | {:.2} | {:.4} | {} |",
+                row.name,
+                row.tokens,
+                row.total_cost_usd,
+                row.blended_usd_per_mtok,
+                row.session_count
+            );
+        }
+        println!();
+    }
+
     println!("### Suggestions");
     for tip in &report.suggestions {
         println!("- {}", tip);
     }
 }
 
-pub fn print_daily_table(
-    report: &DailyReport,
+// "Daily", "Weekly", ... for report headings.
+fn granularity_title(granularity: Granularity) -> &'static str {
+    match granularity {
+        Granularity::Hour => "Hourly",
+        Granularity::Day => "Daily",
+        Granularity::Week => "Weekly",
+        Granularity::Month => "Monthly",
+        Granularity::Quarter => "Quarterly",
+        Granularity::Year => "Yearly",
+    }
+}
+
+pub fn print_timeseries_table(
+    report: &TimeSeriesReport,
     top_providers: Option<usize>,
     top_models: Option<usize>,
 ) {
-    println!("Daily Cost Summary ({})", report.month);
+    let title = granularity_title(report.granularity);
+    println!("{} Cost Summary ({})", title, report.period);
     println!();
-    print_table("Monthly Totals", &report.totals, top_providers, top_models);
+    print_table("Period Totals", &report.totals, top_providers, top_models);
     println!();
 
-    for day in &report.days {
+    for bucket in &report.buckets {
         println!("==================================================");
-        println!("Day: {}", day.day);
-        print_table("Daily", &day.breakdown, top_providers, top_models);
+        println!("Period: {}", bucket.period);
+        print_table(title, &bucket.breakdown, top_providers, top_models);
         println!();
     }
 }
 
-pub fn print_daily_markdown(
-    report: &DailyReport,
+pub fn print_timeseries_markdown(
+    report: &TimeSeriesReport,
     top_providers: Option<usize>,
     top_models: Option<usize>,
 ) {
-    println!("# Daily Cost Summary ({})", report.month);
+    let title = granularity_title(report.granularity);
+    println!("# {} Cost Summary ({})", title, report.period);
     println!();
-    print_markdown("Monthly Totals", &report.totals, top_providers, top_models);
+    print_markdown("Period Totals", &report.totals, top_providers, top_models);
 
-    for day in &report.days {
+    for bucket in &report.buckets {
         println!();
         println!("---");
         println!();
-        println!("## {}", day.day);
+        println!("## {}", bucket.period);
         println!();
-        print_markdown("Daily", &day.breakdown, top_providers, top_models);
+        print_markdown(title, &bucket.breakdown, top_providers, top_models);
+    }
+}
+
+fn pivot_title(report: &PivotReport) -> String {
+    let dimensions: Vec<&str> = report
+        .dimensions
+        .iter()
+        .map(|dimension| dimension.as_str())
+        .collect();
+    format!("Cost by {} ({})", dimensions.join(" / "), report.period)
+}
+
+// Rows depth first with their depth; nested rows come before their children.
+fn pivot_rows<'a>(rows: &'a [PivotRow], depth: usize, out: &mut Vec<(usize, &'a PivotRow)>) {
+    for row in rows {
+        out.push((depth, row));
+        pivot_rows(&row.children, depth + 1, out);
+    }
+}
+
+// Flat rows are labelled by name; nested rows by their own key part, indented under their parent.
+pub fn print_pivot_table(report: &PivotReport) {
+    let sym = currency_symbol(&report.currency);
+    println!("{}", pivot_title(report));
+    let mut rows = Vec::new();
+    pivot_rows(&report.rows, 0, &mut rows);
+    let line = |label: &str, row: &NamedMetric| {
+        println!(
+            "  {:<32} tokens={} total={}{:.2} blended={}{:.4}/MTok sessions={} tool_share={:.2}%",
+            label,
+            row.tokens,
+            sym,
+            row.total_cost_usd,
+            sym,
+            row.blended_usd_per_mtok,
+            row.session_count,
+            row.tool_share * 100.0
+        )
+    };
+    for (depth, row) in rows {
+        let label = if report.nested {
+            let key = row.keys.last().map(String::as_str).unwrap_or_default();
+            format!("{}{}", "  ".repeat(depth), key)
+        } else {
+            row.metric.name.clone()
+        };
+        line(&label, &row.metric);
+    }
+    line("TOTAL", &report.totals);
+}
+
+// One column per dimension; nested subtotal rows leave their deeper columns blank.
+pub fn print_pivot_markdown(report: &PivotReport) {
+    println!("## {}", pivot_title(report));
+    println!();
+    let mut header = String::from("|");
+    let mut align = String::from("|");
+    for dimension in &report.dimensions {
+        header.push_str(&format!(" {} |", dimension.as_str()));
+        align.push_str("---|");
+    }
+    println!(
+        "{} Tokens | Total {1} | Blended {1}/MTok | Sessions | Tool Share |",
+        header, report.currency
+    );
+    println!("{}---:|---:|---:|---:|---:|", align);
+    let line = |keys: &[String], row: &NamedMetric| {
+        let mut cells = String::from("|");
+        for idx in 0..report.dimensions.len() {
+            cells.push_str(&format!(" {} |", keys.get(idx).map_or("", String::as_str)));
+        }
+        println!(
+            "{} {} | {:.2} | {:.4} | {} | {:.2}% |",
+            cells,
+            row.tokens,
+            row.total_cost_usd,
+            row.blended_usd_per_mtok,
+            row.session_count,
+            row.tool_share * 100.0
+        );
+    };
+    let mut rows = Vec::new();
+    pivot_rows(&report.rows, 0, &mut rows);
+    for (_, row) in rows {
+        line(&row.keys, &row.metric);
+    }
+    line(&["**Total**".to_string()], &report.totals);
+}
+
+// "45s", "12m05s", "3h07m" or "2d04h".
+pub fn format_duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3_600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else if secs < 86_400 {
+        format!("{}h{:02}m", secs / 3_600, secs % 3_600 / 60)
+    } else {
+        format!("{}d{:02}h", secs / 86_400, secs % 86_400 / 3_600)
+    }
+}
+
+fn session_models(session: &SessionSummary) -> String {
+    session
+        .models
+        .iter()
+        .map(|model| model.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn print_session_table(report: &SessionReport) {
+    let sym = currency_symbol(&report.currency);
+    println!(
+        "Sessions ({}): {} sessions, {} listed, total {}{:.2}",
+        report.period,
+        report.session_count,
+        report.sessions.len(),
+        sym,
+        report.total_cost_usd
+    );
+    println!(
+        "  {:<36} {:<16} {:>8} {:>7} {:>12} {:>7} {:>10}  Models",
+        "Session", "Start", "Duration", "Events", "Tokens", "Cache", "Total"
+    );
+    for session in &report.sessions {
+        println!(
+            "  {:<36} {:<16} {:>8} {:>7} {:>12} {:>6.1}% {:>10.2}  {}",
+            format!("{}/{}", session.provider, session.session_id),
+            session.start.format("%Y-%m-%d %H:%M"),
+            format_duration(session.duration_secs),
+            session.event_count,
+            session.total_tokens,
+            session.cache_hit_ratio * 100.0,
+            session.total_cost_usd,
+            session_models(session)
+        );
+    }
+}
+
+pub fn print_session_timeline(timeline: &SessionTimeline) {
+    let sym = currency_symbol(&timeline.currency);
+    println!("Session {} ({})", timeline.session_id, timeline.period);
+    for session in &timeline.sessions {
+        println!(
+            "  {:<16} {} .. {} ({}) events={} tokens={} cache_hit={:.1}%",
+            session.provider,
+            session.start.to_rfc3339(),
+            session.end.to_rfc3339(),
+            format_duration(session.duration_secs),
+            session.event_count,
+            session.total_tokens,
+            session.cache_hit_ratio * 100.0
+        );
+        println!(
+            "  {:<16} input={} output={} cache_write={} cache_read={} tool={}",
+            "",
+            session.input_tokens,
+            session.output_tokens,
+            session.cache_write_tokens + session.cache_write_1h_tokens,
+            session.cache_read_tokens,
+            session.tool_input_tokens + session.tool_output_tokens
+        );
+        println!(
+            "  {:<16} variable={}{:.2} subscription={}{:.2} total={}{:.2} models={}",
+            "",
+            sym,
+            session.variable_cost_usd,
+            sym,
+            session.subscription_allocated_usd,
+            sym,
+            session.total_cost_usd,
+            session_models(session)
+        );
+    }
+    println!();
+    println!("Timeline");
+    for event in &timeline.events {
+        println!(
+            "  {} {:>8} {:<12} {:<28} tokens={:<10} cost={:>10} cumulative={}{:.2}",
+            event.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            format!("+{}", format_duration(event.gap_secs)),
+            event.provider,
+            event.model,
+            event.usage.total(),
+            event
+                .variable_cost_usd
+                .map_or("unpriced".to_string(), |cost| format!("{}{:.2}", sym, cost)),
+            sym,
+            event.cumulative_cost_usd
+        );
     }
 }
 
@@ -0,0 +1,216 @@
+// Currency conversion for reports: a dated FX table and the converter that turns amounts priced
+// in a provider's currency into the report currency on the event day or at month end.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::FxDateMode;
+
+pub const USD: &str = "USD";
+
+// Rates are units of each currency per one unit of `base`; a rate applies from its `date` until
+// the next dated entry for the same currency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FxTable {
+    #[serde(default = "default_base_currency")]
+    pub base: String,
+    pub rates: HashMap<String, Vec<FxRate>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FxRate {
+    pub date: NaiveDate,
+    pub rate: f64,
+}
+
+fn default_base_currency() -> String {
+    USD.to_string()
+}
+
+impl FxTable {
+    // Units of `currency` per base unit on `day`.
+    pub fn rate_on(&self, currency: &str, day: NaiveDate) -> Result<f64> {
+        if currency.eq_ignore_ascii_case(&self.base) {
+            return Ok(1.0);
+        }
+        let rates = self
+            .rates
+            .iter()
+            .find(|(code, _)| code.eq_ignore_ascii_case(currency))
+            .map(|(_, rates)| rates)
+            .ok_or_else(|| anyhow!("FX table has no rates for {}", currency))?;
+        rates
+            .iter()
+            .filter(|rate| rate.date <= day)
+            .max_by_key(|rate| rate.date)
+            .map(|rate| rate.rate)
+            .ok_or_else(|| anyhow!("FX table has no {} rate on or before {}", currency, day))
+    }
+}
+
+pub fn load_fx_table(path: &Path) -> Result<FxTable> {
+    let file = File::open(path).with_context(|| format!("opening {:?}", path))?;
+    let table: FxTable =
+        serde_json::from_reader(file).with_context(|| format!("parsing FX rates {:?}", path))?;
+    for (currency, rates) in &table.rates {
+        if let Some(rate) = rates.iter().find(|rate| rate.rate <= 0.0) {
+            return Err(anyhow!(
+                "FX rate for {} on {} must be positive",
+                currency,
+                rate.date
+            ));
+        }
+    }
+    Ok(table)
+}
+
+#[derive(Debug, Clone)]
+pub struct CurrencyConverter {
+    pub currency: String,
+    pub date_mode: FxDateMode,
+    table: Option<FxTable>,
+}
+
+impl Default for CurrencyConverter {
+    fn default() -> Self {
+        Self::usd()
+    }
+}
+
+impl CurrencyConverter {
+    pub fn usd() -> Self {
+        Self::new(USD, FxDateMode::EventDay, None)
+    }
+
+    pub fn new(currency: &str, date_mode: FxDateMode, table: Option<FxTable>) -> Self {
+        Self {
+            currency: currency.to_ascii_uppercase(),
+            date_mode,
+            table,
+        }
+    }
+
+    // Multiplier from `from` amounts to the report currency for an event at `at`.
+    pub fn factor(&self, from: &str, at: DateTime<Utc>) -> Result<f64> {
+        let day = at.date_naive();
+        let day = match self.date_mode {
+            FxDateMode::EventDay => day,
+            FxDateMode::MonthEnd => month_end(day),
+        };
+        self.factor_on(from, day)
+    }
+
+    pub fn factor_on(&self, from: &str, day: NaiveDate) -> Result<f64> {
+        if from.eq_ignore_ascii_case(&self.currency) {
+            return Ok(1.0);
+        }
+        let table = self.table.as_ref().ok_or_else(|| {
+            anyhow!(
+                "converting {} to {} requires --fx-rates",
+                from.to_ascii_uppercase(),
+                self.currency
+            )
+        })?;
+        Ok(table.rate_on(&self.currency, day)? / table.rate_on(from, day)?)
+    }
+
+    pub fn symbol(&self) -> String {
+        currency_symbol(&self.currency)
+    }
+}
+
+pub fn currency_symbol(currency: &str) -> String {
+    match currency {
+        "USD" => "$".to_string(),
+        "EUR" => "€".to_string(),
+        "GBP" => "£".to_string(),
+        "JPY" => "¥".to_string(),
+        other => format!("{} ", other),
+    }
+}
+
+pub fn month_end(day: NaiveDate) -> NaiveDate {
+    let (year, month) = if day.month() == 12 {
+        (day.year() + 1, 1)
+    } else {
+        (day.year(), day.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).expect("valid month") - Duration::days(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn table() -> FxTable {
+        FxTable {
+            base: USD.to_string(),
+            rates: HashMap::from([
+                (
+                    "EUR".to_string(),
+                    vec![
+                        FxRate {
+                            date: day(2, 1),
+                            rate: 0.9,
+                        },
+                        FxRate {
+                            date: day(2, 20),
+                            rate: 0.8,
+                        },
+                    ],
+                ),
+                (
+                    "GBP".to_string(),
+                    vec![FxRate {
+                        date: day(1, 1),
+                        rate: 0.75,
+                    }],
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_rate_on_uses_latest_entry_not_after_day() {
+        let table = table();
+        assert_eq!(table.rate_on("eur", day(2, 19)).unwrap(), 0.9);
+        assert_eq!(table.rate_on("EUR", day(2, 20)).unwrap(), 0.8);
+        assert_eq!(table.rate_on("USD", day(1, 1)).unwrap(), 1.0);
+        assert!(table.rate_on("EUR", day(1, 31)).is_err());
+        assert!(table.rate_on("CHF", day(2, 1)).is_err());
+    }
+
+    #[test]
+    fn test_converter_event_day_and_month_end() {
+        let at = Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap();
+        let event_day = CurrencyConverter::new("eur", FxDateMode::EventDay, Some(table()));
+        assert!((event_day.factor(USD, at).unwrap() - 0.9).abs() < 1e-12);
+        let month_end = CurrencyConverter::new("EUR", FxDateMode::MonthEnd, Some(table()));
+        assert!((month_end.factor(USD, at).unwrap() - 0.8).abs() < 1e-12);
+        // Cross rate through the table base: 1 GBP = 0.8 / 0.75 EUR.
+        assert!((month_end.factor("GBP", at).unwrap() - 0.8 / 0.75).abs() < 1e-12);
+        assert_eq!(event_day.factor("EUR", at).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_converter_without_table_only_handles_same_currency() {
+        let usd = CurrencyConverter::usd();
+        assert_eq!(usd.factor_on("usd", day(2, 1)).unwrap(), 1.0);
+        let err = usd.factor_on("EUR", day(2, 1)).unwrap_err();
+        assert!(err.to_string().contains("--fx-rates"));
+        assert_eq!(
+            month_end(day(12, 5)),
+            NaiveDate::from_ymd_opt(2026, 12, 31).unwrap()
+        );
+    }
+}
@@ -0,0 +1,798 @@
+// Self-contained HTML reports: one file with inline CSS, SVG charts drawn here and a few lines of
+// script for sortable tables, so it can be mailed or published without any network assets.
+
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::fs;
+use std::io::{self, Write};
+
+use anyhow::{Context, Result};
+
+use crate::cli::{ExportArgs, Granularity};
+use crate::fx::currency_symbol;
+use crate::models::*;
+
+const PALETTE: [&str; 8] = [
+    "#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#76b7b2", "#edc948", "#b07aa1", "#9c755f",
+];
+const OTHER_COLOR: &str = "#bab0ac";
+// Series drawn on their own in charts; the rest are summed as "other".
+const CHART_SERIES: usize = 7;
+
+const STYLE: &str = r#"
+body { font: 14px/1.45 -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: #1f2933; background: #f5f7fa; margin: 0; }
+main { max-width: 1080px; margin: 0 auto; padding: 24px; }
+h1 { font-size: 22px; margin: 0 0 4px; }
+h2 { font-size: 16px; margin: 0 0 12px; }
+.meta { color: #52606d; margin: 0 0 20px; }
+section { background: #fff; border-radius: 8px; box-shadow: 0 1px 3px rgba(0,0,0,.08); padding: 16px 20px; margin: 0 0 20px; }
+.cards { display: flex; flex-wrap: wrap; gap: 12px; margin: 0 0 20px; }
+.card { background: #fff; border-radius: 8px; box-shadow: 0 1px 3px rgba(0,0,0,.08); padding: 12px 16px; min-width: 140px; flex: 1; }
+.card .label { color: #52606d; font-size: 12px; text-transform: uppercase; letter-spacing: .04em; }
+.card .value { font-size: 20px; font-weight: 600; }
+.row { display: flex; flex-wrap: wrap; gap: 20px; }
+.row > section { flex: 1; min-width: 320px; }
+.legend { list-style: none; padding: 0; margin: 8px 0 0; display: flex; flex-wrap: wrap; gap: 4px 16px; }
+.legend li { white-space: nowrap; }
+.swatch { display: inline-block; width: 10px; height: 10px; border-radius: 2px; margin-right: 6px; }
+table { border-collapse: collapse; width: 100%; }
+th, td { padding: 6px 8px; border-bottom: 1px solid #e4e7eb; text-align: right; white-space: nowrap; }
+th:first-child, td:first-child { text-align: left; }
+thead th { cursor: pointer; user-select: none; color: #52606d; font-weight: 600; }
+thead th[data-dir="asc"]::after { content: " \25B2"; }
+thead th[data-dir="desc"]::after { content: " \25BC"; }
+tfoot td { font-weight: 600; border-bottom: none; }
+.up { color: #c81e1e; }
+.down { color: #057a55; }
+svg text { font-size: 11px; fill: #52606d; }
+"#;
+
+const SORT_SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable thead th").forEach(function (th) {
+  th.addEventListener("click", function () {
+    var table = th.closest("table"), body = table.tBodies[0], idx = th.cellIndex;
+    var dir = th.dataset.dir === "desc" ? "asc" : "desc";
+    table.querySelectorAll("thead th").forEach(function (other) { delete other.dataset.dir; });
+    th.dataset.dir = dir;
+    var key = function (row) {
+      var cell = row.cells[idx];
+      return cell.dataset.v === undefined ? cell.textContent : parseFloat(cell.dataset.v);
+    };
+    Array.from(body.rows).sort(function (a, b) {
+      var x = key(a), y = key(b);
+      var order = typeof x === "number" ? x - y : String(x).localeCompare(String(y));
+      return dir === "asc" ? order : -order;
+    }).forEach(function (row) { body.appendChild(row); });
+  });
+});
+"#;
+
+// Writes `html` to `--output-path`, or stdout without one.
+pub fn write_html(html: &str, export: &ExportArgs) -> Result<()> {
+    match export.output_path.as_deref() {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                if !parent.as_os_str().is_empty() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("creating output directory {:?}", parent))?;
+                }
+            }
+            fs::write(path, html).with_context(|| format!("writing html {:?}", path))
+        }
+        None => {
+            io::stdout().lock().write_all(html.as_bytes())?;
+            Ok(())
+        }
+    }
+}
+
+// A monthly or time-series cost report: headline figures, spend per bucket stacked by provider,
+// model share, token types and sortable provider and model tables.
+pub fn cost_report_html(title: &str, report: &TimeSeriesReport) -> String {
+    let totals = &report.totals;
+    let sym = currency_symbol(&totals.currency);
+    let mut body = String::new();
+    body.push_str(&format!(
+        "<h1>{} Cost Report</h1>\n<p class=\"meta\">{} &middot; {}</p>\n",
+        escape(title),
+        escape(&report.period),
+        escape(&totals.currency)
+    ));
+    body.push_str("<div class=\"cards\">\n");
+    for (label, value) in [
+        ("Total Cost", money(&sym, totals.monthly_total_usd)),
+        ("Variable", money(&sym, totals.variable_cost_usd)),
+        (
+            "Subscription Allocated",
+            money(&sym, totals.subscription_allocated_usd),
+        ),
+        ("Tokens", format!("{:.2}M", totals.total_mtok)),
+        (
+            "Blended / MTok",
+            format!("{}{:.4}", sym, totals.blended_usd_per_mtok),
+        ),
+        ("Sessions", totals.session_count.to_string()),
+    ] {
+        body.push_str(&card(label, &value));
+    }
+    body.push_str("</div>\n");
+
+    if !report.buckets.is_empty() {
+        body.push_str(&format!(
+            "<section>\n<h2>Spend per {} by Provider</h2>\n{}</section>\n",
+            report.granularity.as_str(),
+            stacked_bars(report, &sym)
+        ));
+    }
+    body.push_str("<div class=\"row\">\n");
+    body.push_str(&format!(
+        "<section>\n<h2>Model Share</h2>\n{}</section>\n",
+        donut(&totals.model_breakdown, &sym)
+    ));
+    body.push_str(&format!(
+        "<section>\n<h2>Token Types</h2>\n{}</section>\n",
+        token_types(totals)
+    ));
+    body.push_str("</div>\n");
+    for (heading, rows) in [
+        ("Providers", &totals.provider_breakdown),
+        ("Models", &totals.model_breakdown),
+    ] {
+        body.push_str(&format!(
+            "<section>\n<h2>{}</h2>\n{}</section>\n",
+            heading,
+            metric_table(heading, rows, totals, &sym)
+        ));
+    }
+    if !totals.suggestions.is_empty() {
+        body.push_str("<section>\n<h2>Suggestions</h2>\n<ul>\n");
+        for tip in &totals.suggestions {
+            body.push_str(&format!("<li>{}</li>\n", escape(tip)));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+    page(&format!("{} Cost Report {}", title, report.period), &body)
+}
+
+// A period comparison: headline change and its split, volume and price/mix effects per provider,
+// and sortable provider and model tables.
+pub fn compare_html(report: &CompareReport) -> String {
+    let sym = currency_symbol(&report.currency);
+    let totals = &report.totals;
+    let mut body = String::new();
+    body.push_str(&format!(
+        "<h1>Cost Comparison</h1>\n<p class=\"meta\">{} vs {} &middot; {}</p>\n",
+        escape(&report.period),
+        escape(&report.against),
+        escape(&report.currency)
+    ));
+    body.push_str("<div class=\"cards\">\n");
+    let pct = totals
+        .cost_delta_pct
+        .map_or(String::new(), |pct| format!(" ({:+.2}%)", pct));
+    for (label, value) in [
+        (
+            format!("{} Cost", report.against),
+            money(&sym, totals.baseline_cost_usd),
+        ),
+        (
+            format!("{} Cost", report.period),
+            money(&sym, totals.cost_usd),
+        ),
+        (
+            "Change".to_string(),
+            format!("{}{}", signed_money(&sym, totals.cost_delta_usd), pct),
+        ),
+        (
+            "Volume Effect".to_string(),
+            signed_money(&sym, totals.volume_effect_usd),
+        ),
+        (
+            "Price/Mix Effect".to_string(),
+            signed_money(&sym, totals.price_mix_effect_usd),
+        ),
+        ("Tokens".to_string(), format!("{:+}", totals.tokens_delta)),
+    ] {
+        body.push_str(&card(&label, &value));
+    }
+    body.push_str("</div>\n");
+    if !report.providers.is_empty() {
+        body.push_str(&format!(
+            "<section>\n<h2>Change by Provider</h2>\n{}</section>\n",
+            effect_bars(&report.providers, &sym)
+        ));
+    }
+    for (heading, rows) in [("Providers", &report.providers), ("Models", &report.models)] {
+        body.push_str(&format!(
+            "<section>\n<h2>{}</h2>\n{}</section>\n",
+            heading,
+            compare_table(heading, rows, totals, report, &sym)
+        ));
+    }
+    page(
+        &format!("Cost Comparison {} vs {}", report.period, report.against),
+        &body,
+    )
+}
+
+// "Daily" for a daily series, and so on.
+pub fn series_title(granularity: Granularity) -> &'static str {
+    match granularity {
+        Granularity::Hour => "Hourly",
+        Granularity::Day => "Daily",
+        Granularity::Week => "Weekly",
+        Granularity::Month => "Monthly",
+        Granularity::Quarter => "Quarterly",
+        Granularity::Year => "Yearly",
+    }
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<main>\n{}</main>\n<script>{}</script>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body,
+        SORT_SCRIPT
+    )
+}
+
+fn card(label: &str, value: &str) -> String {
+    format!(
+        "<div class=\"card\"><div class=\"label\">{}</div><div class=\"value\">{}</div></div>\n",
+        escape(label),
+        escape(value)
+    )
+}
+
+// Spend per bucket as stacked bars, one stack per provider; providers past the largest few are
+// drawn together as "other".
+fn stacked_bars(report: &TimeSeriesReport, sym: &str) -> String {
+    let names = chart_names(&report.totals.provider_breakdown);
+    let stacks: Vec<Vec<f64>> = report
+        .buckets
+        .iter()
+        .map(|bucket| {
+            let mut stack = vec![0.0; names.len()];
+            for row in &bucket.breakdown.provider_breakdown {
+                let slot = names
+                    .iter()
+                    .position(|name| name == &row.name)
+                    .unwrap_or(names.len() - 1);
+                stack[slot] += row.total_cost_usd;
+            }
+            stack
+        })
+        .collect();
+    let peak = stacks
+        .iter()
+        .map(|stack| stack.iter().sum::<f64>())
+        .fold(0.0, f64::max);
+    let scale = nice_ceiling(peak);
+    let (width, height, left, bottom, top) = (960.0, 280.0, 64.0, 28.0, 8.0);
+    let plot_width = width - left - 8.0;
+    let plot_height = height - bottom - top;
+    let band = plot_width / stacks.len() as f64;
+    let label_every = stacks.len().div_ceil(12);
+
+    let mut svg = format!(
+        "<svg viewBox=\"0 0 {} {}\" width=\"100%\" role=\"img\">\n",
+        width, height
+    );
+    for tick in 0..=4 {
+        let value = scale * tick as f64 / 4.0;
+        let y = top + plot_height - plot_height * tick as f64 / 4.0;
+        svg.push_str(&format!(
+            "<line x1=\"{left}\" x2=\"{}\" y1=\"{y:.1}\" y2=\"{y:.1}\" stroke=\"#e4e7eb\"/><text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+            width - 8.0,
+            left - 6.0,
+            y + 4.0,
+            escape(&money(sym, value))
+        ));
+    }
+    for (idx, (bucket, stack)) in report.buckets.iter().zip(&stacks).enumerate() {
+        let x = left + band * idx as f64 + band * 0.1;
+        let mut y = top + plot_height;
+        for (slot, value) in stack.iter().enumerate() {
+            if *value <= 0.0 || scale <= 0.0 {
+                continue;
+            }
+            let bar = plot_height * value / scale;
+            y -= bar;
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{} {}: {}</title></rect>\n",
+                x,
+                y,
+                band * 0.8,
+                bar,
+                color(slot, &names),
+                escape(&bucket.period),
+                escape(&names[slot]),
+                escape(&money(sym, *value))
+            ));
+        }
+        if idx % label_every == 0 {
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{}</text>\n",
+                x + band * 0.4,
+                height - 8.0,
+                escape(&bucket.period)
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg.push_str(&legend(
+        names
+            .iter()
+            .enumerate()
+            .map(|(slot, name)| (color(slot, &names), escape(name))),
+    ));
+    svg
+}
+
+// Each row's share of total cost as a donut.
+fn donut(rows: &[NamedMetric], sym: &str) -> String {
+    let names = chart_names(rows);
+    let mut shares = vec![0.0; names.len()];
+    for row in rows {
+        let slot = names
+            .iter()
+            .position(|name| name == &row.name)
+            .unwrap_or(names.len() - 1);
+        shares[slot] += row.total_cost_usd;
+    }
+    let total: f64 = shares.iter().sum();
+    if total <= 0.0 {
+        return "<p>No cost in this period.</p>\n".to_string();
+    }
+    let radius = 70.0;
+    let circumference = 2.0 * PI * radius;
+    let mut svg = String::from(
+        "<svg viewBox=\"0 0 200 200\" width=\"220\" height=\"220\" role=\"img\"><g transform=\"rotate(-90 100 100)\">\n",
+    );
+    let mut offset = 0.0;
+    for (slot, value) in shares.iter().enumerate() {
+        let arc = circumference * value / total;
+        svg.push_str(&format!(
+            "<circle cx=\"100\" cy=\"100\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"36\" stroke-dasharray=\"{:.2} {:.2}\" stroke-dashoffset=\"{:.2}\"><title>{}: {} ({:.1}%)</title></circle>\n",
+            radius,
+            color(slot, &names),
+            arc,
+            circumference - arc,
+            -offset,
+            escape(&names[slot]),
+            escape(&money(sym, *value)),
+            value / total * 100.0
+        ));
+        offset += arc;
+    }
+    svg.push_str("</g></svg>\n");
+    svg.push_str(&legend(names.iter().zip(&shares).enumerate().map(
+        |(slot, (name, value))| {
+            (
+                color(slot, &names),
+                format!("{} {:.1}%", escape(name), value / total * 100.0),
+            )
+        },
+    )));
+    svg
+}
+
+// Tokens by type as one 100% bar, with counts.
+fn token_types(totals: &CostBreakdown) -> String {
+    let types = [
+        ("Input", totals.input_tokens),
+        ("Output", totals.output_tokens),
+        ("Cache write 5m", totals.cache_write_tokens),
+        ("Cache write 1h", totals.cache_write_1h_tokens),
+        ("Cache read", totals.cache_read_tokens),
+        ("Tool input", totals.tool_input_tokens),
+        ("Tool output", totals.tool_output_tokens),
+    ];
+    let total: u64 = types.iter().map(|(_, tokens)| tokens).sum();
+    if total == 0 {
+        return "<p>No tokens in this period.</p>\n".to_string();
+    }
+    let mut svg = String::from("<svg viewBox=\"0 0 400 28\" width=\"100%\" role=\"img\">\n");
+    let mut x = 0.0;
+    for (slot, (label, tokens)) in types.iter().enumerate() {
+        let width = 400.0 * *tokens as f64 / total as f64;
+        if width > 0.0 {
+            svg.push_str(&format!(
+                "<rect x=\"{:.2}\" y=\"0\" width=\"{:.2}\" height=\"28\" fill=\"{}\"><title>{}: {}</title></rect>\n",
+                x, width, PALETTE[slot], label, tokens
+            ));
+        }
+        x += width;
+    }
+    svg.push_str("</svg>\n<table>\n<tbody>\n");
+    for (slot, (label, tokens)) in types.iter().enumerate() {
+        svg.push_str(&format!(
+            "<tr><td><span class=\"swatch\" style=\"background:{}\"></span>{}</td><td>{}</td><td>{:.1}%</td></tr>\n",
+            PALETTE[slot],
+            label,
+            tokens,
+            *tokens as f64 / total as f64 * 100.0
+        ));
+    }
+    svg.push_str("</tbody>\n</table>\n");
+    svg
+}
+
+// Volume and price/mix effects per row as bars either side of zero.
+fn effect_bars(rows: &[CompareRow], sym: &str) -> String {
+    let peak = rows
+        .iter()
+        .flat_map(|row| [row.volume_effect_usd.abs(), row.price_mix_effect_usd.abs()])
+        .fold(0.0, f64::max);
+    let scale = nice_ceiling(peak);
+    let (width, label_width, row_height) = (960.0, 200.0, 30.0);
+    let half = (width - label_width - 16.0) / 2.0;
+    let zero = label_width + half;
+    let height = row_height * rows.len() as f64 + 8.0;
+    let mut svg = format!(
+        "<svg viewBox=\"0 0 {} {}\" width=\"100%\" role=\"img\">\n<line x1=\"{zero}\" x2=\"{zero}\" y1=\"0\" y2=\"{}\" stroke=\"#9aa5b1\"/>\n",
+        width, height, height
+    );
+    for (idx, row) in rows.iter().enumerate() {
+        let y = row_height * idx as f64 + 4.0;
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+            label_width - 8.0,
+            y + 16.0,
+            escape(&row.name)
+        ));
+        for (offset, value, fill, label) in [
+            (0.0, row.volume_effect_usd, PALETTE[0], "volume"),
+            (11.0, row.price_mix_effect_usd, PALETTE[1], "price/mix"),
+        ] {
+            if scale <= 0.0 || value == 0.0 {
+                continue;
+            }
+            let bar = half * value.abs() / scale;
+            let x = if value < 0.0 { zero - bar } else { zero };
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"10\" fill=\"{}\"><title>{} {}: {}</title></rect>\n",
+                x,
+                y + offset,
+                bar,
+                fill,
+                escape(&row.name),
+                label,
+                escape(&signed_money(sym, value))
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg.push_str(&legend(
+        [
+            (PALETTE[0], "Volume effect".to_string()),
+            (PALETTE[1], "Price/mix effect".to_string()),
+        ]
+        .into_iter(),
+    ));
+    svg
+}
+
+fn metric_table(heading: &str, rows: &[NamedMetric], totals: &CostBreakdown, sym: &str) -> String {
+    let mut html = table_head(&[
+        &heading[..heading.len() - 1],
+        "Tokens",
+        "Variable",
+        "Subscription",
+        "Total",
+        "Share",
+        "Blended / MTok",
+        "Sessions",
+        "Tool Share",
+    ]);
+    let share = |cost: f64| {
+        if totals.monthly_total_usd > 0.0 {
+            cost / totals.monthly_total_usd * 100.0
+        } else {
+            0.0
+        }
+    };
+    for row in rows {
+        html.push_str(&format!(
+            "<tr><td>{}</td>{}{}{}{}{}{}{}{}</tr>\n",
+            escape(&row.name),
+            number_cell(row.tokens as f64, row.tokens.to_string()),
+            number_cell(row.variable_cost_usd, money(sym, row.variable_cost_usd)),
+            number_cell(
+                row.subscription_allocated_usd,
+                money(sym, row.subscription_allocated_usd)
+            ),
+            number_cell(row.total_cost_usd, money(sym, row.total_cost_usd)),
+            number_cell(
+                share(row.total_cost_usd),
+                format!("{:.1}%", share(row.total_cost_usd))
+            ),
+            number_cell(
+                row.blended_usd_per_mtok,
+                format!("{}{:.4}", sym, row.blended_usd_per_mtok)
+            ),
+            number_cell(row.session_count as f64, row.session_count.to_string()),
+            number_cell(row.tool_share, format!("{:.1}%", row.tool_share * 100.0)),
+        ));
+    }
+    html.push_str(&format!(
+        "</tbody>\n<tfoot><tr><td>Total</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>100.0%</td><td>{}{:.4}</td><td>{}</td><td></td></tr></tfoot>\n</table>\n",
+        totals.total_tokens,
+        money(sym, totals.variable_cost_usd),
+        money(sym, totals.subscription_allocated_usd),
+        money(sym, totals.monthly_total_usd),
+        sym,
+        totals.blended_usd_per_mtok,
+        totals.session_count
+    ));
+    html
+}
+
+fn compare_table(
+    heading: &str,
+    rows: &[CompareRow],
+    totals: &CompareRow,
+    report: &CompareReport,
+    sym: &str,
+) -> String {
+    let mut html = table_head(&[
+        &heading[..heading.len() - 1],
+        &report.against,
+        &report.period,
+        "Change",
+        "Change %",
+        "Tokens Change",
+        &format!("{} / MTok", report.against),
+        &format!("{} / MTok", report.period),
+        "Sessions Change",
+        "Volume",
+        "Price/Mix",
+    ]);
+    let line = |row: &CompareRow| {
+        format!(
+            "<td>{}</td>{}{}{}{}{}{}{}{}{}{}",
+            escape(&row.name),
+            number_cell(row.baseline_cost_usd, money(sym, row.baseline_cost_usd)),
+            number_cell(row.cost_usd, money(sym, row.cost_usd)),
+            delta_cell(row.cost_delta_usd, signed_money(sym, row.cost_delta_usd)),
+            row.cost_delta_pct.map_or_else(
+                || "<td data-v=\"0\">-</td>".to_string(),
+                |pct| delta_cell(pct, format!("{:+.2}%", pct))
+            ),
+            number_cell(row.tokens_delta as f64, format!("{:+}", row.tokens_delta)),
+            number_cell(
+                row.baseline_blended_usd_per_mtok,
+                format!("{}{:.4}", sym, row.baseline_blended_usd_per_mtok)
+            ),
+            number_cell(
+                row.blended_usd_per_mtok,
+                format!("{}{:.4}", sym, row.blended_usd_per_mtok)
+            ),
+            number_cell(
+                row.sessions_delta as f64,
+                format!("{:+}", row.sessions_delta)
+            ),
+            delta_cell(
+                row.volume_effect_usd,
+                signed_money(sym, row.volume_effect_usd)
+            ),
+            delta_cell(
+                row.price_mix_effect_usd,
+                signed_money(sym, row.price_mix_effect_usd)
+            ),
+        )
+    };
+    for row in rows {
+        html.push_str(&format!("<tr>{}</tr>\n", line(row)));
+    }
+    let mut total = totals.clone();
+    total.name = "Total".to_string();
+    html.push_str(&format!(
+        "</tbody>\n<tfoot><tr>{}</tr></tfoot>\n</table>\n",
+        line(&total)
+    ));
+    html
+}
+
+fn table_head(headers: &[&str]) -> String {
+    let mut html = String::from("<table class=\"sortable\">\n<thead><tr>");
+    for header in headers {
+        html.push_str(&format!("<th>{}</th>", escape(header)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    html
+}
+
+fn number_cell(value: f64, text: String) -> String {
+    format!("<td data-v=\"{}\">{}</td>", value, escape(&text))
+}
+
+// Cost increases are red and decreases green.
+fn delta_cell(value: f64, text: String) -> String {
+    let class = if value > 0.0 {
+        " class=\"up\""
+    } else if value < 0.0 {
+        " class=\"down\""
+    } else {
+        ""
+    };
+    format!("<td data-v=\"{}\"{}>{}</td>", value, class, escape(&text))
+}
+
+fn legend(items: impl Iterator<Item = (&'static str, String)>) -> String {
+    let mut html = String::from("<ul class=\"legend\">");
+    for (color, label) in items {
+        html.push_str(&format!(
+            "<li><span class=\"swatch\" style=\"background:{}\"></span>{}</li>",
+            color, label
+        ));
+    }
+    html.push_str("</ul>\n");
+    html
+}
+
+// Rows charted on their own, costliest first, then "other" when there are more.
+fn chart_names(rows: &[NamedMetric]) -> Vec<String> {
+    let mut costs: BTreeMap<&str, f64> = BTreeMap::new();
+    for row in rows {
+        *costs.entry(&row.name).or_default() += row.total_cost_usd;
+    }
+    let mut ranked: Vec<(&str, f64)> = costs.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+    let mut names: Vec<String> = ranked
+        .iter()
+        .take(CHART_SERIES)
+        .map(|(name, _)| name.to_string())
+        .collect();
+    if ranked.len() > CHART_SERIES {
+        names.push("other".to_string());
+    }
+    names
+}
+
+fn color(slot: usize, names: &[String]) -> &'static str {
+    if slot == CHART_SERIES && names.len() > CHART_SERIES {
+        OTHER_COLOR
+    } else {
+        PALETTE[slot % PALETTE.len()]
+    }
+}
+
+// The axis maximum: `value` rounded up to 1, 2, 2.5 or 5 times a power of ten.
+fn nice_ceiling(value: f64) -> f64 {
+    if value <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(value.log10().floor());
+    [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|ceiling| *ceiling >= value)
+        .unwrap_or(10.0 * magnitude)
+}
+
+fn money(sym: &str, amount: f64) -> String {
+    format!("{}{:.2}", sym, amount)
+}
+
+fn signed_money(sym: &str, amount: f64) -> String {
+    if amount < 0.0 {
+        format!("-{}{:.2}", sym, -amount)
+    } else {
+        format!("+{}{:.2}", sym, amount)
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn metric(name: &str, tokens: u64, total: f64) -> NamedMetric {
+        NamedMetric {
+            name: name.to_string(),
+            tokens,
+            mtok: tokens as f64 / 1_000_000.0,
+            variable_cost_usd: total,
+            subscription_allocated_usd: 0.0,
+            total_cost_usd: total,
+            blended_usd_per_mtok: 0.0,
+            session_count: 1,
+            tool_share: 0.0,
+        }
+    }
+
+    fn breakdown(providers: Vec<NamedMetric>) -> CostBreakdown {
+        let total: f64 = providers.iter().map(|row| row.total_cost_usd).sum();
+        let tokens: u64 = providers.iter().map(|row| row.tokens).sum();
+        CostBreakdown {
+            currency: "USD".to_string(),
+            variable_cost_usd: total,
+            subscription_allocated_usd: 0.0,
+            monthly_total_usd: total,
+            blended_usd_per_mtok: 0.0,
+            total_tokens: tokens,
+            total_mtok: tokens as f64 / 1_000_000.0,
+            input_tokens: tokens,
+            output_tokens: 0,
+            cache_write_tokens: 0,
+            cache_write_1h_tokens: 0,
+            cache_read_tokens: 0,
+            tool_input_tokens: 0,
+            tool_output_tokens: 0,
+            session_count: providers.len(),
+            skipped_unpriced_count: 0,
+            model_breakdown: providers.clone(),
+            provider_breakdown: providers,
+            service_tier_breakdown: Vec::new(),
+            batch_savings_estimate_usd: 0.0,
+            idle_subscription_usd: 0.0,
+            contract: None,
+            cache: None,
+            suggestions: vec!["Route <cheap> jobs & save".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_cost_report_is_self_contained() {
+        let day = |d: u32| TimeSeriesBucket {
+            period: format!("2026-02-{:02}", d),
+            start: Utc.with_ymd_and_hms(2026, 2, d, 0, 0, 0).unwrap(),
+            breakdown: breakdown(vec![
+                metric("claude", 1_000, 2.0),
+                metric("codex", 500, 1.0),
+            ]),
+        };
+        let report = TimeSeriesReport {
+            period: "2026-02".to_string(),
+            granularity: Granularity::Day,
+            totals: breakdown(vec![
+                metric("claude", 2_000, 4.0),
+                metric("codex", 1_000, 2.0),
+            ]),
+            buckets: vec![day(1), day(2)],
+        };
+        let html = cost_report_html("Monthly", &report);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("http://") && !html.contains("https://"));
+        assert!(!html.contains("<link") && !html.contains(" src="));
+        // A bar per provider per day, and names and tips are escaped.
+        assert_eq!(html.matches("<rect ").count(), 4 + 1);
+        assert!(html.contains("2026-02-02 codex: $1.00"));
+        assert!(html.contains("Route &lt;cheap&gt; jobs &amp; save"));
+        assert!(html.contains("<table class=\"sortable\">"));
+    }
+
+    #[test]
+    fn test_charts_group_small_series_as_other() {
+        let rows: Vec<NamedMetric> = (0..10)
+            .map(|idx| metric(&format!("model-{}", idx), 10, 10.0 - idx as f64))
+            .collect();
+        let names = chart_names(&rows);
+        assert_eq!(names.len(), CHART_SERIES + 1);
+        assert_eq!(names[0], "model-0");
+        assert_eq!(names[CHART_SERIES], "other");
+        assert_eq!(color(CHART_SERIES, &names), OTHER_COLOR);
+        assert_eq!(nice_ceiling(0.0), 1.0);
+        assert_eq!(nice_ceiling(7.3), 10.0);
+        assert_eq!(nice_ceiling(180.0), 200.0);
+        assert_eq!(nice_ceiling(2.1), 2.5);
+    }
+}
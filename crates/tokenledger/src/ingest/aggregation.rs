@@ -10,3 +10,287 @@ pub use super::{
     quote_sqlite_identifier, select_cursor_sqlite_columns, source_mtime_unix,
     unwrap_otel_attribute_value, write_ingest_checkpoint, CursorSqliteColumn,
 };
+
+// The group-by engine behind `--group-by` pivot reports.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+
+use crate::cli::GroupDimension;
+use crate::cost::{merge_partial, named_metric};
+use crate::models::{Acc, PivotReport, PivotRow};
+use crate::money::{round_parts, Micros, MICROS_PER_CENT};
+
+// The dimensions a pivot groups by, in `--group-by` order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupBy {
+    dimensions: Vec<GroupDimension>,
+}
+
+impl GroupBy {
+    pub fn new(dimensions: &[GroupDimension]) -> Result<Self> {
+        if dimensions.is_empty() {
+            return Err(anyhow!("--group-by needs at least one dimension"));
+        }
+        for (idx, dimension) in dimensions.iter().enumerate() {
+            if dimensions[..idx].contains(dimension) {
+                return Err(anyhow!(
+                    "--group-by lists '{}' more than once",
+                    dimension.as_str()
+                ));
+            }
+        }
+        Ok(Self {
+            dimensions: dimensions.to_vec(),
+        })
+    }
+
+    pub fn dimensions(&self) -> &[GroupDimension] {
+        &self.dimensions
+    }
+
+    pub fn needs_sessions(&self) -> bool {
+        self.dimensions.contains(&GroupDimension::Session)
+    }
+
+    // Key parts of the group an event falls in. The first provider, model or session part is
+    // written "provider/..." so names that repeat across providers stay apart.
+    pub fn key(
+        &self,
+        provider: &str,
+        model: &str,
+        day: NaiveDate,
+        session: Option<&str>,
+    ) -> Vec<String> {
+        let mut has_provider = false;
+        self.dimensions
+            .iter()
+            .map(|dimension| {
+                let value = match dimension {
+                    GroupDimension::Provider => {
+                        has_provider = true;
+                        return provider.to_string();
+                    }
+                    GroupDimension::Day => return day.format("%Y-%m-%d").to_string(),
+                    GroupDimension::Model => model,
+                    GroupDimension::Session => session.unwrap_or_default(),
+                };
+                if std::mem::replace(&mut has_provider, true) {
+                    value.to_string()
+                } else {
+                    format!("{}/{}", provider, value)
+                }
+            })
+            .collect()
+    }
+}
+
+// A group's key parts and its sums.
+type Group = (Vec<String>, Acc);
+
+// Groups being summed: each key's usage, variable cost and subscription share.
+#[derive(Debug)]
+pub struct PivotAcc {
+    group: GroupBy,
+    groups: BTreeMap<Vec<String>, Acc>,
+}
+
+impl PivotAcc {
+    pub fn new(group: GroupBy) -> Self {
+        Self {
+            group,
+            groups: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, key: Vec<String>, usage: &Acc, subscription: Micros) {
+        let acc = self.groups.entry(key).or_default();
+        merge_partial(acc, usage);
+        acc.subscription_allocated_micros += subscription;
+    }
+
+    // Rows whose cents add up to the given totals, like `build_breakdown`.
+    pub fn finish(
+        self,
+        period: String,
+        currency: String,
+        variable_cents: i64,
+        subscription_cents: i64,
+        nested: bool,
+    ) -> PivotReport {
+        let mut global = Acc::default();
+        for acc in self.groups.values() {
+            merge_partial(&mut global, acc);
+        }
+        let groups: Vec<Group> = self.groups.into_iter().collect();
+        let rows = if nested {
+            nested_rows(&groups, 0, variable_cents, subscription_cents)
+        } else {
+            leaf_rows(groups, variable_cents, subscription_cents)
+        };
+        PivotReport {
+            period,
+            currency,
+            dimensions: self.group.dimensions,
+            nested,
+            totals: named_metric("total", &global, variable_cents, subscription_cents),
+            rows,
+        }
+    }
+}
+
+fn leaf_rows(groups: Vec<Group>, variable_cents: i64, subscription_cents: i64) -> Vec<PivotRow> {
+    let accs: Vec<&Acc> = groups.iter().map(|(_, acc)| acc).collect();
+    let cents = rounded_cents(&accs, variable_cents, subscription_cents);
+    groups
+        .iter()
+        .zip(cents)
+        .map(|((keys, acc), (variable, subscription))| PivotRow {
+            keys: keys.clone(),
+            metric: named_metric(&keys.join("/"), acc, variable, subscription),
+            children: Vec::new(),
+        })
+        .collect()
+}
+
+// Rows for the `depth`-th key part of `groups`, which share every earlier part and are sorted by
+// key, so each row's groups are a contiguous run.
+fn nested_rows(
+    groups: &[Group],
+    depth: usize,
+    variable_cents: i64,
+    subscription_cents: i64,
+) -> Vec<PivotRow> {
+    let mut runs: Vec<(&[Group], Acc)> = Vec::new();
+    let mut start = 0;
+    for end in 1..=groups.len() {
+        if end < groups.len() && groups[end].0[depth] == groups[start].0[depth] {
+            continue;
+        }
+        let run = &groups[start..end];
+        let mut acc = Acc::default();
+        for (_, part) in run {
+            merge_partial(&mut acc, part);
+        }
+        runs.push((run, acc));
+        start = end;
+    }
+    let accs: Vec<&Acc> = runs.iter().map(|(_, acc)| acc).collect();
+    let cents = rounded_cents(&accs, variable_cents, subscription_cents);
+    runs.iter()
+        .zip(cents)
+        .map(|((run, acc), (variable, subscription))| {
+            let keys = run[0].0[..=depth].to_vec();
+            let children = if depth + 1 < run[0].0.len() {
+                nested_rows(run, depth + 1, variable, subscription)
+            } else {
+                Vec::new()
+            };
+            PivotRow {
+                metric: named_metric(&keys.join("/"), acc, variable, subscription),
+                keys,
+                children,
+            }
+        })
+        .collect()
+}
+
+fn rounded_cents(accs: &[&Acc], variable_cents: i64, subscription_cents: i64) -> Vec<(i64, i64)> {
+    let variable: Vec<Micros> = accs.iter().map(|acc| acc.variable_cost_micros).collect();
+    let subscription: Vec<Micros> = accs
+        .iter()
+        .map(|acc| acc.subscription_allocated_micros)
+        .collect();
+    round_parts(&variable, MICROS_PER_CENT, variable_cents)
+        .into_iter()
+        .zip(round_parts(
+            &subscription,
+            MICROS_PER_CENT,
+            subscription_cents,
+        ))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::session_hash;
+    use crate::money::units_to_cents;
+
+    fn usage(tokens: u64, variable_micros: Micros, session: &str) -> Acc {
+        Acc {
+            tokens,
+            input_tokens: tokens,
+            variable_cost_micros: variable_micros,
+            sessions: [session_hash("claude", session)].into(),
+            ..Acc::default()
+        }
+    }
+
+    #[test]
+    fn test_group_keys_carry_provider_once() {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        let key = |dimensions: &[GroupDimension]| {
+            GroupBy::new(dimensions)
+                .unwrap()
+                .key("claude", "sonnet", day, Some("s1"))
+        };
+        use GroupDimension::*;
+        assert_eq!(key(&[Model]), ["claude/sonnet"]);
+        assert_eq!(key(&[Provider, Model]), ["claude", "sonnet"]);
+        assert_eq!(
+            key(&[Day, Model, Session]),
+            ["2026-01-05", "claude/sonnet", "s1"]
+        );
+        assert_eq!(key(&[Session, Provider]), ["claude/s1", "claude"]);
+        assert!(GroupBy::new(&[Model, Day, Model]).is_err());
+        assert!(GroupBy::new(&[]).is_err());
+    }
+
+    #[test]
+    fn test_nested_rows_add_up_to_parents() {
+        use GroupDimension::*;
+        let group = GroupBy::new(&[Provider, Model]).unwrap();
+        let mut pivot = PivotAcc::new(group);
+        // A third of a cent per group: rounding each row on its own would drop the cent.
+        pivot.add(
+            vec!["claude".into(), "haiku".into()],
+            &usage(10, 3_333, "a"),
+            3_333,
+        );
+        pivot.add(
+            vec!["claude".into(), "sonnet".into()],
+            &usage(20, 3_333, "b"),
+            3_333,
+        );
+        pivot.add(
+            vec!["codex".into(), "gpt-5".into()],
+            &usage(30, 3_334, "c"),
+            3_334,
+        );
+        let report = pivot.finish("2026-01".into(), "USD".into(), 1, 1, true);
+
+        assert_eq!(report.totals.tokens, 60);
+        assert_eq!(report.totals.session_count, 3);
+        assert_eq!(report.rows.len(), 2);
+        let claude = &report.rows[0];
+        assert_eq!(claude.metric.name, "claude");
+        assert_eq!(claude.metric.tokens, 30);
+        let names: Vec<&str> = claude
+            .children
+            .iter()
+            .map(|row| row.metric.name.as_str())
+            .collect();
+        assert_eq!(names, ["claude/haiku", "claude/sonnet"]);
+        let cents = |rows: &[PivotRow]| -> i64 {
+            rows.iter()
+                .map(|row| units_to_cents(row.metric.total_cost_usd))
+                .sum()
+        };
+        assert_eq!(cents(&report.rows), 2);
+        assert_eq!(cents(&claude.children), 2);
+        assert_eq!(units_to_cents(report.totals.total_cost_usd), 2);
+    }
+}
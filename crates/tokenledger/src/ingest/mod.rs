@@ -865,65 +865,71 @@ pub fn normalize_ingest_record(
         session_id,
         timestamp,
         usage,
+        service_tier: extract_provider_service_tier(provider, value),
+        tags: ingest_source_tags(provider, source),
     })
 }
 
 pub fn extract_provider_token_usage(provider: IngestProvider, value: &Value) -> Option<TokenUsage> {
     let usage = match provider {
-        IngestProvider::Claude => TokenUsage {
-            input_tokens: extract_u64_by_paths(
-                value,
-                &[
-                    &["message", "usage", "input_tokens"],
-                    &["event", "message", "usage", "input_tokens"],
-                    &["request", "usage", "input_tokens"],
-                ],
-            )
-            .unwrap_or(0),
-            output_tokens: extract_u64_by_paths(
-                value,
-                &[
-                    &["message", "usage", "output_tokens"],
-                    &["event", "message", "usage", "output_tokens"],
-                    &["request", "usage", "output_tokens"],
-                ],
-            )
-            .unwrap_or(0),
-            cache_write_tokens: extract_u64_by_paths(
-                value,
-                &[
-                    &["message", "usage", "cache_creation_input_tokens"],
-                    &["event", "message", "usage", "cache_creation_input_tokens"],
-                    &["request", "usage", "cache_creation_input_tokens"],
-                ],
-            )
-            .unwrap_or(0),
-            cache_read_tokens: extract_u64_by_paths(
-                value,
-                &[
-                    &["message", "usage", "cache_read_input_tokens"],
-                    &["event", "message", "usage", "cache_read_input_tokens"],
-                    &["request", "usage", "cache_read_input_tokens"],
-                ],
-            )
-            .unwrap_or(0),
-            tool_input_tokens: extract_u64_by_paths(
-                value,
-                &[
-                    &["message", "usage", "tool_input_tokens"],
-                    &["event", "message", "usage", "tool_input_tokens"],
-                ],
-            )
-            .unwrap_or(0),
-            tool_output_tokens: extract_u64_by_paths(
-                value,
-                &[
-                    &["message", "usage", "tool_output_tokens"],
-                    &["event", "message", "usage", "tool_output_tokens"],
-                ],
-            )
-            .unwrap_or(0),
-        },
+        IngestProvider::Claude => split_claude_cache_writes(
+            value,
+            TokenUsage {
+                input_tokens: extract_u64_by_paths(
+                    value,
+                    &[
+                        &["message", "usage", "input_tokens"],
+                        &["event", "message", "usage", "input_tokens"],
+                        &["request", "usage", "input_tokens"],
+                    ],
+                )
+                .unwrap_or(0),
+                output_tokens: extract_u64_by_paths(
+                    value,
+                    &[
+                        &["message", "usage", "output_tokens"],
+                        &["event", "message", "usage", "output_tokens"],
+                        &["request", "usage", "output_tokens"],
+                    ],
+                )
+                .unwrap_or(0),
+                cache_write_tokens: extract_u64_by_paths(
+                    value,
+                    &[
+                        &["message", "usage", "cache_creation_input_tokens"],
+                        &["event", "message", "usage", "cache_creation_input_tokens"],
+                        &["request", "usage", "cache_creation_input_tokens"],
+                    ],
+                )
+                .unwrap_or(0),
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: extract_u64_by_paths(
+                    value,
+                    &[
+                        &["message", "usage", "cache_read_input_tokens"],
+                        &["event", "message", "usage", "cache_read_input_tokens"],
+                        &["request", "usage", "cache_read_input_tokens"],
+                    ],
+                )
+                .unwrap_or(0),
+                tool_input_tokens: extract_u64_by_paths(
+                    value,
+                    &[
+                        &["message", "usage", "tool_input_tokens"],
+                        &["event", "message", "usage", "tool_input_tokens"],
+                    ],
+                )
+                .unwrap_or(0),
+                tool_output_tokens: extract_u64_by_paths(
+                    value,
+                    &[
+                        &["message", "usage", "tool_output_tokens"],
+                        &["event", "message", "usage", "tool_output_tokens"],
+                    ],
+                )
+                .unwrap_or(0),
+            },
+        ),
         IngestProvider::Codex => TokenUsage {
             input_tokens: extract_u64_by_paths(
                 value,
@@ -961,6 +967,7 @@ pub fn extract_provider_token_usage(provider: IngestProvider, value: &Value) ->
                 ],
             )
             .unwrap_or(0),
+            cache_write_1h_tokens: 0,
             cache_read_tokens: extract_u64_by_paths(
                 value,
                 &[
@@ -1139,6 +1146,7 @@ pub fn extract_provider_token_usage(provider: IngestProvider, value: &Value) ->
                 input_tokens,
                 output_tokens,
                 cache_write_tokens,
+                cache_write_1h_tokens: 0,
                 cache_read_tokens,
                 tool_input_tokens,
                 tool_output_tokens,
@@ -1172,6 +1180,7 @@ pub fn extract_provider_token_usage(provider: IngestProvider, value: &Value) ->
                 ],
             )
             .unwrap_or(0),
+            cache_write_1h_tokens: 0,
             cache_read_tokens: extract_u64_by_paths(
                 value,
                 &[
@@ -1228,6 +1237,7 @@ pub fn extract_provider_token_usage(provider: IngestProvider, value: &Value) ->
                 ],
             )
             .unwrap_or(0),
+            cache_write_1h_tokens: 0,
             cache_read_tokens: extract_u64_by_paths(
                 value,
                 &[
@@ -1260,6 +1270,26 @@ pub fn extract_provider_token_usage(provider: IngestProvider, value: &Value) ->
     (usage.total() > 0).then_some(usage)
 }
 
+// Claude reports `cache_creation_input_tokens` as the total and splits it by TTL under
+// `cache_creation`; older logs only carry the total, which is treated as 5-minute writes.
+fn split_claude_cache_writes(value: &Value, mut usage: TokenUsage) -> TokenUsage {
+    let by_ttl = |key: &str| {
+        extract_u64_by_paths(
+            value,
+            &[
+                &["message", "usage", "cache_creation", key],
+                &["event", "message", "usage", "cache_creation", key],
+                &["request", "usage", "cache_creation", key],
+            ],
+        )
+    };
+    let one_hour = by_ttl("ephemeral_1h_input_tokens").unwrap_or(0);
+    usage.cache_write_tokens = by_ttl("ephemeral_5m_input_tokens")
+        .unwrap_or_else(|| usage.cache_write_tokens.saturating_sub(one_hour));
+    usage.cache_write_1h_tokens = one_hour;
+    usage
+}
+
 pub fn extract_provider_timestamp(
     provider: IngestProvider,
     value: &Value,
@@ -1364,6 +1394,63 @@ pub fn extract_provider_model(provider: IngestProvider, value: &Value) -> Option
     })
 }
 
+// Claude Code keeps each project's transcripts under `~/.claude/projects/<project>/`; the directory
+// name becomes a "project:<project>" tag.
+pub fn ingest_source_tags(provider: IngestProvider, source: &Path) -> Vec<String> {
+    if provider != IngestProvider::Claude {
+        return Vec::new();
+    }
+    let parts: Vec<_> = source.iter().collect();
+    // The project directory must not be the file itself.
+    (0..parts.len().saturating_sub(3))
+        .find(|&idx| parts[idx] == ".claude" && parts[idx + 1] == "projects")
+        .map(|idx| vec![format!("project:{}", parts[idx + 2].to_string_lossy())])
+        .unwrap_or_default()
+}
+
+// Only Codex and ProxyAPI logs expose the billing tier. Standard-equivalent values map to None.
+pub fn extract_provider_service_tier(provider: IngestProvider, value: &Value) -> Option<String> {
+    let paths: &[&[&str]] = match provider {
+        IngestProvider::Codex => &[
+            &["response", "service_tier"],
+            &["result", "service_tier"],
+            &["payload", "service_tier"],
+        ],
+        IngestProvider::Proxyapi => &[
+            &["service_tier"],
+            &["usage_record", "service_tier"],
+            &["management", "service_tier"],
+            &["request", "service_tier"],
+            &["response", "service_tier"],
+        ],
+        IngestProvider::Claude | IngestProvider::Cursor | IngestProvider::Droid => return None,
+    };
+    let raw = extract_string_by_paths(value, paths).or_else(|| {
+        if provider == IngestProvider::Proxyapi {
+            extract_proxyapi_attribute_string(
+                value,
+                &[
+                    "gen_ai.openai.response.service_tier",
+                    "gen_ai.openai.request.service_tier",
+                    "proxyapi.service_tier",
+                    "service_tier",
+                ],
+            )
+        } else {
+            None
+        }
+    })?;
+    normalize_service_tier(&raw)
+}
+
+pub fn normalize_service_tier(raw: &str) -> Option<String> {
+    let tier = raw.trim().to_lowercase();
+    match tier.as_str() {
+        "" | "default" | "auto" | "standard" | "standard_only" => None,
+        _ => Some(tier),
+    }
+}
+
 pub fn extract_provider_session_id(provider: IngestProvider, value: &Value) -> Option<String> {
     let paths: &[&[&str]] = match provider {
         IngestProvider::Claude => &[
@@ -1573,6 +1660,11 @@ pub fn find_value_by_path<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Val
 }
 
 pub fn extract_token_usage(value: &Value) -> TokenUsage {
+    let cache_write_1h_tokens = extract_u64_by_keys(
+        value,
+        &["cache_write_1h_tokens", "ephemeral_1h_input_tokens"],
+    )
+    .unwrap_or(0);
     TokenUsage {
         input_tokens: extract_u64_by_keys(
             value,
@@ -1600,11 +1692,16 @@ pub fn extract_token_usage(value: &Value) -> TokenUsage {
             value,
             &[
                 "cache_write_tokens",
-                "cache_creation_input_tokens",
                 "cacheWriteTokens",
+                "ephemeral_5m_input_tokens",
             ],
         )
+        .or_else(|| {
+            extract_u64_by_keys(value, &["cache_creation_input_tokens"])
+                .map(|total| total.saturating_sub(cache_write_1h_tokens))
+        })
         .unwrap_or(0),
+        cache_write_1h_tokens,
         cache_read_tokens: extract_u64_by_keys(
             value,
             &[
@@ -0,0 +1,1414 @@
+// Unified model/provider ledger builder (CSV + SQL artifacts under ledger/)
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::benchmarks::{BenchmarkData, BenchmarkStore};
+use crate::cli::{LedgerArgs, LedgerBuildArgs, LedgerCommand};
+use crate::models::PricingBook;
+use crate::routing::mappings::{infer_ledger_provider, slugify_model};
+use crate::utils::load_pricing;
+
+pub const LEDGER_CSV: &str = "unified_model_provider_ledger.csv";
+pub const LEDGER_SCHEMA_SQL: &str = "unified_model_provider_ledger.schema.sql";
+pub const LEDGER_SEED_SQL: &str = "unified_model_provider_ledger.seed.sql";
+pub const RUNTIME_METRICS_CSV: &str = "cliproxyapi_runtime_metrics_snapshot.csv";
+pub const PARETO_CSV: &str = "unified_model_provider_pareto.csv";
+pub const PARETO_VIEW_SQL: &str = "unified_model_provider_pareto.view.sql";
+
+const MISSING_TOKENS: &[&str] = &["", "-", "—", "n/a", "na", "null"];
+const PRICE_BENCHMARKS: &[&str] = &["Input Price", "Output Price"];
+
+const LEDGER_HEADERS: &[&str] = &[
+    "ledger_row_id",
+    "source_model",
+    "source_model_slug",
+    "inferred_provider",
+    "provider_mapping_rule",
+    "provider_mapping_confidence",
+    "canonical_model_guess",
+    "model_mapping_rule",
+    "model_mapping_confidence",
+    "pricing_provider",
+    "pricing_model",
+    "pricing_subscription_usd_month",
+    "pricing_input_usd_per_mtok",
+    "pricing_output_usd_per_mtok",
+    "pricing_cache_write_usd_per_mtok",
+    "pricing_cache_read_usd_per_mtok",
+    "pricing_tool_input_usd_per_mtok",
+    "pricing_tool_output_usd_per_mtok",
+    "benchmark_input_usd_per_mtok",
+    "benchmark_output_usd_per_mtok",
+    "benchmark_rows_total",
+    "benchmark_rows_non_missing",
+    "benchmark_rows_missing",
+    "benchmark_prior_rows_total",
+    "benchmark_prior_rows_non_missing",
+    "benchmark_prior_rows_missing",
+    "benchmark_distinct_total",
+    "benchmark_distinct_non_missing",
+    "pricing_vs_benchmark_input_delta",
+    "pricing_vs_benchmark_output_delta",
+];
+
+const PARETO_HEADERS: &[&str] = &[
+    "ledger_row_id",
+    "provider",
+    "model",
+    "pareto_score",
+    "quality_component",
+    "cost_component",
+    "latency_component",
+    "blended_cost_usd_per_mtok",
+    "runtime_latency_ms",
+    "runtime_quality_score",
+    "benchmark_quality_score",
+    "runtime_source_path",
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelStats {
+    pub source_model: String,
+    pub source_model_slug: String,
+    pub benchmark_rows_total: u64,
+    pub benchmark_rows_non_missing: u64,
+    pub benchmark_rows_missing: u64,
+    pub benchmark_prior_rows_total: u64,
+    pub benchmark_prior_rows_non_missing: u64,
+    pub benchmark_prior_rows_missing: u64,
+    pub benchmark_distinct_total: u64,
+    pub benchmark_distinct_non_missing: u64,
+    pub benchmark_input_usd_per_mtok: Option<Decimal>,
+    pub benchmark_output_usd_per_mtok: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerRow {
+    pub ledger_row_id: u64,
+    pub source_model: String,
+    pub source_model_slug: String,
+    pub inferred_provider: String,
+    pub provider_mapping_rule: String,
+    pub provider_mapping_confidence: u8,
+    pub canonical_model_guess: String,
+    pub model_mapping_rule: String,
+    pub model_mapping_confidence: u8,
+    pub pricing_provider: String,
+    pub pricing_model: String,
+    pub pricing_subscription_usd_month: Option<Decimal>,
+    pub pricing_input_usd_per_mtok: Option<Decimal>,
+    pub pricing_output_usd_per_mtok: Option<Decimal>,
+    pub pricing_cache_write_usd_per_mtok: Option<Decimal>,
+    pub pricing_cache_read_usd_per_mtok: Option<Decimal>,
+    pub pricing_tool_input_usd_per_mtok: Option<Decimal>,
+    pub pricing_tool_output_usd_per_mtok: Option<Decimal>,
+    pub benchmark_input_usd_per_mtok: Option<Decimal>,
+    pub benchmark_output_usd_per_mtok: Option<Decimal>,
+    pub benchmark_rows_total: u64,
+    pub benchmark_rows_non_missing: u64,
+    pub benchmark_rows_missing: u64,
+    pub benchmark_prior_rows_total: u64,
+    pub benchmark_prior_rows_non_missing: u64,
+    pub benchmark_prior_rows_missing: u64,
+    pub benchmark_distinct_total: u64,
+    pub benchmark_distinct_non_missing: u64,
+    pub pricing_vs_benchmark_input_delta: Option<Decimal>,
+    pub pricing_vs_benchmark_output_delta: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriorsAggregationRow {
+    pub inferred_provider: String,
+    pub pricing_provider: String,
+    pub models_count: u64,
+    pub mapped_models_count: u64,
+    pub prior_rows_total: u64,
+    pub prior_rows_non_missing: u64,
+    pub prior_rows_missing: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeMetric {
+    pub provider: String,
+    pub model: String,
+    pub latency_ms: Option<f64>,
+    pub quality_score: Option<f64>,
+    pub source_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParetoRow {
+    pub ledger_row_id: u64,
+    pub provider: String,
+    pub model: String,
+    pub pareto_score: f64,
+    pub quality_component: f64,
+    pub cost_component: f64,
+    pub latency_component: f64,
+    pub blended_cost_usd_per_mtok: Option<f64>,
+    pub runtime_latency_ms: Option<f64>,
+    pub runtime_quality_score: Option<f64>,
+    pub benchmark_quality_score: Option<f64>,
+    pub runtime_source_path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerBuildSummary {
+    pub ledger_rows: usize,
+    pub priors_aggregation_rows: usize,
+    pub benchmark_store_models: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_metric_rows: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pareto_rows: Option<usize>,
+    pub artifacts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NormalizedBenchmarkRow {
+    model: String,
+    benchmark: String,
+    is_missing: u8,
+    #[serde(default)]
+    value_primary: String,
+}
+
+/// (provider, model, rule) lookups keyed by model slug
+struct PricingIndex<'a> {
+    pricing: &'a PricingBook,
+    global: HashMap<String, (String, String, &'static str)>,
+    by_provider: HashMap<String, HashMap<String, (String, String, &'static str)>>,
+}
+
+pub fn run_ledger(args: LedgerArgs) -> Result<()> {
+    match args.command {
+        LedgerCommand::Build(args) => run_ledger_build(args),
+    }
+}
+
+pub fn run_ledger_build(args: LedgerBuildArgs) -> Result<()> {
+    let summary = execute_ledger_build(&args)?;
+    if args.json_output {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        println!("ledger_rows={}", summary.ledger_rows);
+        println!(
+            "priors_aggregation_rows={}",
+            summary.priors_aggregation_rows
+        );
+        println!("benchmark_store_models={}", summary.benchmark_store_models);
+        if let Some(count) = summary.runtime_metric_rows {
+            println!("runtime_metric_rows={}", count);
+        }
+        if let Some(count) = summary.pareto_rows {
+            println!("pareto_rows={}", count);
+        }
+        for artifact in &summary.artifacts {
+            println!("artifact={}", artifact);
+        }
+    }
+    Ok(())
+}
+
+pub fn execute_ledger_build(args: &LedgerBuildArgs) -> Result<LedgerBuildSummary> {
+    let pricing = load_pricing(&args.pricing)?;
+    let stats = collect_model_stats(&args.models_csv)?;
+    let benchmarks = load_benchmarks(args.benchmarks.as_deref())?;
+    let rows = build_ledger_rows(&stats, &pricing, &benchmarks);
+    if rows.is_empty() {
+        return Err(anyhow!(
+            "no benchmark rows found in {:?}; cannot build ledger",
+            args.models_csv
+        ));
+    }
+    let priors = priors_aggregation(&rows);
+
+    let dir = &args.ledger_dir;
+    fs::create_dir_all(dir).with_context(|| format!("creating ledger directory {:?}", dir))?;
+    let mut artifacts = Vec::new();
+
+    let csv_path = dir.join(LEDGER_CSV);
+    write_ledger_csv(&csv_path, &rows)?;
+    artifacts.push(csv_path);
+    let schema_path = dir.join(LEDGER_SCHEMA_SQL);
+    write_text(&schema_path, &ledger_schema_sql())?;
+    artifacts.push(schema_path);
+    let seed_path = dir.join(LEDGER_SEED_SQL);
+    write_text(&seed_path, &ledger_seed_sql(&rows, &priors))?;
+    artifacts.push(seed_path);
+
+    let mut runtime_metric_rows = None;
+    let mut pareto_rows = None;
+    if !args.skip_pareto {
+        let mut runtime = load_runtime_metrics(&args.runtime_snapshots)?;
+        if let Some(path) = args.benchmarks.as_deref() {
+            add_benchmark_runtime_metrics(&benchmarks, path, &mut runtime);
+        }
+        let scored = build_pareto_rows(&rows, &runtime);
+
+        let runtime_path = dir.join(RUNTIME_METRICS_CSV);
+        write_runtime_metrics_csv(&runtime_path, &runtime)?;
+        artifacts.push(runtime_path);
+        let pareto_path = dir.join(PARETO_CSV);
+        write_pareto_csv(&pareto_path, &scored)?;
+        artifacts.push(pareto_path);
+        let view_path = dir.join(PARETO_VIEW_SQL);
+        write_text(&view_path, PARETO_VIEW_SQL_TEXT)?;
+        artifacts.push(view_path);
+
+        runtime_metric_rows = Some(runtime.len());
+        pareto_rows = Some(scored.len());
+    }
+
+    Ok(LedgerBuildSummary {
+        ledger_rows: rows.len(),
+        priors_aggregation_rows: priors.len(),
+        benchmark_store_models: benchmarks.len(),
+        runtime_metric_rows,
+        pareto_rows,
+        artifacts: artifacts
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect(),
+    })
+}
+
+/// Merge benchmark entries through the store's source-priority rules, keyed by model slug
+pub fn load_benchmarks(path: Option<&Path>) -> Result<BTreeMap<String, BenchmarkData>> {
+    let Some(path) = path else {
+        return Ok(BTreeMap::new());
+    };
+    let text = fs::read_to_string(path).with_context(|| format!("reading {:?}", path))?;
+    let entries: Vec<BenchmarkData> =
+        serde_json::from_str(&text).with_context(|| format!("parsing {:?}", path))?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .context("creating benchmark store runtime")?;
+    let merged = rt.block_on(async {
+        let store = BenchmarkStore::new(0);
+        for entry in entries {
+            store.merge(slugify_model(&entry.model_id), entry).await;
+        }
+        store.get_all().await
+    });
+    Ok(merged
+        .into_iter()
+        .map(|entry| (slugify_model(&entry.model_id), entry))
+        .collect())
+}
+
+pub fn collect_model_stats(path: &Path) -> Result<BTreeMap<String, ModelStats>> {
+    struct Grouped {
+        stats: ModelStats,
+        all: BTreeSet<String>,
+        non_missing: BTreeSet<String>,
+    }
+
+    let mut reader = csv::Reader::from_path(path).with_context(|| format!("opening {:?}", path))?;
+    let mut grouped: BTreeMap<String, Grouped> = BTreeMap::new();
+    for (idx, record) in reader.deserialize::<NormalizedBenchmarkRow>().enumerate() {
+        let row = record.with_context(|| format!("parsing row {} in {:?}", idx + 2, path))?;
+        let model = row.model.trim().to_string();
+        let benchmark = row.benchmark.trim().to_string();
+        let is_missing = row.is_missing != 0;
+
+        let entry = grouped.entry(model.clone()).or_insert_with(|| Grouped {
+            stats: ModelStats {
+                source_model_slug: slugify_model(&model),
+                source_model: model.clone(),
+                ..ModelStats::default()
+            },
+            all: BTreeSet::new(),
+            non_missing: BTreeSet::new(),
+        });
+        let stats = &mut entry.stats;
+
+        stats.benchmark_rows_total += 1;
+        entry.all.insert(benchmark.clone());
+        if is_missing {
+            stats.benchmark_rows_missing += 1;
+        } else {
+            stats.benchmark_rows_non_missing += 1;
+            entry.non_missing.insert(benchmark.clone());
+        }
+
+        if !PRICE_BENCHMARKS.contains(&benchmark.as_str()) {
+            stats.benchmark_prior_rows_total += 1;
+            if is_missing {
+                stats.benchmark_prior_rows_missing += 1;
+            } else {
+                stats.benchmark_prior_rows_non_missing += 1;
+            }
+        } else if !is_missing {
+            if let Some(parsed) = parse_price_decimal(&row.value_primary) {
+                if benchmark == "Input Price" {
+                    stats.benchmark_input_usd_per_mtok = Some(parsed);
+                } else {
+                    stats.benchmark_output_usd_per_mtok = Some(parsed);
+                }
+            }
+        }
+    }
+
+    Ok(grouped
+        .into_iter()
+        .map(|(model, mut entry)| {
+            entry.stats.benchmark_distinct_total = entry.all.len() as u64;
+            entry.stats.benchmark_distinct_non_missing = entry.non_missing.len() as u64;
+            (model, entry.stats)
+        })
+        .collect())
+}
+
+/// Parse a benchmark price cell such as `$2.00 / $4.00 (>200k)` primary part `$2.00`
+pub fn parse_price_decimal(value: &str) -> Option<Decimal> {
+    let raw = value.trim();
+    if MISSING_TOKENS.contains(&slugify_model(raw).as_str()) {
+        return None;
+    }
+    let mut cleaned = raw.replace(['$', ','], "");
+    while let Some(open) = cleaned.find('(') {
+        let Some(close) = cleaned[open..].find(')') else {
+            break;
+        };
+        let start = cleaned[..open].trim_end().len();
+        cleaned.replace_range(start..open + close + 1, "");
+    }
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() {
+        return None;
+    }
+    Decimal::from_str(cleaned).ok()
+}
+
+fn decimal_from_f64(value: f64) -> Option<Decimal> {
+    Decimal::from_str(&value.to_string()).ok()
+}
+
+pub fn decimal_to_str(value: Option<Decimal>) -> String {
+    match value {
+        None => String::new(),
+        Some(value) => {
+            let text = value.normalize().to_string();
+            if text == "-0" {
+                "0".to_string()
+            } else {
+                text
+            }
+        }
+    }
+}
+
+impl<'a> PricingIndex<'a> {
+    fn new(pricing: &'a PricingBook) -> Self {
+        let mut global = HashMap::new();
+        let mut by_provider = HashMap::new();
+        // Providers are visited in name order so the global index is deterministic.
+        let providers: BTreeMap<_, _> = pricing.providers.iter().collect();
+        for (provider_name, provider) in providers {
+            let mut lookup = HashMap::new();
+            let models: BTreeSet<_> = provider.models.keys().collect();
+            for model in models {
+                let mapped = (provider_name.clone(), model.clone(), "pricing:model_exact");
+                global
+                    .entry(slugify_model(model))
+                    .or_insert_with(|| mapped.clone());
+                lookup.insert(slugify_model(model), mapped);
+            }
+            let aliases: BTreeMap<_, _> = provider.model_aliases.iter().collect();
+            for (alias, canonical) in aliases {
+                let mapped = (
+                    provider_name.clone(),
+                    canonical.clone(),
+                    "pricing:model_alias",
+                );
+                global
+                    .entry(slugify_model(alias))
+                    .or_insert_with(|| mapped.clone());
+                lookup.insert(slugify_model(alias), mapped);
+            }
+            by_provider.insert(provider_name.clone(), lookup);
+        }
+        Self {
+            pricing,
+            global,
+            by_provider,
+        }
+    }
+
+    fn has_model(&self, provider: &str, model: &str) -> bool {
+        self.pricing
+            .providers
+            .get(provider)
+            .is_some_and(|p| p.models.contains_key(model))
+    }
+
+    /// Returns (pricing_provider, pricing_model, rule, confidence)
+    fn map_model(&self, provider_guess: &str, model_guess: &str) -> (String, String, String, u8) {
+        if let Some((provider, model, rule)) = self
+            .by_provider
+            .get(provider_guess)
+            .and_then(|lookup| lookup.get(model_guess))
+        {
+            return (provider.clone(), model.clone(), rule.to_string(), 100);
+        }
+        if let Some((provider, model, rule)) = self.global.get(model_guess) {
+            return (provider.clone(), model.clone(), rule.to_string(), 92);
+        }
+        if model_guess.starts_with("gpt-5") && self.has_model("codex", "gpt-5") {
+            return (
+                "codex".to_string(),
+                "gpt-5".to_string(),
+                "heuristic:gpt-5-family".to_string(),
+                78,
+            );
+        }
+        if model_guess.starts_with("claude-sonnet-4-5")
+            && self.has_model("claude", "claude-sonnet-4-5")
+        {
+            return (
+                "claude".to_string(),
+                "claude-sonnet-4-5".to_string(),
+                "heuristic:claude-sonnet-4-5".to_string(),
+                80,
+            );
+        }
+        (String::new(), String::new(), "unmapped".to_string(), 0)
+    }
+}
+
+pub fn build_ledger_rows(
+    stats_by_model: &BTreeMap<String, ModelStats>,
+    pricing: &PricingBook,
+    benchmarks: &BTreeMap<String, BenchmarkData>,
+) -> Vec<LedgerRow> {
+    let index = PricingIndex::new(pricing);
+    let mut ordered: Vec<&ModelStats> = stats_by_model.values().collect();
+    ordered.sort_by(|a, b| {
+        a.source_model_slug
+            .cmp(&b.source_model_slug)
+            .then_with(|| a.source_model.cmp(&b.source_model))
+    });
+
+    let mut rows = Vec::with_capacity(ordered.len());
+    for (idx, stats) in ordered.into_iter().enumerate() {
+        let (inferred_provider, provider_rule, provider_confidence) =
+            infer_ledger_provider(&stats.source_model, &pricing.provider_aliases);
+        let model_guess = slugify_model(&stats.source_model);
+        let (pricing_provider, pricing_model, model_rule, model_confidence) =
+            index.map_model(&inferred_provider, &model_guess);
+
+        let provider_cfg = pricing.providers.get(&pricing_provider);
+        let rate = provider_cfg
+            .and_then(|p| p.models.get(&pricing_model))
+            .and_then(|schedule| schedule.latest());
+
+        let mut benchmark_input = stats.benchmark_input_usd_per_mtok;
+        let mut benchmark_output = stats.benchmark_output_usd_per_mtok;
+        if benchmark_input.is_none() || benchmark_output.is_none() {
+            if let Some(found) = benchmarks
+                .get(&model_guess)
+                .or_else(|| benchmarks.get(&slugify_model(&pricing_model)))
+            {
+                benchmark_input =
+                    benchmark_input.or(found.price_input_per_1m.and_then(decimal_from_f64));
+                benchmark_output =
+                    benchmark_output.or(found.price_output_per_1m.and_then(decimal_from_f64));
+            }
+        }
+
+        let pricing_input = rate.and_then(|r| decimal_from_f64(r.input_usd_per_mtok));
+        let pricing_output = rate.and_then(|r| decimal_from_f64(r.output_usd_per_mtok));
+        let delta = |price: Option<Decimal>, bench: Option<Decimal>| match (price, bench) {
+            (Some(price), Some(bench)) => Some(price - bench),
+            _ => None,
+        };
+
+        rows.push(LedgerRow {
+            ledger_row_id: idx as u64 + 1,
+            source_model: stats.source_model.clone(),
+            source_model_slug: stats.source_model_slug.clone(),
+            inferred_provider,
+            provider_mapping_rule: provider_rule,
+            provider_mapping_confidence: provider_confidence,
+            canonical_model_guess: model_guess,
+            model_mapping_rule: model_rule,
+            model_mapping_confidence: model_confidence,
+            pricing_subscription_usd_month: provider_cfg
+                .and_then(|p| decimal_from_f64(p.subscription_usd_month)),
+            pricing_provider,
+            pricing_model,
+            pricing_input_usd_per_mtok: pricing_input,
+            pricing_output_usd_per_mtok: pricing_output,
+            pricing_cache_write_usd_per_mtok: rate
+                .and_then(|r| r.cache_write_usd_per_mtok)
+                .and_then(decimal_from_f64),
+            pricing_cache_read_usd_per_mtok: rate
+                .and_then(|r| r.cache_read_usd_per_mtok)
+                .and_then(decimal_from_f64),
+            pricing_tool_input_usd_per_mtok: rate
+                .and_then(|r| r.tool_input_usd_per_mtok)
+                .and_then(decimal_from_f64),
+            pricing_tool_output_usd_per_mtok: rate
+                .and_then(|r| r.tool_output_usd_per_mtok)
+                .and_then(decimal_from_f64),
+            benchmark_input_usd_per_mtok: benchmark_input,
+            benchmark_output_usd_per_mtok: benchmark_output,
+            benchmark_rows_total: stats.benchmark_rows_total,
+            benchmark_rows_non_missing: stats.benchmark_rows_non_missing,
+            benchmark_rows_missing: stats.benchmark_rows_missing,
+            benchmark_prior_rows_total: stats.benchmark_prior_rows_total,
+            benchmark_prior_rows_non_missing: stats.benchmark_prior_rows_non_missing,
+            benchmark_prior_rows_missing: stats.benchmark_prior_rows_missing,
+            benchmark_distinct_total: stats.benchmark_distinct_total,
+            benchmark_distinct_non_missing: stats.benchmark_distinct_non_missing,
+            pricing_vs_benchmark_input_delta: delta(pricing_input, benchmark_input),
+            pricing_vs_benchmark_output_delta: delta(pricing_output, benchmark_output),
+        });
+    }
+    rows
+}
+
+pub fn priors_aggregation(rows: &[LedgerRow]) -> Vec<PriorsAggregationRow> {
+    let mut grouped: BTreeMap<(String, String), PriorsAggregationRow> = BTreeMap::new();
+    for row in rows {
+        let pricing_provider = if row.pricing_provider.is_empty() {
+            "unmapped".to_string()
+        } else {
+            row.pricing_provider.clone()
+        };
+        let entry = grouped
+            .entry((row.inferred_provider.clone(), pricing_provider.clone()))
+            .or_insert_with(|| PriorsAggregationRow {
+                inferred_provider: row.inferred_provider.clone(),
+                pricing_provider,
+                models_count: 0,
+                mapped_models_count: 0,
+                prior_rows_total: 0,
+                prior_rows_non_missing: 0,
+                prior_rows_missing: 0,
+            });
+        entry.models_count += 1;
+        if !row.pricing_model.is_empty() {
+            entry.mapped_models_count += 1;
+        }
+        entry.prior_rows_total += row.benchmark_prior_rows_total;
+        entry.prior_rows_non_missing += row.benchmark_prior_rows_non_missing;
+        entry.prior_rows_missing += row.benchmark_prior_rows_missing;
+    }
+    grouped.into_values().collect()
+}
+
+fn ledger_record(row: &LedgerRow) -> Vec<String> {
+    vec![
+        row.ledger_row_id.to_string(),
+        row.source_model.clone(),
+        row.source_model_slug.clone(),
+        row.inferred_provider.clone(),
+        row.provider_mapping_rule.clone(),
+        row.provider_mapping_confidence.to_string(),
+        row.canonical_model_guess.clone(),
+        row.model_mapping_rule.clone(),
+        row.model_mapping_confidence.to_string(),
+        row.pricing_provider.clone(),
+        row.pricing_model.clone(),
+        decimal_to_str(row.pricing_subscription_usd_month),
+        decimal_to_str(row.pricing_input_usd_per_mtok),
+        decimal_to_str(row.pricing_output_usd_per_mtok),
+        decimal_to_str(row.pricing_cache_write_usd_per_mtok),
+        decimal_to_str(row.pricing_cache_read_usd_per_mtok),
+        decimal_to_str(row.pricing_tool_input_usd_per_mtok),
+        decimal_to_str(row.pricing_tool_output_usd_per_mtok),
+        decimal_to_str(row.benchmark_input_usd_per_mtok),
+        decimal_to_str(row.benchmark_output_usd_per_mtok),
+        row.benchmark_rows_total.to_string(),
+        row.benchmark_rows_non_missing.to_string(),
+        row.benchmark_rows_missing.to_string(),
+        row.benchmark_prior_rows_total.to_string(),
+        row.benchmark_prior_rows_non_missing.to_string(),
+        row.benchmark_prior_rows_missing.to_string(),
+        row.benchmark_distinct_total.to_string(),
+        row.benchmark_distinct_non_missing.to_string(),
+        decimal_to_str(row.pricing_vs_benchmark_input_delta),
+        decimal_to_str(row.pricing_vs_benchmark_output_delta),
+    ]
+}
+
+fn csv_writer(path: &Path) -> Result<csv::Writer<File>> {
+    let file = File::create(path).with_context(|| format!("creating {:?}", path))?;
+    Ok(csv::WriterBuilder::new()
+        .terminator(csv::Terminator::CRLF)
+        .from_writer(file))
+}
+
+fn write_text(path: &Path, text: &str) -> Result<()> {
+    fs::write(path, text).with_context(|| format!("writing {:?}", path))
+}
+
+pub fn write_ledger_csv(path: &Path, rows: &[LedgerRow]) -> Result<()> {
+    let mut writer = csv_writer(path)?;
+    writer.write_record(LEDGER_HEADERS)?;
+    for row in rows {
+        writer.write_record(ledger_record(row))?;
+    }
+    writer
+        .flush()
+        .with_context(|| format!("writing {:?}", path))?;
+    Ok(())
+}
+
+fn sql_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn sql_text_or_null(value: &str) -> String {
+    if value.is_empty() {
+        "NULL".to_string()
+    } else {
+        sql_quote(value)
+    }
+}
+
+fn sql_number(value: Option<Decimal>) -> String {
+    match value {
+        None => "NULL".to_string(),
+        some => decimal_to_str(some),
+    }
+}
+
+pub fn ledger_schema_sql() -> String {
+    let mut lines = vec![
+        "-- Deterministic unified ledger schema generated by `tokenledger ledger build`"
+            .to_string(),
+        "DROP TABLE IF EXISTS unified_model_provider_ledger;".to_string(),
+        String::new(),
+        "CREATE TABLE unified_model_provider_ledger (".to_string(),
+    ];
+    let columns: Vec<String> = LEDGER_HEADERS
+        .iter()
+        .map(|column| {
+            let sql_type = match *column {
+                "ledger_row_id" => "INTEGER PRIMARY KEY",
+                "pricing_provider" | "pricing_model" => "TEXT",
+                "source_model"
+                | "source_model_slug"
+                | "inferred_provider"
+                | "provider_mapping_rule"
+                | "canonical_model_guess"
+                | "model_mapping_rule" => "TEXT NOT NULL",
+                c if c.starts_with("pricing_")
+                    || (c.starts_with("benchmark_") && c.ends_with("_mtok")) =>
+                {
+                    "NUMERIC"
+                }
+                _ => "INTEGER NOT NULL",
+            };
+            format!("  {} {}", column, sql_type)
+        })
+        .collect();
+    lines.push(columns.join(",\n"));
+    lines.extend(
+        [
+            ");",
+            "",
+            "DROP TABLE IF EXISTS benchmark_priors_aggregation;",
+            "",
+            "CREATE TABLE benchmark_priors_aggregation (",
+            "  aggregation_row_id INTEGER PRIMARY KEY,",
+            "  inferred_provider TEXT NOT NULL,",
+            "  pricing_provider TEXT NOT NULL,",
+            "  models_count INTEGER NOT NULL,",
+            "  mapped_models_count INTEGER NOT NULL,",
+            "  prior_rows_total INTEGER NOT NULL,",
+            "  prior_rows_non_missing INTEGER NOT NULL,",
+            "  prior_rows_missing INTEGER NOT NULL",
+            ");",
+        ]
+        .iter()
+        .map(|line| line.to_string()),
+    );
+    lines.join("\n") + "\n"
+}
+
+pub fn ledger_seed_sql(rows: &[LedgerRow], priors: &[PriorsAggregationRow]) -> String {
+    let mut lines = vec![
+        "-- Deterministic unified ledger seed generated by `tokenledger ledger build`".to_string(),
+        "INSERT INTO unified_model_provider_ledger (".to_string(),
+        format!("  {}", LEDGER_HEADERS.join(",\n  ")),
+        ") VALUES".to_string(),
+    ];
+    let tuples: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let values = [
+                row.ledger_row_id.to_string(),
+                sql_quote(&row.source_model),
+                sql_quote(&row.source_model_slug),
+                sql_quote(&row.inferred_provider),
+                sql_quote(&row.provider_mapping_rule),
+                row.provider_mapping_confidence.to_string(),
+                sql_quote(&row.canonical_model_guess),
+                sql_quote(&row.model_mapping_rule),
+                row.model_mapping_confidence.to_string(),
+                sql_text_or_null(&row.pricing_provider),
+                sql_text_or_null(&row.pricing_model),
+                sql_number(row.pricing_subscription_usd_month),
+                sql_number(row.pricing_input_usd_per_mtok),
+                sql_number(row.pricing_output_usd_per_mtok),
+                sql_number(row.pricing_cache_write_usd_per_mtok),
+                sql_number(row.pricing_cache_read_usd_per_mtok),
+                sql_number(row.pricing_tool_input_usd_per_mtok),
+                sql_number(row.pricing_tool_output_usd_per_mtok),
+                sql_number(row.benchmark_input_usd_per_mtok),
+                sql_number(row.benchmark_output_usd_per_mtok),
+                row.benchmark_rows_total.to_string(),
+                row.benchmark_rows_non_missing.to_string(),
+                row.benchmark_rows_missing.to_string(),
+                row.benchmark_prior_rows_total.to_string(),
+                row.benchmark_prior_rows_non_missing.to_string(),
+                row.benchmark_prior_rows_missing.to_string(),
+                row.benchmark_distinct_total.to_string(),
+                row.benchmark_distinct_non_missing.to_string(),
+                sql_number(row.pricing_vs_benchmark_input_delta),
+                sql_number(row.pricing_vs_benchmark_output_delta),
+            ];
+            format!("({})", values.join(", "))
+        })
+        .collect();
+    lines.push(tuples.join(",\n") + ";");
+
+    lines.extend(
+        [
+            "",
+            "INSERT INTO benchmark_priors_aggregation (",
+            "  aggregation_row_id,",
+            "  inferred_provider,",
+            "  pricing_provider,",
+            "  models_count,",
+            "  mapped_models_count,",
+            "  prior_rows_total,",
+            "  prior_rows_non_missing,",
+            "  prior_rows_missing",
+            ") VALUES",
+        ]
+        .iter()
+        .map(|line| line.to_string()),
+    );
+    let prior_tuples: Vec<String> = priors
+        .iter()
+        .enumerate()
+        .map(|(idx, row)| {
+            format!(
+                "({}, {}, {}, {}, {}, {}, {}, {})",
+                idx + 1,
+                sql_quote(&row.inferred_provider),
+                sql_quote(&row.pricing_provider),
+                row.models_count,
+                row.mapped_models_count,
+                row.prior_rows_total,
+                row.prior_rows_non_missing,
+                row.prior_rows_missing
+            )
+        })
+        .collect();
+    lines.push(prior_tuples.join(",\n") + ";");
+    lines.join("\n") + "\n"
+}
+
+// ---------------------------------------------------------------------------
+// Runtime metrics + Pareto view
+// ---------------------------------------------------------------------------
+
+fn value_f64(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().replace(',', "").parse().ok(),
+        _ => None,
+    }
+}
+
+fn first_f64(obj: &serde_json::Map<String, Value>, keys: &[&str]) -> Option<f64> {
+    keys.iter()
+        .filter_map(|key| value_f64(obj.get(*key)))
+        .find(|v| *v != 0.0)
+}
+
+/// Quality as a 0..1 ratio; percentages are scaled down and out-of-range values dropped
+pub fn normalize_quality(value: Option<f64>) -> Option<f64> {
+    match value? {
+        v if v < 0.0 => None,
+        v if v <= 1.0 => Some(v),
+        v if v <= 100.0 => Some(v / 100.0),
+        _ => None,
+    }
+}
+
+fn collect_runtime_candidates(value: &Value, source_path: &str, out: &mut Vec<RuntimeMetric>) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                collect_runtime_candidates(item, source_path, out);
+            }
+        }
+        Value::Object(obj) => {
+            let text = |keys: &[&str]| {
+                keys.iter()
+                    .filter_map(|key| obj.get(*key).and_then(Value::as_str))
+                    .find(|s| !s.is_empty())
+                    .map(str::to_string)
+            };
+            let provider = text(&["provider", "inferred_provider"]);
+            let model = text(&["model", "pricing_model", "canonical_model"]);
+            let latency = first_f64(
+                obj,
+                &[
+                    "latency_ms",
+                    "median_latency_ms",
+                    "p50_latency_ms",
+                    "p95_latency_ms",
+                ],
+            );
+            let quality = normalize_quality(first_f64(
+                obj,
+                &["quality_score", "success_rate", "win_rate", "accuracy"],
+            ));
+            if let (Some(provider), Some(model)) = (provider, model) {
+                if latency.is_some() || quality.is_some() {
+                    out.push(RuntimeMetric {
+                        provider: slugify_model(&provider),
+                        model: slugify_model(&model),
+                        latency_ms: latency,
+                        quality_score: quality,
+                        source_path: source_path.to_string(),
+                    });
+                }
+            }
+            for child in obj.values() {
+                collect_runtime_candidates(child, source_path, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn load_runtime_metrics(
+    paths: &[PathBuf],
+) -> Result<BTreeMap<(String, String), RuntimeMetric>> {
+    let mut out = BTreeMap::new();
+    for path in paths {
+        let text = fs::read_to_string(path).with_context(|| format!("reading {:?}", path))?;
+        let payload: Value =
+            serde_json::from_str(&text).with_context(|| format!("parsing {:?}", path))?;
+        let mut found = Vec::new();
+        collect_runtime_candidates(&payload, &path.display().to_string(), &mut found);
+        for metric in found {
+            out.entry((metric.provider.clone(), metric.model.clone()))
+                .or_insert(metric);
+        }
+    }
+    Ok(out)
+}
+
+/// Snapshot metrics win; benchmark store entries only fill (provider, model) gaps.
+fn add_benchmark_runtime_metrics(
+    benchmarks: &BTreeMap<String, BenchmarkData>,
+    source: &Path,
+    out: &mut BTreeMap<(String, String), RuntimeMetric>,
+) {
+    for entry in benchmarks.values() {
+        let latency = entry.latency_e2e_ms.or(entry.latency_ttft_ms);
+        let quality = normalize_quality(entry.intelligence_index);
+        if latency.is_none() && quality.is_none() {
+            continue;
+        }
+        let provider = slugify_model(entry.provider.as_deref().unwrap_or("unknown"));
+        let model = slugify_model(&entry.model_id);
+        out.entry((provider.clone(), model.clone()))
+            .or_insert_with(|| RuntimeMetric {
+                provider,
+                model,
+                latency_ms: latency,
+                quality_score: quality,
+                source_path: source.display().to_string(),
+            });
+    }
+}
+
+fn round6(value: f64) -> f64 {
+    (value * 1_000_000.0).round() / 1_000_000.0
+}
+
+/// Render floats the way the previous Python tooling did (`3.0`, not `3`)
+fn float_str(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e16 {
+        format!("{:.1}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn opt_float_str(value: Option<f64>) -> String {
+    value.map(float_str).unwrap_or_default()
+}
+
+fn decimal_f64(value: Option<Decimal>) -> Option<f64> {
+    value.and_then(|v| decimal_to_str(Some(v)).parse().ok())
+}
+
+pub fn build_pareto_rows(
+    rows: &[LedgerRow],
+    runtime: &BTreeMap<(String, String), RuntimeMetric>,
+) -> Vec<ParetoRow> {
+    let mut scored: Vec<ParetoRow> = rows
+        .iter()
+        .map(|row| {
+            let provider = slugify_model(if row.pricing_provider.is_empty() {
+                &row.inferred_provider
+            } else {
+                &row.pricing_provider
+            });
+            let model = slugify_model(if row.pricing_model.is_empty() {
+                &row.canonical_model_guess
+            } else {
+                &row.pricing_model
+            });
+            let metric = runtime.get(&(provider.clone(), model.clone()));
+
+            let costs: Vec<f64> = [
+                decimal_f64(row.pricing_input_usd_per_mtok),
+                decimal_f64(row.pricing_output_usd_per_mtok),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|v| *v >= 0.0)
+            .collect();
+            let (cost_component, avg_cost) = if costs.is_empty() {
+                (0.0, None)
+            } else {
+                let avg = costs.iter().sum::<f64>() / costs.len() as f64;
+                (1.0 / (1.0 + avg), Some(avg))
+            };
+
+            let latency = metric.and_then(|m| m.latency_ms);
+            let latency_component = match latency {
+                Some(ms) if ms >= 0.0 => 1.0 / (1.0 + ms / 1000.0),
+                _ => 0.0,
+            };
+            let bench_quality = if row.benchmark_prior_rows_total > 0 {
+                Some(
+                    (row.benchmark_prior_rows_non_missing as f64
+                        / row.benchmark_prior_rows_total as f64)
+                        .clamp(0.0, 1.0),
+                )
+            } else {
+                None
+            };
+            let runtime_quality = metric.and_then(|m| m.quality_score);
+            let qualities: Vec<f64> = [runtime_quality, bench_quality]
+                .into_iter()
+                .flatten()
+                .collect();
+            let quality_component = if qualities.is_empty() {
+                0.0
+            } else {
+                qualities.iter().sum::<f64>() / qualities.len() as f64
+            };
+
+            let score = 0.50 * quality_component + 0.30 * cost_component + 0.20 * latency_component;
+            ParetoRow {
+                ledger_row_id: row.ledger_row_id,
+                provider,
+                model,
+                pareto_score: round6(score * 100.0),
+                quality_component: round6(quality_component),
+                cost_component: round6(cost_component),
+                latency_component: round6(latency_component),
+                blended_cost_usd_per_mtok: avg_cost.map(round6),
+                runtime_latency_ms: latency.map(round6),
+                runtime_quality_score: runtime_quality.map(round6),
+                benchmark_quality_score: bench_quality.map(round6),
+                runtime_source_path: metric.map(|m| m.source_path.clone()).unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    scored.sort_by(|a, b| {
+        b.pareto_score
+            .total_cmp(&a.pareto_score)
+            .then_with(|| a.provider.cmp(&b.provider))
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    scored
+}
+
+pub fn write_runtime_metrics_csv(
+    path: &Path,
+    metrics: &BTreeMap<(String, String), RuntimeMetric>,
+) -> Result<()> {
+    let mut writer = csv_writer(path)?;
+    writer.write_record([
+        "provider",
+        "model",
+        "latency_ms",
+        "quality_score",
+        "source_path",
+    ])?;
+    for metric in metrics.values() {
+        writer.write_record([
+            metric.provider.clone(),
+            metric.model.clone(),
+            metric
+                .latency_ms
+                .map(|v| format!("{:.6}", v))
+                .unwrap_or_default(),
+            metric
+                .quality_score
+                .map(|v| format!("{:.6}", v))
+                .unwrap_or_default(),
+            metric.source_path.clone(),
+        ])?;
+    }
+    writer
+        .flush()
+        .with_context(|| format!("writing {:?}", path))?;
+    Ok(())
+}
+
+pub fn write_pareto_csv(path: &Path, rows: &[ParetoRow]) -> Result<()> {
+    let mut writer = csv_writer(path)?;
+    writer.write_record(PARETO_HEADERS)?;
+    for row in rows {
+        writer.write_record([
+            row.ledger_row_id.to_string(),
+            row.provider.clone(),
+            row.model.clone(),
+            float_str(row.pareto_score),
+            float_str(row.quality_component),
+            float_str(row.cost_component),
+            float_str(row.latency_component),
+            opt_float_str(row.blended_cost_usd_per_mtok),
+            opt_float_str(row.runtime_latency_ms),
+            opt_float_str(row.runtime_quality_score),
+            opt_float_str(row.benchmark_quality_score),
+            row.runtime_source_path.clone(),
+        ])?;
+    }
+    writer
+        .flush()
+        .with_context(|| format!("writing {:?}", path))?;
+    Ok(())
+}
+
+const PARETO_VIEW_SQL_TEXT: &str = r#"-- Runtime metrics + Pareto scoring view generated by `tokenledger ledger build`
+-- Seed runtime metrics from ledger/cliproxyapi_runtime_metrics_snapshot.csv as needed.
+
+DROP TABLE IF EXISTS cliproxyapi_runtime_metrics_snapshot;
+CREATE TABLE cliproxyapi_runtime_metrics_snapshot (
+  provider TEXT NOT NULL,
+  model TEXT NOT NULL,
+  latency_ms NUMERIC,
+  quality_score NUMERIC,
+  source_path TEXT,
+  PRIMARY KEY (provider, model)
+);
+
+DROP VIEW IF EXISTS unified_model_provider_pareto_view;
+CREATE VIEW unified_model_provider_pareto_view AS
+SELECT
+  l.ledger_row_id,
+  COALESCE(NULLIF(l.pricing_provider, ''), l.inferred_provider) AS provider,
+  COALESCE(NULLIF(l.pricing_model, ''), l.canonical_model_guess) AS model,
+  l.pricing_input_usd_per_mtok,
+  l.pricing_output_usd_per_mtok,
+  m.latency_ms AS runtime_latency_ms,
+  m.quality_score AS runtime_quality_score,
+  CASE
+    WHEN l.benchmark_prior_rows_total > 0
+      THEN CAST(l.benchmark_prior_rows_non_missing AS NUMERIC) / CAST(l.benchmark_prior_rows_total AS NUMERIC)
+    ELSE NULL
+  END AS benchmark_quality_score,
+  (
+    100.0 * (
+      0.50 * COALESCE(
+        (
+          COALESCE(m.quality_score, 0.0) +
+          COALESCE(
+            CASE
+              WHEN l.benchmark_prior_rows_total > 0
+                THEN CAST(l.benchmark_prior_rows_non_missing AS NUMERIC) / CAST(l.benchmark_prior_rows_total AS NUMERIC)
+              ELSE NULL
+            END,
+            0.0
+          )
+        ) /
+        CASE
+          WHEN m.quality_score IS NOT NULL
+               AND l.benchmark_prior_rows_total > 0 THEN 2.0
+          WHEN m.quality_score IS NOT NULL
+               OR l.benchmark_prior_rows_total > 0 THEN 1.0
+          ELSE 1.0
+        END,
+        0.0
+      )
+      + 0.30 * (
+        CASE
+          WHEN COALESCE(l.pricing_input_usd_per_mtok, l.pricing_output_usd_per_mtok) IS NULL THEN 0.0
+          ELSE 1.0 / (
+            1.0 + (
+              COALESCE(l.pricing_input_usd_per_mtok, 0.0) +
+              COALESCE(l.pricing_output_usd_per_mtok, 0.0)
+            ) /
+            CASE
+              WHEN l.pricing_input_usd_per_mtok IS NOT NULL
+                   AND l.pricing_output_usd_per_mtok IS NOT NULL THEN 2.0
+              ELSE 1.0
+            END
+          )
+        END
+      )
+      + 0.20 * (
+        CASE
+          WHEN m.latency_ms IS NULL THEN 0.0
+          ELSE 1.0 / (1.0 + (m.latency_ms / 1000.0))
+        END
+      )
+    )
+  ) AS pareto_score
+FROM unified_model_provider_ledger l
+LEFT JOIN cliproxyapi_runtime_metrics_snapshot m
+  ON m.provider = COALESCE(NULLIF(l.pricing_provider, ''), l.inferred_provider)
+ AND m.model = COALESCE(NULLIF(l.pricing_model, ''), l.canonical_model_guess);
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ModelRate, ProviderPricing};
+
+    fn pricing() -> PricingBook {
+        let claude = ProviderPricing {
+            currency: None,
+            subscription_usd_month: 30.0,
+            models: HashMap::from([(
+                "claude-sonnet-4-5".to_string(),
+                ModelRate {
+                    input_usd_per_mtok: 3.0,
+                    output_usd_per_mtok: 15.0,
+                    cache_write_usd_per_mtok: Some(3.75),
+                    cache_write_1h_usd_per_mtok: None,
+                    cache_read_usd_per_mtok: Some(0.3),
+                    tool_input_usd_per_mtok: None,
+                    tool_output_usd_per_mtok: None,
+                    effective_from: None,
+                    effective_to: None,
+                    context_tiers: Vec::new(),
+                }
+                .into(),
+            )]),
+            model_aliases: HashMap::new(),
+            service_tiers: HashMap::new(),
+            allocation: None,
+            seats: Vec::new(),
+            plan: None,
+            plans: HashMap::new(),
+            contract: None,
+        };
+        let codex = ProviderPricing {
+            currency: None,
+            subscription_usd_month: 20.0,
+            models: HashMap::from([(
+                "gpt-5".to_string(),
+                ModelRate {
+                    input_usd_per_mtok: 1.25,
+                    output_usd_per_mtok: 10.0,
+                    cache_write_usd_per_mtok: None,
+                    cache_write_1h_usd_per_mtok: None,
+                    cache_read_usd_per_mtok: None,
+                    tool_input_usd_per_mtok: None,
+                    tool_output_usd_per_mtok: None,
+                    effective_from: None,
+                    effective_to: None,
+                    context_tiers: Vec::new(),
+                }
+                .into(),
+            )]),
+            model_aliases: HashMap::from([("gpt-5-latest".to_string(), "gpt-5".to_string())]),
+            service_tiers: HashMap::new(),
+            allocation: None,
+            seats: Vec::new(),
+            plan: None,
+            plans: HashMap::new(),
+            contract: None,
+        };
+        PricingBook {
+            providers: HashMap::from([
+                ("claude".to_string(), claude),
+                ("codex".to_string(), codex),
+            ]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        }
+    }
+
+    fn stats(model: &str, input: Option<&str>) -> ModelStats {
+        ModelStats {
+            source_model: model.to_string(),
+            source_model_slug: slugify_model(model),
+            benchmark_rows_total: 4,
+            benchmark_rows_non_missing: 3,
+            benchmark_rows_missing: 1,
+            benchmark_prior_rows_total: 2,
+            benchmark_prior_rows_non_missing: 1,
+            benchmark_prior_rows_missing: 1,
+            benchmark_distinct_total: 4,
+            benchmark_distinct_non_missing: 3,
+            benchmark_input_usd_per_mtok: input.map(|v| Decimal::from_str(v).unwrap()),
+            benchmark_output_usd_per_mtok: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_price_decimal() {
+        assert_eq!(
+            parse_price_decimal("$2.00"),
+            Some(Decimal::from_str("2.00").unwrap())
+        );
+        assert_eq!(
+            parse_price_decimal("$1,250.5 (>200k)"),
+            Some(Decimal::from_str("1250.5").unwrap())
+        );
+        assert_eq!(parse_price_decimal("—"), None);
+        assert_eq!(parse_price_decimal("n/a"), None);
+        assert_eq!(decimal_to_str(parse_price_decimal("$2.00")), "2");
+        assert_eq!(
+            decimal_to_str(Some(Decimal::from_str("0.30").unwrap())),
+            "0.3"
+        );
+    }
+
+    #[test]
+    fn test_build_ledger_rows_mapping_rules_and_deltas() {
+        let stats_by_model = BTreeMap::from([
+            ("GPT-5 mini".to_string(), stats("GPT-5 mini", None)),
+            (
+                "Claude Sonnet 4.5".to_string(),
+                stats("Claude Sonnet 4.5", Some("2.5")),
+            ),
+            ("Kimi K2".to_string(), stats("Kimi K2", None)),
+        ]);
+        let rows = build_ledger_rows(&stats_by_model, &pricing(), &BTreeMap::new());
+        let summary: Vec<(&str, &str, u8, &str)> = rows
+            .iter()
+            .map(|r| {
+                (
+                    r.source_model_slug.as_str(),
+                    r.model_mapping_rule.as_str(),
+                    r.model_mapping_confidence,
+                    r.pricing_model.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "claude-sonnet-4-5",
+                    "pricing:model_exact",
+                    100,
+                    "claude-sonnet-4-5"
+                ),
+                ("gpt-5-mini", "heuristic:gpt-5-family", 78, "gpt-5"),
+                ("kimi-k2", "unmapped", 0, ""),
+            ]
+        );
+        assert_eq!(rows[0].ledger_row_id, 1);
+        assert_eq!(
+            decimal_to_str(rows[0].pricing_vs_benchmark_input_delta),
+            "0.5"
+        );
+        assert_eq!(
+            decimal_to_str(rows[0].pricing_cache_read_usd_per_mtok),
+            "0.3"
+        );
+        assert_eq!(rows[2].pricing_subscription_usd_month, None);
+
+        let priors = priors_aggregation(&rows);
+        assert_eq!(priors.len(), 3);
+        assert_eq!(priors[2].inferred_provider, "kimi");
+        assert_eq!(priors[2].pricing_provider, "unmapped");
+        assert_eq!(priors[2].mapped_models_count, 0);
+    }
+
+    #[test]
+    fn test_benchmark_store_fills_missing_benchmark_prices() {
+        let stats_by_model = BTreeMap::from([("GPT-5".to_string(), stats("GPT-5", None))]);
+        let benchmarks = BTreeMap::from([(
+            "gpt-5".to_string(),
+            BenchmarkData {
+                model_id: "gpt-5".to_string(),
+                price_input_per_1m: Some(1.0),
+                price_output_per_1m: Some(8.0),
+                ..BenchmarkData::default()
+            },
+        )]);
+        let rows = build_ledger_rows(&stats_by_model, &pricing(), &benchmarks);
+        assert_eq!(rows[0].model_mapping_rule, "pricing:model_exact");
+        assert_eq!(decimal_to_str(rows[0].benchmark_output_usd_per_mtok), "8");
+        assert_eq!(
+            decimal_to_str(rows[0].pricing_vs_benchmark_input_delta),
+            "0.25"
+        );
+        assert_eq!(
+            decimal_to_str(rows[0].pricing_vs_benchmark_output_delta),
+            "2"
+        );
+    }
+
+    #[test]
+    fn test_pareto_rows_blend_runtime_metrics() {
+        let stats_by_model = BTreeMap::from([
+            ("GPT-5".to_string(), stats("GPT-5", None)),
+            ("Kimi K2".to_string(), stats("Kimi K2", None)),
+        ]);
+        let rows = build_ledger_rows(&stats_by_model, &pricing(), &BTreeMap::new());
+        let runtime = BTreeMap::from([(
+            ("codex".to_string(), "gpt-5".to_string()),
+            RuntimeMetric {
+                provider: "codex".to_string(),
+                model: "gpt-5".to_string(),
+                latency_ms: Some(1000.0),
+                quality_score: Some(0.9),
+                source_path: "snapshot.json".to_string(),
+            },
+        )]);
+        let scored = build_pareto_rows(&rows, &runtime);
+        assert_eq!(scored[0].model, "gpt-5");
+        assert_eq!(scored[0].quality_component, 0.7);
+        assert_eq!(scored[0].latency_component, 0.5);
+        assert_eq!(scored[0].blended_cost_usd_per_mtok, Some(5.625));
+        assert_eq!(scored[1].model, "kimi-k2");
+        assert_eq!(scored[1].pareto_score, 25.0);
+        assert_eq!(float_str(scored[1].cost_component), "0.0");
+    }
+
+    #[test]
+    fn test_seed_sql_uses_null_for_unmapped_pricing() {
+        let stats_by_model = BTreeMap::from([("Kimi K2".to_string(), stats("Kimi K2", None))]);
+        let rows = build_ledger_rows(&stats_by_model, &pricing(), &BTreeMap::new());
+        let sql = ledger_seed_sql(&rows, &priors_aggregation(&rows));
+        assert!(sql.contains(
+            "(1, 'Kimi K2', 'kimi-k2', 'kimi', 'prefix:kimi', 95, 'kimi-k2', 'unmapped', 0, NULL, NULL,"
+        ));
+        assert!(sql.contains("(1, 'kimi', 'unmapped', 1, 0, 2, 1, 1);"));
+        assert!(ledger_schema_sql().contains("  pricing_vs_benchmark_output_delta NUMERIC\n);"));
+    }
+}
@@ -1,15 +1,35 @@
 #![allow(non_snake_case)]
 
+pub mod allocation;
 pub mod analytics;
+pub mod anomalies;
 pub mod bench;
 pub mod benchmarks;
+pub mod budgets;
 pub mod cache;
 pub mod cli;
+pub mod compare;
+pub mod contracts;
 pub mod cost;
+pub mod enrich;
+pub mod export;
+pub mod forecast;
 pub mod format;
+pub mod fx;
+pub mod html;
 pub mod ingest;
+pub mod ledger;
 pub mod models;
+pub mod money;
 pub mod orchestrate;
+pub mod period;
+pub mod plans;
 pub mod pricing;
+pub mod prompt_cache;
 pub mod routing;
+pub mod sessions;
+pub mod stream;
+pub mod tui;
 pub mod utils;
+pub mod whatif;
+pub mod windows;
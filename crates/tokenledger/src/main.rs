@@ -1,23 +1,47 @@
 use anyhow::Result;
 use clap::Parser;
 
-use TokenLedgerRs::analytics::{run_coverage, run_daily, run_monthly};
+use TokenLedgerRs::analytics::{run_coverage, run_daily, run_monthly, run_series};
+use TokenLedgerRs::anomalies::run_anomalies;
 use TokenLedgerRs::bench::run_bench;
 use TokenLedgerRs::benchmarks::run_benchmarks;
+use TokenLedgerRs::budgets::run_budget;
 use TokenLedgerRs::cli::{Cli, Command};
+use TokenLedgerRs::compare::run_compare;
+use TokenLedgerRs::enrich::run_price;
+use TokenLedgerRs::forecast::run_forecast;
 use TokenLedgerRs::ingest::run_ingest;
+use TokenLedgerRs::ledger::run_ledger;
 use TokenLedgerRs::orchestrate::run_orchestrate;
+use TokenLedgerRs::plans::run_plan_analysis;
 use TokenLedgerRs::pricing::{
     run_pricing_apply, run_pricing_audit, run_pricing_check, run_pricing_lint,
     run_pricing_reconcile,
 };
+use TokenLedgerRs::prompt_cache::run_cache;
+use TokenLedgerRs::sessions::run_sessions;
+use TokenLedgerRs::tui::run_tui;
+use TokenLedgerRs::whatif::run_whatif;
+use TokenLedgerRs::windows::run_windows;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     match cli.command {
         Command::Monthly(args) => run_monthly(args),
         Command::Daily(args) => run_daily(args),
+        Command::Series(args) => run_series(args),
         Command::Coverage(args) => run_coverage(args),
+        Command::PlanAnalysis(args) => run_plan_analysis(args),
+        Command::Windows(args) => run_windows(args),
+        Command::Price(args) => run_price(args),
+        Command::Whatif(args) => run_whatif(args),
+        Command::Cache(args) => run_cache(args),
+        Command::Sessions(args) => run_sessions(args),
+        Command::Forecast(args) => run_forecast(args),
+        Command::Anomalies(args) => run_anomalies(args),
+        Command::Compare(args) => run_compare(args),
+        Command::Budget(args) => run_budget(args),
+        Command::Tui(args) => run_tui(args),
         Command::PricingCheck(args) => run_pricing_check(args),
         Command::PricingApply(args) => run_pricing_apply(args),
         Command::PricingReconcile(args) => run_pricing_reconcile(args),
@@ -27,5 +51,6 @@ fn main() -> Result<()> {
         Command::Bench(args) => run_bench(args),
         Command::Orchestrate(args) => run_orchestrate(args),
         Command::Benchmarks(args) => run_benchmarks(args),
+        Command::Ledger(args) => run_ledger(args),
     }
 }
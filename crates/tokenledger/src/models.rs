@@ -1,9 +1,12 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::Write;
 
-use crate::cli::UiSnapshotMode;
+use crate::cli::{
+    AllocationStrategy, BudgetStatus, ForecastMethod, Granularity, GroupDimension, UiSnapshotMode,
+};
+use crate::money::Micros;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenUsage {
@@ -11,9 +14,12 @@ pub struct TokenUsage {
     pub input_tokens: u64,
     #[serde(default)]
     pub output_tokens: u64,
+    // Cache writes with the default (5-minute) TTL; 1-hour writes are tracked separately.
     #[serde(default)]
     pub cache_write_tokens: u64,
     #[serde(default)]
+    pub cache_write_1h_tokens: u64,
+    #[serde(default)]
     pub cache_read_tokens: u64,
     #[serde(default)]
     pub tool_input_tokens: u64,
@@ -26,10 +32,19 @@ impl TokenUsage {
         self.input_tokens
             + self.output_tokens
             + self.cache_write_tokens
+            + self.cache_write_1h_tokens
             + self.cache_read_tokens
             + self.tool_input_tokens
             + self.tool_output_tokens
     }
+
+    // Tokens that count toward a request's context size for tiered pricing.
+    pub fn prompt_tokens(&self) -> u64 {
+        self.input_tokens
+            + self.cache_write_tokens
+            + self.cache_write_1h_tokens
+            + self.cache_read_tokens
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,29 +54,430 @@ pub struct UsageEvent {
     pub session_id: String,
     pub timestamp: DateTime<Utc>,
     pub usage: TokenUsage,
+    // Provider billing tier (e.g. "batch", "flex", "priority"); None means standard pricing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<String>,
+    // Free-form labels such as "project:api" or "team:infra", matched by budget scopes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl UsageEvent {
+    pub fn service_tier_name(&self) -> &str {
+        self.service_tier
+            .as_deref()
+            .unwrap_or(STANDARD_SERVICE_TIER)
+    }
 }
 
+pub const STANDARD_SERVICE_TIER: &str = "standard";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ModelRate {
     pub input_usd_per_mtok: f64,
     pub output_usd_per_mtok: f64,
     #[serde(default)]
     pub cache_write_usd_per_mtok: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_1h_usd_per_mtok: Option<f64>,
     #[serde(default)]
     pub cache_read_usd_per_mtok: Option<f64>,
     #[serde(default)]
     pub tool_input_usd_per_mtok: Option<f64>,
     #[serde(default)]
     pub tool_output_usd_per_mtok: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_from: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_to: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_tiers: Vec<ContextTier>,
+}
+
+impl ModelRate {
+    pub fn covers(&self, at: DateTime<Utc>) -> bool {
+        self.effective_from.is_none_or(|from| from <= at)
+            && self.effective_to.is_none_or(|to| at < to)
+    }
+
+    // Highest tier whose threshold the request's prompt exceeds, if any.
+    pub fn context_tier(&self, prompt_tokens: u64) -> Option<&ContextTier> {
+        self.context_tiers
+            .iter()
+            .filter(|tier| prompt_tokens > tier.above_prompt_tokens)
+            .max_by_key(|tier| tier.above_prompt_tokens)
+    }
+
+    pub fn context_tier_issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        let mut thresholds = HashSet::new();
+        for tier in &self.context_tiers {
+            let label = format!("tier above {} prompt tokens", tier.above_prompt_tokens);
+            if tier.above_prompt_tokens == 0 {
+                issues.push(format!("{label}: threshold must be greater than zero"));
+            }
+            if !thresholds.insert(tier.above_prompt_tokens) {
+                issues.push(format!("{label}: duplicate threshold"));
+            }
+            if tier.input_usd_per_mtok < self.input_usd_per_mtok
+                || tier.output_usd_per_mtok < self.output_usd_per_mtok
+            {
+                issues.push(format!("{label}: rate is below the base rate"));
+            }
+        }
+        issues
+    }
+}
+
+// Long-context rates that replace the base rates once a request's prompt (input plus cache
+// read/write tokens) exceeds `above_prompt_tokens`. Unset cache and tool rates fall back to the
+// base rate, or when the base has none either, to the tier's input/output rates.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContextTier {
+    pub above_prompt_tokens: u64,
+    pub input_usd_per_mtok: f64,
+    pub output_usd_per_mtok: f64,
+    #[serde(default)]
+    pub cache_write_usd_per_mtok: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_1h_usd_per_mtok: Option<f64>,
+    #[serde(default)]
+    pub cache_read_usd_per_mtok: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_input_usd_per_mtok: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_output_usd_per_mtok: Option<f64>,
+}
+
+// A model's rate periods, sorted by effective_from (undated first). Serialized as a bare
+// rate object when there is a single undated period so legacy pricing files round-trip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelRateSchedule {
+    periods: Vec<ModelRate>,
+}
+
+impl ModelRateSchedule {
+    pub fn new(mut periods: Vec<ModelRate>) -> Self {
+        periods.sort_by_key(|rate| rate.effective_from);
+        Self { periods }
+    }
+
+    pub fn periods(&self) -> &[ModelRate] {
+        &self.periods
+    }
+
+    pub fn rate_at(&self, at: DateTime<Utc>) -> Option<&ModelRate> {
+        self.periods.iter().rev().find(|rate| rate.covers(at))
+    }
+
+    pub fn latest(&self) -> Option<&ModelRate> {
+        self.periods.last()
+    }
+
+    pub fn append_period(
+        &mut self,
+        mut rate: ModelRate,
+        from: DateTime<Utc>,
+    ) -> std::result::Result<(), String> {
+        if let Some(last) = self.periods.last_mut() {
+            if last.effective_from.is_some_and(|start| start >= from) {
+                return Err(format!(
+                    "new period starting {} does not start after the latest period ({})",
+                    from.to_rfc3339(),
+                    last.effective_from
+                        .map(|start| start.to_rfc3339())
+                        .unwrap_or_default()
+                ));
+            }
+            if last.effective_to.is_none_or(|to| to > from) {
+                last.effective_to = Some(from);
+            }
+        }
+        rate.effective_from = Some(from);
+        self.periods.push(rate);
+        Ok(())
+    }
+
+    pub fn period_issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        let fmt = |at: Option<DateTime<Utc>>| {
+            at.map(|at| at.to_rfc3339())
+                .unwrap_or_else(|| "open".to_string())
+        };
+        for rate in &self.periods {
+            if let (Some(from), Some(to)) = (rate.effective_from, rate.effective_to) {
+                if to <= from {
+                    issues.push(format!(
+                        "effective_to {} is not after effective_from {}",
+                        to.to_rfc3339(),
+                        from.to_rfc3339()
+                    ));
+                }
+            }
+        }
+        for pair in self.periods.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            match (prev.effective_to, next.effective_from) {
+                (_, None) => {
+                    issues.push("overlap: multiple periods without effective_from".to_string())
+                }
+                (None, Some(start)) => issues.push(format!(
+                    "overlap: period from {} has no effective_to but a later period starts {}",
+                    fmt(prev.effective_from),
+                    start.to_rfc3339()
+                )),
+                (Some(end), Some(start)) if end > start => issues.push(format!(
+                    "overlap: period from {} ends {} after next period starts {}",
+                    fmt(prev.effective_from),
+                    end.to_rfc3339(),
+                    start.to_rfc3339()
+                )),
+                (Some(end), Some(start)) if end < start => issues.push(format!(
+                    "gap: no rate between {} and {}",
+                    end.to_rfc3339(),
+                    start.to_rfc3339()
+                )),
+                _ => {}
+            }
+        }
+        issues
+    }
+}
+
+impl From<ModelRate> for ModelRateSchedule {
+    fn from(rate: ModelRate) -> Self {
+        Self::new(vec![rate])
+    }
+}
+
+impl Serialize for ModelRateSchedule {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match self.periods.as_slice() {
+            [single] if single.effective_from.is_none() && single.effective_to.is_none() => {
+                single.serialize(serializer)
+            }
+            periods => periods.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ModelRateSchedule {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error;
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let periods = if value.is_array() {
+            Vec::<ModelRate>::deserialize(value).map_err(D::Error::custom)?
+        } else {
+            vec![ModelRate::deserialize(value).map_err(D::Error::custom)?]
+        };
+        if periods.is_empty() {
+            return Err(D::Error::custom("model rate list must not be empty"));
+        }
+        Ok(Self::new(periods))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderPricing {
+    // ISO 4217 code the provider's rates and subscriptions are denominated in; None means USD.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
     #[serde(default)]
     pub subscription_usd_month: f64,
-    pub models: HashMap<String, ModelRate>,
+    pub models: HashMap<String, ModelRateSchedule>,
     #[serde(default)]
     pub model_aliases: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub service_tiers: HashMap<String, ServiceTierPricing>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocation: Option<AllocationStrategy>,
+    // When empty, the provider has one seat at `subscription_usd_month` (or its `plan` price)
+    // active all month.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seats: Vec<SubscriptionSeat>,
+    // Plan the provider-level subscription (and seats without their own `plan`) is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub plans: HashMap<String, SubscriptionPlan>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<ProviderContract>,
+}
+
+impl ProviderPricing {
+    pub fn currency(&self) -> &str {
+        self.currency.as_deref().unwrap_or(crate::fx::USD)
+    }
+
+    pub fn plan_for(&self, seat: Option<&SubscriptionSeat>) -> Option<(&str, &SubscriptionPlan)> {
+        let name = seat
+            .and_then(|seat| seat.plan.as_deref())
+            .or(self.plan.as_deref())?;
+        self.plans
+            .get_key_value(name)
+            .map(|(name, plan)| (name.as_str(), plan))
+    }
+
+    // Monthly price of one seat: the seat's own price, then its own plan's, then the provider
+    // subscription, then the provider plan's.
+    pub fn seat_usd_month(&self, seat: Option<&SubscriptionSeat>) -> f64 {
+        if let Some(usd) = seat.and_then(|seat| seat.usd_month) {
+            return usd;
+        }
+        let seat_plan = seat
+            .and_then(|seat| seat.plan.as_deref())
+            .and_then(|name| self.plans.get(name));
+        if let Some(plan) = seat_plan {
+            return plan.usd_month;
+        }
+        if self.subscription_usd_month > 0.0 {
+            return self.subscription_usd_month;
+        }
+        self.plan_for(None)
+            .map(|(_, plan)| plan.usd_month)
+            .unwrap_or(0.0)
+    }
+}
+
+// A subscription seat (or `count` identical seats), billed per calendar month and prorated by
+// the days between `start` and `end` (both inclusive) that fall inside the month.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubscriptionSeat {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    // Defaults to the seat plan's price, then the provider's `subscription_usd_month`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usd_month: Option<f64>,
+    #[serde(default = "default_seat_count")]
+    pub count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<NaiveDate>,
+    // Overrides the provider's `plan`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+}
+
+impl SubscriptionSeat {
+    pub fn is_active_on(&self, day: NaiveDate) -> bool {
+        self.start.is_none_or(|start| start <= day) && self.end.is_none_or(|end| day <= end)
+    }
+}
+
+fn default_seat_count() -> u32 {
+    1
+}
+
+// Negotiated enterprise terms, in the provider's `currency`: a discount off list variable cost,
+// prepaid credits that pay for discounted usage, and a minimum monthly spend.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProviderContract {
+    // Percent off list variable cost for models without their own `model_discounts` entry.
+    #[serde(default)]
+    pub discount_pct: f64,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_discounts: HashMap<String, f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub credits: Vec<ContractCredit>,
+    // Discounted variable spend billed at minimum each calendar month.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_usd_month: Option<f64>,
+}
+
+impl ProviderContract {
+    pub fn discount_pct_for(&self, model: &str) -> f64 {
+        self.model_discounts
+            .get(model)
+            .copied()
+            .unwrap_or(self.discount_pct)
+    }
+}
+
+// Prepaid credit usable from `granted` through `expires` (both inclusive); earlier-expiring
+// credits burn first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContractCredit {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub usd: f64,
+    pub granted: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<NaiveDate>,
+}
+
+impl ContractCredit {
+    pub fn is_active_on(&self, day: NaiveDate) -> bool {
+        self.granted <= day && self.expires.is_none_or(|expires| day <= expires)
+    }
+}
+
+// A flat-rate subscription plan, priced per seat per month, with an optional monthly token quota
+// per seat (`None` is unlimited) and a rule for usage beyond it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubscriptionPlan {
+    pub usd_month: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub included_tokens: Option<u64>,
+    #[serde(default)]
+    pub overage: PlanOverage,
+    // Required when `overage` is `per_mtok`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overage_usd_per_mtok: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<UsageWindow>,
+}
+
+// A rate limit enforced over `hours` of usage.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UsageWindow {
+    pub name: String,
+    pub hours: u32,
+    #[serde(default)]
+    pub kind: UsageWindowKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageWindowKind {
+    /// Starts with the first request after the previous block expired and resets `hours` later.
+    #[default]
+    Block,
+    /// Always covers the trailing `hours`; usage drops out as it ages.
+    Rolling,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanOverage {
+    /// Usage past the quota is rate-limited rather than billed.
+    #[default]
+    Blocked,
+    /// Usage past the quota is billed at the provider's API list rates.
+    ListRates,
+    /// Usage past the quota is billed at `overage_usd_per_mtok`.
+    PerMtok,
+}
+
+// Pricing for a non-standard service tier: explicit per-model rates win, otherwise the
+// model's base rate is scaled by `multiplier`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceTierPricing {
+    #[serde(default = "default_service_tier_multiplier")]
+    pub multiplier: f64,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub models: HashMap<String, ModelRateSchedule>,
+}
+
+fn default_service_tier_multiplier() -> f64 {
+    1.0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,6 +535,9 @@ pub struct PricingApplySummary {
     pub models_added: usize,
     pub aliases_added: usize,
     pub models_skipped_existing: usize,
+    pub periods_appended: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub period_errors: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -166,6 +585,8 @@ pub struct PricingLintSummary {
     pub alias_integrity_ok: bool,
     pub placeholder_violations: Vec<String>,
     pub allow_placeholders: bool,
+    pub rate_period_violations: Vec<String>,
+    pub context_tier_violations: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -191,6 +612,9 @@ pub struct PricingReconcileOutcome {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CostBreakdown {
+    // Currency of every money field below; the `_usd` names predate multi-currency reports.
+    #[serde(default = "default_report_currency")]
+    pub currency: String,
     pub variable_cost_usd: f64,
     pub subscription_allocated_usd: f64,
     pub monthly_total_usd: f64,
@@ -200,6 +624,8 @@ pub struct CostBreakdown {
     pub input_tokens: u64,
     pub output_tokens: u64,
     pub cache_write_tokens: u64,
+    #[serde(default)]
+    pub cache_write_1h_tokens: u64,
     pub cache_read_tokens: u64,
     pub tool_input_tokens: u64,
     pub tool_output_tokens: u64,
@@ -207,9 +633,121 @@ pub struct CostBreakdown {
     pub skipped_unpriced_count: usize,
     pub provider_breakdown: Vec<NamedMetric>,
     pub model_breakdown: Vec<NamedMetric>,
+    #[serde(default)]
+    pub service_tier_breakdown: Vec<NamedMetric>,
+    // Savings if standard-tier traffic had run on each provider's batch tier, where one is priced.
+    #[serde(default)]
+    pub batch_savings_estimate_usd: f64,
+    // Prorated seat cost the allocation strategy did not attach to any usage (not in totals).
+    #[serde(default)]
+    pub idle_subscription_usd: f64,
+    // List vs net cost under provider contracts; absent when no reported provider has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<ContractSummary>,
+    // Prompt-cache efficiency; absent when no reported event read or wrote the cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheReport>,
     pub suggestions: Vec<String>,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheReport {
+    pub totals: CacheMetric,
+    pub providers: Vec<CacheMetric>,
+    pub models: Vec<CacheMetric>,
+    // Sessions that paid for cache writes on a model no later request of theirs read back; empty
+    // when the report did not track sessions.
+    pub unread_sessions: Vec<UnreadCacheSession>,
+}
+
+// `hit_ratio` is cache reads over reads plus uncached input; `write_amplification` is tokens
+// written per token read (None without reads). Savings compare against sending the same tokens
+// as uncached input, in the report currency.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheMetric {
+    pub name: String,
+    pub input_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub hit_ratio: f64,
+    pub write_amplification: Option<f64>,
+    pub read_savings_usd: f64,
+    pub write_premium_usd: f64,
+    pub net_savings_usd: f64,
+    // None when the report did not track sessions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unread_sessions: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnreadCacheSession {
+    pub provider: String,
+    pub model: String,
+    pub session_id: String,
+    pub cache_write_tokens: u64,
+    pub write_premium_usd: f64,
+}
+
+// `list_cost_usd` is `monthly_total_usd`; `net_cost_usd` takes off discounts and applied credits
+// and adds commit shortfalls. Providers without a contract count at list cost.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContractSummary {
+    pub list_cost_usd: f64,
+    pub discount_usd: f64,
+    pub commit_shortfall_usd: f64,
+    pub credits_applied_usd: f64,
+    pub net_cost_usd: f64,
+    // Unexpired credit left as of the last reported day.
+    pub remaining_credit_usd: f64,
+    pub providers: Vec<ProviderContractSummary>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderContractSummary {
+    pub provider: String,
+    pub list_cost_usd: f64,
+    pub discount_usd: f64,
+    pub commit_shortfall_usd: f64,
+    pub credits_applied_usd: f64,
+    pub net_cost_usd: f64,
+    pub remaining_credit_usd: f64,
+}
+
+// One line of `tokenledger price` output: the input event plus the rate and costs the reports
+// use for it. Money is in the provider's pricing currency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricedEventRecord {
+    #[serde(flatten)]
+    pub event: UsageEvent,
+    pub canonical_provider: String,
+    pub canonical_model: String,
+    pub currency: String,
+    // None when the event has no rate in the pricing book. A long-context tier the event crossed
+    // is already resolved into the component rates.
+    pub rate: Option<ModelRate>,
+    pub service_tier_multiplier: f64,
+    pub costs: Option<EventCosts>,
+}
+
+// Per-component costs sum exactly to `variable_usd`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventCosts {
+    pub input_usd: f64,
+    pub output_usd: f64,
+    pub cache_write_usd: f64,
+    pub cache_write_1h_usd: f64,
+    pub cache_read_usd: f64,
+    pub tool_input_usd: f64,
+    pub tool_output_usd: f64,
+    pub variable_usd: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_usd: Option<f64>,
+}
+
+fn default_report_currency() -> String {
+    crate::fx::USD.to_string()
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct CoverageReport {
     pub month: String,
@@ -220,6 +758,17 @@ pub struct CoverageReport {
     pub missing_models_by_provider: BTreeMap<String, Vec<String>>,
     pub suggested_provider_aliases: BTreeMap<String, Vec<String>>,
     pub suggested_model_aliases_by_provider: BTreeMap<String, Vec<UnknownModelSuggestion>>,
+    pub long_context_events: usize,
+    pub long_context_by_model: Vec<LongContextUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LongContextUsage {
+    pub provider: String,
+    pub model: String,
+    pub above_prompt_tokens: u64,
+    pub events: usize,
+    pub prompt_tokens: u64,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -234,19 +783,321 @@ pub struct UnknownModelSuggestion {
     pub count: usize,
 }
 
+// A range's totals split into buckets; `period` is "YYYY-MM" for a calendar month, otherwise the
+// first and last day covered.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DailyReport {
-    pub month: String,
+pub struct TimeSeriesReport {
+    pub period: String,
+    pub granularity: Granularity,
     pub totals: CostBreakdown,
-    pub days: Vec<DailyEntry>,
+    pub buckets: Vec<TimeSeriesBucket>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DailyEntry {
-    pub day: String,
+pub struct TimeSeriesBucket {
+    pub period: String,
+    pub start: DateTime<Utc>,
     pub breakdown: CostBreakdown,
 }
 
+// A `--group-by` report. `rows` are the leaf groups, or when `nested` the first dimension's
+// groups with each deeper level under `children`; row cents add up to `totals`, and children to
+// their parent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PivotReport {
+    pub period: String,
+    pub currency: String,
+    pub dimensions: Vec<GroupDimension>,
+    pub nested: bool,
+    pub totals: NamedMetric,
+    pub rows: Vec<PivotRow>,
+}
+
+// One group: its key parts in dimension order and its metrics, named by the parts joined with
+// "/". Model and session parts carry their provider ("claude/sonnet") unless an earlier part does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PivotRow {
+    pub keys: Vec<String>,
+    #[serde(flatten)]
+    pub metric: NamedMetric,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PivotRow>,
+}
+
+// Sessions in a period. Session costs are shares of the period totals and add up to
+// `total_cost_usd` before `--min-cost`/`--top` drop rows; `session_count` counts every session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReport {
+    pub period: String,
+    pub currency: String,
+    pub session_count: usize,
+    pub total_cost_usd: f64,
+    pub sessions: Vec<SessionSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub provider: String,
+    pub session_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration_secs: i64,
+    pub event_count: u64,
+    pub total_tokens: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cache_write_1h_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub tool_input_tokens: u64,
+    pub tool_output_tokens: u64,
+    pub variable_cost_usd: f64,
+    pub subscription_allocated_usd: f64,
+    pub total_cost_usd: f64,
+    // Cache reads over prompt input (cache reads plus uncached input).
+    pub cache_hit_ratio: f64,
+    // Model mix, costliest first.
+    pub models: Vec<NamedMetric>,
+}
+
+// One session id's events in time order, with its summary per provider that used the id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTimeline {
+    pub period: String,
+    pub currency: String,
+    pub session_id: String,
+    pub sessions: Vec<SessionSummary>,
+    pub events: Vec<SessionEvent>,
+}
+
+// Event costs are list variable cost; their cents add up to the sessions' variable cost.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEvent {
+    pub timestamp: DateTime<Utc>,
+    // Seconds since the session's previous event.
+    pub gap_secs: i64,
+    pub provider: String,
+    pub model: String,
+    pub service_tier: String,
+    pub usage: TokenUsage,
+    // None for events without a rate.
+    pub variable_cost_usd: Option<f64>,
+    pub cumulative_cost_usd: f64,
+}
+
+// Projected spend at the end of `period` from daily history through `as_of`. Every `_usd` figure
+// is the period's variable cost: spent so far plus the projection for the remaining days.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastReport {
+    pub period: String,
+    pub currency: String,
+    pub as_of: NaiveDate,
+    pub method: ForecastMethod,
+    // Coverage of the `low_usd`..`high_usd` band.
+    pub confidence: f64,
+    pub elapsed_days: usize,
+    pub remaining_days: usize,
+    // Variable cost plus subscription allocated so far.
+    pub spent_total_usd: f64,
+    // `spent_total_usd` plus the projected remaining variable cost.
+    pub projected_total_usd: f64,
+    pub overall: ForecastSeries,
+    pub providers: Vec<ForecastSeries>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastSeries {
+    pub name: String,
+    pub spent_usd: f64,
+    pub daily_average_usd: f64,
+    // By the report's method.
+    pub projected_usd: f64,
+    pub low_usd: f64,
+    pub high_usd: f64,
+    pub run_rate_usd: f64,
+    pub weekday_usd: f64,
+    pub ewma_usd: f64,
+}
+
+// Spend spikes in a daily or hourly series, highest z-score first. `buckets` counts the buckets
+// scored, from the first with events to the last.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyReport {
+    pub period: String,
+    pub currency: String,
+    pub granularity: Granularity,
+    pub threshold: f64,
+    pub min_usd: f64,
+    pub buckets: usize,
+    pub anomalies: Vec<Anomaly>,
+}
+
+// One bucket of one provider's or model's variable cost, against the median of the other
+// buckets of its weekday (daily series) or hour of day (hourly series).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anomaly {
+    pub bucket: String,
+    pub start: DateTime<Utc>,
+    pub scope: AnomalyScope,
+    pub name: String,
+    pub spend_usd: f64,
+    pub baseline_usd: f64,
+    pub z_score: f64,
+    pub excess_usd: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnomalyScope {
+    Provider,
+    Model,
+}
+
+impl AnomalyScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Provider => "provider",
+            Self::Model => "model",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanAnalysisReport {
+    pub month: String,
+    pub providers: Vec<ProviderPlanAnalysis>,
+}
+
+// Subscription cost for one provider and month against the same events at API list rates;
+// `net_savings_usd` is positive when the subscription is cheaper. Money, here and in the seat
+// rows, is in the provider's pricing `currency`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderPlanAnalysis {
+    pub provider: String,
+    #[serde(default = "default_report_currency")]
+    pub currency: String,
+    pub plan: Option<String>,
+    pub tokens: u64,
+    pub api_list_cost_usd: f64,
+    pub subscription_usd: f64,
+    pub overage_usd: f64,
+    pub plan_cost_usd: f64,
+    pub net_savings_usd: f64,
+    pub seats: Vec<SeatPlanAnalysis>,
+}
+
+// Provider usage is attributed to seats by active seat-days, since events carry no seat.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatPlanAnalysis {
+    pub seat: String,
+    pub plan: Option<String>,
+    pub count: u32,
+    pub active_days: u32,
+    pub tokens: u64,
+    pub included_tokens: Option<u64>,
+    pub utilization_pct: Option<f64>,
+    pub quota_exceeded: bool,
+    pub break_even_tokens: Option<u64>,
+    pub subscription_usd: f64,
+    pub overage_usd: f64,
+    pub plan_cost_usd: f64,
+    pub api_list_cost_usd: f64,
+    pub savings_usd: f64,
+    pub best_option: String,
+    pub best_option_usd: f64,
+    pub recommendation: String,
+}
+
+// A month priced as recorded and again after `whatif` substitutions. Deltas are simulated minus
+// actual, so negative means the simulation is cheaper.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatifReport {
+    pub month: String,
+    pub rules: Vec<WhatifRuleSummary>,
+    pub actual: CostBreakdown,
+    pub simulated: CostBreakdown,
+    // Monthly total plus idle seats, so moving all of a provider's traffic away does not make its
+    // subscription look free.
+    pub actual_total_usd: f64,
+    pub simulated_total_usd: f64,
+    pub delta_usd: f64,
+    pub providers: Vec<WhatifDelta>,
+    pub models: Vec<WhatifDelta>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatifRuleSummary {
+    pub rule: String,
+    pub events: usize,
+    pub tokens_before: u64,
+    pub tokens_after: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatifDelta {
+    pub name: String,
+    pub actual_usd: f64,
+    pub simulated_usd: f64,
+    pub delta_usd: f64,
+}
+
+// `period` against the baseline `against`; deltas are period minus baseline. Each row's cost
+// delta splits into a volume effect (the token change at baseline $/MTok) and a price/mix effect
+// (the $/MTok change on the period's tokens), which add up to the delta to the cent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareReport {
+    pub period: String,
+    pub against: String,
+    pub currency: String,
+    pub totals: CompareRow,
+    pub providers: Vec<CompareRow>,
+    pub models: Vec<CompareRow>,
+}
+
+// Rows present in only one period compare against zero, and their whole delta is volume.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareRow {
+    pub name: String,
+    pub baseline_cost_usd: f64,
+    pub cost_usd: f64,
+    pub cost_delta_usd: f64,
+    // None when the baseline cost is zero.
+    pub cost_delta_pct: Option<f64>,
+    pub baseline_tokens: u64,
+    pub tokens: u64,
+    pub tokens_delta: i64,
+    pub baseline_blended_usd_per_mtok: f64,
+    pub blended_usd_per_mtok: f64,
+    pub blended_delta_usd_per_mtok: f64,
+    pub baseline_sessions: usize,
+    pub sessions: usize,
+    pub sessions_delta: i64,
+    pub volume_effect_usd: f64,
+    pub price_mix_effect_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowReport {
+    pub as_of: DateTime<Utc>,
+    pub windows: Vec<WindowUsage>,
+}
+
+// Consumption of one plan window as of the report time; `started_at`/`resets_at` are unset
+// when the window holds no usage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowUsage {
+    pub provider: String,
+    pub plan: String,
+    pub window: String,
+    pub kind: UsageWindowKind,
+    pub hours: u32,
+    pub tokens: u64,
+    pub limit_tokens: Option<u64>,
+    pub used_pct: Option<f64>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NamedMetric {
     pub name: String,
@@ -272,6 +1123,24 @@ pub struct UiSnapshot {
     pub suggestions: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reconcile_latest_summary_path: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<WindowUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forecast: Option<UiSnapshotForecast>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budgets: Option<BudgetReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub anomalies: Vec<Anomaly>,
+}
+
+// Month-end total cost projected from the snapshot month's daily spend, for the status bar.
+#[derive(Debug, Clone, Serialize)]
+pub struct UiSnapshotForecast {
+    pub as_of: NaiveDate,
+    pub method: ForecastMethod,
+    pub projected_cost_usd: f64,
+    pub low_usd: f64,
+    pub high_usd: f64,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -315,7 +1184,7 @@ pub struct OrchestrateAggregateCacheEntry {
     pub pricing_hash: String,
     pub events_fingerprint: String,
     pub monthly: CostBreakdown,
-    pub daily: DailyReport,
+    pub daily: TimeSeriesReport,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -324,6 +1193,8 @@ pub struct OrchestrateAggregateCacheSelector {
     pub providers: Vec<String>,
     pub models: Vec<String>,
     pub on_unpriced: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocation: Option<AllocationStrategy>,
 }
 
 #[derive(Debug, Clone)]
@@ -348,6 +1219,14 @@ pub enum OrchestrateAggregateCacheLookup {
     Invalidate,
 }
 
+// How an aggregate cache lookup went, once a miss has been rebuilt and stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrchestrateAggregateCacheOutcome {
+    Hit,
+    Miss,
+    Invalidate,
+}
+
 #[derive(Debug, Clone)]
 pub struct PipelineStageSummaries {
     pub ingest: OrchestrateIngestStageSummary,
@@ -357,6 +1236,7 @@ pub struct PipelineStageSummaries {
     pub aggregate_cache: OrchestrateAggregateCacheMetrics,
     pub bench: OrchestrateBenchStageSummary,
     pub perf_gate: OrchestrateStageSummary,
+    pub budgets: OrchestrateBudgetStageSummary,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -375,7 +1255,18 @@ pub struct OrchestratePipelineSummary {
     pub aggregate_cache: OrchestrateAggregateCacheMetrics,
     pub bench: OrchestrateBenchStageSummary,
     pub perf_gate: OrchestrateStageSummary,
+    pub budgets: OrchestrateBudgetStageSummary,
     pub ui_snapshot_path: Option<String>,
+    pub html_report_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrchestrateBudgetStageSummary {
+    pub skipped: bool,
+    pub duration_ms: u128,
+    pub config_path: Option<String>,
+    pub alerts_sent: usize,
+    pub report: Option<BudgetReport>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -419,19 +1310,24 @@ pub struct Acc {
     pub input_tokens: u64,
     pub output_tokens: u64,
     pub cache_write_tokens: u64,
+    pub cache_write_1h_tokens: u64,
     pub cache_read_tokens: u64,
     pub tool_input_tokens: u64,
     pub tool_output_tokens: u64,
-    pub variable_cost_usd: f64,
-    pub subscription_allocated_usd: f64,
+    pub variable_cost_micros: Micros,
+    pub subscription_allocated_micros: Micros,
+    // Prompt-cache savings against uncached input and the premium paid for cache writes.
+    pub cache_read_savings_micros: Micros,
+    pub cache_write_premium_micros: Micros,
     pub sessions: HashSet<u64>,
 }
 
+// Money is summed in cents so accumulated scenarios compare exactly against golden fixtures.
 #[derive(Default, Debug, Clone)]
 pub struct BenchCorrectnessAccumulator {
-    pub variable_cost_usd: f64,
-    pub subscription_allocated_usd: f64,
-    pub monthly_total_usd: f64,
+    pub variable_cost_cents: i64,
+    pub subscription_allocated_cents: i64,
+    pub monthly_total_cents: i64,
     pub total_tokens: u64,
     pub input_tokens: u64,
     pub output_tokens: u64,
@@ -526,6 +1422,8 @@ pub struct BenchScenarioResult {
     pub elapsed_ms: f64,
     pub events_processed: usize,
     pub events_per_sec: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_rss_kb: Option<u64>,
     #[serde(default)]
     pub correctness: Option<BenchScenarioCorrectness>,
     pub elapsed_ms_delta: Option<f64>,
@@ -561,6 +1459,104 @@ pub struct BenchTrendReport {
     pub scenarios: Vec<BenchTrendScenarioSummary>,
 }
 
+// A budgets file: spending limits and where to send alerts when they are crossed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    pub budgets: Vec<Budget>,
+    #[serde(default)]
+    pub alerts: BudgetAlertConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Budget {
+    pub name: String,
+    pub amount_usd: f64,
+    #[serde(default)]
+    pub scope: BudgetScope,
+    #[serde(default)]
+    pub period: BudgetPeriod,
+    // Length of a `rolling` period, e.g. "7d" or "24h".
+    #[serde(default)]
+    pub window: Option<String>,
+    // Share of `amount_usd` spent (or projected for the month) at which the budget warns.
+    #[serde(default = "default_budget_warn_at")]
+    pub warn_at: f64,
+}
+
+fn default_budget_warn_at() -> f64 {
+    0.8
+}
+
+// Which events count against a budget; an empty scope counts every event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetScope {
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    // The calendar month, with a month-end forecast.
+    #[default]
+    Monthly,
+    // The trailing `window`.
+    Rolling,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetAlertConfig {
+    // Receives the alert JSON as a POST body.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    // Run through the shell with the alert JSON on stdin.
+    #[serde(default)]
+    pub command: Option<String>,
+    // Lowest status that alerts.
+    #[serde(default = "default_budget_alert_on")]
+    pub on: BudgetStatus,
+}
+
+fn default_budget_alert_on() -> BudgetStatus {
+    BudgetStatus::Warn
+}
+
+// Budget standings at `as_of`; `status` is the worst budget's.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetReport {
+    pub as_of: DateTime<Utc>,
+    pub currency: String,
+    pub status: BudgetStatus,
+    pub budgets: Vec<BudgetResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetResult {
+    pub name: String,
+    pub scope: String,
+    pub period: String,
+    pub amount_usd: f64,
+    pub spent_usd: f64,
+    pub used_ratio: f64,
+    // Month-end total for monthly budgets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub projected_usd: Option<f64>,
+    pub status: BudgetStatus,
+}
+
+// The payload of webhook and command alerts: the budgets at or above the alert status.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetAlert {
+    pub generated_at: DateTime<Utc>,
+    pub as_of: DateTime<Utc>,
+    pub status: BudgetStatus,
+    pub budgets: Vec<BudgetResult>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerfGateConfig {
     #[serde(default)]
@@ -611,6 +1607,7 @@ mod tests {
             input_tokens: 100,
             output_tokens: 200,
             cache_write_tokens: 50,
+            cache_write_1h_tokens: 0,
             cache_read_tokens: 25,
             tool_input_tokens: 10,
             tool_output_tokens: 15,
@@ -624,6 +1621,7 @@ mod tests {
             input_tokens: 100,
             output_tokens: 200,
             cache_write_tokens: 0,
+            cache_write_1h_tokens: 0,
             cache_read_tokens: 0,
             tool_input_tokens: 0,
             tool_output_tokens: 0,
@@ -637,6 +1635,7 @@ mod tests {
             input_tokens: 0,
             output_tokens: 0,
             cache_write_tokens: 0,
+            cache_write_1h_tokens: 0,
             cache_read_tokens: 0,
             tool_input_tokens: 0,
             tool_output_tokens: 0,
@@ -650,9 +1649,13 @@ mod tests {
             input_usd_per_mtok: 0.5,
             output_usd_per_mtok: 1.0,
             cache_write_usd_per_mtok: Some(0.1),
+            cache_write_1h_usd_per_mtok: None,
             cache_read_usd_per_mtok: Some(0.05),
             tool_input_usd_per_mtok: None,
             tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
         };
         assert_eq!(rate.input_usd_per_mtok, 0.5);
         assert_eq!(rate.output_usd_per_mtok, 1.0);
@@ -675,12 +1678,16 @@ mod tests {
             models_added: 2,
             aliases_added: 3,
             models_skipped_existing: 4,
+            periods_appended: 0,
+            period_errors: Vec::new(),
         };
         let s2 = PricingApplySummary {
             providers_added: 1,
             models_added: 2,
             aliases_added: 3,
             models_skipped_existing: 4,
+            periods_appended: 0,
+            period_errors: Vec::new(),
         };
         assert_eq!(s1, s2);
     }
@@ -692,6 +1699,7 @@ mod tests {
             input_tokens: 100,
             output_tokens: 200,
             cache_write_tokens: 0,
+            cache_write_1h_tokens: 0,
             cache_read_tokens: 0,
             tool_input_tokens: 0,
             tool_output_tokens: 0,
@@ -702,6 +1710,8 @@ mod tests {
             session_id: "session123".to_string(),
             timestamp: now,
             usage,
+            service_tier: None,
+            tags: Vec::new(),
         };
         assert_eq!(event.provider, "openai");
         assert_eq!(event.model, "gpt-4");
@@ -718,15 +1728,27 @@ mod tests {
                 input_usd_per_mtok: 0.5,
                 output_usd_per_mtok: 1.0,
                 cache_write_usd_per_mtok: None,
+                cache_write_1h_usd_per_mtok: None,
                 cache_read_usd_per_mtok: None,
                 tool_input_usd_per_mtok: None,
                 tool_output_usd_per_mtok: None,
-            },
+                effective_from: None,
+                effective_to: None,
+                context_tiers: Vec::new(),
+            }
+            .into(),
         );
         let provider = ProviderPricing {
+            currency: None,
             subscription_usd_month: 20.0,
             models,
             model_aliases: std::collections::HashMap::new(),
+            service_tiers: std::collections::HashMap::new(),
+            allocation: None,
+            seats: Vec::new(),
+            plan: None,
+            plans: HashMap::new(),
+            contract: None,
         };
         assert_eq!(provider.subscription_usd_month, 20.0);
         assert_eq!(provider.models.len(), 1);
@@ -738,9 +1760,16 @@ mod tests {
         providers.insert(
             "openai".to_string(),
             ProviderPricing {
+                currency: None,
                 subscription_usd_month: 0.0,
                 models: std::collections::HashMap::new(),
                 model_aliases: std::collections::HashMap::new(),
+                service_tiers: std::collections::HashMap::new(),
+                allocation: None,
+                seats: Vec::new(),
+                plan: None,
+                plans: HashMap::new(),
+                contract: None,
             },
         );
         let book = PricingBook {
@@ -773,4 +1802,121 @@ mod tests {
         assert!(patch.provider_aliases.is_empty());
         assert!(patch.model_aliases_by_provider.is_empty());
     }
+
+    fn flat_rate(input: f64) -> ModelRate {
+        ModelRate {
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: input * 2.0,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: None,
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        }
+    }
+
+    fn at(raw: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(raw)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_schedule_rate_at_picks_period_by_timestamp() {
+        let mut schedule = ModelRateSchedule::from(flat_rate(3.0));
+        schedule
+            .append_period(flat_rate(2.0), at("2026-03-01T00:00:00Z"))
+            .unwrap();
+
+        assert_eq!(schedule.periods().len(), 2);
+        assert_eq!(
+            schedule.periods()[0].effective_to,
+            Some(at("2026-03-01T00:00:00Z"))
+        );
+        let before = schedule.rate_at(at("2026-02-28T23:59:59Z")).unwrap();
+        assert_eq!(before.input_usd_per_mtok, 3.0);
+        let after = schedule.rate_at(at("2026-03-01T00:00:00Z")).unwrap();
+        assert_eq!(after.input_usd_per_mtok, 2.0);
+        assert_eq!(schedule.latest().unwrap().input_usd_per_mtok, 2.0);
+        assert!(schedule.period_issues().is_empty());
+    }
+
+    #[test]
+    fn test_schedule_append_rejects_non_increasing_start() {
+        let mut schedule = ModelRateSchedule::from(flat_rate(3.0));
+        schedule
+            .append_period(flat_rate(2.0), at("2026-03-01T00:00:00Z"))
+            .unwrap();
+        let err = schedule
+            .append_period(flat_rate(1.0), at("2026-02-01T00:00:00Z"))
+            .unwrap_err();
+        assert!(err.contains("does not start after"));
+        assert_eq!(schedule.periods().len(), 2);
+    }
+
+    #[test]
+    fn test_schedule_period_issues_reports_overlap_and_gap() {
+        let mut first = flat_rate(3.0);
+        first.effective_from = Some(at("2026-01-01T00:00:00Z"));
+        first.effective_to = Some(at("2026-02-15T00:00:00Z"));
+        let mut second = flat_rate(2.0);
+        second.effective_from = Some(at("2026-02-01T00:00:00Z"));
+        second.effective_to = Some(at("2026-03-01T00:00:00Z"));
+        let mut third = flat_rate(1.0);
+        third.effective_from = Some(at("2026-04-01T00:00:00Z"));
+
+        let issues = ModelRateSchedule::new(vec![third, first, second]).period_issues();
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].starts_with("overlap:"));
+        assert!(issues[1].starts_with("gap:"));
+    }
+
+    #[test]
+    fn test_schedule_serde_accepts_object_and_array() {
+        let legacy: ModelRateSchedule =
+            serde_json::from_str(r#"{"input_usd_per_mtok":3.0,"output_usd_per_mtok":15.0}"#)
+                .unwrap();
+        assert_eq!(legacy.periods().len(), 1);
+        let encoded = serde_json::to_value(&legacy).unwrap();
+        assert!(encoded.is_object());
+
+        let dated: ModelRateSchedule = serde_json::from_str(
+            r#"[
+                {"input_usd_per_mtok":2.0,"output_usd_per_mtok":10.0,"effective_from":"2026-03-01T00:00:00Z"},
+                {"input_usd_per_mtok":3.0,"output_usd_per_mtok":15.0,"effective_to":"2026-03-01T00:00:00Z"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(dated.periods()[0].input_usd_per_mtok, 3.0);
+        let encoded = serde_json::to_value(&dated).unwrap();
+        assert_eq!(encoded.as_array().map(Vec::len), Some(2));
+
+        assert!(serde_json::from_str::<ModelRateSchedule>("[]").is_err());
+    }
+
+    #[test]
+    fn test_context_tier_issues() {
+        let mut rate = flat_rate(3.0);
+        let tier = |above_prompt_tokens: u64, input: f64| ContextTier {
+            above_prompt_tokens,
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: input * 2.0,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: None,
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+        };
+        rate.context_tiers = vec![tier(200_000, 6.0), tier(500_000, 9.0)];
+        assert!(rate.context_tier_issues().is_empty());
+
+        rate.context_tiers = vec![tier(200_000, 6.0), tier(200_000, 2.0)];
+        let issues = rate.context_tier_issues();
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].ends_with("duplicate threshold"));
+        assert!(issues[1].ends_with("below the base rate"));
+    }
 }
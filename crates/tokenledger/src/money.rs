@@ -0,0 +1,151 @@
+// Fixed-point money for the cost engine: amounts are integer micro-units (1e-6 of a dollar, or of
+// the report currency), so sums are exact and split rows add up to their totals to the cent.
+
+pub type Micros = i64;
+
+pub const MICROS_PER_UNIT: i64 = 1_000_000;
+pub const MICROS_PER_CENT: i64 = 10_000;
+
+pub fn to_micros(amount: f64) -> Micros {
+    (amount * MICROS_PER_UNIT as f64).round() as Micros
+}
+
+pub fn from_micros(micros: Micros) -> f64 {
+    micros as f64 / MICROS_PER_UNIT as f64
+}
+
+// A `$/MTok` rate as integer micro-units per million tokens; exact for rates with up to six
+// decimals, which covers every published rate card.
+pub fn rate_micros(usd_per_mtok: f64) -> i128 {
+    (usd_per_mtok * MICROS_PER_UNIT as f64).round() as i128
+}
+
+// Cost of a sum of `tokens * rate_micros` terms, rounded once to whole micro-units.
+pub fn token_cost_micros(tokens_times_rate: i128) -> Micros {
+    div_round(tokens_times_rate, MICROS_PER_UNIT as i128) as Micros
+}
+
+// Applies a non-integer factor (service tier multiplier, FX rate) to an amount.
+pub fn scale_micros(micros: Micros, factor: f64) -> Micros {
+    if factor == 1.0 {
+        micros
+    } else {
+        (micros as f64 * factor).round() as Micros
+    }
+}
+
+pub fn to_cents(micros: Micros) -> i64 {
+    div_round(micros as i128, MICROS_PER_CENT as i128) as i64
+}
+
+pub fn cents_to_units(cents: i64) -> f64 {
+    cents as f64 / 100.0
+}
+
+// Cents of an amount that was already rounded to cents, such as a report field.
+pub fn units_to_cents(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
+
+// Rounds half away from zero, matching `f64::round` on the displayed values.
+pub fn div_round(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder.abs() * 2 >= denominator.abs() {
+        quotient + numerator.signum() * denominator.signum()
+    } else {
+        quotient
+    }
+}
+
+// Splits `total` in proportion to `weights` so the parts sum to exactly `total`: every part gets
+// its floor and the leftover units go to the largest remainders, earliest index first on ties.
+pub fn largest_remainder(total: Micros, weights: &[u128]) -> Vec<Micros> {
+    let weight_sum: u128 = weights.iter().sum();
+    if weight_sum == 0 {
+        return vec![0; weights.len()];
+    }
+    let sign = total.signum() as i128;
+    let magnitude = total.unsigned_abs() as u128;
+    let mut parts = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (idx, weight) in weights.iter().enumerate() {
+        let scaled = magnitude * weight;
+        parts.push(scaled / weight_sum);
+        remainders.push((scaled % weight_sum, idx));
+    }
+    let leftover = magnitude - parts.iter().sum::<u128>();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, idx) in remainders.into_iter().take(leftover as usize) {
+        parts[idx] += 1;
+    }
+    parts
+        .into_iter()
+        .map(|part| (part as i128 * sign) as Micros)
+        .collect()
+}
+
+// Rounds each of `parts` to whole `unit`s so the rounded parts sum to `target` units, normally
+// the rounded sum of `parts`: parts are floored and the difference goes to the largest remainders.
+pub fn round_parts(parts: &[Micros], unit: i64, target: i64) -> Vec<i64> {
+    let mut rounded: Vec<i64> = parts.iter().map(|part| part.div_euclid(unit)).collect();
+    let mut order: Vec<usize> = (0..parts.len()).collect();
+    order.sort_by(|a, b| {
+        parts[*b]
+            .rem_euclid(unit)
+            .cmp(&parts[*a].rem_euclid(unit))
+            .then(a.cmp(b))
+    });
+    let leftover = target - rounded.iter().sum::<i64>();
+    if leftover >= 0 {
+        for idx in order.into_iter().cycle().take(leftover as usize) {
+            rounded[idx] += 1;
+        }
+    } else {
+        for idx in order
+            .into_iter()
+            .rev()
+            .cycle()
+            .take(leftover.unsigned_abs() as usize)
+        {
+            rounded[idx] -= 1;
+        }
+    }
+    rounded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_cost_micros_is_exact_for_published_rates() {
+        // 0.1 + 0.2 style drift: 1_234_567 tokens at $0.3/MTok and 7 tokens at $15/MTok.
+        let sum = 1_234_567 * rate_micros(0.3) + 7 * rate_micros(15.0);
+        assert_eq!(token_cost_micros(sum), 370_475);
+        assert_eq!(to_cents(370_475), 37);
+        assert_eq!(to_cents(-5_000), -1);
+        assert_eq!(div_round(5, 2), 3);
+        assert_eq!(scale_micros(1_000_001, 0.5), 1_000_001 / 2 + 1);
+    }
+
+    #[test]
+    fn test_largest_remainder_sums_to_total() {
+        assert_eq!(largest_remainder(10, &[1, 1, 1]), vec![4, 3, 3]);
+        assert_eq!(largest_remainder(-10, &[1, 1, 1]), vec![-4, -3, -3]);
+        assert_eq!(largest_remainder(100, &[1, 2, 0, 3]), vec![17, 33, 0, 50]);
+        assert_eq!(largest_remainder(7, &[0, 0]), vec![0, 0]);
+        let parts = largest_remainder(28_000_000, &[3_000_000, 1_000_000, 7]);
+        assert_eq!(parts.iter().sum::<Micros>(), 28_000_000);
+    }
+
+    #[test]
+    fn test_round_parts_hits_target() {
+        // 0.333 + 0.333 + 0.334 dollars: naive rounding gives 0.33 * 3 = 0.99.
+        let parts = [333_000, 333_000, 334_000];
+        assert_eq!(round_parts(&parts, MICROS_PER_CENT, 100), vec![33, 33, 34]);
+        let parts = [5_000, 5_000, 5_000];
+        assert_eq!(round_parts(&parts, MICROS_PER_CENT, 2), vec![1, 1, 0]);
+        assert_eq!(round_parts(&parts, MICROS_PER_CENT, 1), vec![1, 0, 0]);
+    }
+}
@@ -8,22 +8,34 @@ use std::io::{BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+use crate::allocation::SubscriptionAllocator;
 use crate::analytics::*;
+use crate::anomalies::{
+    add_anomaly_suggestions, detect_anomalies, DEFAULT_ANOMALY_MIN_USD, DEFAULT_ANOMALY_THRESHOLD,
+};
 use crate::bench::{execute_bench, load_bench_report, load_perf_gate_config, PERF_GATES_PATH};
+use crate::budgets::{budget_as_of, evaluate_budgets, load_budget_config, send_budget_alerts};
 use crate::cli::{
-    BenchArgs, BenchScenario, IngestArgs, IngestProvider, OnUnpricedAction, OrchestrateArgs,
-    OutputMode, PricingAuditArgs, PricingLintArgs, PricingReconcileArgs, QueryArgs, UiSnapshotMode,
+    AllocationStrategy, BenchArgs, BenchScenario, ExportArgs, ForecastMethod, FxDateMode,
+    Granularity, IngestArgs, IngestProvider, OnUnpricedAction, OrchestrateArgs, OutputMode,
+    PricingAuditArgs, PricingLintArgs, PricingReconcileArgs, QueryArgs, UiSnapshotMode,
 };
+use crate::forecast::{build_forecast, ui_snapshot_forecast};
+use crate::fx::{CurrencyConverter, USD};
+use crate::html::{cost_report_html, write_html};
 use crate::ingest::{
     discover_provider_sources, ingest_provider_name, run_ingest, source_mtime_unix,
 };
 use crate::models::*;
+use crate::period::TimeRange;
 use crate::pricing::{execute_pricing_reconcile, run_pricing_audit, run_pricing_lint};
+use crate::stream::{CostStream, ReportView};
 use crate::utils::*;
+use crate::windows::WindowTracker;
 
 pub const ORCHESTRATE_PIPELINE_SUMMARY_SCHEMA_VERSION: u32 = 1;
 pub const ORCHESTRATE_INGEST_CACHE_VERSION: u8 = 1;
-pub const ORCHESTRATE_AGGREGATE_CACHE_VERSION: u8 = 1;
+pub const ORCHESTRATE_AGGREGATE_CACHE_VERSION: u8 = 3;
 pub const UI_SNAPSHOT_SCHEMA_VERSION: u32 = 1;
 
 pub fn run_orchestrate(args: OrchestrateArgs) -> Result<()> {
@@ -79,6 +91,13 @@ pub fn run_orchestrate(args: OrchestrateArgs) -> Result<()> {
         skipped: args.skip_bench || args.skip_gate,
         duration_ms: 0,
     };
+    let mut budget_stage = OrchestrateBudgetStageSummary {
+        skipped: args.budgets.is_none(),
+        duration_ms: 0,
+        config_path: args.budgets.as_ref().map(|path| path.display().to_string()),
+        alerts_sent: 0,
+        report: None,
+    };
 
     if !args.skip_ingest {
         let ingest_timer = Instant::now();
@@ -136,6 +155,8 @@ pub fn run_orchestrate(args: OrchestrateArgs) -> Result<()> {
             allow_stale: false,
             allow_missing_source: false,
             json_output: false,
+            output: OutputMode::Table,
+            export: ExportArgs::default(),
         })?;
     }
 
@@ -186,110 +207,118 @@ pub fn run_orchestrate(args: OrchestrateArgs) -> Result<()> {
         top_providers: Some(5),
         output: OutputMode::Table,
         on_unpriced: args.on_unpriced,
+        allocation: args.allocation,
+        currency: USD.to_string(),
+        fx_rates: None,
+        fx_date: FxDateMode::EventDay,
+        no_session_counts: false,
+        export: ExportArgs::default(),
     };
-    if let Some(cache_path) = args.aggregate_cache_path.as_ref() {
-        let key = build_orchestrate_aggregate_cache_key(&query, args.month.as_deref())?;
-        let (mut cache, lookup) = orchestrate_aggregate_cache_lookup(cache_path, &key)?;
-        match lookup {
-            OrchestrateAggregateCacheLookup::Hit(entry) => {
-                aggregate_cache.hit_count += 1;
-                let monthly_timer = Instant::now();
-                render_cost_breakdown(
-                    "Monthly",
-                    &entry.monthly,
-                    query.output,
-                    query.top_providers,
-                    query.top_models,
-                )?;
-                monthly_stage.duration_ms = monthly_timer.elapsed().as_millis();
-                monthly_stage.skipped = true;
-                let daily_timer = Instant::now();
-                render_daily_report(
-                    &entry.daily,
-                    query.output,
-                    query.top_providers,
-                    query.top_models,
-                )?;
-                daily_stage.duration_ms = daily_timer.elapsed().as_millis();
-                daily_stage.skipped = true;
-                eprintln!(
-                    "orchestrate aggregate cache hit: reusing monthly/daily outputs from {}",
-                    cache_path.display()
-                );
-            }
-            OrchestrateAggregateCacheLookup::Miss | OrchestrateAggregateCacheLookup::Invalidate => {
-                match lookup {
-                    OrchestrateAggregateCacheLookup::Miss => aggregate_cache.miss_count += 1,
-                    OrchestrateAggregateCacheLookup::Invalidate => {
-                        aggregate_cache.invalidate_count += 1;
-                        aggregate_cache.miss_count += 1;
-                    }
-                    OrchestrateAggregateCacheLookup::Hit(_) => {}
-                }
-                let monthly_timer = Instant::now();
-                let monthly_report = build_monthly_report(&query, args.month.as_deref())?;
-                render_cost_breakdown(
-                    "Monthly",
-                    &monthly_report,
-                    query.output,
-                    query.top_providers,
-                    query.top_models,
-                )?;
-                monthly_stage.duration_ms = monthly_timer.elapsed().as_millis();
-
-                let daily_timer = Instant::now();
-                let daily_report = build_daily_report(&query, args.month.as_deref())?;
-                render_daily_report(
-                    &daily_report,
-                    query.output,
-                    query.top_providers,
-                    query.top_models,
-                )?;
-                daily_stage.duration_ms = daily_timer.elapsed().as_millis();
-
-                let selector_id = orchestrate_aggregate_selector_id(&key.selector);
-                cache.entries.insert(
-                    selector_id,
-                    OrchestrateAggregateCacheEntry {
-                        selector: key.selector,
-                        pricing_hash: key.pricing_hash,
-                        events_fingerprint: key.events_fingerprint,
-                        monthly: monthly_report,
-                        daily: daily_report,
-                    },
-                );
-                write_orchestrate_aggregate_cache(cache_path, &cache)?;
-            }
-        }
-    } else {
+    let range = args
+        .month
+        .as_deref()
+        .map(parse_month)
+        .transpose()?
+        .map_or_else(TimeRange::default, TimeRange::month);
+    let mut build_aggregates = || -> Result<(CostBreakdown, TimeSeriesReport)> {
         let monthly_timer = Instant::now();
-        let monthly_report = build_monthly_report(&query, args.month.as_deref())?;
-        render_cost_breakdown(
-            "Monthly",
-            &monthly_report,
-            query.output,
-            query.top_providers,
-            query.top_models,
-        )?;
+        let monthly_report = build_monthly_report(&query, &range)?;
         monthly_stage.duration_ms = monthly_timer.elapsed().as_millis();
-
         let daily_timer = Instant::now();
-        let daily_report = build_daily_report(&query, args.month.as_deref())?;
-        render_daily_report(
-            &daily_report,
-            query.output,
-            query.top_providers,
-            query.top_models,
-        )?;
+        let daily_report = build_timeseries_report(&query, &range, Granularity::Day)?;
         daily_stage.duration_ms = daily_timer.elapsed().as_millis();
+        Ok((monthly_report, daily_report))
+    };
+    // The daily series is kept for the HTML report.
+    let (monthly_report, daily_report) = match args.aggregate_cache_path.as_ref() {
+        Some(cache_path) => {
+            let (entry, outcome) = cached_orchestrate_aggregates(
+                &query,
+                args.month.as_deref(),
+                cache_path,
+                build_aggregates,
+            )?;
+            match outcome {
+                OrchestrateAggregateCacheOutcome::Hit => {
+                    aggregate_cache.hit_count += 1;
+                    monthly_stage.skipped = true;
+                    daily_stage.skipped = true;
+                    eprintln!(
+                        "orchestrate aggregate cache hit: reusing monthly/daily outputs from {}",
+                        cache_path.display()
+                    );
+                }
+                OrchestrateAggregateCacheOutcome::Miss => aggregate_cache.miss_count += 1,
+                OrchestrateAggregateCacheOutcome::Invalidate => {
+                    aggregate_cache.invalidate_count += 1;
+                    aggregate_cache.miss_count += 1;
+                }
+            }
+            (entry.monthly, entry.daily)
+        }
+        None => build_aggregates()?,
+    };
+    let monthly_timer = Instant::now();
+    render_cost_breakdown(
+        "Monthly",
+        &monthly_report,
+        query.output,
+        query.top_providers,
+        query.top_models,
+        &query.export,
+    )?;
+    monthly_stage.duration_ms += monthly_timer.elapsed().as_millis();
+    let daily_timer = Instant::now();
+    render_timeseries_report(
+        &daily_report,
+        query.output,
+        query.top_providers,
+        query.top_models,
+        &query.export,
+    )?;
+    daily_stage.duration_ms += daily_timer.elapsed().as_millis();
+
+    if let Some(budgets_path) = args.budgets.as_ref() {
+        let budget_timer = Instant::now();
+        let config = load_budget_config(budgets_path)?;
+        let pricing = load_pricing(&args.pricing)?;
+        let events = normalize_events(
+            load_events(std::slice::from_ref(&args.events_out))?,
+            &pricing,
+        );
+        let report = evaluate_budgets(
+            &events,
+            &pricing,
+            &config,
+            args.on_unpriced,
+            args.allocation,
+            budget_as_of(args.month.as_deref(), Utc::now())?,
+        )?;
+        print_budget_table(&report);
+        if args.budget_alerts {
+            budget_stage.alerts_sent = send_budget_alerts(&config.alerts, &report)?;
+        }
+        budget_stage.duration_ms = budget_timer.elapsed().as_millis();
+        budget_stage.report = Some(report);
     }
 
     if let Some(snapshot_path) = args.ui_snapshot_path.as_ref() {
-        let snapshot =
+        let mut snapshot =
             build_orchestrate_ui_snapshot(args.month.as_deref(), &args.events_out, &args)?;
+        snapshot.budgets = budget_stage.report.clone();
         write_ui_snapshot(snapshot_path, &snapshot)?;
     }
 
+    if let Some(html_path) = args.html_report_path.as_ref() {
+        write_html(
+            &cost_report_html("Monthly", &daily_report),
+            &ExportArgs {
+                output_path: Some(html_path.clone()),
+                ..ExportArgs::default()
+            },
+        )?;
+    }
+
     if args.skip_bench {
         maybe_write_orchestrate_pipeline_summary(
             &args,
@@ -302,6 +331,7 @@ pub fn run_orchestrate(args: OrchestrateArgs) -> Result<()> {
                 aggregate_cache,
                 bench: bench_stage,
                 perf_gate: perf_gate_stage,
+                budgets: budget_stage,
             },
         )?;
         return Ok(());
@@ -318,6 +348,7 @@ pub fn run_orchestrate(args: OrchestrateArgs) -> Result<()> {
         warm_iterations: 5,
         warm_tail_events: 10_000,
         burst_batch_events: 2_000,
+        stream_events: 0,
         json_output: false,
         on_unpriced: args.on_unpriced,
         json_output_path: Some(latest_summary.clone()),
@@ -358,6 +389,7 @@ pub fn run_orchestrate(args: OrchestrateArgs) -> Result<()> {
             aggregate_cache,
             bench: bench_stage,
             perf_gate: perf_gate_stage,
+            budgets: budget_stage,
         },
     )?;
 
@@ -443,10 +475,15 @@ pub fn maybe_write_orchestrate_pipeline_summary(
         aggregate_cache: stages.aggregate_cache,
         bench: stages.bench,
         perf_gate: stages.perf_gate,
+        budgets: stages.budgets,
         ui_snapshot_path: args
             .ui_snapshot_path
             .as_ref()
             .map(|value| value.display().to_string()),
+        html_report_path: args
+            .html_report_path
+            .as_ref()
+            .map(|value| value.display().to_string()),
     };
     write_json_file_pretty(path, &summary)?;
     let latest_path = path
@@ -546,6 +583,7 @@ pub fn build_orchestrate_aggregate_cache_key(
         providers,
         models,
         on_unpriced: on_unpriced_to_str(query.on_unpriced).to_string(),
+        allocation: query.allocation,
     };
     Ok(OrchestrateAggregateCacheKey {
         selector,
@@ -582,8 +620,12 @@ pub fn file_content_fingerprint(path: &Path) -> Result<String> {
 
 pub fn orchestrate_aggregate_selector_id(selector: &OrchestrateAggregateCacheSelector) -> String {
     let month = selector.month_filter.as_deref().unwrap_or("*");
+    // Without --allocation each provider's pricing `allocation` applies.
+    let allocation = selector
+        .allocation
+        .map_or("pricing", AllocationStrategy::as_str);
     format!(
-        "month={month}|providers={}|models={}|on_unpriced={}",
+        "month={month}|providers={}|models={}|on_unpriced={}|allocation={allocation}",
         selector.providers.join(","),
         selector.models.join(","),
         selector.on_unpriced
@@ -599,7 +641,9 @@ pub fn orchestrate_aggregate_cache_lookup(
     let Some(entry) = cache.entries.get(&selector_id).cloned() else {
         return Ok((cache, OrchestrateAggregateCacheLookup::Miss));
     };
-    if entry.pricing_hash == key.pricing_hash && entry.events_fingerprint == key.events_fingerprint
+    if entry.selector == key.selector
+        && entry.pricing_hash == key.pricing_hash
+        && entry.events_fingerprint == key.events_fingerprint
     {
         return Ok((cache, OrchestrateAggregateCacheLookup::Hit(Box::new(entry))));
     }
@@ -615,15 +659,18 @@ pub fn load_orchestrate_aggregate_cache(path: &Path) -> Result<OrchestrateAggreg
         });
     }
     let file = File::open(path).with_context(|| format!("opening aggregate cache {:?}", path))?;
-    let cache: OrchestrateAggregateCache = serde_json::from_reader(file)
+    // Read the version first: entries written by another version may not parse.
+    let raw: serde_json::Value = serde_json::from_reader(file)
         .with_context(|| format!("parsing aggregate cache {:?}", path))?;
-    if cache.version != ORCHESTRATE_AGGREGATE_CACHE_VERSION {
+    if raw.get("version").and_then(serde_json::Value::as_u64)
+        != Some(ORCHESTRATE_AGGREGATE_CACHE_VERSION as u64)
+    {
         return Ok(OrchestrateAggregateCache {
             version: ORCHESTRATE_AGGREGATE_CACHE_VERSION,
             entries: BTreeMap::new(),
         });
     }
-    Ok(cache)
+    serde_json::from_value(raw).with_context(|| format!("parsing aggregate cache {:?}", path))
 }
 
 pub fn write_orchestrate_aggregate_cache(
@@ -633,6 +680,42 @@ pub fn write_orchestrate_aggregate_cache(
     write_json_file_pretty(path, cache)
 }
 
+// The month's monthly and daily reports from the aggregate cache, or from `build` and stored
+// when the pricing, events or selectors changed.
+pub fn cached_orchestrate_aggregates(
+    query: &QueryArgs,
+    month: Option<&str>,
+    cache_path: &Path,
+    build: impl FnOnce() -> Result<(CostBreakdown, TimeSeriesReport)>,
+) -> Result<(
+    OrchestrateAggregateCacheEntry,
+    OrchestrateAggregateCacheOutcome,
+)> {
+    let key = build_orchestrate_aggregate_cache_key(query, month)?;
+    let (mut cache, lookup) = orchestrate_aggregate_cache_lookup(cache_path, &key)?;
+    let outcome = match lookup {
+        OrchestrateAggregateCacheLookup::Hit(entry) => {
+            return Ok((*entry, OrchestrateAggregateCacheOutcome::Hit))
+        }
+        OrchestrateAggregateCacheLookup::Miss => OrchestrateAggregateCacheOutcome::Miss,
+        OrchestrateAggregateCacheLookup::Invalidate => OrchestrateAggregateCacheOutcome::Invalidate,
+    };
+    let (monthly, daily) = build()?;
+    let entry = OrchestrateAggregateCacheEntry {
+        selector: key.selector,
+        pricing_hash: key.pricing_hash,
+        events_fingerprint: key.events_fingerprint,
+        monthly,
+        daily,
+    };
+    cache.entries.insert(
+        orchestrate_aggregate_selector_id(&entry.selector),
+        entry.clone(),
+    );
+    write_orchestrate_aggregate_cache(cache_path, &cache)?;
+    Ok((entry, outcome))
+}
+
 pub fn load_ingest_summary(path: &Path) -> Result<IngestSummary> {
     let file = File::open(path).with_context(|| format!("opening ingest summary {:?}", path))?;
     serde_json::from_reader(file).with_context(|| format!("parsing ingest summary {:?}", path))
@@ -646,6 +729,8 @@ pub fn build_orchestrate_ui_snapshot(
     let pricing = load_pricing(&args.pricing)?;
     let events = load_events(&[events_path.to_path_buf()])?;
     let normalized = normalize_events(events, &pricing);
+    let mut windows = WindowTracker::default();
+    windows.observe(&normalized, &pricing);
     let month_filtered = filter_month(normalized, month)?;
     let filtered = filter_provider_model(month_filtered, &pricing, &[], &[]);
     if filtered.is_empty() {
@@ -653,20 +738,53 @@ pub fn build_orchestrate_ui_snapshot(
             "no events matched selected month/provider/model filters"
         ));
     }
-    let breakdown = compute_costs(&filtered, &pricing, args.on_unpriced)?;
+    let allocator = SubscriptionAllocator::build(&filtered, &pricing, args.allocation);
+    let breakdown =
+        compute_costs_with_allocator(&filtered, &pricing, args.on_unpriced, &allocator)?;
     let snapshot_month = format!(
         "{:04}-{:02}",
         filtered[0].timestamp.year(),
         filtered[0].timestamp.month()
     );
-    Ok(build_ui_snapshot_from_breakdown(
+    let mut snapshot = build_ui_snapshot_from_breakdown(
         Utc::now(),
         snapshot_month,
         args.ui_snapshot_mode,
         &breakdown,
         5,
         discover_reconcile_latest_summary_path(Path::new("benchmarks/results")),
-    ))
+    );
+    snapshot.windows = windows.report(&pricing, snapshot.generated_at);
+    let (range, daily) = snapshot_daily_series(&filtered, pricing, args)?;
+    let forecast = build_forecast(&daily, &range, None, ForecastMethod::Weekday)?;
+    snapshot.forecast = Some(ui_snapshot_forecast(&forecast));
+    let anomalies = detect_anomalies(&daily, DEFAULT_ANOMALY_THRESHOLD, DEFAULT_ANOMALY_MIN_USD)?;
+    add_anomaly_suggestions(&mut snapshot.suggestions, &anomalies);
+    snapshot.anomalies = anomalies.anomalies;
+    Ok(snapshot)
+}
+
+// The snapshot month and its events streamed by day, for the month-end projection and spend
+// anomalies.
+fn snapshot_daily_series(
+    events: &[UsageEvent],
+    pricing: PricingBook,
+    args: &OrchestrateArgs,
+) -> Result<(TimeRange, TimeSeriesReport)> {
+    let first = events[0].timestamp;
+    let range = TimeRange::month((first.year(), first.month()));
+    let view = ReportView::new(&pricing, &[], &[], args.on_unpriced);
+    let mut stream = CostStream::new(
+        pricing,
+        CurrencyConverter::usd(),
+        args.allocation,
+        range,
+        false,
+    );
+    for evt in events {
+        stream.observe(evt.clone());
+    }
+    Ok((range, stream.series(&view, Granularity::Day)?))
 }
 
 pub fn discover_reconcile_latest_summary_path(results_dir: &Path) -> Option<String> {
@@ -708,6 +826,10 @@ pub fn build_ui_snapshot_from_breakdown(
             .collect(),
         suggestions: breakdown.suggestions.clone(),
         reconcile_latest_summary_path,
+        windows: Vec::new(),
+        forecast: None,
+        budgets: None,
+        anomalies: Vec::new(),
     }
 }
 
@@ -739,3 +861,62 @@ pub fn write_json_file_pretty<T: Serialize>(path: &Path, value: &T) -> Result<()
         .with_context(|| format!("writing newline {:?}", path))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::BreakdownAcc;
+
+    fn key(allocation: Option<AllocationStrategy>) -> OrchestrateAggregateCacheKey {
+        OrchestrateAggregateCacheKey {
+            selector: OrchestrateAggregateCacheSelector {
+                month_filter: Some("2026-02".to_string()),
+                providers: Vec::new(),
+                models: Vec::new(),
+                on_unpriced: "error".to_string(),
+                allocation,
+            },
+            pricing_hash: "pricing".to_string(),
+            events_fingerprint: "events".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_allocation_strategies_do_not_share_aggregate_cache_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("aggregate-cache.json");
+        let tokens = key(Some(AllocationStrategy::Tokens));
+        let empty = || BreakdownAcc::default().finish(&CurrencyConverter::usd(), None);
+        let mut cache = load_orchestrate_aggregate_cache(&path).unwrap();
+        cache.entries.insert(
+            orchestrate_aggregate_selector_id(&tokens.selector),
+            OrchestrateAggregateCacheEntry {
+                selector: tokens.selector.clone(),
+                pricing_hash: tokens.pricing_hash.clone(),
+                events_fingerprint: tokens.events_fingerprint.clone(),
+                monthly: empty(),
+                daily: TimeSeriesReport {
+                    period: "2026-02".to_string(),
+                    granularity: Granularity::Day,
+                    totals: empty(),
+                    buckets: Vec::new(),
+                },
+            },
+        );
+        write_orchestrate_aggregate_cache(&path, &cache).unwrap();
+
+        let lookup = |key: &OrchestrateAggregateCacheKey| {
+            orchestrate_aggregate_cache_lookup(&path, key).unwrap().1
+        };
+        assert!(matches!(
+            lookup(&tokens),
+            OrchestrateAggregateCacheLookup::Hit(_)
+        ));
+        for other in [None, Some(AllocationStrategy::VariableCost)] {
+            assert!(matches!(
+                lookup(&key(other)),
+                OrchestrateAggregateCacheLookup::Miss
+            ));
+        }
+    }
+}
@@ -0,0 +1,328 @@
+// Reporting periods: the time range a report covers (`--month`, `--from`/`--to` or `--last`) and
+// the buckets a time-series report splits it into. Ranges are half-open, `[from, to)`, in UTC.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use crate::cli::{Granularity, PeriodArgs};
+use crate::utils::parse_month;
+
+type Month = (i32, u32);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    pub fn month(month: Month) -> Self {
+        let start = month_start(month);
+        Self {
+            from: Some(start),
+            to: Some(month_start(next_month(month))),
+        }
+    }
+
+    // `--month`, or `--from`/`--to`, or `--last` ending at `now`; no flags is every event.
+    pub fn from_args(args: &PeriodArgs, now: DateTime<Utc>) -> Result<Self> {
+        if let Some(month) = args.month.as_deref() {
+            return Ok(Self::month(parse_month(month)?));
+        }
+        if let Some(last) = args.last.as_deref() {
+            return Self::trailing(last, now);
+        }
+        let range = Self {
+            from: args
+                .from
+                .as_deref()
+                .map(|raw| parse_bound(raw, false))
+                .transpose()?,
+            to: args
+                .to
+                .as_deref()
+                .map(|raw| parse_bound(raw, true))
+                .transpose()?,
+        };
+        if let (Some(from), Some(to)) = (range.from, range.to) {
+            if from >= to {
+                return Err(anyhow!("--from must be before --to"));
+            }
+        }
+        Ok(range)
+    }
+
+    // The `window` (e.g. `30d`) ending at `end`.
+    pub fn trailing(window: &str, end: DateTime<Utc>) -> Result<Self> {
+        Ok(Self {
+            from: Some(end - parse_duration(window)?),
+            to: Some(end),
+        })
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| from <= at) && self.to.is_none_or(|to| at < to)
+    }
+
+    // The calendar month the range is exactly, if it is one.
+    pub fn as_month(&self) -> Option<Month> {
+        let from = self.from?;
+        let month = (from.year(), from.month());
+        (*self == Self::month(month)).then_some(month)
+    }
+
+    // The period just before: the previous calendar month for a month, otherwise the same length
+    // ending where this range starts. Open ranges have none.
+    pub fn previous(&self) -> Option<Self> {
+        if let Some(month) = self.as_month() {
+            return Some(Self::month(previous_month(month)));
+        }
+        let (from, to) = (self.from?, self.to?);
+        Some(Self {
+            from: Some(from - (to - from)),
+            to: Some(from),
+        })
+    }
+
+    pub fn covers_month(&self, month: Month) -> bool {
+        self.from.is_none_or(|from| from <= month_start(month))
+            && self
+                .to
+                .is_none_or(|to| to >= month_start(next_month(month)))
+    }
+
+    // Share of `day` inside the range, from 0 to 1.
+    pub fn day_coverage(&self, day: NaiveDate) -> f64 {
+        let start = day.and_time(NaiveTime::MIN).and_utc();
+        let end = start + Duration::days(1);
+        let start = self.from.map_or(start, |from| from.max(start));
+        let end = self.to.map_or(end, |to| to.min(end));
+        if end <= start {
+            return 0.0;
+        }
+        (end - start).num_seconds() as f64 / 86_400.0
+    }
+
+    // "YYYY-MM" for calendar months, otherwise the first and last day covered; open ends are
+    // filled in from the first and last day with events.
+    pub fn label(&self, first_day: NaiveDate, last_day: NaiveDate) -> String {
+        if let Some((year, month)) = self.as_month() {
+            return format!("{:04}-{:02}", year, month);
+        }
+        let start = self.from.map_or(first_day, |from| from.date_naive());
+        let end = self
+            .to
+            .map_or(last_day, |to| (to - Duration::seconds(1)).date_naive());
+        if start == end {
+            start.format("%Y-%m-%d").to_string()
+        } else {
+            format!("{}..{}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"))
+        }
+    }
+}
+
+// The bucket an hour of `day` falls in: its start and its label.
+pub fn bucket_of(granularity: Granularity, day: NaiveDate, hour: u32) -> (NaiveDateTime, String) {
+    let midnight = |day: NaiveDate| day.and_time(NaiveTime::MIN);
+    match granularity {
+        Granularity::Hour => {
+            let start = midnight(day) + Duration::hours(hour as i64);
+            (start, start.format("%Y-%m-%dT%H:00Z").to_string())
+        }
+        Granularity::Day => (midnight(day), day.format("%Y-%m-%d").to_string()),
+        Granularity::Week => {
+            let week = day.iso_week();
+            let monday = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+            (
+                midnight(monday),
+                format!("{:04}-W{:02}", week.year(), week.week()),
+            )
+        }
+        Granularity::Month => (
+            midnight(day.with_day(1).expect("first of month")),
+            format!("{:04}-{:02}", day.year(), day.month()),
+        ),
+        Granularity::Quarter => {
+            let quarter = (day.month() - 1) / 3;
+            let first = NaiveDate::from_ymd_opt(day.year(), quarter * 3 + 1, 1).expect("quarter");
+            (
+                midnight(first),
+                format!("{:04}-Q{}", day.year(), quarter + 1),
+            )
+        }
+        Granularity::Year => (
+            midnight(NaiveDate::from_ymd_opt(day.year(), 1, 1).expect("first of year")),
+            format!("{:04}", day.year()),
+        ),
+    }
+}
+
+// RFC3339 timestamps are used as given. A date (YYYY-MM-DD) or month (YYYY-MM) starts at its
+// first instant, or as an end bound includes the whole day or month.
+fn parse_bound(raw: &str, end: bool) -> Result<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        let day = if end { day + Duration::days(1) } else { day };
+        return Ok(day.and_time(NaiveTime::MIN).and_utc());
+    }
+    let month = parse_month(raw).with_context(|| {
+        format!(
+            "invalid time '{}', expected RFC3339, YYYY-MM-DD or YYYY-MM",
+            raw
+        )
+    })?;
+    Ok(month_start(if end { next_month(month) } else { month }))
+}
+
+// `24h`, `30d` or `12w`.
+fn parse_duration(raw: &str) -> Result<Duration> {
+    let invalid = || anyhow!("invalid duration '{}', expected e.g. 24h, 30d or 12w", raw);
+    let split = raw.len().checked_sub(1).ok_or_else(invalid)?;
+    let (count, unit) = raw.split_at(split);
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+    if count <= 0 {
+        return Err(invalid());
+    }
+    match unit {
+        "h" => Ok(Duration::hours(count)),
+        "d" => Ok(Duration::days(count)),
+        "w" => Ok(Duration::weeks(count)),
+        _ => Err(invalid()),
+    }
+}
+
+fn month_start(month: Month) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(month.0, month.1, 1)
+        .expect("valid month")
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+}
+
+fn next_month((year, month): Month) -> Month {
+    if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    }
+}
+
+fn previous_month((year, month): Month) -> Month {
+    if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn args(
+        month: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+        last: Option<&str>,
+    ) -> PeriodArgs {
+        PeriodArgs {
+            month: month.map(str::to_string),
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+            last: last.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_range_from_args() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 15, 12, 0, 0).unwrap();
+        let quarter =
+            TimeRange::from_args(&args(None, Some("2026-01"), Some("2026-03"), None), now).unwrap();
+        assert_eq!(
+            quarter.from,
+            Some(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            quarter.to,
+            Some(Utc.with_ymd_and_hms(2026, 4, 1, 0, 0, 0).unwrap())
+        );
+        assert!(quarter.covers_month((2026, 2)));
+
+        let days = TimeRange::from_args(
+            &args(None, Some("2026-02-10"), Some("2026-02-10"), None),
+            now,
+        )
+        .unwrap();
+        assert!(days.contains(Utc.with_ymd_and_hms(2026, 2, 10, 23, 59, 59).unwrap()));
+        assert!(!days.contains(Utc.with_ymd_and_hms(2026, 2, 11, 0, 0, 0).unwrap()));
+        assert_eq!(days.label(NaiveDate::MIN, NaiveDate::MAX), "2026-02-10");
+
+        let last = TimeRange::from_args(&args(None, None, None, Some("36h")), now).unwrap();
+        assert_eq!(
+            last.from,
+            Some(Utc.with_ymd_and_hms(2026, 3, 14, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            last.day_coverage(NaiveDate::from_ymd_opt(2026, 3, 15).unwrap()),
+            0.5
+        );
+
+        let month = TimeRange::from_args(&args(Some("2026-02"), None, None, None), now).unwrap();
+        assert_eq!(month.as_month(), Some((2026, 2)));
+        assert_eq!(month.label(NaiveDate::MIN, NaiveDate::MAX), "2026-02");
+
+        assert!(TimeRange::from_args(&args(None, None, None, Some("30x")), now).is_err());
+        assert!(TimeRange::from_args(
+            &args(None, Some("2026-03-02"), Some("2026-03-01"), None),
+            now
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_previous_period() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 15, 12, 0, 0).unwrap();
+        let previous = |period: PeriodArgs| {
+            TimeRange::from_args(&period, now)
+                .unwrap()
+                .previous()
+                .map(|range| range.label(NaiveDate::MIN, NaiveDate::MAX))
+        };
+        // Months step back a calendar month whatever their length.
+        assert_eq!(
+            previous(args(Some("2026-03"), None, None, None)).as_deref(),
+            Some("2026-02")
+        );
+        assert_eq!(
+            previous(args(Some("2026-01"), None, None, None)).as_deref(),
+            Some("2025-12")
+        );
+        assert_eq!(
+            previous(args(None, Some("2026-03-08"), Some("2026-03-14"), None)).as_deref(),
+            Some("2026-03-01..2026-03-07")
+        );
+        assert_eq!(
+            previous(args(None, None, None, Some("7d"))).as_deref(),
+            Some("2026-03-01..2026-03-08")
+        );
+        assert_eq!(previous(args(None, Some("2026-03-01"), None, None)), None);
+    }
+
+    #[test]
+    fn test_bucket_labels() {
+        let day = NaiveDate::from_ymd_opt(2026, 5, 14).unwrap();
+        let label = |granularity| bucket_of(granularity, day, 9).1;
+        assert_eq!(label(Granularity::Hour), "2026-05-14T09:00Z");
+        assert_eq!(label(Granularity::Day), "2026-05-14");
+        assert_eq!(label(Granularity::Week), "2026-W20");
+        assert_eq!(label(Granularity::Month), "2026-05");
+        assert_eq!(label(Granularity::Quarter), "2026-Q2");
+        assert_eq!(label(Granularity::Year), "2026");
+        assert_eq!(
+            bucket_of(Granularity::Week, day, 0).0.date(),
+            NaiveDate::from_ymd_opt(2026, 5, 11).unwrap()
+        );
+    }
+}
@@ -0,0 +1,530 @@
+// Subscription plan analysis: compares what each provider's seats cost under their plan against
+// the same events priced at API list rates, and recommends the cheapest plan (or the API) per seat.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::Datelike;
+
+use crate::allocation::{month_days, prorated_subscription};
+use crate::cli::{OutputMode, PlanAnalysisArgs};
+use crate::cost::MTOK;
+use crate::export::{plan_analysis_tables, write_export};
+use crate::fx::currency_symbol;
+use crate::models::*;
+use crate::money::{from_micros, scale_micros, Micros};
+use crate::utils::{
+    calc_variable_cost, event_pricing, filter_month, load_events, load_pricing, normalize_events,
+    normalize_provider_filters, parse_month, print_plan_analysis_table, round2,
+};
+
+const API_OPTION: &str = "api";
+
+pub fn run_plan_analysis(args: PlanAnalysisArgs) -> Result<()> {
+    let pricing = load_pricing(&args.pricing)?;
+    let events = normalize_events(load_events(&args.events)?, &pricing);
+    let month = match args.month.as_deref() {
+        Some(raw) => parse_month(raw)?,
+        None => events
+            .iter()
+            .map(|evt| (evt.timestamp.year(), evt.timestamp.month()))
+            .max()
+            .ok_or_else(|| anyhow!("no events found"))?,
+    };
+    let month_label = format!("{:04}-{:02}", month.0, month.1);
+    let filtered = filter_month(events, Some(&month_label))?;
+
+    let mut report = build_plan_analysis(&filtered, &pricing, month);
+    if !args.providers.is_empty() {
+        let wanted = normalize_provider_filters(&pricing, &args.providers);
+        report
+            .providers
+            .retain(|provider| wanted.contains(&provider.provider));
+    }
+    if report.providers.is_empty() {
+        return Err(anyhow!(
+            "no providers with a subscription or plans in {}",
+            month_label
+        ));
+    }
+
+    let output = if args.json_output {
+        OutputMode::Json
+    } else {
+        args.output
+    };
+    match output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_plan_analysis_table(&report),
+        OutputMode::Csv | OutputMode::Xlsx => {
+            write_export(&plan_analysis_tables(&report), output, &args.export)?
+        }
+        OutputMode::Markdown | OutputMode::Html => {
+            return Err(anyhow!(
+                "plan-analysis supports table, json, csv and xlsx output"
+            ))
+        }
+    }
+    Ok(())
+}
+
+// `events` must already be limited to `month`. Providers without a subscription or plans are
+// left out; unpriced events are ignored since they have no list cost to compare against.
+pub fn build_plan_analysis(
+    events: &[UsageEvent],
+    pricing: &PricingBook,
+    month: (i32, u32),
+) -> PlanAnalysisReport {
+    let mut usage: BTreeMap<&str, (u64, Micros)> = BTreeMap::new();
+    for evt in events {
+        let Some((_, rate, multiplier)) = event_pricing(evt, pricing) else {
+            continue;
+        };
+        let entry = usage.entry(evt.provider.as_str()).or_default();
+        entry.0 += evt.usage.total();
+        entry.1 += scale_micros(calc_variable_cost(&evt.usage, rate), multiplier);
+    }
+
+    let mut providers: Vec<(&String, &ProviderPricing)> = pricing.providers.iter().collect();
+    providers.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut rows = Vec::new();
+    for (name, provider) in providers {
+        if prorated_subscription(provider, month) <= 0.0 && provider.plans.is_empty() {
+            continue;
+        }
+        let (tokens, api_cost) = usage.get(name.as_str()).copied().unwrap_or_default();
+        let api_cost = from_micros(api_cost);
+        let seats = analyze_seats(provider, month, tokens, api_cost);
+        let subscription_usd: f64 = seats.iter().map(|seat| seat.subscription_usd).sum();
+        let overage_usd: f64 = seats.iter().map(|seat| seat.overage_usd).sum();
+        let plan_cost_usd: f64 = seats.iter().map(|seat| seat.plan_cost_usd).sum();
+        rows.push(ProviderPlanAnalysis {
+            provider: name.clone(),
+            currency: provider.currency().to_string(),
+            plan: provider.plan.clone(),
+            tokens,
+            api_list_cost_usd: round2(api_cost),
+            subscription_usd: round2(subscription_usd),
+            overage_usd: round2(overage_usd),
+            plan_cost_usd: round2(plan_cost_usd),
+            net_savings_usd: round2(round2(api_cost) - round2(plan_cost_usd)),
+            seats,
+        });
+    }
+
+    PlanAnalysisReport {
+        month: format!("{:04}-{:02}", month.0, month.1),
+        providers: rows,
+    }
+}
+
+struct SeatSlot<'a> {
+    label: String,
+    seat: Option<&'a SubscriptionSeat>,
+    count: u32,
+    active_days: u32,
+}
+
+fn analyze_seats(
+    provider: &ProviderPricing,
+    month: (i32, u32),
+    tokens: u64,
+    api_cost: f64,
+) -> Vec<SeatPlanAnalysis> {
+    let days: Vec<_> = month_days(month).collect();
+    let slots: Vec<SeatSlot> = if provider.seats.is_empty() {
+        vec![SeatSlot {
+            label: "default".to_string(),
+            seat: None,
+            count: 1,
+            active_days: days.len() as u32,
+        }]
+    } else {
+        provider
+            .seats
+            .iter()
+            .enumerate()
+            .map(|(idx, seat)| SeatSlot {
+                label: seat
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("seat-{}", idx + 1)),
+                seat: Some(seat),
+                count: seat.count,
+                active_days: days.iter().filter(|day| seat.is_active_on(**day)).count() as u32,
+            })
+            .collect()
+    };
+
+    let seat_days: u64 = slots
+        .iter()
+        .map(|slot| slot.count as u64 * slot.active_days as u64)
+        .sum();
+    slots
+        .into_iter()
+        .filter(|slot| slot.active_days > 0 && slot.count > 0)
+        .map(|slot| {
+            let share = (slot.count as u64 * slot.active_days as u64) as f64 / seat_days as f64;
+            let seat_tokens = (tokens as f64 * share).round() as u64;
+            let seat_api_cost = api_cost * share;
+            // Seats times the fraction of the month they were active.
+            let seat_months = slot.count as f64 * slot.active_days as f64 / days.len() as f64;
+            analyze_seat(provider, &slot, seat_months, seat_tokens, seat_api_cost)
+        })
+        .collect()
+}
+
+fn analyze_seat(
+    provider: &ProviderPricing,
+    slot: &SeatSlot,
+    seat_months: f64,
+    tokens: u64,
+    api_cost: f64,
+) -> SeatPlanAnalysis {
+    let plan = provider.plan_for(slot.seat);
+    let subscription_usd = provider.seat_usd_month(slot.seat) * seat_months;
+    let included_tokens = plan.and_then(|(_, plan)| quota_tokens(plan, seat_months));
+    // A blocked plan that ran out of quota bills nothing more, but it did not cover the usage,
+    // so it can be neither kept nor recommended.
+    let overage = plan.map(|(_, plan)| plan_overage_usd(plan, seat_months, tokens, api_cost));
+    let current_fits = !matches!(overage, Some(None));
+    let overage_usd = overage.flatten().unwrap_or(0.0);
+    // A seat with neither a plan nor a price is paying API list rates today.
+    let on_api = plan.is_none() && subscription_usd <= 0.0;
+    let plan_cost_usd = if on_api {
+        api_cost
+    } else {
+        subscription_usd + overage_usd
+    };
+    let break_even_tokens = (!on_api && tokens > 0 && api_cost > 0.0)
+        .then(|| (subscription_usd / (api_cost / tokens as f64)).round() as u64);
+
+    let current = match plan {
+        Some((name, _)) => name,
+        None if on_api => API_OPTION,
+        None => "current",
+    };
+    let mut best = if current_fits {
+        (current.to_string(), plan_cost_usd)
+    } else {
+        (API_OPTION.to_string(), api_cost)
+    };
+    let mut alternatives: Vec<(&String, &SubscriptionPlan)> = provider.plans.iter().collect();
+    alternatives.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, alternative) in alternatives {
+        let Some(overage) = plan_overage_usd(alternative, seat_months, tokens, api_cost) else {
+            continue;
+        };
+        let cost = alternative.usd_month * seat_months + overage;
+        if cost < best.1 - 0.005 {
+            best = (name.clone(), cost);
+        }
+    }
+    if api_cost < best.1 - 0.005 {
+        best = (API_OPTION.to_string(), api_cost);
+    }
+
+    let sym = currency_symbol(provider.currency());
+    let saves = money(&sym, round2(round2(plan_cost_usd) - round2(best.1)));
+    let switch = if best.0 == API_OPTION {
+        format!(
+            "drop the subscription and pay API list rates (saves {})",
+            saves
+        )
+    } else {
+        format!("switch to {} (saves {})", best.0, saves)
+    };
+    let recommendation = if best.0 == current && on_api {
+        "keep paying API list rates".to_string()
+    } else if best.0 == current {
+        format!("keep {}", current)
+    } else if !current_fits {
+        format!("usage exceeds the {} quota; {}", current, switch)
+    } else {
+        switch
+    };
+
+    SeatPlanAnalysis {
+        seat: slot.label.clone(),
+        plan: plan.map(|(name, _)| name.to_string()),
+        count: slot.count,
+        active_days: slot.active_days,
+        tokens,
+        included_tokens,
+        utilization_pct: included_tokens
+            .filter(|quota| *quota > 0)
+            .map(|quota| round2(tokens as f64 / quota as f64 * 100.0)),
+        quota_exceeded: included_tokens.is_some_and(|quota| tokens > quota),
+        break_even_tokens,
+        subscription_usd: round2(subscription_usd),
+        overage_usd: round2(overage_usd),
+        plan_cost_usd: round2(plan_cost_usd),
+        api_list_cost_usd: round2(api_cost),
+        savings_usd: round2(round2(api_cost) - round2(plan_cost_usd)),
+        best_option: best.0,
+        best_option_usd: round2(best.1),
+        recommendation,
+    }
+}
+
+fn quota_tokens(plan: &SubscriptionPlan, seat_months: f64) -> Option<u64> {
+    plan.included_tokens
+        .map(|quota| (quota as f64 * seat_months).round() as u64)
+}
+
+// Cost of usage past the plan quota; list-rate overage uses the seat's observed $/token.
+// None when the plan blocks usage past a quota the seat exceeded.
+fn plan_overage_usd(
+    plan: &SubscriptionPlan,
+    seat_months: f64,
+    tokens: u64,
+    api_cost: f64,
+) -> Option<f64> {
+    let Some(quota) = quota_tokens(plan, seat_months) else {
+        return Some(0.0);
+    };
+    let excess = tokens.saturating_sub(quota);
+    if excess == 0 {
+        return Some(0.0);
+    }
+    match plan.overage {
+        PlanOverage::Blocked => None,
+        PlanOverage::ListRates => Some(api_cost * excess as f64 / tokens as f64),
+        PlanOverage::PerMtok => {
+            Some(excess as f64 / MTOK * plan.overage_usd_per_mtok.unwrap_or(0.0))
+        }
+    }
+}
+
+// "$19.27" or "-$19.27".
+fn money(sym: &str, amount: f64) -> String {
+    if amount < 0.0 {
+        format!("-{}{:.2}", sym, -amount)
+    } else {
+        format!("{}{:.2}", sym, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn event(provider: &str, tokens: u64) -> UsageEvent {
+        UsageEvent {
+            provider: provider.to_string(),
+            model: "m".to_string(),
+            session_id: "s1".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap(),
+            usage: TokenUsage {
+                input_tokens: tokens,
+                output_tokens: 0,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 0,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn plan(
+        usd_month: f64,
+        included_tokens: Option<u64>,
+        overage: PlanOverage,
+    ) -> SubscriptionPlan {
+        SubscriptionPlan {
+            usd_month,
+            included_tokens,
+            overage,
+            overage_usd_per_mtok: Some(2.0),
+            windows: Vec::new(),
+        }
+    }
+
+    fn provider(
+        subscription_usd_month: f64,
+        plan_name: Option<&str>,
+        seats: Vec<SubscriptionSeat>,
+    ) -> ProviderPricing {
+        ProviderPricing {
+            currency: None,
+            subscription_usd_month,
+            models: HashMap::from([(
+                "m".to_string(),
+                ModelRate {
+                    input_usd_per_mtok: 10.0,
+                    output_usd_per_mtok: 10.0,
+                    cache_write_usd_per_mtok: None,
+                    cache_write_1h_usd_per_mtok: None,
+                    cache_read_usd_per_mtok: None,
+                    tool_input_usd_per_mtok: None,
+                    tool_output_usd_per_mtok: None,
+                    effective_from: None,
+                    effective_to: None,
+                    context_tiers: Vec::new(),
+                }
+                .into(),
+            )]),
+            model_aliases: HashMap::new(),
+            service_tiers: HashMap::new(),
+            allocation: None,
+            seats,
+            plan: plan_name.map(str::to_string),
+            plans: HashMap::from([
+                (
+                    "pro".to_string(),
+                    plan(20.0, Some(4_000_000), PlanOverage::PerMtok),
+                ),
+                ("max".to_string(), plan(100.0, None, PlanOverage::Blocked)),
+            ]),
+            contract: None,
+        }
+    }
+
+    fn book(providers: Vec<(&str, ProviderPricing)>) -> PricingBook {
+        PricingBook {
+            providers: providers
+                .into_iter()
+                .map(|(name, provider)| (name.to_string(), provider))
+                .collect(),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_analysis_splits_usage_by_seat_days() {
+        let seats = vec![
+            SubscriptionSeat {
+                name: Some("alice".to_string()),
+                usd_month: None,
+                count: 1,
+                start: None,
+                end: None,
+                plan: None,
+            },
+            SubscriptionSeat {
+                name: Some("bob".to_string()),
+                usd_month: None,
+                count: 1,
+                start: NaiveDate::from_ymd_opt(2026, 2, 15),
+                end: None,
+                plan: None,
+            },
+        ];
+        let pricing = book(vec![("claude", provider(0.0, Some("pro"), seats))]);
+        let report = build_plan_analysis(&[event("claude", 3_000_000)], &pricing, (2026, 2));
+
+        let claude = &report.providers[0];
+        assert_eq!(claude.api_list_cost_usd, 30.0);
+        assert_eq!(claude.subscription_usd, 30.0);
+        let alice = &claude.seats[0];
+        assert_eq!(alice.tokens, 2_000_000);
+        assert_eq!(alice.included_tokens, Some(4_000_000));
+        assert_eq!(alice.utilization_pct, Some(50.0));
+        assert_eq!(alice.break_even_tokens, Some(2_000_000));
+        assert_eq!(alice.recommendation, "keep pro");
+        let bob = &claude.seats[1];
+        assert_eq!((bob.active_days, bob.tokens), (14, 1_000_000));
+        assert_eq!(bob.subscription_usd, 10.0);
+        assert_eq!(bob.included_tokens, Some(2_000_000));
+    }
+
+    #[test]
+    fn test_plan_analysis_recommends_cheapest_option() {
+        let pricing = book(vec![
+            ("light", provider(0.0, Some("pro"), Vec::new())),
+            ("heavy", provider(0.0, Some("pro"), Vec::new())),
+            ("payg", provider(0.0, None, Vec::new())),
+            ("unused", provider(0.0, None, Vec::new())),
+        ]);
+        let events = vec![
+            event("light", 500_000),
+            event("heavy", 60_000_000),
+            event("payg", 5_000_000),
+        ];
+        let report = build_plan_analysis(&events, &pricing, (2026, 2));
+        let seat = |name: &str| {
+            report
+                .providers
+                .iter()
+                .find(|provider| provider.provider == name)
+                .map(|provider| provider.seats[0].clone())
+                .unwrap()
+        };
+
+        let light = seat("light");
+        assert_eq!(light.best_option, "api");
+        assert_eq!(
+            light.recommendation,
+            "drop the subscription and pay API list rates (saves $15.00)"
+        );
+
+        // 56M tokens over the pro quota at $2/MTok overage outgrow the unlimited max plan.
+        let heavy = seat("heavy");
+        assert!(heavy.quota_exceeded);
+        assert_eq!(heavy.overage_usd, 112.0);
+        assert_eq!(heavy.best_option, "max");
+        assert_eq!(heavy.recommendation, "switch to max (saves $32.00)");
+
+        let payg = seat("payg");
+        assert_eq!(payg.plan, None);
+        assert_eq!(payg.plan_cost_usd, 50.0);
+        assert_eq!(payg.break_even_tokens, None);
+        assert_eq!(payg.recommendation, "switch to pro (saves $28.00)");
+
+        assert_eq!(seat("unused").recommendation, "keep paying API list rates");
+    }
+
+    #[test]
+    fn test_plan_analysis_never_keeps_a_blocked_plan_over_quota() {
+        let mut capped = provider(0.0, Some("capped"), Vec::new());
+        capped.currency = Some("EUR".to_string());
+        capped.plans.insert(
+            "capped".to_string(),
+            plan(30.0, Some(1_000_000), PlanOverage::Blocked),
+        );
+        // 8M tokens at the 10/MTok list rate cost 80.00; pro pays 20.00 plus 8.00 overage.
+        let report = build_plan_analysis(
+            &[event("claude", 8_000_000)],
+            &book(vec![("claude", capped)]),
+            (2026, 2),
+        );
+        let claude = &report.providers[0];
+        assert_eq!(claude.currency, "EUR");
+        let seat = &claude.seats[0];
+        assert!(seat.quota_exceeded);
+        assert_eq!((seat.overage_usd, seat.plan_cost_usd), (0.0, 30.0));
+        assert_eq!(seat.best_option, "pro");
+        assert_eq!(
+            seat.recommendation,
+            "usage exceeds the capped quota; switch to pro (saves €2.00)"
+        );
+
+        // When nothing that fits is cheaper, the seat moves off the plan even though it costs more.
+        let mut capped = provider(0.0, Some("capped"), Vec::new());
+        capped.plans = HashMap::from([
+            (
+                "capped".to_string(),
+                plan(30.0, Some(1_000_000), PlanOverage::Blocked),
+            ),
+            ("max".to_string(), plan(100.0, None, PlanOverage::Blocked)),
+        ]);
+        let report = build_plan_analysis(
+            &[event("claude", 8_000_000)],
+            &book(vec![("claude", capped)]),
+            (2026, 2),
+        );
+        let seat = &report.providers[0].seats[0];
+        assert_eq!(seat.best_option, "api");
+        assert_eq!(
+            seat.recommendation,
+            "usage exceeds the capped quota; drop the subscription and pay API list rates (saves -$50.00)"
+        );
+    }
+}
@@ -7,8 +7,10 @@ use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 
 use crate::cli::{
-    PricingApplyArgs, PricingAuditArgs, PricingCheckArgs, PricingLintArgs, PricingReconcileArgs,
+    OutputMode, PricingApplyArgs, PricingAuditArgs, PricingCheckArgs, PricingLintArgs,
+    PricingReconcileArgs,
 };
+use crate::export::{pricing_audit_tables, write_export};
 use crate::models::*;
 use crate::utils::*;
 
@@ -53,6 +55,7 @@ pub fn run_pricing_apply(args: PricingApplyArgs) -> Result<()> {
         args.dry_run,
         args.write_backup,
         args.allow_overwrite_model_rates,
+        args.effective_from,
     )?;
     println!("{}", serde_json::to_string_pretty(&apply_result.summary)?);
     Ok(())
@@ -100,6 +103,7 @@ pub fn execute_pricing_reconcile(args: PricingReconcileArgs) -> Result<PricingRe
         args.dry_run,
         args.write_backup,
         args.allow_overwrite_model_rates,
+        None,
     )?;
     let metadata_updated = if !args.dry_run && apply_result.wrote_pricing {
         stamp_reconcile_metadata(&args.pricing)?
@@ -169,15 +173,31 @@ pub fn run_pricing_lint(args: PricingLintArgs) -> Result<()> {
     let pricing = load_pricing(&args.pricing)?;
     let mut violations = collect_pricing_placeholder_violations(&pricing);
     violations.sort();
+    let period_violations = collect_rate_period_violations(&pricing);
+    let tier_violations = collect_context_tier_violations(&pricing);
 
     let summary = PricingLintSummary {
         pricing: args.pricing.display().to_string(),
         alias_integrity_ok: true,
         placeholder_violations: violations.clone(),
         allow_placeholders: args.allow_placeholders,
+        rate_period_violations: period_violations.clone(),
+        context_tier_violations: tier_violations.clone(),
     };
     println!("{}", serde_json::to_string_pretty(&summary)?);
 
+    if !period_violations.is_empty() {
+        return Err(anyhow!(
+            "pricing-lint failed: found {} rate period gap/overlap violation(s)",
+            period_violations.len()
+        ));
+    }
+    if !tier_violations.is_empty() {
+        return Err(anyhow!(
+            "pricing-lint failed: found {} context tier violation(s)",
+            tier_violations.len()
+        ));
+    }
     if !violations.is_empty() && !args.allow_placeholders {
         return Err(anyhow!(
             "pricing-lint failed: found {} placeholder violation(s); re-run with --allow-placeholders to continue",
@@ -189,10 +209,22 @@ pub fn run_pricing_lint(args: PricingLintArgs) -> Result<()> {
 
 pub fn run_pricing_audit(args: PricingAuditArgs) -> Result<()> {
     let report = execute_pricing_audit(&args)?;
-    if args.json_output {
-        println!("{}", serde_json::to_string_pretty(&report)?);
+    let output = if args.json_output {
+        OutputMode::Json
     } else {
-        print_pricing_audit_report(&report);
+        args.output
+    };
+    match output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_pricing_audit_report(&report),
+        OutputMode::Csv | OutputMode::Xlsx => {
+            write_export(&pricing_audit_tables(&report), output, &args.export)?
+        }
+        OutputMode::Markdown | OutputMode::Html => {
+            return Err(anyhow!(
+                "pricing-audit supports table, json, csv and xlsx output"
+            ))
+        }
     }
 
     if !report.pass {
@@ -406,6 +438,9 @@ pub fn build_coverage_report(events: &[UsageEvent], pricing: &PricingBook) -> Co
             })
             .collect();
 
+    let long_context_by_model = crate::cost::collect_long_context_usage(events, pricing);
+    let long_context_events = long_context_by_model.iter().map(|usage| usage.events).sum();
+
     CoverageReport {
         month,
         totals: CoverageTotals {
@@ -418,6 +453,8 @@ pub fn build_coverage_report(events: &[UsageEvent], pricing: &PricingBook) -> Co
         missing_models_by_provider,
         suggested_provider_aliases,
         suggested_model_aliases_by_provider,
+        long_context_events,
+        long_context_by_model,
     }
 }
 
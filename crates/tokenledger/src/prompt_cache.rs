@@ -0,0 +1,306 @@
+// Prompt-cache efficiency: how much input was served from cache, what reads saved and writes
+// cost against paying the full input rate, and which sessions wrote cache nothing read back.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+
+use crate::analytics::stream_query;
+use crate::cli::{CacheArgs, OutputMode};
+use crate::export::{cache_tables, write_export};
+use crate::format::round4;
+use crate::fx::CurrencyConverter;
+use crate::models::*;
+use crate::money::*;
+use crate::period::TimeRange;
+use crate::utils::print_cache_table;
+
+// Rows of `unread_sessions` kept in a report, largest write premium first.
+const UNREAD_SESSION_LIMIT: usize = 20;
+
+pub fn run_cache(args: CacheArgs) -> Result<()> {
+    let range = TimeRange::from_args(&args.period, Utc::now())?;
+    // Unread sessions are only counted by a stream that keeps sessions apart.
+    let (stream, view) = stream_query(&args.query, &range, false, true)?;
+    let report = stream.totals(&view)?;
+    let Some(cache) = report.cache else {
+        return Err(anyhow!(
+            "no prompt-cache reads or writes in the selected events"
+        ));
+    };
+    match args.query.output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&cache)?),
+        OutputMode::Table => print_cache_table(&cache, &report.currency),
+        OutputMode::Csv | OutputMode::Xlsx => {
+            write_export(&cache_tables(&cache), args.query.output, &args.query.export)?
+        }
+        OutputMode::Markdown | OutputMode::Html => {
+            return Err(anyhow!("cache supports table, json, csv and xlsx output"))
+        }
+    }
+    Ok(())
+}
+
+// (provider, model, session id).
+pub type CacheSessionKey = (String, String, String);
+
+// One session's cache traffic on one model.
+#[derive(Debug, Clone, Default)]
+pub struct CacheSession {
+    pub write_tokens: u64,
+    pub read_tokens: u64,
+    pub write_premium_micros: Micros,
+}
+
+impl CacheSession {
+    // None for events that neither read nor wrote the cache.
+    pub fn of_event(evt: &UsageEvent, write_premium_micros: Micros) -> Option<Self> {
+        let write_tokens = evt.usage.cache_write_tokens + evt.usage.cache_write_1h_tokens;
+        if write_tokens == 0 && evt.usage.cache_read_tokens == 0 {
+            return None;
+        }
+        Some(Self {
+            write_tokens,
+            read_tokens: evt.usage.cache_read_tokens,
+            write_premium_micros,
+        })
+    }
+
+    pub fn key(evt: &UsageEvent) -> CacheSessionKey {
+        (
+            evt.provider.clone(),
+            evt.model.clone(),
+            evt.session_id.clone(),
+        )
+    }
+
+    pub fn merge(&mut self, other: &CacheSession) {
+        self.write_tokens += other.write_tokens;
+        self.read_tokens += other.read_tokens;
+        self.write_premium_micros += other.write_premium_micros;
+    }
+
+    fn unread(&self) -> bool {
+        self.write_tokens > 0 && self.read_tokens == 0
+    }
+}
+
+// The cache section of a report; None when no event touched the cache.
+pub fn build_cache_report(
+    global: &Acc,
+    by_provider: &BTreeMap<String, Acc>,
+    by_model: &BTreeMap<String, Acc>,
+    sessions: Option<&BTreeMap<CacheSessionKey, CacheSession>>,
+) -> Option<CacheReport> {
+    if cache_write_tokens(global) + global.cache_read_tokens == 0 {
+        return None;
+    }
+    let mut unread_by_provider: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut unread_by_model: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut unread_sessions = Vec::new();
+    for ((provider, model, session_id), session) in sessions.into_iter().flatten() {
+        if !session.unread() {
+            continue;
+        }
+        *unread_by_provider.entry(provider).or_default() += 1;
+        *unread_by_model.entry(model).or_default() += 1;
+        unread_sessions.push(UnreadCacheSession {
+            provider: provider.clone(),
+            model: model.clone(),
+            session_id: session_id.clone(),
+            cache_write_tokens: session.write_tokens,
+            write_premium_usd: cents_to_units(to_cents(session.write_premium_micros)),
+        });
+    }
+    let unread_count = sessions.map(|_| unread_sessions.len());
+    unread_sessions.sort_by(|a, b| {
+        b.write_premium_usd
+            .total_cmp(&a.write_premium_usd)
+            .then(b.cache_write_tokens.cmp(&a.cache_write_tokens))
+    });
+    unread_sessions.truncate(UNREAD_SESSION_LIMIT);
+
+    let rows = |items: &BTreeMap<String, Acc>, unread: &BTreeMap<&str, usize>| {
+        items
+            .iter()
+            .filter(|(_, acc)| cache_write_tokens(acc) + acc.cache_read_tokens > 0)
+            .map(|(name, acc)| {
+                let count = sessions.map(|_| unread.get(name.as_str()).copied().unwrap_or(0));
+                cache_metric(name, acc, count)
+            })
+            .collect()
+    };
+    Some(CacheReport {
+        totals: cache_metric("total", global, unread_count),
+        providers: rows(by_provider, &unread_by_provider),
+        models: rows(by_model, &unread_by_model),
+        unread_sessions,
+    })
+}
+
+fn cache_write_tokens(acc: &Acc) -> u64 {
+    acc.cache_write_tokens + acc.cache_write_1h_tokens
+}
+
+fn cache_metric(name: &str, acc: &Acc, unread_sessions: Option<usize>) -> CacheMetric {
+    let writes = cache_write_tokens(acc);
+    let prompt = acc.cache_read_tokens + acc.input_tokens;
+    let hit_ratio = if prompt == 0 {
+        0.0
+    } else {
+        acc.cache_read_tokens as f64 / prompt as f64
+    };
+    let write_amplification =
+        (acc.cache_read_tokens > 0).then(|| round4(writes as f64 / acc.cache_read_tokens as f64));
+    CacheMetric {
+        name: name.to_string(),
+        input_tokens: acc.input_tokens,
+        cache_read_tokens: acc.cache_read_tokens,
+        cache_write_tokens: writes,
+        hit_ratio: round4(hit_ratio),
+        write_amplification,
+        read_savings_usd: cents_to_units(to_cents(acc.cache_read_savings_micros)),
+        write_premium_usd: cents_to_units(to_cents(acc.cache_write_premium_micros)),
+        net_savings_usd: cents_to_units(to_cents(
+            acc.cache_read_savings_micros - acc.cache_write_premium_micros,
+        )),
+        unread_sessions,
+    }
+}
+
+// Tips on top of `make_suggestions` for caches that cost more than they return.
+pub fn cache_suggestions(cache: &CacheReport, money: &CurrencyConverter) -> Vec<String> {
+    let mut tips = Vec::new();
+    let totals = &cache.totals;
+    if let Some(unread) = totals.unread_sessions.filter(|count| *count > 0) {
+        tips.push(format!(
+            "{} session(s) wrote prompt cache that was never read; drop cache breakpoints on one-shot prompts and keep follow-up turns on the same model.",
+            unread
+        ));
+    }
+    if totals.write_amplification.is_some_and(|ratio| ratio > 1.0) {
+        tips.push(format!(
+            "Cache writes outnumber reads ({:.1}x): cache only stable prefixes such as system prompts and tool definitions.",
+            totals.write_amplification.unwrap_or_default()
+        ));
+    }
+    if totals.net_savings_usd < 0.0 {
+        tips.push(format!(
+            "Prompt caching cost {}{:.2} more than uncached input this period; shorten cache TTLs or stop caching low-reuse prompts.",
+            money.symbol(),
+            -totals.net_savings_usd
+        ));
+    }
+    tips
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::compute_costs;
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+    use ParetoRs::OnUnpricedAction;
+
+    fn event(model: &str, session: &str, input: u64, write: u64, read: u64) -> UsageEvent {
+        UsageEvent {
+            provider: "anthropic".to_string(),
+            model: model.to_string(),
+            session_id: session.to_string(),
+            timestamp: Utc.with_ymd_and_hms(2026, 2, 3, 10, 0, 0).unwrap(),
+            usage: TokenUsage {
+                input_tokens: input,
+                output_tokens: 0,
+                cache_write_tokens: write,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: read,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn rate(input: f64) -> ModelRateSchedule {
+        ModelRate {
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: input * 5.0,
+            cache_write_usd_per_mtok: Some(input * 1.25),
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: Some(input * 0.1),
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        }
+        .into()
+    }
+
+    fn pricing() -> PricingBook {
+        PricingBook {
+            providers: HashMap::from([(
+                "anthropic".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 0.0,
+                    models: HashMap::from([
+                        ("sonnet".to_string(), rate(3.0)),
+                        ("haiku".to_string(), rate(1.0)),
+                    ]),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::new(),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        }
+    }
+
+    #[test]
+    fn cache_report_prices_savings_against_input_rate() {
+        let events = vec![
+            event("sonnet", "s1", 1_000_000, 1_000_000, 0),
+            event("sonnet", "s1", 0, 0, 3_000_000),
+            event("haiku", "s2", 500_000, 2_000_000, 0),
+        ];
+        let report = compute_costs(&events, &pricing(), OnUnpricedAction::Error).unwrap();
+        let cache = report.cache.expect("cache section");
+
+        let totals = &cache.totals;
+        assert_eq!(totals.cache_write_tokens, 3_000_000);
+        assert_eq!(totals.cache_read_tokens, 3_000_000);
+        assert_eq!(totals.hit_ratio, 0.6667);
+        assert_eq!(totals.write_amplification, Some(1.0));
+        // 3M reads at $3.00 - $0.30; writes pay $0.75 (sonnet) and $0.50 (haiku) above input.
+        assert_eq!(totals.read_savings_usd, 8.1);
+        assert_eq!(totals.write_premium_usd, 1.25);
+        assert_eq!(totals.net_savings_usd, 6.85);
+        assert_eq!(totals.unread_sessions, Some(1));
+
+        let haiku = cache.models.iter().find(|m| m.name == "haiku").unwrap();
+        assert_eq!(haiku.write_amplification, None);
+        assert_eq!(haiku.net_savings_usd, -0.5);
+        assert_eq!(cache.unread_sessions.len(), 1);
+        assert_eq!(cache.unread_sessions[0].session_id, "s2");
+        assert_eq!(cache.unread_sessions[0].write_premium_usd, 0.5);
+        assert!(report
+            .suggestions
+            .iter()
+            .any(|tip| tip.contains("never read")));
+    }
+
+    #[test]
+    fn cache_section_absent_without_cache_traffic() {
+        let events = vec![event("sonnet", "s1", 1_000_000, 0, 0)];
+        let report = compute_costs(&events, &pricing(), OnUnpricedAction::Error).unwrap();
+        assert!(report.cache.is_none());
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Provides deterministic mapping from source model IDs to canonical forms.
 
+use std::collections::HashMap;
+
 use super::ports::*;
 
 // =============================================================================
@@ -136,6 +138,68 @@ pub fn resolve_harness(_model_id: &str, _headers: Option<&str>) -> (Option<Strin
     (None, MappingRule::None)
 }
 
+// =============================================================================
+// LEDGER PROVIDER RULES
+// =============================================================================
+
+/// Slug prefix rules used by the unified ledger: (prefix, rule, provider, confidence)
+pub const LEDGER_PROVIDER_RULES: &[(&str, &str, &str, u8)] = &[
+    ("claude", "prefix:claude", "claude", 99),
+    ("gpt", "prefix:gpt", "codex", 95),
+    ("gemini", "prefix:gemini", "google", 95),
+    ("grok", "prefix:grok", "xai", 95),
+    ("qwen", "prefix:qwen", "qwen", 95),
+    ("deepseek", "prefix:deepseek", "deepseek", 95),
+    ("minimax", "prefix:minimax", "minimax", 95),
+    ("kimi", "prefix:kimi", "kimi", 95),
+    ("glm", "prefix:glm", "zhipu", 95),
+    ("step", "prefix:step", "stepfun", 95),
+    ("devstral", "prefix:devstral", "mistral", 95),
+];
+
+/// Lowercase slug with runs of non-alphanumerics collapsed to a single dash
+pub fn slugify_model(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for ch in value.trim().chars() {
+        if ch.is_ascii_alphanumeric() {
+            out.push(ch.to_ascii_lowercase());
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_matches('-').to_string()
+}
+
+/// Infer the pricing provider for a source model name.
+///
+/// Returns (provider, rule, confidence); unknown models map to `heuristic:unknown` with 0.
+pub fn infer_ledger_provider(
+    source_model: &str,
+    provider_aliases: &HashMap<String, String>,
+) -> (String, String, u8) {
+    let slug = slugify_model(source_model);
+
+    if let Some(provider) = provider_aliases
+        .iter()
+        .find(|(alias, _)| slugify_model(alias) == slug)
+        .map(|(_, provider)| provider)
+    {
+        return (provider.clone(), "pricing:provider_alias".to_string(), 100);
+    }
+
+    for (prefix, rule, provider, confidence) in LEDGER_PROVIDER_RULES {
+        let matches = slug == *prefix
+            || slug
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('-'));
+        if matches {
+            return (provider.to_string(), rule.to_string(), *confidence);
+        }
+    }
+
+    ("unknown".to_string(), "heuristic:unknown".to_string(), 0)
+}
+
 // =============================================================================
 // CONVENIENCE FUNCTIONS
 // =============================================================================
@@ -228,4 +292,36 @@ mod tests {
         assert_eq!(mapping.canonical_model, "gpt-4o");
         assert_eq!(mapping.provider, Some("openai".to_string()));
     }
+
+    #[test]
+    fn test_slugify_model() {
+        assert_eq!(
+            slugify_model("Claude Opus 4.6 (Thinking: Max)"),
+            "claude-opus-4-6-thinking-max"
+        );
+        assert_eq!(slugify_model("  GPT-5.3 Codex  "), "gpt-5-3-codex");
+    }
+
+    #[test]
+    fn test_infer_ledger_provider() {
+        let aliases = HashMap::new();
+        assert_eq!(
+            infer_ledger_provider("GPT-5 mini", &aliases),
+            ("codex".to_string(), "prefix:gpt".to_string(), 95)
+        );
+        assert_eq!(
+            infer_ledger_provider("Stepfun", &aliases),
+            ("unknown".to_string(), "heuristic:unknown".to_string(), 0)
+        );
+
+        let aliases = HashMap::from([("Sonnet".to_string(), "claude".to_string())]);
+        assert_eq!(
+            infer_ledger_provider("sonnet", &aliases),
+            (
+                "claude".to_string(),
+                "pricing:provider_alias".to_string(),
+                100
+            )
+        );
+    }
 }
@@ -0,0 +1,465 @@
+// Per-session cost: each session's usage, time span and share of the period's cost, and one
+// session's event-by-event timeline, for finding runaway agent loops.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+
+use crate::analytics::{build_session_report, open_stream};
+use crate::cli::{
+    OutputMode, QueryArgs, SessionShowArgs, SessionSort, SessionsArgs, SessionsCommand,
+};
+use crate::cost::{build_breakdown, event_cost, merge_acc, merge_partial};
+use crate::export::{session_tables, session_timeline_tables, write_export};
+use crate::format::round4;
+use crate::models::*;
+use crate::money::*;
+use crate::period::TimeRange;
+use crate::stream::{CostStream, ReportView};
+use crate::utils::{
+    for_each_jsonl_event, print_session_table, print_session_timeline, resolve_model_alias,
+    resolve_provider_alias,
+};
+
+pub fn run_sessions(args: SessionsArgs) -> Result<()> {
+    if let Some(SessionsCommand::Show(show)) = args.command {
+        return run_session_show(*show);
+    }
+    let range = TimeRange::from_args(&args.period, Utc::now())?;
+    let mut report = build_session_report(&args.query, &range)?;
+    sort_sessions(&mut report.sessions, args.sort);
+    if let Some(min_cost) = args.min_cost {
+        report
+            .sessions
+            .retain(|session| session.total_cost_usd >= min_cost);
+    }
+    if let Some(top) = args.top {
+        report.sessions.truncate(top);
+    }
+    match args.query.output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_session_table(&report),
+        OutputMode::Csv | OutputMode::Xlsx => write_export(
+            &session_tables(&report),
+            args.query.output,
+            &args.query.export,
+        )?,
+        OutputMode::Markdown | OutputMode::Html => {
+            return Err(anyhow!(
+                "sessions supports table, json, csv and xlsx output"
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn run_session_show(args: SessionShowArgs) -> Result<()> {
+    let range = TimeRange::from_args(&args.period, Utc::now())?;
+    let timeline = build_session_timeline(&args.query, &range, &args.session_id)?;
+    match args.query.output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&timeline)?),
+        OutputMode::Table => print_session_timeline(&timeline),
+        OutputMode::Csv | OutputMode::Xlsx => write_export(
+            &session_timeline_tables(&timeline),
+            args.query.output,
+            &args.query.export,
+        )?,
+        OutputMode::Markdown | OutputMode::Html => {
+            return Err(anyhow!(
+                "sessions show supports table, json, csv and xlsx output"
+            ))
+        }
+    }
+    Ok(())
+}
+
+// The session's summaries come from the same streamed read as the full session report, so its
+// costs match `sessions`; only the session's own events are kept.
+pub fn build_session_timeline(
+    query: &QueryArgs,
+    range: &TimeRange,
+    session_id: &str,
+) -> Result<SessionTimeline> {
+    let (mut stream, view) = open_stream(query, range, false)?;
+    stream.track_sessions();
+    let mut events = Vec::new();
+    for path in &query.events {
+        for_each_jsonl_event(path, |evt| {
+            if evt.session_id == session_id {
+                events.push(evt.clone());
+            }
+            stream.observe(evt);
+            Ok(())
+        })?;
+    }
+    let report = stream.sessions(&view)?;
+    let sessions: Vec<SessionSummary> = report
+        .sessions
+        .into_iter()
+        .filter(|session| session.session_id == session_id)
+        .collect();
+    if sessions.is_empty() {
+        return Err(anyhow!(
+            "no priced events for session '{}' in the selected period",
+            session_id
+        ));
+    }
+    let variable_cents = sessions
+        .iter()
+        .map(|session| units_to_cents(session.variable_cost_usd))
+        .sum();
+    Ok(SessionTimeline {
+        period: report.period,
+        currency: report.currency,
+        session_id: session_id.to_string(),
+        sessions,
+        events: timeline_events(events, &stream, &view, variable_cents)?,
+    })
+}
+
+// The events in the stream's range and view, oldest first, with cents apportioned from the
+// sessions' variable cost.
+fn timeline_events(
+    events: Vec<UsageEvent>,
+    stream: &CostStream,
+    view: &ReportView,
+    variable_cents: i64,
+) -> Result<Vec<SessionEvent>> {
+    let pricing = stream.pricing();
+    let mut priced = Vec::new();
+    for mut evt in events {
+        evt.provider = resolve_provider_alias(&evt.provider, pricing);
+        evt.model = resolve_model_alias(&evt.provider, &evt.model, pricing);
+        if !stream.range().contains(evt.timestamp) || !view.selects(&evt.provider, &evt.model) {
+            continue;
+        }
+        let variable = match event_cost(&evt, pricing) {
+            Some(cost) => Some(scale_micros(
+                cost.variable,
+                stream
+                    .money()
+                    .factor(cost.provider.currency(), evt.timestamp)?,
+            )),
+            None => None,
+        };
+        priced.push((evt, variable));
+    }
+    priced.sort_by_key(|(evt, _)| evt.timestamp);
+
+    let micros: Vec<Micros> = priced
+        .iter()
+        .map(|(_, variable)| variable.unwrap_or(0))
+        .collect();
+    let cents = round_parts(&micros, MICROS_PER_CENT, variable_cents);
+    let mut previous: Option<DateTime<Utc>> = None;
+    let mut cumulative = 0;
+    Ok(priced
+        .into_iter()
+        .zip(cents)
+        .map(|((evt, variable), cents)| {
+            cumulative += cents;
+            let gap = previous.map_or(0, |at| (evt.timestamp - at).num_seconds());
+            previous = Some(evt.timestamp);
+            SessionEvent {
+                timestamp: evt.timestamp,
+                gap_secs: gap,
+                service_tier: evt.service_tier_name().to_string(),
+                provider: evt.provider,
+                model: evt.model,
+                usage: evt.usage,
+                variable_cost_usd: variable.map(|_| cents_to_units(cents)),
+                cumulative_cost_usd: cents_to_units(cumulative),
+            }
+        })
+        .collect())
+}
+
+// One session's events in one allocation cell: usage, allocation weight and time span.
+#[derive(Debug, Clone, Default)]
+pub struct SessionPart {
+    pub usage: Acc,
+    pub weight: u128,
+    pub events: u64,
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+}
+
+impl SessionPart {
+    pub fn add(&mut self, evt: &UsageEvent, variable: Micros, weight: u128) {
+        merge_acc(&mut self.usage, evt, variable, 0);
+        self.weight += weight;
+        self.events += 1;
+        self.first = Some(self.first.map_or(evt.timestamp, |at| at.min(evt.timestamp)));
+        self.last = self.last.max(Some(evt.timestamp));
+    }
+
+    fn merge(&mut self, other: &SessionPart) {
+        merge_partial(&mut self.usage, &other.usage);
+        self.weight += other.weight;
+        self.events += other.events;
+        self.first = match (self.first, other.first) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.last = self.last.max(other.last);
+    }
+}
+
+// Sessions being summed, keyed by (provider, session id), with their usage per model.
+#[derive(Debug, Default)]
+pub struct SessionAcc {
+    sessions: BTreeMap<(String, String), (SessionPart, BTreeMap<String, Acc>)>,
+}
+
+impl SessionAcc {
+    pub fn add(
+        &mut self,
+        provider: &str,
+        model: &str,
+        session_id: &str,
+        part: &SessionPart,
+        subscription: Micros,
+    ) {
+        let (session, models) = self
+            .sessions
+            .entry((provider.to_string(), session_id.to_string()))
+            .or_default();
+        session.merge(part);
+        session.usage.subscription_allocated_micros += subscription;
+        let acc = models.entry(model.to_string()).or_default();
+        merge_partial(acc, &part.usage);
+        acc.subscription_allocated_micros += subscription;
+    }
+
+    // Sessions costliest first, with cents that add up to the given totals.
+    pub fn finish(
+        self,
+        period: String,
+        currency: String,
+        variable_cents: i64,
+        subscription_cents: i64,
+    ) -> SessionReport {
+        let variable: Vec<Micros> = self
+            .sessions
+            .values()
+            .map(|(session, _)| session.usage.variable_cost_micros)
+            .collect();
+        let subscription: Vec<Micros> = self
+            .sessions
+            .values()
+            .map(|(session, _)| session.usage.subscription_allocated_micros)
+            .collect();
+        let variable = round_parts(&variable, MICROS_PER_CENT, variable_cents);
+        let subscription = round_parts(&subscription, MICROS_PER_CENT, subscription_cents);
+        let mut sessions: Vec<SessionSummary> = self
+            .sessions
+            .into_iter()
+            .zip(variable.into_iter().zip(subscription))
+            .map(|(((provider, session_id), (session, models)), cents)| {
+                session_summary(provider, session_id, &session, &models, cents)
+            })
+            .collect();
+        sort_sessions(&mut sessions, SessionSort::Cost);
+        SessionReport {
+            period,
+            currency,
+            session_count: sessions.len(),
+            total_cost_usd: cents_to_units(variable_cents + subscription_cents),
+            sessions,
+        }
+    }
+}
+
+fn session_summary(
+    provider: String,
+    session_id: String,
+    session: &SessionPart,
+    models: &BTreeMap<String, Acc>,
+    (variable_cents, subscription_cents): (i64, i64),
+) -> SessionSummary {
+    let usage = &session.usage;
+    let start = session.first.unwrap_or_default();
+    let end = session.last.unwrap_or_default();
+    let prompt = usage.cache_read_tokens + usage.input_tokens;
+    let cache_hit_ratio = if prompt == 0 {
+        0.0
+    } else {
+        usage.cache_read_tokens as f64 / prompt as f64
+    };
+    let mut models = build_breakdown(models, variable_cents, subscription_cents);
+    models.sort_by(|a, b| b.total_cost_usd.total_cmp(&a.total_cost_usd));
+    SessionSummary {
+        provider,
+        session_id,
+        start,
+        end,
+        duration_secs: (end - start).num_seconds(),
+        event_count: session.events,
+        total_tokens: usage.tokens,
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        cache_write_tokens: usage.cache_write_tokens,
+        cache_write_1h_tokens: usage.cache_write_1h_tokens,
+        cache_read_tokens: usage.cache_read_tokens,
+        tool_input_tokens: usage.tool_input_tokens,
+        tool_output_tokens: usage.tool_output_tokens,
+        variable_cost_usd: cents_to_units(variable_cents),
+        subscription_allocated_usd: cents_to_units(subscription_cents),
+        total_cost_usd: cents_to_units(variable_cents + subscription_cents),
+        cache_hit_ratio: round4(cache_hit_ratio),
+        models,
+    }
+}
+
+// Stable, so ties keep (provider, session id) order.
+pub fn sort_sessions(sessions: &mut [SessionSummary], sort: SessionSort) {
+    match sort {
+        SessionSort::Cost => sessions.sort_by(|a, b| b.total_cost_usd.total_cmp(&a.total_cost_usd)),
+        SessionSort::Tokens => sessions.sort_by_key(|session| Reverse(session.total_tokens)),
+        SessionSort::Events => sessions.sort_by_key(|session| Reverse(session.event_count)),
+        SessionSort::Duration => sessions.sort_by_key(|session| Reverse(session.duration_secs)),
+        SessionSort::Start => sessions.sort_by_key(|session| session.start),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fx::CurrencyConverter;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+    use ParetoRs::OnUnpricedAction;
+
+    fn event(model: &str, session: &str, minute: u32, input: u64, read: u64) -> UsageEvent {
+        UsageEvent {
+            provider: "claude".to_string(),
+            model: model.to_string(),
+            session_id: session.to_string(),
+            timestamp: Utc.with_ymd_and_hms(2026, 3, 2, 23, minute, 0).unwrap(),
+            usage: TokenUsage {
+                input_tokens: input,
+                output_tokens: input / 10,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: read,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn rate(input: f64) -> ModelRateSchedule {
+        ModelRate {
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: input * 5.0,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: Some(input * 0.1),
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        }
+        .into()
+    }
+
+    fn stream(events: &[UsageEvent]) -> CostStream {
+        let pricing = PricingBook {
+            providers: HashMap::from([(
+                "claude".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 10.0,
+                    models: HashMap::from([
+                        ("sonnet".to_string(), rate(3.0)),
+                        ("haiku".to_string(), rate(1.0)),
+                    ]),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::new(),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        };
+        let mut stream = CostStream::new(
+            pricing,
+            CurrencyConverter::usd(),
+            None,
+            TimeRange::month((2026, 3)),
+            false,
+        );
+        stream.track_sessions();
+        for evt in events {
+            stream.observe(evt.clone());
+        }
+        stream
+    }
+
+    #[test]
+    fn test_session_report_splits_period_cost() {
+        let events = vec![
+            event("sonnet", "loop", 5, 333_333, 0),
+            event("haiku", "loop", 50, 333_333, 1_000_000),
+            event("sonnet", "loop", 20, 333_333, 0),
+            event("sonnet", "quick", 10, 100_000, 0),
+            event("opus", "quick", 11, 100_000, 0),
+        ];
+        let stream = stream(&events);
+        let view = ReportView::new(stream.pricing(), &[], &[], OnUnpricedAction::Skip);
+        let totals = stream.totals(&view).unwrap();
+        let report = stream.sessions(&view).unwrap();
+
+        assert_eq!(report.session_count, 2);
+        assert_eq!(report.total_cost_usd, totals.monthly_total_usd);
+        let cents: i64 = report
+            .sessions
+            .iter()
+            .map(|session| units_to_cents(session.total_cost_usd))
+            .sum();
+        assert_eq!(cents, units_to_cents(totals.monthly_total_usd));
+
+        let looped = &report.sessions[0];
+        assert_eq!(looped.session_id, "loop");
+        assert_eq!(looped.event_count, 3);
+        assert_eq!(looped.duration_secs, 45 * 60);
+        assert_eq!(looped.cache_hit_ratio, 0.5);
+        // Haiku's cache reads earn it most of the token-weighted subscription.
+        let models: Vec<&str> = looped.models.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(models, ["haiku", "sonnet"]);
+        let model_cents: i64 = looped
+            .models
+            .iter()
+            .map(|model| units_to_cents(model.total_cost_usd))
+            .sum();
+        assert_eq!(model_cents, units_to_cents(looped.total_cost_usd));
+
+        let mut sessions = report.sessions.clone();
+        sort_sessions(&mut sessions, SessionSort::Start);
+        assert_eq!(sessions[0].session_id, "loop");
+        sort_sessions(&mut sessions, SessionSort::Events);
+        assert_eq!(sessions[1].session_id, "quick");
+
+        // The unpriced opus call shows in the timeline without a cost.
+        let quick: Vec<UsageEvent> = events
+            .iter()
+            .filter(|evt| evt.session_id == "quick")
+            .cloned()
+            .collect();
+        let variable = units_to_cents(report.sessions[1].variable_cost_usd);
+        let timeline = timeline_events(quick, &stream, &view, variable).unwrap();
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[1].gap_secs, 60);
+        assert_eq!(timeline[1].variable_cost_usd, None);
+        assert_eq!(units_to_cents(timeline[1].cumulative_cost_usd), variable);
+    }
+}
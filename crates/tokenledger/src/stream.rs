@@ -0,0 +1,1037 @@
+// Streaming aggregation for cost reports and time series. Events are read from JSONL once and
+// folded into partial accumulators per allocation cell (provider, scope, model, service tier, day)
+// and, for hourly series, hour; subscriptions are allocated when a report is finalized. Memory
+// grows with distinct cells rather than with events, and reports match pricing the same events in
+// memory. Only session state grows with sessions: session ids for session counts, unless the
+// stream skips them, and per-session costs and cache traffic, only when it tracks sessions.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDate, NaiveDateTime, Timelike};
+use ParetoRs::OnUnpricedAction;
+
+use crate::allocation::{event_weight, resolve_strategy, AllocCell, SubscriptionAllocator};
+use crate::cli::{AllocationStrategy, Granularity, GroupDimension};
+use crate::contracts::{contract_summary, ContractLedger, ContractUsage};
+use crate::cost::{check_unpriced, event_cost, merge_acc, BreakdownAcc};
+use crate::fx::{month_end, CurrencyConverter, USD};
+use crate::ingest::aggregation::{GroupBy, PivotAcc};
+use crate::models::*;
+use crate::money::*;
+use crate::period::{bucket_of, TimeRange};
+use crate::prompt_cache::CacheSession;
+use crate::sessions::{SessionAcc, SessionPart};
+use crate::utils::{
+    for_each_jsonl_event, normalize_model_filters, normalize_provider_filters, resolve_model_alias,
+    resolve_provider_alias,
+};
+
+// Time-series buckets by start, with their labels.
+type Buckets = BTreeMap<NaiveDateTime, (String, BreakdownAcc)>;
+// An allocation cell and the hour of its day (always 0 unless the stream tracks hours).
+type Slot = (AllocCell, u32);
+// Unpriced events by (provider, model, day, hour): their count and the first one's stream
+// position.
+type UnpricedKey = (String, String, NaiveDate, u32);
+type UnpricedEntry = (usize, u64);
+// Cache traffic per (provider, model, day, hour, session), folded into a view's cache report.
+type CacheSlotKey = (String, String, NaiveDate, u32, String);
+
+#[derive(Debug, Default)]
+struct StreamCell {
+    // Tokens, sessions and variable cost in the report currency, converted event by event.
+    usage: Acc,
+    weight: u128,
+    // FX factor of the cell's provider currency on its day, or why there is none; only an
+    // error once the cell is reported.
+    fx: Option<Result<f64, String>>,
+    batch_savings: Micros,
+    discount: Micros,
+    // Discounted variable spend in the provider's currency, for contract providers.
+    contract: bool,
+    contract_spend: Micros,
+    first_seen: u64,
+    // The cell's events per session id, kept only when the stream tracks sessions.
+    sessions: BTreeMap<String, SessionPart>,
+}
+
+pub struct CostStream {
+    pricing: PricingBook,
+    money: CurrencyConverter,
+    strategy_override: Option<AllocationStrategy>,
+    range: TimeRange,
+    hourly: bool,
+    sessions: bool,
+    session_counts: bool,
+    cells: BTreeMap<Slot, StreamCell>,
+    unpriced: BTreeMap<UnpricedKey, UnpricedEntry>,
+    cache_sessions: BTreeMap<CacheSlotKey, CacheSession>,
+    // Contract spend of events outside `range`, which still burns credits.
+    outside_contract_spend: BTreeMap<(String, NaiveDate), Micros>,
+    seen: u64,
+}
+
+// The provider/model filters a report is finalized for.
+#[derive(Debug, Clone)]
+pub struct ReportView {
+    pub providers: HashSet<String>,
+    pub models: HashSet<String>,
+    pub on_unpriced: OnUnpricedAction,
+}
+
+impl ReportView {
+    pub fn new(
+        pricing: &PricingBook,
+        providers: &[String],
+        models: &[String],
+        on_unpriced: OnUnpricedAction,
+    ) -> Self {
+        Self {
+            providers: normalize_provider_filters(pricing, providers),
+            models: normalize_model_filters(pricing, models),
+            on_unpriced,
+        }
+    }
+
+    pub fn selects(&self, provider: &str, model: &str) -> bool {
+        (self.providers.is_empty() || self.providers.contains(provider))
+            && (self.models.is_empty() || self.models.contains(model))
+    }
+}
+
+// A cell slot in a finalized view, with its subscription share in the report currency.
+struct ViewSlot<'a> {
+    cell: &'a AllocCell,
+    hour: u32,
+    data: &'a StreamCell,
+    subscription: Micros,
+}
+
+// A finalized view: its slots, and its unpriced events.
+struct ViewCells<'a> {
+    slots: Vec<ViewSlot<'a>>,
+    unpriced: Vec<(&'a UnpricedKey, &'a UnpricedEntry)>,
+    totals: CostBreakdown,
+}
+
+impl CostStream {
+    // Reports cover events inside `range`; `hourly` keeps hours apart for hourly series.
+    pub fn new(
+        pricing: PricingBook,
+        money: CurrencyConverter,
+        strategy_override: Option<AllocationStrategy>,
+        range: TimeRange,
+        hourly: bool,
+    ) -> Self {
+        Self {
+            pricing,
+            money,
+            strategy_override,
+            range,
+            hourly,
+            sessions: false,
+            session_counts: true,
+            cells: BTreeMap::new(),
+            unpriced: BTreeMap::new(),
+            cache_sessions: BTreeMap::new(),
+            outside_contract_spend: BTreeMap::new(),
+            seen: 0,
+        }
+    }
+
+    // Keeps each cell's sessions apart for session reports and pivots, and cache traffic per
+    // session for unread-session counts; call before reading events.
+    pub fn track_sessions(&mut self) {
+        self.sessions = true;
+        self.session_counts = true;
+    }
+
+    // Keeps no session ids, so memory stays bounded by cells however many sessions the events
+    // span; reports then count no sessions. Undone by `track_sessions`.
+    pub fn skip_session_counts(&mut self) {
+        self.session_counts = false;
+    }
+
+    pub fn pricing(&self) -> &PricingBook {
+        &self.pricing
+    }
+
+    pub fn money(&self) -> &CurrencyConverter {
+        &self.money
+    }
+
+    pub fn range(&self) -> &TimeRange {
+        &self.range
+    }
+
+    pub fn events_seen(&self) -> u64 {
+        self.seen
+    }
+
+    pub fn read_jsonl(&mut self, path: &Path) -> Result<()> {
+        for_each_jsonl_event(path, |evt| {
+            self.observe(evt);
+            Ok(())
+        })
+    }
+
+    // Folds one event in, resolving aliases the way `normalize_events` does.
+    pub fn observe(&mut self, mut evt: UsageEvent) {
+        evt.provider = resolve_provider_alias(&evt.provider, &self.pricing);
+        evt.model = resolve_model_alias(&evt.provider, &evt.model, &self.pricing);
+        let seen = self.seen;
+        self.seen += 1;
+        let day = evt.timestamp.date_naive();
+        let hour = if self.hourly { evt.timestamp.hour() } else { 0 };
+        let in_range = self.range.contains(evt.timestamp);
+
+        let Some(cost) = event_cost(&evt, &self.pricing) else {
+            if in_range {
+                self.unpriced
+                    .entry((evt.provider, evt.model, day, hour))
+                    .or_insert((0, seen))
+                    .0 += 1;
+            }
+            return;
+        };
+        if !in_range {
+            if cost.provider.contract.is_some() {
+                *self
+                    .outside_contract_spend
+                    .entry((evt.provider, day))
+                    .or_default() += cost.variable - cost.discount;
+            }
+            return;
+        }
+        let strategy = resolve_strategy(cost.provider, self.strategy_override);
+        let cell = self
+            .cells
+            .entry((AllocCell::new(strategy, &evt), hour))
+            .or_insert_with(|| StreamCell {
+                first_seen: seen,
+                ..StreamCell::default()
+            });
+        let fx = cell.fx.get_or_insert_with(|| {
+            self.money
+                .factor(cost.provider.currency(), evt.timestamp)
+                .map_err(|err| err.to_string())
+        });
+        let fx = fx.as_ref().copied().unwrap_or(1.0);
+        let variable = scale_micros(cost.variable, fx);
+        let weight = event_weight(strategy, &evt, cost.variable);
+        merge_acc(&mut cell.usage, &evt, variable, 0);
+        if !self.session_counts {
+            cell.usage.sessions.clear();
+        }
+        cell.weight += weight;
+        if self.sessions {
+            cell.sessions
+                .entry(evt.session_id.clone())
+                .or_default()
+                .add(&evt, variable, weight);
+        }
+        cell.batch_savings += scale_micros(cost.batch_savings, fx);
+        cell.discount += scale_micros(cost.discount, fx);
+        let cache_write_premium = scale_micros(cost.cache_write_premium, fx);
+        cell.usage.cache_read_savings_micros += scale_micros(cost.cache_read_savings, fx);
+        cell.usage.cache_write_premium_micros += cache_write_premium;
+        if cost.provider.contract.is_some() {
+            cell.contract = true;
+            cell.contract_spend += cost.variable - cost.discount;
+        }
+        if self.sessions {
+            if let Some(session) = CacheSession::of_event(&evt, cache_write_premium) {
+                self.cache_sessions
+                    .entry((evt.provider, evt.model, day, hour, evt.session_id))
+                    .or_default()
+                    .merge(&session);
+            }
+        }
+    }
+
+    // Whether no event in the range, priced or not, passes the view's filters.
+    pub fn is_empty(&self, view: &ReportView) -> bool {
+        !self
+            .cells
+            .keys()
+            .any(|(cell, _)| view.selects(&cell.provider, &cell.model))
+            && !self
+                .unpriced
+                .keys()
+                .any(|(provider, model, _, _)| view.selects(provider, model))
+    }
+
+    // The range's totals.
+    pub fn totals(&self, view: &ReportView) -> Result<CostBreakdown> {
+        Ok(self.finalize(view)?.totals)
+    }
+
+    // The range's label and totals.
+    pub fn labelled_totals(&self, view: &ReportView) -> Result<(String, CostBreakdown)> {
+        let finalized = self.finalize(view)?;
+        Ok((self.period_label(&finalized), finalized.totals))
+    }
+
+    // The range's totals and one breakdown per `granularity` bucket with events.
+    pub fn series(&self, view: &ReportView, granularity: Granularity) -> Result<TimeSeriesReport> {
+        if granularity == Granularity::Hour && !self.hourly {
+            return Err(anyhow!("hourly series need a stream that tracks hours"));
+        }
+        let finalized = self.finalize(view)?;
+
+        // Buckets are slices of the range's cells, and their cents are apportioned from the
+        // range totals so the bucket rows add up to them exactly.
+        let mut buckets: Buckets = BTreeMap::new();
+        for slot in &finalized.slots {
+            bucket_acc(&mut buckets, granularity, slot.cell.day, slot.hour).add_cell(
+                slot.cell,
+                &slot.data.usage,
+                slot.subscription,
+                slot.data.batch_savings,
+            );
+        }
+        for ((_, _, day, hour), (count, _)) in &finalized.unpriced {
+            bucket_acc(&mut buckets, granularity, *day, *hour).skipped_unpriced += count;
+        }
+        for ((provider, model, day, hour, session_id), session) in self.view_cache_sessions(view) {
+            bucket_acc(&mut buckets, granularity, *day, *hour).add_cache_session(
+                (provider.clone(), model.clone(), session_id.clone()),
+                session,
+            );
+        }
+        let variable_cents = round_parts(
+            &buckets
+                .values()
+                .map(|(_, acc)| acc.variable_micros())
+                .collect::<Vec<_>>(),
+            MICROS_PER_CENT,
+            units_to_cents(finalized.totals.variable_cost_usd),
+        );
+        let subscription_cents = round_parts(
+            &buckets
+                .values()
+                .map(|(_, acc)| acc.subscription_micros())
+                .collect::<Vec<_>>(),
+            MICROS_PER_CENT,
+            units_to_cents(finalized.totals.subscription_allocated_usd),
+        );
+        let buckets = buckets
+            .into_iter()
+            .zip(variable_cents.into_iter().zip(subscription_cents))
+            .map(|((start, (period, acc)), cents)| TimeSeriesBucket {
+                period,
+                start: start.and_utc(),
+                breakdown: acc.finish(&self.money, Some(cents)),
+            })
+            .collect();
+
+        Ok(TimeSeriesReport {
+            period: self.period_label(&finalized),
+            granularity,
+            totals: finalized.totals,
+            buckets,
+        })
+    }
+
+    // The range's totals split into `group` pivot rows.
+    pub fn pivot(&self, view: &ReportView, group: &GroupBy, nested: bool) -> Result<PivotReport> {
+        if group.needs_sessions() && !self.sessions {
+            return Err(anyhow!("session pivots need a stream that tracks sessions"));
+        }
+        let finalized = self.finalize(view)?;
+        let mut pivot = PivotAcc::new(group.clone());
+        for slot in &finalized.slots {
+            let cell = slot.cell;
+            if !group.needs_sessions() {
+                let key = group.key(&cell.provider, &cell.model, cell.day, None);
+                pivot.add(key, &slot.data.usage, slot.subscription);
+                continue;
+            }
+            for (session, part, share) in session_shares(slot) {
+                let key = group.key(&cell.provider, &cell.model, cell.day, Some(session));
+                pivot.add(key, &part.usage, share);
+            }
+        }
+        Ok(pivot.finish(
+            self.period_label(&finalized),
+            finalized.totals.currency.clone(),
+            units_to_cents(finalized.totals.variable_cost_usd),
+            units_to_cents(finalized.totals.subscription_allocated_usd),
+            nested,
+        ))
+    }
+
+    // The range's totals split per model, keyed "provider/model" as `--group-by model` keys them.
+    pub fn model_rows(&self, view: &ReportView) -> Result<Vec<NamedMetric>> {
+        let group = GroupBy::new(&[GroupDimension::Model])?;
+        let pivot = self.pivot(view, &group, false)?;
+        Ok(pivot.rows.into_iter().map(|row| row.metric).collect())
+    }
+
+    // One row per (provider, session) in the view, with its share of the range's cost.
+    pub fn sessions(&self, view: &ReportView) -> Result<SessionReport> {
+        if !self.sessions {
+            return Err(anyhow!(
+                "session reports need a stream that tracks sessions"
+            ));
+        }
+        let finalized = self.finalize(view)?;
+        let mut sessions = SessionAcc::default();
+        for slot in &finalized.slots {
+            for (session, part, share) in session_shares(slot) {
+                sessions.add(&slot.cell.provider, &slot.cell.model, session, part, share);
+            }
+        }
+        Ok(sessions.finish(
+            self.period_label(&finalized),
+            finalized.totals.currency.clone(),
+            units_to_cents(finalized.totals.variable_cost_usd),
+            units_to_cents(finalized.totals.subscription_allocated_usd),
+        ))
+    }
+
+    // The range's label; open ends are taken from the first and last day with events.
+    fn period_label(&self, finalized: &ViewCells<'_>) -> String {
+        let days = finalized
+            .slots
+            .iter()
+            .map(|slot| slot.cell.day)
+            .chain(finalized.unpriced.iter().map(|((_, _, day, _), _)| *day));
+        let (first_day, last_day) = days.fold((NaiveDate::MAX, NaiveDate::MIN), |(lo, hi), day| {
+            (lo.min(day), hi.max(day))
+        });
+        self.range.label(first_day, last_day)
+    }
+
+    fn finalize(&self, view: &ReportView) -> Result<ViewCells<'_>> {
+        // Every cell in the range, before provider/model filters, fixes allocation rates for the
+        // view.
+        let mut reference: BTreeMap<AllocCell, u128> = BTreeMap::new();
+        for ((cell, _), data) in &self.cells {
+            *reference.entry(cell.clone()).or_default() += data.weight;
+        }
+        let allocator = SubscriptionAllocator::from_cells(
+            &reference,
+            &self.pricing,
+            self.strategy_override,
+            &self.range,
+        );
+
+        let slots: Vec<(&Slot, &StreamCell)> = self
+            .cells
+            .iter()
+            .filter(|((cell, _), _)| view.selects(&cell.provider, &cell.model))
+            .collect();
+        let unpriced: Vec<_> = self
+            .unpriced
+            .iter()
+            .filter(|((provider, model, _, _), _)| view.selects(provider, model))
+            .collect();
+        if slots.is_empty() && unpriced.is_empty() {
+            return Err(anyhow!(
+                "no events matched selected period/provider/model filters"
+            ));
+        }
+
+        let mut missing: BTreeMap<String, usize> = BTreeMap::new();
+        for ((provider, model, _, _), (count, _)) in &unpriced {
+            *missing
+                .entry(format!("{}:{}", provider, model))
+                .or_default() += count;
+        }
+        check_unpriced(&missing, view.on_unpriced)?;
+        // Report the missing rate the first affected event would have hit when priced in order.
+        if let Some(err) = slots
+            .iter()
+            .filter_map(|(_, data)| match &data.fx {
+                Some(Err(err)) => Some((data.first_seen, err)),
+                _ => None,
+            })
+            .min()
+        {
+            return Err(anyhow!("{}", err.1));
+        }
+
+        // A cell's share is converted, then split between its hours by weight.
+        let mut cells: BTreeMap<&AllocCell, Vec<(u32, &StreamCell)>> = BTreeMap::new();
+        for ((cell, hour), data) in slots {
+            cells.entry(cell).or_default().push((*hour, data));
+        }
+        let shares = allocator.allocate_cells(
+            &cells
+                .iter()
+                .map(|(cell, hours)| {
+                    let weight = hours.iter().map(|(_, data)| data.weight).sum();
+                    ((*cell).clone(), weight)
+                })
+                .collect(),
+        );
+        let mut slots = Vec::new();
+        for (cell, hours) in cells {
+            let fx = hours[0]
+                .1
+                .fx
+                .as_ref()
+                .and_then(|fx| fx.as_ref().ok())
+                .copied();
+            let share = scale_micros(shares.get(cell).copied().unwrap_or(0), fx.unwrap_or(1.0));
+            let weights: Vec<u128> = hours.iter().map(|(_, data)| data.weight).collect();
+            let parts = if hours.len() == 1 {
+                vec![share]
+            } else {
+                largest_remainder(share, &weights)
+            };
+            for ((hour, data), subscription) in hours.into_iter().zip(parts) {
+                slots.push(ViewSlot {
+                    cell,
+                    hour,
+                    data,
+                    subscription,
+                });
+            }
+        }
+
+        let mut acc = BreakdownAcc::default();
+        let mut usage = ContractUsage::default();
+        for slot in &slots {
+            acc.add_cell(
+                slot.cell,
+                &slot.data.usage,
+                slot.subscription,
+                slot.data.batch_savings,
+            );
+            usage.last_day = usage.last_day.max(Some(slot.cell.day));
+            if slot.data.contract {
+                usage.add(
+                    &slot.cell.provider,
+                    slot.cell.day,
+                    slot.data.discount,
+                    slot.data.contract_spend,
+                );
+            }
+        }
+        acc.skipped_unpriced = unpriced.iter().map(|(_, (count, _))| count).sum();
+        for ((provider, model, _, _, session_id), session) in self.view_cache_sessions(view) {
+            acc.add_cache_session(
+                (provider.clone(), model.clone(), session_id.clone()),
+                session,
+            );
+        }
+        let mut totals = acc.finish(&self.money, None);
+        totals.idle_subscription_usd = self.idle_subscription_usd(&allocator, &slots, &unpriced)?;
+        let ledger = ContractLedger::from_spend(
+            self.cells
+                .iter()
+                .filter(|(_, data)| data.contract)
+                .map(|((cell, _), data)| ((cell.provider.clone(), cell.day), data.contract_spend))
+                .chain(
+                    self.outside_contract_spend
+                        .iter()
+                        .map(|(key, spend)| (key.clone(), *spend)),
+                ),
+            &self.pricing,
+        );
+        totals.contract = contract_summary(&usage, &totals, &self.pricing, &ledger, &self.money)?;
+
+        Ok(ViewCells {
+            slots,
+            unpriced,
+            totals,
+        })
+    }
+
+    fn view_cache_sessions<'a>(
+        &'a self,
+        view: &'a ReportView,
+    ) -> impl Iterator<Item = (&'a CacheSlotKey, &'a CacheSession)> {
+        self.cache_sessions
+            .iter()
+            .filter(|((provider, model, _, _, _), _)| view.selects(provider, model))
+    }
+
+    // Idle seat cost for the providers in the view, converted at month end; model filters keep
+    // the provider's full idle amount since idle cost has no model.
+    fn idle_subscription_usd(
+        &self,
+        allocator: &SubscriptionAllocator,
+        slots: &[ViewSlot<'_>],
+        unpriced: &[(&UnpricedKey, &UnpricedEntry)],
+    ) -> Result<f64> {
+        let mut providers: BTreeSet<&str> = BTreeSet::new();
+        let mut last_day = None;
+        for slot in slots {
+            providers.insert(&slot.cell.provider);
+            last_day = last_day.max(Some(slot.cell.day));
+        }
+        for ((provider, _, day, _), _) in unpriced {
+            providers.insert(provider);
+            last_day = last_day.max(Some(*day));
+        }
+        let Some(day) = last_day else {
+            return Ok(0.0);
+        };
+        let mut idle = 0;
+        for provider in providers {
+            let currency = self
+                .pricing
+                .providers
+                .get(provider)
+                .map(|pricing| pricing.currency())
+                .unwrap_or(USD);
+            idle += scale_micros(
+                allocator.idle_micros_for([provider]),
+                self.money.factor_on(currency, month_end(day))?,
+            );
+        }
+        Ok(cents_to_units(to_cents(idle)))
+    }
+}
+
+// A slot's sessions with their shares of its subscription, split the way a cell's share is split
+// between its hours.
+fn session_shares<'a>(
+    slot: &ViewSlot<'a>,
+) -> impl Iterator<Item = (&'a String, &'a SessionPart, Micros)> {
+    let weights: Vec<u128> = slot
+        .data
+        .sessions
+        .values()
+        .map(|part| part.weight)
+        .collect();
+    let shares = largest_remainder(slot.subscription, &weights);
+    slot.data
+        .sessions
+        .iter()
+        .zip(shares)
+        .map(|((session, part), share)| (session, part, share))
+}
+
+fn bucket_acc(
+    buckets: &mut Buckets,
+    granularity: Granularity,
+    day: NaiveDate,
+    hour: u32,
+) -> &mut BreakdownAcc {
+    let (start, label) = bucket_of(granularity, day, hour);
+    &mut buckets
+        .entry(start)
+        .or_insert_with(|| (label, BreakdownAcc::default()))
+        .1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{FxDateMode, GroupDimension};
+    use crate::cost::{price_events, summarize_priced};
+    use crate::fx::{FxRate, FxTable};
+    use crate::utils::{filter_month, filter_provider_model, normalize_events};
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn provider(
+        currency: Option<&str>,
+        subscription: f64,
+        models: &[(&str, f64)],
+        allocation: Option<AllocationStrategy>,
+        contract: Option<ProviderContract>,
+    ) -> ProviderPricing {
+        ProviderPricing {
+            currency: currency.map(str::to_string),
+            subscription_usd_month: subscription,
+            models: models
+                .iter()
+                .map(|(model, input)| {
+                    (
+                        model.to_string(),
+                        ModelRate {
+                            input_usd_per_mtok: *input,
+                            output_usd_per_mtok: 7.0,
+                            cache_write_usd_per_mtok: None,
+                            cache_write_1h_usd_per_mtok: None,
+                            cache_read_usd_per_mtok: None,
+                            tool_input_usd_per_mtok: None,
+                            tool_output_usd_per_mtok: None,
+                            effective_from: None,
+                            effective_to: None,
+                            context_tiers: Vec::new(),
+                        }
+                        .into(),
+                    )
+                })
+                .collect(),
+            model_aliases: HashMap::new(),
+            service_tiers: HashMap::new(),
+            allocation,
+            seats: Vec::new(),
+            plan: None,
+            plans: HashMap::new(),
+            contract,
+        }
+    }
+
+    fn pricing() -> PricingBook {
+        PricingBook {
+            providers: HashMap::from([
+                (
+                    "claude".to_string(),
+                    provider(
+                        None,
+                        30.0,
+                        &[("sonnet", 3.0), ("haiku", 1.0)],
+                        Some(AllocationStrategy::PerSession),
+                        None,
+                    ),
+                ),
+                (
+                    "codex".to_string(),
+                    provider(
+                        None,
+                        20.0,
+                        &[("gpt-5", 1.25)],
+                        None,
+                        Some(ProviderContract {
+                            discount_pct: 10.0,
+                            model_discounts: HashMap::new(),
+                            credits: Vec::new(),
+                            commit_usd_month: Some(5.0),
+                        }),
+                    ),
+                ),
+                (
+                    "mistral".to_string(),
+                    provider(Some("EUR"), 13.0, &[("large", 2.0)], None, None),
+                ),
+            ]),
+            provider_aliases: HashMap::from([("anthropic".to_string(), "claude".to_string())]),
+            meta: None,
+        }
+    }
+
+    fn euro_rates() -> CurrencyConverter {
+        let rate = |day: u32, rate: f64| FxRate {
+            date: NaiveDate::from_ymd_opt(2026, 1, day).unwrap(),
+            rate,
+        };
+        let table = FxTable {
+            base: USD.to_string(),
+            rates: HashMap::from([("EUR".to_string(), vec![rate(1, 0.9), rate(25, 0.8)])]),
+        };
+        CurrencyConverter::new(USD, FxDateMode::EventDay, Some(table))
+    }
+
+    fn events() -> Vec<UsageEvent> {
+        let event =
+            |provider: &str, model: &str, session: &str, month, day, input: u64| UsageEvent {
+                provider: provider.to_string(),
+                model: model.to_string(),
+                session_id: session.to_string(),
+                timestamp: Utc.with_ymd_and_hms(2026, month, day, 9, 0, 0).unwrap(),
+                usage: TokenUsage {
+                    input_tokens: input,
+                    output_tokens: input / 3,
+                    cache_write_tokens: input / 7,
+                    cache_write_1h_tokens: 0,
+                    // Only c1 reads its cache back; every other session's writes go unread.
+                    cache_read_tokens: if session == "c1" { input / 2 } else { 0 },
+                    tool_input_tokens: 0,
+                    tool_output_tokens: 0,
+                },
+                service_tier: None,
+                tags: Vec::new(),
+            };
+        vec![
+            event("claude", "sonnet", "c1", 1, 30, 700_001),
+            event("anthropic", "haiku", "c2", 1, 20, 333_333),
+            event("codex", "gpt-5", "x1", 1, 21, 1_234_567),
+            event("mistral", "large", "m1", 1, 22, 500_000),
+            event("claude", "sonnet", "c1", 1, 21, 100_000),
+            event("mistral", "large", "m1", 1, 28, 250_001),
+            event("codex", "gpt-5", "x2", 1, 28, 99_999),
+            event("codex", "gpt-9", "x2", 1, 28, 10),
+            event("claude", "sonnet", "c3", 2, 2, 1_000_000),
+        ]
+    }
+
+    // The reports `monthly` built before streaming: every event in memory, priced one by one.
+    fn in_memory(events: &[UsageEvent], view: &ReportView, month: &str) -> CostBreakdown {
+        let book = pricing();
+        let money = euro_rates();
+        let normalized = normalize_events(events.to_vec(), &book);
+        let ledger = ContractLedger::build(&normalized, &book);
+        let month_filtered = filter_month(normalized, Some(month)).unwrap();
+        let allocator = SubscriptionAllocator::build(&month_filtered, &book, None);
+        let providers: Vec<String> = view.providers.iter().cloned().collect();
+        let filtered = filter_provider_model(month_filtered, &book, &providers, &[]);
+        let priced = price_events(&filtered, &book, view.on_unpriced, &allocator, &money).unwrap();
+        let mut breakdown = summarize_priced(&priced, &money, None);
+        breakdown.contract = contract_summary(
+            &ContractUsage::from_priced(&priced, &book),
+            &breakdown,
+            &book,
+            &ledger,
+            &money,
+        )
+        .unwrap();
+        breakdown
+    }
+
+    fn stream(events: &[UsageEvent], range: TimeRange, hourly: bool) -> CostStream {
+        let mut stream = CostStream::new(pricing(), euro_rates(), None, range, hourly);
+        for evt in events {
+            stream.observe(evt.clone());
+        }
+        stream
+    }
+
+    fn tracked(events: &[UsageEvent], range: TimeRange) -> CostStream {
+        let mut stream = CostStream::new(pricing(), euro_rates(), None, range, false);
+        stream.track_sessions();
+        for evt in events {
+            stream.observe(evt.clone());
+        }
+        stream
+    }
+
+    #[test]
+    fn test_monthly_matches_in_memory_pricing() {
+        let events = events();
+        let january = tracked(&events, TimeRange::month((2026, 1)));
+        for providers in [
+            vec![],
+            vec!["anthropic".to_string()],
+            vec!["codex".to_string()],
+        ] {
+            let view = ReportView::new(&pricing(), &providers, &[], OnUnpricedAction::Skip);
+            let mut expected = in_memory(&events, &view, "2026-01");
+            let actual = january.totals(&view).unwrap();
+            // Idle seat cost is only computed by the stream; the rest must match exactly.
+            expected.idle_subscription_usd = actual.idle_subscription_usd;
+            assert_eq!(
+                serde_json::to_value(&actual).unwrap(),
+                serde_json::to_value(&expected).unwrap(),
+                "providers {:?}",
+                providers
+            );
+        }
+
+        let view = ReportView::new(&pricing(), &[], &[], OnUnpricedAction::Skip);
+        let report = january.totals(&view).unwrap();
+        assert_eq!(report.provider_breakdown.len(), 3);
+        assert_eq!(report.skipped_unpriced_count, 1);
+        assert!(report.subscription_allocated_usd > 0.0);
+        assert!(report.contract.is_some());
+        assert!(report
+            .cache
+            .as_ref()
+            .is_some_and(|cache| cache.totals.unread_sessions > Some(0)));
+
+        let everything = stream(&events, TimeRange::default(), false);
+        let view = ReportView::new(&pricing(), &[], &[], OnUnpricedAction::Error);
+        let err = everything.totals(&view).unwrap_err().to_string();
+        assert!(err.contains("codex:gpt-9"), "{err}");
+    }
+
+    fn assert_buckets_add_up(report: &TimeSeriesReport) {
+        let cents = |f: fn(&CostBreakdown) -> f64| {
+            report
+                .buckets
+                .iter()
+                .map(|bucket| units_to_cents(f(&bucket.breakdown)))
+                .sum::<i64>()
+        };
+        assert_eq!(
+            cents(|b| b.variable_cost_usd),
+            units_to_cents(report.totals.variable_cost_usd)
+        );
+        assert_eq!(
+            cents(|b| b.subscription_allocated_usd),
+            units_to_cents(report.totals.subscription_allocated_usd)
+        );
+        assert_eq!(
+            report
+                .buckets
+                .iter()
+                .map(|bucket| bucket.breakdown.skipped_unpriced_count)
+                .sum::<usize>(),
+            report.totals.skipped_unpriced_count
+        );
+    }
+
+    #[test]
+    fn test_daily_rows_add_up_to_totals() {
+        let stream = stream(&events(), TimeRange::month((2026, 1)), false);
+        let view = ReportView::new(&pricing(), &[], &[], OnUnpricedAction::Skip);
+        let report = stream.series(&view, Granularity::Day).unwrap();
+        assert_eq!(report.period, "2026-01");
+        assert_eq!(
+            report
+                .buckets
+                .iter()
+                .map(|bucket| bucket.period.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "2026-01-20",
+                "2026-01-21",
+                "2026-01-22",
+                "2026-01-28",
+                "2026-01-30"
+            ]
+        );
+        assert_eq!(report.totals.skipped_unpriced_count, 1);
+        assert_buckets_add_up(&report);
+
+        let weekly = stream.series(&view, Granularity::Week).unwrap();
+        assert_eq!(weekly.buckets.len(), 2);
+        assert_buckets_add_up(&weekly);
+        assert!(stream.series(&view, Granularity::Hour).is_err());
+    }
+
+    #[test]
+    fn test_range_series_prorates_subscriptions() {
+        let events = events();
+        let range = TimeRange {
+            from: Some(Utc.with_ymd_and_hms(2026, 1, 21, 0, 0, 0).unwrap()),
+            to: Some(Utc.with_ymd_and_hms(2026, 1, 29, 0, 0, 0).unwrap()),
+        };
+        let view = ReportView::new(
+            &pricing(),
+            &["codex".to_string()],
+            &[],
+            OnUnpricedAction::Skip,
+        );
+        let report = stream(&events, range, true)
+            .series(&view, Granularity::Hour)
+            .unwrap();
+        assert_eq!(report.period, "2026-01-21..2026-01-28");
+        assert_eq!(report.buckets.len(), 2);
+        assert_eq!(report.buckets[0].period, "2026-01-21T09:00Z");
+        assert_buckets_add_up(&report);
+        // Codex's $20 seat is charged for 8 of January's 31 days.
+        assert_eq!(
+            units_to_cents(
+                report.totals.subscription_allocated_usd + report.totals.idle_subscription_usd
+            ),
+            516
+        );
+    }
+
+    #[test]
+    fn test_untracked_stream_keeps_no_session_state() {
+        let events = events();
+        let plain = stream(&events, TimeRange::month((2026, 1)), false);
+        assert!(plain.cache_sessions.is_empty());
+        assert!(plain.cells.values().all(|cell| cell.sessions.is_empty()));
+
+        let view = ReportView::new(&pricing(), &[], &[], OnUnpricedAction::Skip);
+        let cache = plain.totals(&view).unwrap().cache.expect("cache section");
+        assert_eq!(cache.totals.unread_sessions, None);
+        assert!(cache.unread_sessions.is_empty());
+        let full = tracked(&events, TimeRange::month((2026, 1)))
+            .totals(&view)
+            .unwrap()
+            .cache
+            .expect("cache section");
+        assert_eq!(cache.totals.net_savings_usd, full.totals.net_savings_usd);
+        assert_eq!(full.unread_sessions.len(), 4);
+
+        let mut bounded = CostStream::new(
+            pricing(),
+            euro_rates(),
+            None,
+            TimeRange::month((2026, 1)),
+            false,
+        );
+        bounded.skip_session_counts();
+        for evt in &events {
+            bounded.observe(evt.clone());
+        }
+        assert!(bounded
+            .cells
+            .values()
+            .all(|cell| cell.usage.sessions.is_empty()));
+        let totals = bounded.totals(&view).unwrap();
+        assert_eq!(totals.session_count, 0);
+        assert_eq!(
+            totals.monthly_total_usd,
+            plain.totals(&view).unwrap().monthly_total_usd
+        );
+    }
+
+    #[test]
+    fn test_pivot_rows_add_up_to_totals() {
+        use GroupDimension::*;
+        let events = events();
+        let mut sessions = CostStream::new(
+            pricing(),
+            euro_rates(),
+            None,
+            TimeRange::month((2026, 1)),
+            false,
+        );
+        sessions.track_sessions();
+        for evt in &events {
+            sessions.observe(evt.clone());
+        }
+        let view = ReportView::new(&pricing(), &[], &[], OnUnpricedAction::Skip);
+        let totals = sessions.totals(&view).unwrap();
+        let cents = |rows: &[PivotRow], f: fn(&NamedMetric) -> f64| {
+            rows.iter()
+                .map(|row| units_to_cents(f(&row.metric)))
+                .sum::<i64>()
+        };
+
+        let by_session = sessions
+            .pivot(&view, &GroupBy::new(&[Model, Session]).unwrap(), false)
+            .unwrap();
+        let names: Vec<&str> = by_session
+            .rows
+            .iter()
+            .map(|row| row.metric.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "claude/haiku/c2",
+                "claude/sonnet/c1",
+                "codex/gpt-5/x1",
+                "codex/gpt-5/x2",
+                "mistral/large/m1"
+            ]
+        );
+        assert_eq!(by_session.totals.tokens, totals.total_tokens);
+        assert_eq!(
+            cents(&by_session.rows, |m| m.variable_cost_usd),
+            units_to_cents(totals.variable_cost_usd)
+        );
+        assert_eq!(
+            cents(&by_session.rows, |m| m.subscription_allocated_usd),
+            units_to_cents(totals.subscription_allocated_usd)
+        );
+
+        let nested = sessions
+            .pivot(&view, &GroupBy::new(&[Provider, Day]).unwrap(), true)
+            .unwrap();
+        assert_eq!(nested.rows.len(), 3);
+        for row in &nested.rows {
+            assert_eq!(
+                cents(&row.children, |m| m.total_cost_usd),
+                units_to_cents(row.metric.total_cost_usd),
+                "{}",
+                row.metric.name
+            );
+        }
+        assert_eq!(
+            cents(&nested.rows, |m| m.total_cost_usd),
+            units_to_cents(totals.monthly_total_usd)
+        );
+        assert_eq!(nested.rows[1].children[0].metric.name, "codex/2026-01-21");
+
+        let plain = stream(&events, TimeRange::month((2026, 1)), false);
+        assert!(plain
+            .pivot(&view, &GroupBy::new(&[Session]).unwrap(), false)
+            .is_err());
+    }
+}
@@ -0,0 +1,796 @@
+// Interactive terminal dashboard: the month's totals, provider and model tables, daily spend,
+// sessions with an event timeline drill-down, and budget and usage-window gauges. The watched files
+// are polled and the reports rebuilt only when one changes, so redraws are cheap; with an aggregate
+// cache the monthly and daily reports are shared with `orchestrate`.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration as StdDuration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, LineGauge, Paragraph, Row, Sparkline, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::analytics::stream_query;
+use crate::budgets::{budget_as_of, evaluate_budgets, load_budget_config};
+use crate::cli::{BudgetStatus, Granularity, SessionSort, TuiArgs};
+use crate::format::format_duration;
+use crate::fx::{currency_symbol, USD};
+use crate::models::*;
+use crate::money::units_to_cents;
+use crate::orchestrate::cached_orchestrate_aggregates;
+use crate::period::TimeRange;
+use crate::sessions::{build_session_timeline, sort_sessions};
+use crate::utils::{
+    load_events, load_pricing, normalize_events, normalize_provider_filters, parse_month,
+};
+use crate::windows::{refresh_window_checkpoint, WindowCheckpoint};
+
+// How long to wait for a key before redrawing.
+const TICK: StdDuration = StdDuration::from_millis(250);
+const FOCUS: Color = Color::Cyan;
+
+pub fn run_tui(args: TuiArgs) -> Result<()> {
+    if args.aggregate_cache_path.is_some() && args.query.currency != USD {
+        return Err(anyhow!(
+            "the aggregate cache holds USD reports; drop --currency or --aggregate-cache-path"
+        ));
+    }
+    let mut app = App::new(args);
+    app.refresh(true);
+    let mut terminal = ratatui::try_init()?;
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+// A watched file's size and modification time; None while it is missing.
+type FileStamp = Option<(u64, SystemTime)>;
+
+pub fn file_stamps(paths: &[PathBuf]) -> Vec<FileStamp> {
+    paths
+        .iter()
+        .map(|path| {
+            fs::metadata(path)
+                .ok()
+                .map(|meta| (meta.len(), meta.modified().unwrap_or(UNIX_EPOCH)))
+        })
+        .collect()
+}
+
+// Variable spend in cents for each day from `first` through `last`, zero on days without events.
+pub fn daily_spend_cents(days: &[(NaiveDate, f64)], first: NaiveDate, last: NaiveDate) -> Vec<u64> {
+    let len = (last - first).num_days() + 1;
+    let mut cents = vec![0; len.max(0) as usize];
+    for (day, spend) in days {
+        let idx = (*day - first).num_days();
+        if (0..len).contains(&idx) {
+            cents[idx as usize] += units_to_cents(*spend).max(0) as u64;
+        }
+    }
+    cents
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Panel {
+    Providers,
+    Models,
+    Sessions,
+}
+
+impl Panel {
+    const ALL: [Panel; 3] = [Panel::Providers, Panel::Models, Panel::Sessions];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn next(self, step: usize) -> Self {
+        Self::ALL[(self.index() + step) % Self::ALL.len()]
+    }
+}
+
+// Everything rebuilt when a watched file changes.
+struct Dashboard {
+    month: String,
+    range: TimeRange,
+    monthly: CostBreakdown,
+    daily_cents: Vec<u64>,
+    sessions: Vec<SessionSummary>,
+    budgets: Option<BudgetReport>,
+    windows: Vec<WindowUsage>,
+    cache_hit: bool,
+    refreshed_at: DateTime<Utc>,
+}
+
+impl Dashboard {
+    fn rows(&self, panel: Panel) -> usize {
+        match panel {
+            Panel::Providers => self.monthly.provider_breakdown.len(),
+            Panel::Models => self.monthly.model_breakdown.len(),
+            Panel::Sessions => self.sessions.len(),
+        }
+    }
+}
+
+struct App {
+    args: TuiArgs,
+    // Pricing, then the budgets file, then the events files.
+    watched: Vec<PathBuf>,
+    stamps: Option<Vec<FileStamp>>,
+    checkpoint: WindowCheckpoint,
+    dashboard: Option<Dashboard>,
+    error: Option<String>,
+    focus: Panel,
+    selected: [usize; 3],
+    // The drilled-down session and the selected event.
+    timeline: Option<(SessionTimeline, usize)>,
+}
+
+impl App {
+    fn new(args: TuiArgs) -> Self {
+        let mut watched = vec![args.query.pricing.clone()];
+        watched.extend(args.budgets.clone());
+        watched.extend(args.query.events.iter().cloned());
+        Self {
+            args,
+            watched,
+            stamps: None,
+            checkpoint: WindowCheckpoint::default(),
+            dashboard: None,
+            error: None,
+            focus: Panel::Providers,
+            selected: [0; 3],
+            timeline: None,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let every = StdDuration::from_secs(self.args.refresh_secs);
+        let mut checked = Instant::now();
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !self.handle_key(key) {
+                        return Ok(());
+                    }
+                }
+            }
+            if checked.elapsed() >= every {
+                self.refresh(false);
+                checked = Instant::now();
+            }
+        }
+    }
+
+    // Rebuilds the dashboard when forced, a watched file changed or the current month rolled
+    // over. A failed rebuild keeps the last dashboard and is retried on the next check.
+    fn refresh(&mut self, force: bool) {
+        let stamps = file_stamps(&self.watched);
+        let now = Utc::now();
+        let rolled_over = self.args.month.is_none()
+            && self.dashboard.as_ref().is_some_and(|dashboard| {
+                dashboard.month != format!("{:04}-{:02}", now.year(), now.month())
+            });
+        if !force && !rolled_over && self.stamps.as_ref() == Some(&stamps) {
+            return;
+        }
+        if self
+            .stamps
+            .as_ref()
+            .is_some_and(|previous| previous[0] != stamps[0])
+        {
+            // Pricing changed, and with it possibly the plan windows.
+            self.checkpoint = WindowCheckpoint::default();
+        }
+        match self.load(now) {
+            Ok(dashboard) => {
+                for panel in Panel::ALL {
+                    let last = dashboard.rows(panel).saturating_sub(1);
+                    self.selected[panel.index()] = self.selected[panel.index()].min(last);
+                }
+                self.dashboard = Some(dashboard);
+                self.stamps = Some(stamps);
+                self.error = None;
+            }
+            Err(err) => {
+                // Rebuild everything on the next check, the windows included.
+                self.stamps = None;
+                self.checkpoint = WindowCheckpoint::default();
+                self.error = Some(format!("{:#}", err));
+            }
+        }
+    }
+
+    fn load(&mut self, now: DateTime<Utc>) -> Result<Dashboard> {
+        let query = &self.args.query;
+        let month = match self.args.month.as_deref() {
+            Some(month) => parse_month(month)?,
+            None => (now.year(), now.month()),
+        };
+        let range = TimeRange::month(month);
+        let label = format!("{:04}-{:02}", month.0, month.1);
+        // One pass over the events feeds the monthly, daily and session views; on an aggregate
+        // cache hit the monthly and daily reports come from the cache instead.
+        let (stream, view) = stream_query(query, &range, false, true)?;
+        let build = || -> Result<(CostBreakdown, TimeSeriesReport)> {
+            Ok((
+                stream.totals(&view)?,
+                stream.series(&view, Granularity::Day)?,
+            ))
+        };
+        let (monthly, daily, cache_hit) = match self.args.aggregate_cache_path.as_deref() {
+            Some(path) => {
+                let (entry, outcome) =
+                    cached_orchestrate_aggregates(query, Some(&label), path, build)?;
+                let hit = outcome == OrchestrateAggregateCacheOutcome::Hit;
+                (entry.monthly, entry.daily, hit)
+            }
+            None => {
+                let (monthly, daily) = build()?;
+                (monthly, daily, false)
+            }
+        };
+        let first = range.from.expect("months have a start").date_naive();
+        let end = range
+            .to
+            .expect("months have an end")
+            .min(now + Duration::days(1));
+        let days: Vec<(NaiveDate, f64)> = daily
+            .buckets
+            .iter()
+            .map(|bucket| {
+                (
+                    bucket.start.date_naive(),
+                    bucket.breakdown.variable_cost_usd,
+                )
+            })
+            .collect();
+        let last = days
+            .iter()
+            .map(|(day, _)| *day)
+            .max()
+            .unwrap_or(first)
+            .max((end - Duration::seconds(1)).date_naive());
+
+        let mut sessions = stream.sessions(&view)?.sessions;
+        sort_sessions(&mut sessions, SessionSort::Cost);
+
+        let pricing = load_pricing(&query.pricing)?;
+        let budgets = match self.args.budgets.as_deref() {
+            Some(path) => {
+                let config = load_budget_config(path)?;
+                let events = normalize_events(load_events(&query.events)?, &pricing);
+                Some(evaluate_budgets(
+                    &events,
+                    &pricing,
+                    &config,
+                    query.on_unpriced,
+                    query.allocation,
+                    budget_as_of(self.args.month.as_deref(), now)?,
+                )?)
+            }
+            None => None,
+        };
+        refresh_window_checkpoint(&mut self.checkpoint, &query.events, &pricing)?;
+        let mut windows = self.checkpoint.tracker.report(&pricing, now);
+        if !query.providers.is_empty() {
+            let wanted = normalize_provider_filters(&pricing, &query.providers);
+            windows.retain(|window| wanted.contains(&window.provider));
+        }
+
+        Ok(Dashboard {
+            month: label,
+            range,
+            monthly,
+            daily_cents: daily_spend_cents(&days, first, last),
+            sessions,
+            budgets,
+            windows,
+            cache_hit,
+            refreshed_at: now,
+        })
+    }
+
+    // Returns false to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Char('q')
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+        {
+            return false;
+        }
+        if let Some((timeline, selected)) = self.timeline.as_mut() {
+            let last = timeline.events.len().saturating_sub(1);
+            match key.code {
+                KeyCode::Esc | KeyCode::Backspace | KeyCode::Left => self.timeline = None,
+                KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => *selected = (*selected + 1).min(last),
+                KeyCode::Home => *selected = 0,
+                KeyCode::End => *selected = last,
+                _ => {}
+            }
+            return true;
+        }
+        let rows = self
+            .dashboard
+            .as_ref()
+            .map_or(0, |dashboard| dashboard.rows(self.focus));
+        let selected = &mut self.selected[self.focus.index()];
+        match key.code {
+            KeyCode::Esc => return false,
+            KeyCode::Tab | KeyCode::Right => self.focus = self.focus.next(1),
+            KeyCode::BackTab | KeyCode::Left => self.focus = self.focus.next(Panel::ALL.len() - 1),
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                *selected = (*selected + 1).min(rows.saturating_sub(1))
+            }
+            KeyCode::Home => *selected = 0,
+            KeyCode::End => *selected = rows.saturating_sub(1),
+            KeyCode::Char('r') => self.refresh(true),
+            KeyCode::Enter if self.focus == Panel::Sessions => self.open_session(),
+            _ => {}
+        }
+        true
+    }
+
+    fn open_session(&mut self) {
+        let Some(dashboard) = self.dashboard.as_ref() else {
+            return;
+        };
+        let Some(session) = dashboard
+            .sessions
+            .get(self.selected[Panel::Sessions.index()])
+        else {
+            return;
+        };
+        match build_session_timeline(&self.args.query, &dashboard.range, &session.session_id) {
+            Ok(timeline) => self.timeline = Some((timeline, 0)),
+            Err(err) => self.error = Some(format!("{:#}", err)),
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [body, footer] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let help = match (&self.timeline, &self.dashboard) {
+            (Some((timeline, selected)), _) => {
+                draw_timeline(frame, body, timeline, *selected);
+                "esc back  ↑/↓ scroll  q quit"
+            }
+            (None, Some(dashboard)) => {
+                self.draw_dashboard(frame, body, dashboard);
+                "tab switch panel  ↑/↓ select  enter open session  r refresh  q quit"
+            }
+            (None, None) => {
+                let block = Block::bordered().title(" tokenledger ");
+                frame.render_widget(Paragraph::new("Waiting for events...").block(block), body);
+                "r refresh  q quit"
+            }
+        };
+        let footer_line = match &self.error {
+            Some(error) => Line::styled(format!("error: {}", error), Style::new().fg(Color::Red)),
+            None => Line::styled(help, Style::new().fg(Color::DarkGray)),
+        };
+        frame.render_widget(Paragraph::new(footer_line), footer);
+    }
+
+    fn draw_dashboard(&self, frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+        let [header, spark, tables, bottom] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(7),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        let monthly = &dashboard.monthly;
+        let sym = currency_symbol(&monthly.currency);
+
+        let mut totals = vec![
+            Span::styled(
+                format!("{}{:.2}", sym, monthly.monthly_total_usd),
+                Style::new().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                "  variable {}{:.2}  subscriptions {}{:.2}  {:.2} MTok  {}{:.2}/MTok  {} sessions",
+                sym,
+                monthly.variable_cost_usd,
+                sym,
+                monthly.subscription_allocated_usd,
+                monthly.total_mtok,
+                sym,
+                monthly.blended_usd_per_mtok,
+                monthly.session_count
+            )),
+        ];
+        if monthly.skipped_unpriced_count > 0 {
+            totals.push(Span::styled(
+                format!("  {} unpriced skipped", monthly.skipped_unpriced_count),
+                Style::new().fg(Color::Yellow),
+            ));
+        }
+        let title = format!(
+            " tokenledger {} · refreshed {}{} ",
+            dashboard.month,
+            dashboard.refreshed_at.format("%H:%M:%S"),
+            if dashboard.cache_hit {
+                " (cache hit)"
+            } else {
+                ""
+            }
+        );
+        frame.render_widget(
+            Paragraph::new(Line::from(totals)).block(Block::bordered().title(title)),
+            header,
+        );
+
+        let peak = dashboard.daily_cents.iter().max().copied().unwrap_or(0);
+        let spark_title = format!(
+            " Daily variable spend · {} days · peak {}{:.2} ",
+            dashboard.daily_cents.len(),
+            sym,
+            peak as f64 / 100.0
+        );
+        frame.render_widget(
+            Sparkline::default()
+                .data(&dashboard.daily_cents)
+                .style(Style::new().fg(Color::Green))
+                .block(Block::bordered().title(spark_title)),
+            spark,
+        );
+
+        let [providers, models] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(tables);
+        self.draw_metrics(
+            frame,
+            providers,
+            Panel::Providers,
+            &monthly.provider_breakdown,
+            monthly,
+        );
+        self.draw_metrics(
+            frame,
+            models,
+            Panel::Models,
+            &monthly.model_breakdown,
+            monthly,
+        );
+
+        let [sessions, gauges] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(bottom);
+        self.draw_sessions(frame, sessions, dashboard, &sym);
+        draw_gauges(frame, gauges, dashboard);
+    }
+
+    fn panel_block(&self, panel: Panel, title: String) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if self.focus == panel {
+            block.border_style(Style::new().fg(FOCUS))
+        } else {
+            block
+        }
+    }
+
+    fn draw_metrics(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        panel: Panel,
+        rows: &[NamedMetric],
+        monthly: &CostBreakdown,
+    ) {
+        let sym = currency_symbol(&monthly.currency);
+        let total = monthly.monthly_total_usd;
+        let title = match panel {
+            Panel::Providers => format!(" Providers ({}) ", rows.len()),
+            _ => format!(" Models ({}) ", rows.len()),
+        };
+        let table = Table::new(
+            rows.iter().map(|row| {
+                let share = if total > 0.0 {
+                    row.total_cost_usd / total * 100.0
+                } else {
+                    0.0
+                };
+                Row::new(vec![
+                    row.name.clone(),
+                    format!("{}{:.2}", sym, row.total_cost_usd),
+                    format!("{:.1}%", share),
+                    format!("{:.2}", row.mtok),
+                    format!("{:.2}", row.blended_usd_per_mtok),
+                ])
+            }),
+            [
+                Constraint::Fill(1),
+                Constraint::Length(11),
+                Constraint::Length(6),
+                Constraint::Length(8),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header_row(["Name", "Total", "Share", "MTok", "$/MTok"]))
+        .row_highlight_style(highlight(self.focus == panel))
+        .block(self.panel_block(panel, title));
+        let mut state = TableState::default().with_selected(Some(self.selected[panel.index()]));
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+
+    fn draw_sessions(&self, frame: &mut Frame, area: Rect, dashboard: &Dashboard, sym: &str) {
+        let table = Table::new(
+            dashboard.sessions.iter().map(|session| {
+                Row::new(vec![
+                    session.start.format("%m-%d %H:%M").to_string(),
+                    format!("{}/{}", session.provider, session.session_id),
+                    format_duration(session.duration_secs),
+                    session.event_count.to_string(),
+                    format!("{}{:.2}", sym, session.total_cost_usd),
+                ])
+            }),
+            [
+                Constraint::Length(11),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(7),
+                Constraint::Length(10),
+            ],
+        )
+        .header(header_row([
+            "Start", "Session", "Duration", "Events", "Total",
+        ]))
+        .row_highlight_style(highlight(self.focus == Panel::Sessions))
+        .block(self.panel_block(
+            Panel::Sessions,
+            format!(
+                " Sessions ({}) · enter for timeline ",
+                dashboard.sessions.len()
+            ),
+        ));
+        let mut state =
+            TableState::default().with_selected(Some(self.selected[Panel::Sessions.index()]));
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+fn header_row<const N: usize>(titles: [&'static str; N]) -> Row<'static> {
+    Row::new(titles).style(Style::new().add_modifier(Modifier::BOLD))
+}
+
+fn highlight(focused: bool) -> Style {
+    if focused {
+        Style::new().bg(FOCUS).fg(Color::Black)
+    } else {
+        Style::new().add_modifier(Modifier::REVERSED)
+    }
+}
+
+// One line per budget, then one per plan window.
+fn draw_gauges(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let block = Block::bordered().title(" Budgets & windows ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut gauges: Vec<(String, f64, Color)> = Vec::new();
+    if let Some(report) = &dashboard.budgets {
+        let sym = currency_symbol(&report.currency);
+        for budget in &report.budgets {
+            let color = match budget.status {
+                BudgetStatus::Ok => Color::Green,
+                BudgetStatus::Warn => Color::Yellow,
+                BudgetStatus::Exceeded => Color::Red,
+            };
+            gauges.push((
+                format!(
+                    "{} {}{:.2}/{}{:.2}",
+                    budget.name, sym, budget.spent_usd, sym, budget.amount_usd
+                ),
+                budget.used_ratio,
+                color,
+            ));
+        }
+    }
+    for window in &dashboard.windows {
+        let label = format!("{} {}", window.provider, window.window);
+        let (label, ratio) = match (window.limit_tokens, window.used_pct) {
+            (Some(limit), Some(pct)) => (
+                format!("{} {}/{}", label, window.tokens, limit),
+                pct / 100.0,
+            ),
+            _ => (format!("{} {} tokens", label, window.tokens), 0.0),
+        };
+        let color = if ratio >= 1.0 {
+            Color::Red
+        } else if ratio >= 0.8 {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        gauges.push((label, ratio, color));
+    }
+
+    if gauges.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No budgets (--budgets) or plan windows configured.")
+                .style(Style::new().fg(Color::DarkGray)),
+            inner,
+        );
+        return;
+    }
+    let width = gauges
+        .iter()
+        .map(|(label, _, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let rows = Layout::vertical(vec![Constraint::Length(1); gauges.len()]).split(inner);
+    for ((label, ratio, color), row) in gauges.into_iter().zip(rows.iter()) {
+        frame.render_widget(
+            LineGauge::default()
+                .ratio(ratio.clamp(0.0, 1.0))
+                .label(format!("{:<width$} {:>4.0}%", label, ratio * 100.0))
+                .filled_style(Style::new().fg(color))
+                .unfilled_style(Style::new().fg(Color::DarkGray)),
+            *row,
+        );
+    }
+}
+
+fn draw_timeline(frame: &mut Frame, area: Rect, timeline: &SessionTimeline, selected: usize) {
+    let sym = currency_symbol(&timeline.currency);
+    let [header, events] =
+        Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(area);
+    let summary: Vec<String> = timeline
+        .sessions
+        .iter()
+        .map(|session| {
+            format!(
+                "{} {} ({}) · {} events · {} tokens · {:.1}% cache hits · {}{:.2}",
+                session.provider,
+                session.start.format("%Y-%m-%d %H:%M"),
+                format_duration(session.duration_secs),
+                session.event_count,
+                session.total_tokens,
+                session.cache_hit_ratio * 100.0,
+                sym,
+                session.total_cost_usd
+            )
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(summary.join("  |  ")).block(Block::bordered().title(format!(
+            " Session {} ({}) ",
+            timeline.session_id, timeline.period
+        ))),
+        header,
+    );
+
+    let table = Table::new(
+        timeline.events.iter().map(|evt| {
+            Row::new(vec![
+                evt.timestamp.format("%m-%d %H:%M:%S").to_string(),
+                format!("+{}", format_duration(evt.gap_secs)),
+                format!("{}/{}", evt.provider, evt.model),
+                evt.usage.input_tokens.to_string(),
+                evt.usage.output_tokens.to_string(),
+                evt.usage.cache_read_tokens.to_string(),
+                evt.variable_cost_usd.map_or_else(
+                    || "unpriced".to_string(),
+                    |cost| format!("{}{:.2}", sym, cost),
+                ),
+                format!("{}{:.2}", sym, evt.cumulative_cost_usd),
+            ])
+        }),
+        [
+            Constraint::Length(14),
+            Constraint::Length(8),
+            Constraint::Fill(1),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(11),
+        ],
+    )
+    .header(header_row([
+        "Time",
+        "Gap",
+        "Model",
+        "Input",
+        "Output",
+        "Cache read",
+        "Cost",
+        "Cumulative",
+    ]))
+    .row_highlight_style(highlight(true))
+    .block(Block::bordered().title(format!(" Events ({}) ", timeline.events.len())));
+    let mut state = TableState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(table, events, &mut state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{ExportArgs, FxDateMode, OutputMode, QueryArgs};
+    use std::io::Write;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn test_daily_spend_fills_days_without_events() {
+        let days = [(day(2), 1.25), (day(4), 0.5), (day(9), 3.0)];
+        assert_eq!(daily_spend_cents(&days, day(1), day(5)), [0, 125, 0, 50, 0]);
+        // Days outside the range are dropped; an empty range is empty.
+        assert_eq!(daily_spend_cents(&days, day(9), day(9)), [300]);
+        assert!(daily_spend_cents(&days, day(5), day(4)).is_empty());
+    }
+
+    #[test]
+    fn test_file_stamps_change_when_events_are_appended() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events.jsonl");
+        let missing = dir.path().join("budgets.json");
+        fs::write(&events, "{}\n").unwrap();
+        let paths = [events.clone(), missing];
+        let before = file_stamps(&paths);
+        assert!(before[0].is_some());
+        assert_eq!(before[1], None);
+        assert_eq!(file_stamps(&paths), before);
+
+        let mut file = fs::OpenOptions::new().append(true).open(&events).unwrap();
+        file.write_all(b"{}\n").unwrap();
+        let after = file_stamps(&paths);
+        assert_ne!(after, before);
+        assert_eq!(after[0].map(|(len, _)| len), Some(6));
+    }
+
+    #[test]
+    fn test_load_matches_with_and_without_the_aggregate_cache() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let dir = tempfile::tempdir().unwrap();
+        let args = |aggregate_cache_path: Option<PathBuf>| TuiArgs {
+            query: QueryArgs {
+                events: vec![root.join("examples/events.jsonl")],
+                pricing: root.join("pricing.example.json"),
+                providers: Vec::new(),
+                models: Vec::new(),
+                top_models: None,
+                top_providers: None,
+                output: OutputMode::Table,
+                on_unpriced: ParetoRs::OnUnpricedAction::Error,
+                allocation: None,
+                currency: USD.to_string(),
+                fx_rates: None,
+                fx_date: FxDateMode::EventDay,
+                no_session_counts: false,
+                export: ExportArgs::default(),
+            },
+            month: Some("2026-02".to_string()),
+            budgets: None,
+            aggregate_cache_path,
+            refresh_secs: 2,
+        };
+        let now = Utc::now();
+        let direct = App::new(args(None)).load(now).unwrap();
+        let mut cached = App::new(args(Some(dir.path().join("aggregate-cache.json"))));
+        let miss = cached.load(now).unwrap();
+        let hit = cached.load(now).unwrap();
+
+        assert!(!direct.cache_hit && !miss.cache_hit && hit.cache_hit);
+        for dashboard in [&miss, &hit] {
+            assert_eq!(
+                dashboard.monthly.monthly_total_usd,
+                direct.monthly.monthly_total_usd
+            );
+            assert_eq!(dashboard.daily_cents, direct.daily_cents);
+            assert_eq!(dashboard.sessions.len(), direct.sessions.len());
+        }
+        assert!(!direct.sessions.is_empty());
+    }
+}
@@ -6,7 +6,8 @@ use std::path::{Path, PathBuf};
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Datelike, Utc};
 
-use crate::cli::{BenchScenario, OutputMode};
+use crate::cli::{BenchScenario, ExportArgs, OutputMode};
+use crate::export::{cost_breakdown_tables, write_export};
 use crate::models::*;
 
 // Re-export from specialized modules for backwards compatibility
@@ -15,12 +16,17 @@ pub use crate::cache::{
     resolve_ingest_providers, resolve_model_alias, resolve_provider_alias,
 };
 pub use crate::cost::{
-    allocate_subscription, build_breakdown, calc_variable_cost, compute_costs, event_pricing,
+    allocate_subscription, build_breakdown, calc_variable_cost, collect_long_context_usage,
+    compute_costs, compute_costs_in_currency, compute_costs_with_allocator, event_pricing,
     make_suggestions, merge_acc, session_hash,
 };
 pub use crate::format::{
-    default_generated_at, print_coverage_table, print_daily_markdown, print_daily_table,
-    print_markdown, print_pricing_audit_report, print_table, round2, round4, top_rows,
+    default_generated_at, print_anomaly_table, print_budget_table, print_cache_table,
+    print_compare_markdown, print_compare_table, print_coverage_table, print_forecast_table,
+    print_markdown, print_pivot_markdown, print_pivot_table, print_plan_analysis_table,
+    print_pricing_audit_report, print_session_table, print_session_timeline, print_table,
+    print_timeseries_markdown, print_timeseries_table, print_whatif_table, print_window_table,
+    round2, round4, top_rows,
 };
 
 pub const MTOK: f64 = 1_000_000.0;
@@ -158,6 +164,7 @@ pub fn bench_scenario_name(scenario: BenchScenario) -> &'static str {
         BenchScenario::WarmTail => "warm-tail",
         BenchScenario::Burst => "burst",
         BenchScenario::All => "all",
+        BenchScenario::Streaming => "streaming",
     }
 }
 
@@ -213,6 +220,11 @@ pub fn print_bench_table(report: &BenchReport) {
             );
         }
     }
+    for result in &report.results {
+        if let Some(peak_rss_kb) = result.peak_rss_kb {
+            println!("  {} peak RSS: {} KiB", result.scenario, peak_rss_kb);
+        }
+    }
 }
 
 pub fn print_bench_trend_table(report: &BenchTrendReport) {
@@ -265,10 +277,22 @@ pub fn apply_pricing_patch_file(
     dry_run: bool,
     write_backup: bool,
     allow_overwrite_model_rates: bool,
+    effective_from: Option<DateTime<Utc>>,
 ) -> Result<PricingApplyExecution> {
     let mut pricing = load_pricing(pricing_path)?;
     let patch = load_pricing_patch(patch_path)?;
-    let (summary, changed) = merge_pricing_patch(&mut pricing, &patch, allow_overwrite_model_rates);
+    let (summary, changed) = merge_pricing_patch(
+        &mut pricing,
+        &patch,
+        allow_overwrite_model_rates,
+        effective_from,
+    );
+    if !summary.period_errors.is_empty() {
+        return Err(anyhow!(
+            "pricing patch has conflicting effective periods: {}",
+            summary.period_errors.join("; ")
+        ));
+    }
 
     validate_aliases(&pricing)?;
     let merged_json = serde_json::to_string_pretty(&pricing)?;
@@ -358,10 +382,11 @@ pub fn collect_pricing_placeholder_violations(pricing: &PricingBook) -> Vec<Stri
     let mut violations = Vec::new();
 
     for (provider_name, provider) in &pricing.providers {
-        for (model_name, rate) in &provider.models {
-            if has_placeholder_marker(&rate.input_usd_per_mtok.to_string())
-                || has_placeholder_marker(&rate.output_usd_per_mtok.to_string())
-            {
+        for (model_name, schedule) in &provider.models {
+            if schedule.periods().iter().any(|rate| {
+                has_placeholder_marker(&rate.input_usd_per_mtok.to_string())
+                    || has_placeholder_marker(&rate.output_usd_per_mtok.to_string())
+            }) {
                 append_rate_violation(
                     &mut violations,
                     provider_name,
@@ -376,12 +401,128 @@ pub fn collect_pricing_placeholder_violations(pricing: &PricingBook) -> Vec<Stri
                 provider_name, provider.subscription_usd_month
             ));
         }
+        for seat in &provider.seats {
+            let label = seat.name.as_deref().unwrap_or("(unnamed)");
+            if let (Some(start), Some(end)) = (seat.start, seat.end) {
+                if end < start {
+                    violations.push(format!(
+                        "{}: seat {} ends ({}) before it starts ({})",
+                        provider_name, label, end, start
+                    ));
+                }
+            }
+            if seat.usd_month.is_some_and(|usd| usd < 0.0) {
+                violations.push(format!(
+                    "{}: seat {} subscription is negative",
+                    provider_name, label
+                ));
+            }
+        }
+        let plan_refs = provider
+            .plan
+            .iter()
+            .chain(provider.seats.iter().filter_map(|seat| seat.plan.as_ref()));
+        for plan in plan_refs {
+            if !provider.plans.contains_key(plan) {
+                violations.push(format!("{}: unknown plan {}", provider_name, plan));
+            }
+        }
+        for (plan_name, plan) in &provider.plans {
+            if plan.usd_month < 0.0 {
+                violations.push(format!(
+                    "{}: plan {} price is negative",
+                    provider_name, plan_name
+                ));
+            }
+            for window in plan.windows.iter().filter(|window| window.hours == 0) {
+                violations.push(format!(
+                    "{}: plan {} window {} has zero hours",
+                    provider_name, plan_name, window.name
+                ));
+            }
+            if plan.overage == PlanOverage::PerMtok && plan.overage_usd_per_mtok.is_none() {
+                violations.push(format!(
+                    "{}: plan {} has per_mtok overage without overage_usd_per_mtok",
+                    provider_name, plan_name
+                ));
+            }
+        }
+        if let Some(contract) = &provider.contract {
+            let discounts = std::iter::once((None, contract.discount_pct)).chain(
+                contract
+                    .model_discounts
+                    .iter()
+                    .map(|(model, pct)| (Some(model), *pct)),
+            );
+            for (model, pct) in discounts {
+                if !(0.0..=100.0).contains(&pct) {
+                    violations.push(format!(
+                        "{}: contract discount{} {} is outside 0..=100",
+                        provider_name,
+                        model.map(|m| format!(" for {}", m)).unwrap_or_default(),
+                        pct
+                    ));
+                }
+                if let Some(model) = model.filter(|m| !provider.models.contains_key(*m)) {
+                    violations.push(format!(
+                        "{}: contract discount for unknown model {}",
+                        provider_name, model
+                    ));
+                }
+            }
+            for credit in &contract.credits {
+                let label = credit.name.as_deref().unwrap_or("(unnamed)");
+                if credit.usd < 0.0 {
+                    violations.push(format!(
+                        "{}: contract credit {} is negative",
+                        provider_name, label
+                    ));
+                }
+                if credit
+                    .expires
+                    .is_some_and(|expires| expires < credit.granted)
+                {
+                    violations.push(format!(
+                        "{}: contract credit {} expires before it is granted",
+                        provider_name, label
+                    ));
+                }
+            }
+            if contract.commit_usd_month.is_some_and(|usd| usd < 0.0) {
+                violations.push(format!("{}: contract commit is negative", provider_name));
+            }
+        }
     }
 
-    if violations.is_empty() {
-        violations.push("(none)".to_string());
+    violations
+}
+
+pub fn collect_rate_period_violations(pricing: &PricingBook) -> Vec<String> {
+    let mut violations = Vec::new();
+    for (provider_name, provider) in &pricing.providers {
+        for (model_name, schedule) in &provider.models {
+            for issue in schedule.period_issues() {
+                append_rate_violation(&mut violations, provider_name, model_name, &issue);
+            }
+        }
     }
+    violations.sort();
+    violations
+}
 
+pub fn collect_context_tier_violations(pricing: &PricingBook) -> Vec<String> {
+    let mut violations = Vec::new();
+    for (provider_name, provider) in &pricing.providers {
+        for (model_name, schedule) in &provider.models {
+            for rate in schedule.periods() {
+                for issue in rate.context_tier_issues() {
+                    append_rate_violation(&mut violations, provider_name, model_name, &issue);
+                }
+            }
+        }
+    }
+    violations.sort();
+    violations.dedup();
     violations
 }
 
@@ -402,6 +543,7 @@ pub fn merge_pricing_patch(
     pricing: &mut PricingBook,
     patch: &PricingPatch,
     _allow_overwrite_model_rates: bool,
+    effective_from: Option<DateTime<Utc>>,
 ) -> (PricingApplySummary, bool) {
     let mut summary = PricingApplySummary::default();
     let mut changed = false;
@@ -410,9 +552,20 @@ pub fn merge_pricing_patch(
     for (provider_name, missing_patch) in &patch.missing_providers {
         if !pricing.providers.contains_key(provider_name) {
             let new_provider = ProviderPricing {
+                currency: None,
                 subscription_usd_month: missing_patch.subscription_usd_month,
-                models: missing_patch.models.clone(),
+                models: missing_patch
+                    .models
+                    .iter()
+                    .map(|(name, rate)| (name.clone(), ModelRateSchedule::from(rate.clone())))
+                    .collect(),
                 model_aliases: missing_patch.model_aliases.clone(),
+                service_tiers: HashMap::new(),
+                allocation: None,
+                seats: Vec::new(),
+                plan: None,
+                plans: HashMap::new(),
+                contract: None,
             };
             pricing
                 .providers
@@ -426,14 +579,28 @@ pub fn merge_pricing_patch(
     for (provider_name, missing_models) in &patch.missing_models_by_provider {
         if let Some(provider) = pricing.providers.get_mut(provider_name) {
             for (model_name, model_rate) in missing_models {
-                if !provider.models.contains_key(model_name) {
-                    provider
-                        .models
-                        .insert(model_name.clone(), model_rate.clone());
-                    summary.models_added += 1;
-                    changed = true;
-                } else {
-                    summary.models_skipped_existing += 1;
+                let from = model_rate.effective_from.or(effective_from);
+                match (provider.models.get_mut(model_name), from) {
+                    (None, _) => {
+                        provider.models.insert(
+                            model_name.clone(),
+                            ModelRateSchedule::from(model_rate.clone()),
+                        );
+                        summary.models_added += 1;
+                        changed = true;
+                    }
+                    (Some(schedule), Some(from)) => {
+                        match schedule.append_period(model_rate.clone(), from) {
+                            Ok(()) => {
+                                summary.periods_appended += 1;
+                                changed = true;
+                            }
+                            Err(err) => summary
+                                .period_errors
+                                .push(format!("{} / {}: {}", provider_name, model_name, err)),
+                        }
+                    }
+                    (Some(_), None) => summary.models_skipped_existing += 1,
                 }
             }
         }
@@ -481,26 +648,29 @@ pub fn render_cost_breakdown(
     output: OutputMode,
     top_providers: Option<usize>,
     top_models: Option<usize>,
+    export: &ExportArgs,
 ) -> Result<()> {
     match output {
-        OutputMode::Json => {
-            let json = serde_json::json!({
-                "variable_cost_usd": report.variable_cost_usd,
-                "subscription_allocated_usd": report.subscription_allocated_usd,
-                "monthly_total_usd": report.monthly_total_usd,
-                "blended_usd_per_mtok": report.blended_usd_per_mtok,
-                "total_tokens": report.total_tokens,
-                "total_mtok": report.total_mtok,
-                "session_count": report.session_count,
-            });
-            println!("{}", serde_json::to_string_pretty(&json)?);
-        }
+        OutputMode::Json => println!("{}", cost_breakdown_json(report)?),
         OutputMode::Table => print_table(label, report, top_providers, top_models),
         OutputMode::Markdown => print_markdown(label, report, top_providers, top_models),
+        OutputMode::Csv | OutputMode::Xlsx => {
+            write_export(&cost_breakdown_tables(report), output, export)?
+        }
+        OutputMode::Html => {
+            return Err(anyhow!(
+                "html reports are built from a time series; use monthly, daily or series"
+            ))
+        }
     }
     Ok(())
 }
 
+// The whole breakdown, so `currency` says what the `_usd` amounts are in.
+pub fn cost_breakdown_json(report: &CostBreakdown) -> Result<String> {
+    Ok(serde_json::to_string_pretty(report)?)
+}
+
 pub fn load_events(paths: &[PathBuf]) -> Result<Vec<UsageEvent>> {
     let mut events = Vec::new();
     for path in paths {
@@ -510,6 +680,17 @@ pub fn load_events(paths: &[PathBuf]) -> Result<Vec<UsageEvent>> {
 }
 
 pub fn parse_jsonl_file(path: &Path, out: &mut Vec<UsageEvent>) -> Result<()> {
+    for_each_jsonl_event(path, |event| {
+        out.push(event);
+        Ok(())
+    })
+}
+
+// Calls `f` with each event of a JSONL file in order, holding one line in memory at a time.
+pub fn for_each_jsonl_event(
+    path: &Path,
+    mut f: impl FnMut(UsageEvent) -> Result<()>,
+) -> Result<()> {
     let file = File::open(path).with_context(|| format!("opening {:?}", path))?;
     let reader = BufReader::new(file);
 
@@ -520,7 +701,7 @@ pub fn parse_jsonl_file(path: &Path, out: &mut Vec<UsageEvent>) -> Result<()> {
         }
         let event: UsageEvent = serde_json::from_str(&line)
             .with_context(|| format!("parsing line {} in {:?}", line_no + 1, path))?;
-        out.push(event);
+        f(event)?;
     }
 
     Ok(())
@@ -673,6 +854,7 @@ mod tests {
         assert_eq!(bench_scenario_name(BenchScenario::WarmTail), "warm-tail");
         assert_eq!(bench_scenario_name(BenchScenario::Burst), "burst");
         assert_eq!(bench_scenario_name(BenchScenario::All), "all");
+        assert_eq!(bench_scenario_name(BenchScenario::Streaming), "streaming");
     }
 
     #[test]
@@ -734,4 +916,126 @@ mod tests {
     fn test_mtok_constant() {
         assert_eq!(MTOK, 1_000_000.0);
     }
+
+    #[test]
+    fn test_merge_pricing_patch_appends_dated_period() {
+        let rate = |input: f64| ModelRate {
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: 10.0,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: None,
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        };
+        let mut pricing = PricingBook {
+            providers: HashMap::from([(
+                "codex".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 0.0,
+                    models: HashMap::from([("gpt-5".to_string(), rate(1.25).into())]),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::new(),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: None,
+                    plans: HashMap::new(),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        };
+        let mut patch = PricingPatch::default();
+        patch.missing_models_by_provider.insert(
+            "codex".to_string(),
+            HashMap::from([("gpt-5".to_string(), rate(1.0))]),
+        );
+
+        let (summary, changed) = merge_pricing_patch(&mut pricing, &patch, false, None);
+        assert!(!changed);
+        assert_eq!(summary.models_skipped_existing, 1);
+
+        let from = DateTime::parse_from_rfc3339("2026-05-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let (summary, changed) = merge_pricing_patch(&mut pricing, &patch, false, Some(from));
+        assert!(changed);
+        assert_eq!(summary.periods_appended, 1);
+        let schedule = &pricing.providers["codex"].models["gpt-5"];
+        assert_eq!(schedule.periods().len(), 2);
+        assert_eq!(schedule.latest().unwrap().input_usd_per_mtok, 1.0);
+
+        let (summary, _) = merge_pricing_patch(&mut pricing, &patch, false, Some(from));
+        assert_eq!(summary.period_errors.len(), 1);
+    }
+
+    // Runs `tokenledger monthly --output json` over the example data, as `run_monthly` does.
+    fn monthly_json(extra: &[&str]) -> serde_json::Value {
+        use clap::Parser;
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let events = root.join("examples/events.jsonl");
+        let pricing = root.join("pricing.example.json");
+        let mut argv = vec![
+            "tokenledger".to_string(),
+            "monthly".to_string(),
+            "--events".to_string(),
+            events.to_string_lossy().into_owned(),
+            "--pricing".to_string(),
+            pricing.to_string_lossy().into_owned(),
+            "--month".to_string(),
+            "2026-02".to_string(),
+            "--output".to_string(),
+            "json".to_string(),
+        ];
+        argv.extend(extra.iter().map(|arg| arg.to_string()));
+        let crate::cli::Command::Monthly(args) = crate::cli::Cli::parse_from(argv).command else {
+            unreachable!("parsed a monthly command");
+        };
+        let range = crate::period::TimeRange::from_args(&args.period, Utc::now()).unwrap();
+        let report = crate::analytics::build_monthly_report(&args.query, &range).unwrap();
+        serde_json::from_str(&cost_breakdown_json(&report).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_monthly_json_reports_its_currency() {
+        let usd = monthly_json(&[]);
+        assert_eq!(usd["currency"], "USD");
+        assert_eq!(usd["model_breakdown"].as_array().map(Vec::len), Some(4));
+
+        let fx_rates =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples/fx-rates.example.json");
+        let eur = monthly_json(&[
+            "--currency",
+            "EUR",
+            "--fx-rates",
+            fx_rates.to_str().unwrap(),
+        ]);
+        assert_eq!(eur["currency"], "EUR");
+        assert!(eur["monthly_total_usd"].as_f64() < usd["monthly_total_usd"].as_f64());
+    }
+
+    #[test]
+    fn test_monthly_json_includes_the_contract_summary() {
+        let report = monthly_json(&[]);
+        let contract = &report["contract"];
+        assert_eq!(contract["list_cost_usd"], report["monthly_total_usd"]);
+        assert_eq!(contract["providers"][0]["provider"], "codex");
+        assert_eq!(contract["credits_applied_usd"], 5.0);
+    }
+
+    #[test]
+    fn test_monthly_json_includes_the_cache_section() {
+        let report = monthly_json(&[]);
+        let cache = &report["cache"];
+        assert_eq!(cache["totals"]["cache_read_tokens"], 4_870_000);
+        assert_eq!(cache["totals"]["net_savings_usd"], 22.13);
+        assert_eq!(cache["providers"].as_array().map(Vec::len), Some(4));
+        assert_eq!(cache["providers"][0]["name"], "claude");
+    }
 }
@@ -0,0 +1,557 @@
+// What-if repricing: moves a month's traffic to other providers/models (optionally reshaping
+// its tokens) and prices it again, against the same or a different pricing book.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, Result};
+use chrono::Datelike;
+use ParetoRs::OnUnpricedAction;
+
+use crate::allocation::prorated_subscription;
+use crate::cli::{OutputMode, WhatifArgs};
+use crate::export::{whatif_tables, write_export};
+use crate::fx::CurrencyConverter;
+use crate::models::*;
+use crate::money::{cents_to_units, to_cents, to_micros, units_to_cents};
+use crate::period::TimeRange;
+use crate::stream::{CostStream, ReportView};
+use crate::utils::{
+    compute_costs, filter_month, load_events, load_pricing, normalize_events, parse_month,
+    print_whatif_table, resolve_model_alias, resolve_provider_alias, round2,
+};
+
+pub fn run_whatif(args: WhatifArgs) -> Result<()> {
+    let pricing = load_pricing(&args.pricing)?;
+    let target = match args.target_pricing.as_deref() {
+        Some(path) => load_pricing(path)?,
+        None => pricing.clone(),
+    };
+    let rules = args
+        .rules
+        .iter()
+        .map(|raw| SubstitutionRule::parse(raw).map(|rule| rule.resolve(&pricing, &target)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let events = normalize_events(load_events(&args.events)?, &pricing);
+    let month = match args.month.as_deref() {
+        Some(raw) => parse_month(raw)?,
+        None => events
+            .iter()
+            .map(|evt| (evt.timestamp.year(), evt.timestamp.month()))
+            .max()
+            .ok_or_else(|| anyhow!("no events found"))?,
+    };
+    let month_label = format!("{:04}-{:02}", month.0, month.1);
+    let filtered = filter_month(events, Some(&month_label))?;
+    if filtered.is_empty() {
+        return Err(anyhow!("no events matched selected month filters"));
+    }
+
+    let report = build_whatif(
+        &filtered,
+        &pricing,
+        &target,
+        &rules,
+        args.on_unpriced,
+        month,
+    )?;
+    for summary in report.rules.iter().filter(|summary| summary.events == 0) {
+        eprintln!(
+            "warning: whatif rule {:?} matched no events in {}",
+            summary.rule, month_label
+        );
+    }
+    let output = if args.json_output {
+        OutputMode::Json
+    } else {
+        args.output
+    };
+    match output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_whatif_table(&report),
+        OutputMode::Csv | OutputMode::Xlsx => {
+            write_export(&whatif_tables(&report), output, &args.export)?
+        }
+        OutputMode::Markdown | OutputMode::Html => {
+            return Err(anyhow!("whatif supports table, json, csv and xlsx output"))
+        }
+    }
+    Ok(())
+}
+
+// One `FROM=TO[,option=value...]` substitution. A missing model on either side means every
+// model of the provider, and the model name is kept when only the provider changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubstitutionRule {
+    pub raw: String,
+    pub from_provider: String,
+    pub from_model: Option<String>,
+    pub to_provider: String,
+    pub to_model: Option<String>,
+    // Share of input plus cache-read tokens served from cache after the move.
+    pub cache_hit_ratio: Option<f64>,
+    // Scale on prompt tokens (tokenizer differences) and on output tokens (verbosity).
+    pub input_ratio: f64,
+    pub output_ratio: f64,
+}
+
+impl SubstitutionRule {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut parts = raw.split(',');
+        let mapping = parts.next().unwrap_or_default();
+        let (from, to) = mapping
+            .split_once('=')
+            .ok_or_else(|| anyhow!("whatif rule {:?} must look like FROM=TO", raw))?;
+        let (from_provider, from_model) = parse_target(from, raw)?;
+        let (to_provider, to_model) = parse_target(to, raw)?;
+        let mut rule = Self {
+            raw: raw.to_string(),
+            from_provider,
+            from_model,
+            to_provider,
+            to_model,
+            cache_hit_ratio: None,
+            input_ratio: 1.0,
+            output_ratio: 1.0,
+        };
+        for option in parts {
+            let (key, value) = option.split_once('=').ok_or_else(|| {
+                anyhow!("whatif rule {:?}: option {:?} needs a value", raw, option)
+            })?;
+            let value: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("whatif rule {:?}: {} must be a number", raw, key.trim()))?;
+            match key.trim() {
+                "cache_hit_ratio" if (0.0..=1.0).contains(&value) => {
+                    rule.cache_hit_ratio = Some(value)
+                }
+                "input_ratio" | "output_ratio" if value < 0.0 => {
+                    return Err(anyhow!(
+                        "whatif rule {:?}: {} must be >= 0",
+                        raw,
+                        key.trim()
+                    ))
+                }
+                "input_ratio" => rule.input_ratio = value,
+                "output_ratio" => rule.output_ratio = value,
+                "cache_hit_ratio" => {
+                    return Err(anyhow!(
+                        "whatif rule {:?}: cache_hit_ratio must be between 0 and 1",
+                        raw
+                    ))
+                }
+                other => {
+                    return Err(anyhow!(
+                        "whatif rule {:?}: unknown option {:?} (expected cache_hit_ratio, input_ratio or output_ratio)",
+                        raw,
+                        other
+                    ))
+                }
+            }
+        }
+        Ok(rule)
+    }
+
+    // Canonical names: the source side through the events' pricing book, the target side
+    // through the book the simulation is priced with.
+    pub fn resolve(mut self, source: &PricingBook, target: &PricingBook) -> Self {
+        self.from_provider = resolve_provider_alias(&self.from_provider, source);
+        self.from_model = self
+            .from_model
+            .map(|model| resolve_model_alias(&self.from_provider, &model, source));
+        self.to_provider = resolve_provider_alias(&self.to_provider, target);
+        self.to_model = self
+            .to_model
+            .map(|model| resolve_model_alias(&self.to_provider, &model, target));
+        self
+    }
+
+    // Whether every event `other` matches is also matched by this rule.
+    fn covers(&self, other: &SubstitutionRule) -> bool {
+        self.from_provider == other.from_provider
+            && (self.from_model.is_none() || self.from_model == other.from_model)
+    }
+
+    pub fn matches(&self, evt: &UsageEvent) -> bool {
+        evt.provider == self.from_provider
+            && self
+                .from_model
+                .as_ref()
+                .is_none_or(|model| *model == evt.model)
+    }
+
+    pub fn apply(&self, evt: &UsageEvent) -> UsageEvent {
+        let mut moved = evt.clone();
+        moved.provider = self.to_provider.clone();
+        if let Some(model) = &self.to_model {
+            moved.model = model.clone();
+        }
+        let usage = &mut moved.usage;
+        for tokens in [
+            &mut usage.input_tokens,
+            &mut usage.cache_write_tokens,
+            &mut usage.cache_write_1h_tokens,
+            &mut usage.cache_read_tokens,
+        ] {
+            *tokens = scale_tokens(*tokens, self.input_ratio);
+        }
+        usage.output_tokens = scale_tokens(usage.output_tokens, self.output_ratio);
+        if let Some(ratio) = self.cache_hit_ratio {
+            let readable = usage.input_tokens + usage.cache_read_tokens;
+            usage.cache_read_tokens = scale_tokens(readable, ratio);
+            usage.input_tokens = readable - usage.cache_read_tokens;
+        }
+        moved
+    }
+}
+
+fn parse_target(raw: &str, rule: &str) -> Result<(String, Option<String>)> {
+    let (provider, model) = match raw.trim().split_once('/') {
+        Some((provider, model)) => (provider.trim(), Some(model.trim())),
+        None => (raw.trim(), None),
+    };
+    if provider.is_empty() || model.is_some_and(str::is_empty) {
+        return Err(anyhow!(
+            "whatif rule {:?}: {:?} must be provider or provider/model",
+            rule,
+            raw
+        ));
+    }
+    Ok((provider.to_string(), model.map(str::to_string)))
+}
+
+fn scale_tokens(tokens: u64, ratio: f64) -> u64 {
+    if ratio == 1.0 {
+        tokens
+    } else {
+        (tokens as f64 * ratio).round() as u64
+    }
+}
+
+// `events` must already be normalized against `pricing` and limited to one month. Each event
+// takes the first rule that matches it; the rest are priced unchanged under `target`. A rule an
+// earlier one shadows could never match, so it is rejected rather than reported as moving nothing.
+pub fn build_whatif(
+    events: &[UsageEvent],
+    pricing: &PricingBook,
+    target: &PricingBook,
+    rules: &[SubstitutionRule],
+    on_unpriced: OnUnpricedAction,
+    month: (i32, u32),
+) -> Result<WhatifReport> {
+    for (idx, rule) in rules.iter().enumerate() {
+        if let Some(earlier) = rules[..idx].iter().find(|earlier| earlier.covers(rule)) {
+            return Err(anyhow!(
+                "whatif rule {:?} can never match: {:?} already moves all of its traffic; put the more specific rule first",
+                rule.raw,
+                earlier.raw
+            ));
+        }
+    }
+    let mut summaries: Vec<WhatifRuleSummary> = rules
+        .iter()
+        .map(|rule| WhatifRuleSummary {
+            rule: rule.raw.clone(),
+            events: 0,
+            tokens_before: 0,
+            tokens_after: 0,
+        })
+        .collect();
+    let simulated_events: Vec<UsageEvent> = events
+        .iter()
+        .map(|evt| {
+            let Some(idx) = rules.iter().position(|rule| rule.matches(evt)) else {
+                return evt.clone();
+            };
+            let moved = rules[idx].apply(evt);
+            let summary = &mut summaries[idx];
+            summary.events += 1;
+            summary.tokens_before += evt.usage.total();
+            summary.tokens_after += moved.usage.total();
+            moved
+        })
+        .collect();
+    let simulated_events = normalize_events(simulated_events, target);
+
+    let actual = compute_costs(events, pricing, on_unpriced)?;
+    let mut simulated = compute_costs(&simulated_events, target, on_unpriced)?;
+    let actual_models = model_rows(events, pricing, on_unpriced, month)?;
+    let simulated_models = model_rows(&simulated_events, target, on_unpriced, month)?;
+    // Providers whose traffic all moved away still pay for their seats.
+    let remaining: BTreeSet<&str> = simulated_events
+        .iter()
+        .map(|evt| evt.provider.as_str())
+        .collect();
+    let stranded: BTreeSet<&str> = events
+        .iter()
+        .map(|evt| evt.provider.as_str())
+        .filter(|provider| !remaining.contains(provider))
+        .collect();
+    let stranded_cents: i64 = stranded
+        .into_iter()
+        .filter_map(|provider| target.providers.get(provider))
+        .map(|provider| to_cents(to_micros(prorated_subscription(provider, month))))
+        .sum();
+    simulated.idle_subscription_usd =
+        cents_to_units(units_to_cents(simulated.idle_subscription_usd) + stranded_cents);
+    let actual_total_usd = round2(actual.monthly_total_usd + actual.idle_subscription_usd);
+    let simulated_total_usd = round2(simulated.monthly_total_usd + simulated.idle_subscription_usd);
+    Ok(WhatifReport {
+        month: format!("{:04}-{:02}", month.0, month.1),
+        rules: summaries,
+        providers: metric_deltas(&actual.provider_breakdown, &simulated.provider_breakdown),
+        models: metric_deltas(&actual_models, &simulated_models),
+        actual,
+        simulated,
+        actual_total_usd,
+        simulated_total_usd,
+        delta_usd: round2(simulated_total_usd - actual_total_usd),
+    })
+}
+
+// Model rows keyed "provider/model", so traffic moved to the same model at another provider
+// shows as a change on both rows.
+fn model_rows(
+    events: &[UsageEvent],
+    pricing: &PricingBook,
+    on_unpriced: OnUnpricedAction,
+    month: (i32, u32),
+) -> Result<Vec<NamedMetric>> {
+    let mut stream = CostStream::new(
+        pricing.clone(),
+        CurrencyConverter::usd(),
+        None,
+        TimeRange::month(month),
+        false,
+    );
+    for evt in events {
+        stream.observe(evt.clone());
+    }
+    stream.model_rows(&ReportView::new(pricing, &[], &[], on_unpriced))
+}
+
+// Rows present on either side, biggest savings first.
+fn metric_deltas(actual: &[NamedMetric], simulated: &[NamedMetric]) -> Vec<WhatifDelta> {
+    let mut totals: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for row in actual {
+        totals.entry(&row.name).or_default().0 = row.total_cost_usd;
+    }
+    for row in simulated {
+        totals.entry(&row.name).or_default().1 = row.total_cost_usd;
+    }
+    let mut deltas: Vec<WhatifDelta> = totals
+        .into_iter()
+        .map(|(name, (actual_usd, simulated_usd))| WhatifDelta {
+            name: name.to_string(),
+            actual_usd,
+            simulated_usd,
+            delta_usd: round2(simulated_usd - actual_usd),
+        })
+        .collect();
+    deltas.sort_by(|a, b| a.delta_usd.total_cmp(&b.delta_usd));
+    deltas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn rate(input: f64, output: f64, cache_read: f64) -> ModelRateSchedule {
+        ModelRate {
+            input_usd_per_mtok: input,
+            output_usd_per_mtok: output,
+            cache_write_usd_per_mtok: None,
+            cache_write_1h_usd_per_mtok: None,
+            cache_read_usd_per_mtok: Some(cache_read),
+            tool_input_usd_per_mtok: None,
+            tool_output_usd_per_mtok: None,
+            effective_from: None,
+            effective_to: None,
+            context_tiers: Vec::new(),
+        }
+        .into()
+    }
+
+    fn provider(subscription: f64, models: Vec<(&str, ModelRateSchedule)>) -> ProviderPricing {
+        ProviderPricing {
+            currency: None,
+            subscription_usd_month: subscription,
+            models: models
+                .into_iter()
+                .map(|(name, rate)| (name.to_string(), rate))
+                .collect(),
+            model_aliases: HashMap::from([("sonnet".to_string(), "claude-sonnet-4-5".to_string())]),
+            service_tiers: HashMap::new(),
+            allocation: None,
+            seats: Vec::new(),
+            plan: None,
+            plans: HashMap::new(),
+            contract: None,
+        }
+    }
+
+    fn pricing() -> PricingBook {
+        PricingBook {
+            providers: HashMap::from([
+                (
+                    "claude".to_string(),
+                    provider(30.0, vec![("claude-sonnet-4-5", rate(3.0, 15.0, 0.3))]),
+                ),
+                (
+                    "codex".to_string(),
+                    provider(0.0, vec![("gpt-5", rate(1.25, 10.0, 0.125))]),
+                ),
+            ]),
+            provider_aliases: HashMap::from([("anthropic".to_string(), "claude".to_string())]),
+            meta: None,
+        }
+    }
+
+    fn event(provider: &str, model: &str, input: u64, cache_read: u64) -> UsageEvent {
+        UsageEvent {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            session_id: format!("{}-s1", provider),
+            timestamp: Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap(),
+            usage: TokenUsage {
+                input_tokens: input,
+                output_tokens: 100_000,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: cache_read,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_rule_and_reshape_tokens() {
+        let rule = SubstitutionRule::parse(
+            "anthropic/sonnet=codex/gpt-5,cache_hit_ratio=0.75,output_ratio=1.5",
+        )
+        .unwrap()
+        .resolve(&pricing(), &pricing());
+        assert_eq!(rule.from_provider, "claude");
+        assert_eq!(rule.from_model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(rule.to_provider, "codex");
+        assert_eq!(rule.to_model.as_deref(), Some("gpt-5"));
+
+        let evt = event("claude", "claude-sonnet-4-5", 800_000, 200_000);
+        assert!(rule.matches(&evt));
+        let moved = rule.apply(&evt);
+        assert_eq!(moved.model, "gpt-5");
+        assert_eq!(moved.usage.input_tokens, 250_000);
+        assert_eq!(moved.usage.cache_read_tokens, 750_000);
+        assert_eq!(moved.usage.output_tokens, 150_000);
+
+        for bad in [
+            "claude",
+            "claude=",
+            "claude=codex,cache_hit_ratio=2",
+            "claude=codex,speed=1",
+        ] {
+            assert!(SubstitutionRule::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_whatif_rejects_rules_shadowed_by_earlier_ones() {
+        let book = pricing();
+        let events = normalize_events(vec![event("claude", "sonnet", 1_000_000, 0)], &book);
+        let rules = |raw: &[&str]| -> Vec<SubstitutionRule> {
+            raw.iter()
+                .map(|rule| SubstitutionRule::parse(rule).unwrap().resolve(&book, &book))
+                .collect()
+        };
+        let build = |raw: &[&str]| {
+            build_whatif(
+                &events,
+                &book,
+                &book,
+                &rules(raw),
+                OnUnpricedAction::Error,
+                (2026, 2),
+            )
+        };
+
+        let err = build(&["claude=codex/gpt-5", "claude=codex"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("\"claude=codex\" can never match"), "{err}");
+        assert!(build(&["claude=codex", "anthropic/sonnet=codex"]).is_err());
+        // A specific rule ahead of a general one still moves the rest of the provider.
+        let report = build(&["claude/sonnet=codex/gpt-5", "claude=codex/gpt-5"]).unwrap();
+        assert_eq!(report.rules[0].events, 1);
+        assert_eq!(report.rules[1].events, 0);
+    }
+
+    #[test]
+    fn test_whatif_reports_deltas_and_keeps_idle_seats() {
+        let book = pricing();
+        let events = normalize_events(
+            vec![
+                event("claude", "sonnet", 1_000_000, 0),
+                event("codex", "gpt-5", 1_000_000, 0),
+            ],
+            &book,
+        );
+        let rules = vec![SubstitutionRule::parse("claude=codex/gpt-5")
+            .unwrap()
+            .resolve(&book, &book)];
+        let report = build_whatif(
+            &events,
+            &book,
+            &book,
+            &rules,
+            OnUnpricedAction::Error,
+            (2026, 2),
+        )
+        .unwrap();
+
+        assert_eq!(report.rules[0].events, 1);
+        // Claude: $3.00 + $1.50 variable + $30 seat, codex $2.25; as gpt-5 claude's is $2.25.
+        assert_eq!(report.actual.monthly_total_usd, 36.75);
+        assert_eq!(report.simulated.monthly_total_usd, 4.5);
+        // The claude seat is still paid, now idle.
+        assert_eq!(report.simulated.idle_subscription_usd, 30.0);
+        assert_eq!(report.delta_usd, -2.25);
+        assert_eq!(report.providers[0].name, "claude");
+        assert_eq!(report.providers[0].delta_usd, -34.5);
+        assert_eq!(report.providers[1].name, "codex");
+        assert_eq!(report.providers[1].simulated_usd, 4.5);
+    }
+
+    #[test]
+    fn test_model_deltas_keep_providers_apart() {
+        let mut book = pricing();
+        book.providers.insert(
+            "openrouter".to_string(),
+            provider(0.0, vec![("claude-sonnet-4-5", rate(3.0, 15.0, 0.3))]),
+        );
+        let events = normalize_events(vec![event("claude", "sonnet", 1_000_000, 0)], &book);
+        let rules = vec![SubstitutionRule::parse("claude=openrouter")
+            .unwrap()
+            .resolve(&book, &book)];
+        let report = build_whatif(
+            &events,
+            &book,
+            &book,
+            &rules,
+            OnUnpricedAction::Error,
+            (2026, 2),
+        )
+        .unwrap();
+
+        assert_eq!(report.models.len(), 2);
+        assert_eq!(report.models[0].name, "claude/claude-sonnet-4-5");
+        assert_eq!(report.models[0].delta_usd, -34.5);
+        assert_eq!(report.models[1].name, "openrouter/claude-sonnet-4-5");
+        assert_eq!(report.models[1].simulated_usd, 4.5);
+    }
+}
@@ -0,0 +1,455 @@
+// Rolling usage windows for rate-limited plans. Token usage is folded into per-minute buckets
+// kept only as far back as the longest configured window, so the tracker (and its checkpoint)
+// stays small and a refresh only has to read events appended since the last run.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{OutputMode, WindowsArgs};
+use crate::models::*;
+use crate::utils::{
+    load_pricing, normalize_events, normalize_provider_filters, print_window_table, round2,
+};
+
+const BUCKET_SECS: i64 = 60;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct WindowTracker {
+    // Per provider: tokens per minute, keyed by the bucket's unix start second.
+    buckets: BTreeMap<String, BTreeMap<i64, u64>>,
+    // Per provider and block window: unix second the current block started.
+    block_starts: BTreeMap<String, BTreeMap<String, i64>>,
+}
+
+impl WindowTracker {
+    // Folds new events into the tracker. Events for providers without plan windows are ignored;
+    // block windows assume events arrive roughly in time order.
+    pub fn observe(&mut self, events: &[UsageEvent], pricing: &PricingBook) {
+        let mut ordered: Vec<&UsageEvent> = events.iter().collect();
+        ordered.sort_by_key(|evt| evt.timestamp);
+        for evt in ordered {
+            let Some((_, windows)) = pricing.providers.get(&evt.provider).and_then(plan_windows)
+            else {
+                continue;
+            };
+            let at = evt.timestamp.timestamp();
+            let bucket = at.div_euclid(BUCKET_SECS) * BUCKET_SECS;
+            *self
+                .buckets
+                .entry(evt.provider.clone())
+                .or_default()
+                .entry(bucket)
+                .or_default() += evt.usage.total();
+            let starts = self.block_starts.entry(evt.provider.clone()).or_default();
+            for window in windows
+                .iter()
+                .filter(|window| window.kind == UsageWindowKind::Block)
+            {
+                let expired = starts
+                    .get(&window.name)
+                    .is_none_or(|start| at >= start + window_secs(window));
+                if expired {
+                    starts.insert(window.name.clone(), bucket);
+                }
+            }
+        }
+        self.prune(pricing);
+    }
+
+    fn prune(&mut self, pricing: &PricingBook) {
+        for (provider, buckets) in &mut self.buckets {
+            let horizon = pricing
+                .providers
+                .get(provider)
+                .and_then(plan_windows)
+                .and_then(|(_, windows)| windows.iter().map(window_secs).max())
+                .unwrap_or(0);
+            if let Some(&latest) = buckets.keys().next_back() {
+                *buckets = buckets.split_off(&(latest - horizon));
+            }
+        }
+    }
+
+    pub fn report(&self, pricing: &PricingBook, as_of: DateTime<Utc>) -> Vec<WindowUsage> {
+        let now = as_of.timestamp();
+        let empty = BTreeMap::new();
+        let mut providers: Vec<(&String, &ProviderPricing)> = pricing.providers.iter().collect();
+        providers.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut rows = Vec::new();
+        for (provider_name, provider) in providers {
+            let Some((plan, windows)) = plan_windows(provider) else {
+                continue;
+            };
+            let buckets = self.buckets.get(provider_name).unwrap_or(&empty);
+            for window in windows {
+                let span = window_secs(window);
+                // (first second counted, reset second) for the window's current extent. Only the
+                // latest block is tracked, so an `as_of` before it started has no known block.
+                let extent = match window.kind {
+                    UsageWindowKind::Block => self
+                        .block_starts
+                        .get(provider_name)
+                        .and_then(|starts| starts.get(&window.name))
+                        .filter(|start| **start <= now && now < **start + span)
+                        .map(|start| (*start, *start + span)),
+                    UsageWindowKind::Rolling if span > 0 => buckets
+                        .range(now - span + 1..=now)
+                        .next()
+                        .map(|(first, _)| (*first, *first + span)),
+                    UsageWindowKind::Rolling => None,
+                };
+                let tokens: u64 = extent
+                    .map(|(from, _)| buckets.range(from..=now).map(|(_, tokens)| tokens).sum())
+                    .unwrap_or(0);
+                rows.push(WindowUsage {
+                    provider: provider_name.clone(),
+                    plan: plan.to_string(),
+                    window: window.name.clone(),
+                    kind: window.kind,
+                    hours: window.hours,
+                    tokens,
+                    limit_tokens: window.limit_tokens,
+                    used_pct: window
+                        .limit_tokens
+                        .filter(|limit| *limit > 0)
+                        .map(|limit| round2(tokens as f64 / limit as f64 * 100.0)),
+                    started_at: extent.and_then(|(from, _)| DateTime::from_timestamp(from, 0)),
+                    resets_at: extent.and_then(|(_, to)| DateTime::from_timestamp(to, 0)),
+                });
+            }
+        }
+        rows
+    }
+}
+
+// Resumable state for `windows --checkpoint`: the tracker plus how many bytes of each events
+// file it has already consumed.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct WindowCheckpoint {
+    // Window configuration the tracker was built with; a change rebuilds from scratch.
+    pub windows_key: String,
+    pub offsets: BTreeMap<String, u64>,
+    pub tracker: WindowTracker,
+}
+
+pub fn run_windows(args: WindowsArgs) -> Result<()> {
+    let pricing = load_pricing(&args.pricing)?;
+    let windows_key = windows_key(&pricing)?;
+    if windows_key == "{}" {
+        return Err(anyhow!(
+            "no usage windows configured; add `windows` to the provider's current plan"
+        ));
+    }
+
+    let mut checkpoint = match args.checkpoint.as_deref() {
+        Some(path) => load_window_checkpoint(path)?,
+        None => WindowCheckpoint::default(),
+    };
+    if checkpoint.windows_key != windows_key {
+        checkpoint = WindowCheckpoint {
+            windows_key,
+            ..WindowCheckpoint::default()
+        };
+    }
+    refresh_window_checkpoint(&mut checkpoint, &args.events, &pricing)?;
+    if let Some(path) = args.checkpoint.as_deref() {
+        write_window_checkpoint(path, &checkpoint)?;
+    }
+
+    let mut report = WindowReport {
+        as_of: args.now.unwrap_or_else(Utc::now),
+        windows: Vec::new(),
+    };
+    report.windows = checkpoint.tracker.report(&pricing, report.as_of);
+    if !args.providers.is_empty() {
+        let wanted = normalize_provider_filters(&pricing, &args.providers);
+        report
+            .windows
+            .retain(|window| wanted.contains(&window.provider));
+    }
+
+    let output = if args.json_output {
+        OutputMode::Json
+    } else {
+        args.output
+    };
+    match output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputMode::Table => print_window_table(&report),
+        _ => return Err(anyhow!("windows supports table and json output")),
+    }
+    Ok(())
+}
+
+// Reads the bytes appended to each events file since the last refresh. A file shorter than its
+// recorded offset was rewritten, so the tracker is rebuilt from every file.
+pub fn refresh_window_checkpoint(
+    checkpoint: &mut WindowCheckpoint,
+    paths: &[PathBuf],
+    pricing: &PricingBook,
+) -> Result<()> {
+    let rewritten = paths.iter().any(|path| {
+        let key = path.to_string_lossy();
+        let len = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        checkpoint
+            .offsets
+            .get(key.as_ref())
+            .is_some_and(|offset| len < *offset)
+    });
+    if rewritten {
+        checkpoint.offsets.clear();
+        checkpoint.tracker = WindowTracker::default();
+    }
+
+    let mut events = Vec::new();
+    for path in paths {
+        let key = path.to_string_lossy().to_string();
+        let offset = checkpoint.offsets.get(&key).copied().unwrap_or(0);
+        let consumed = read_events_from(path, offset, &mut events)?;
+        checkpoint.offsets.insert(key, consumed);
+    }
+    let events = normalize_events(events, pricing);
+    checkpoint.tracker.observe(&events, pricing);
+    Ok(())
+}
+
+// Parses complete lines from `offset` onward and returns the offset after the last one, so a
+// line still being written is picked up on the next refresh.
+fn read_events_from(path: &Path, offset: u64, out: &mut Vec<UsageEvent>) -> Result<u64> {
+    let mut file = File::open(path).with_context(|| format!("opening {:?}", path))?;
+    file.seek(SeekFrom::Start(offset))
+        .with_context(|| format!("seeking {:?}", path))?;
+    let mut reader = BufReader::new(file);
+    let mut consumed = offset;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .with_context(|| format!("reading {:?}", path))?;
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        if !line.trim().is_empty() {
+            let event: UsageEvent = serde_json::from_str(&line)
+                .with_context(|| format!("parsing event at byte {} in {:?}", consumed, path))?;
+            out.push(event);
+        }
+        consumed += read as u64;
+    }
+    Ok(consumed)
+}
+
+pub fn load_window_checkpoint(path: &Path) -> Result<WindowCheckpoint> {
+    if !path.exists() {
+        return Ok(WindowCheckpoint::default());
+    }
+    serde_json::from_reader(File::open(path).with_context(|| format!("opening {:?}", path))?)
+        .with_context(|| format!("parsing window checkpoint {:?}", path))
+}
+
+pub fn write_window_checkpoint(path: &Path, checkpoint: &WindowCheckpoint) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("creating checkpoint directory {:?}", parent))?;
+        }
+    }
+    let file = File::create(path).with_context(|| format!("creating {:?}", path))?;
+    serde_json::to_writer(file, checkpoint)
+        .with_context(|| format!("writing window checkpoint {:?}", path))
+}
+
+fn plan_windows(provider: &ProviderPricing) -> Option<(&str, &[UsageWindow])> {
+    provider
+        .plan_for(None)
+        .filter(|(_, plan)| !plan.windows.is_empty())
+        .map(|(name, plan)| (name, plan.windows.as_slice()))
+}
+
+fn window_secs(window: &UsageWindow) -> i64 {
+    window.hours as i64 * 3600
+}
+
+fn windows_key(pricing: &PricingBook) -> Result<String> {
+    let configured: BTreeMap<&String, (&str, &[UsageWindow])> = pricing
+        .providers
+        .iter()
+        .filter_map(|(name, provider)| plan_windows(provider).map(|windows| (name, windows)))
+        .collect();
+    Ok(serde_json::to_string(&configured)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 2, 18, hour, minute, 0).unwrap()
+    }
+
+    fn event(timestamp: DateTime<Utc>, tokens: u64) -> UsageEvent {
+        UsageEvent {
+            provider: "claude".to_string(),
+            model: "sonnet".to_string(),
+            session_id: "s1".to_string(),
+            timestamp,
+            usage: TokenUsage {
+                input_tokens: tokens,
+                output_tokens: 0,
+                cache_write_tokens: 0,
+                cache_write_1h_tokens: 0,
+                cache_read_tokens: 0,
+                tool_input_tokens: 0,
+                tool_output_tokens: 0,
+            },
+            service_tier: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn window(name: &str, hours: u32, kind: UsageWindowKind) -> UsageWindow {
+        UsageWindow {
+            name: name.to_string(),
+            hours,
+            kind,
+            limit_tokens: Some(1_000),
+        }
+    }
+
+    fn pricing() -> PricingBook {
+        PricingBook {
+            providers: HashMap::from([(
+                "claude".to_string(),
+                ProviderPricing {
+                    currency: None,
+                    subscription_usd_month: 100.0,
+                    models: HashMap::new(),
+                    model_aliases: HashMap::new(),
+                    service_tiers: HashMap::new(),
+                    allocation: None,
+                    seats: Vec::new(),
+                    plan: Some("max".to_string()),
+                    plans: HashMap::from([(
+                        "max".to_string(),
+                        SubscriptionPlan {
+                            usd_month: 100.0,
+                            included_tokens: None,
+                            overage: PlanOverage::Blocked,
+                            overage_usd_per_mtok: None,
+                            windows: vec![
+                                window("5h", 5, UsageWindowKind::Block),
+                                window("24h", 24, UsageWindowKind::Rolling),
+                            ],
+                        },
+                    )]),
+                    contract: None,
+                },
+            )]),
+            provider_aliases: HashMap::new(),
+            meta: None,
+        }
+    }
+
+    #[test]
+    fn test_block_window_resets_after_expiry() {
+        let pricing = pricing();
+        let mut tracker = WindowTracker::default();
+        tracker.observe(
+            &[
+                event(at(1, 0), 100),
+                event(at(3, 30), 200),
+                event(at(6, 15), 50),
+            ],
+            &pricing,
+        );
+
+        let report = tracker.report(&pricing, at(7, 0));
+        let block = &report[0];
+        assert_eq!(block.tokens, 50);
+        assert_eq!(block.started_at, Some(at(6, 15)));
+        assert_eq!(block.resets_at, Some(at(11, 15)));
+        assert_eq!(block.used_pct, Some(5.0));
+
+        let rolling = &report[1];
+        assert_eq!(rolling.tokens, 350);
+        assert_eq!(
+            rolling.resets_at,
+            Some(at(1, 0) + chrono::Duration::hours(24))
+        );
+
+        let idle = tracker.report(&pricing, at(12, 0));
+        assert_eq!(idle[0].tokens, 0);
+        assert_eq!(idle[0].resets_at, None);
+    }
+
+    #[test]
+    fn test_rolling_window_drops_aged_usage() {
+        let pricing = pricing();
+        let mut tracker = WindowTracker::default();
+        tracker.observe(&[event(at(1, 0), 100), event(at(20, 0), 40)], &pricing);
+        let later = at(20, 0) + chrono::Duration::hours(6);
+        let report = tracker.report(&pricing, later);
+        assert_eq!(report[1].tokens, 40);
+        assert_eq!(report[1].started_at, Some(at(20, 0)));
+    }
+
+    #[test]
+    fn test_report_as_of_before_the_latest_event() {
+        let pricing = pricing();
+        let mut tracker = WindowTracker::default();
+        tracker.observe(
+            &[
+                event(at(1, 0), 100),
+                event(at(3, 30), 200),
+                event(at(6, 15), 50),
+            ],
+            &pricing,
+        );
+
+        let report = tracker.report(&pricing, at(4, 0));
+        assert_eq!(report[0].tokens, 0);
+        assert_eq!(report[0].started_at, None);
+        assert_eq!(report[1].tokens, 300);
+        assert_eq!(report[1].started_at, Some(at(1, 0)));
+    }
+
+    #[test]
+    fn test_refresh_reads_only_appended_complete_lines() {
+        let pricing = pricing();
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("events.jsonl");
+        let line = |evt: &UsageEvent| serde_json::to_string(evt).unwrap();
+        let first = line(&event(at(1, 0), 100));
+        let second = line(&event(at(1, 30), 20));
+        fs::write(&path, format!("{}\n{}", first, &second[..10])).unwrap();
+
+        let mut checkpoint = WindowCheckpoint::default();
+        let paths = vec![path.clone()];
+        refresh_window_checkpoint(&mut checkpoint, &paths, &pricing).unwrap();
+        assert_eq!(tracker_tokens(&checkpoint, at(2, 0)), 100);
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{}", &second[10..]).unwrap();
+        refresh_window_checkpoint(&mut checkpoint, &paths, &pricing).unwrap();
+        assert_eq!(tracker_tokens(&checkpoint, at(2, 0)), 120);
+
+        fs::write(&path, format!("{}\n", second)).unwrap();
+        refresh_window_checkpoint(&mut checkpoint, &paths, &pricing).unwrap();
+        assert_eq!(tracker_tokens(&checkpoint, at(2, 0)), 20);
+    }
+
+    fn tracker_tokens(checkpoint: &WindowCheckpoint, as_of: DateTime<Utc>) -> u64 {
+        checkpoint.tracker.report(&pricing(), as_of)[1].tokens
+    }
+}
@@ -1,4 +1,4 @@
--- Deterministic unified ledger schema generated by scripts/build_unified_ledger.py
+-- Deterministic unified ledger schema generated by `tokenledger ledger build`
 DROP TABLE IF EXISTS unified_model_provider_ledger;
 
 CREATE TABLE unified_model_provider_ledger (
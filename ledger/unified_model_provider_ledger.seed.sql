@@ -1,4 +1,4 @@
--- Deterministic unified ledger seed generated by scripts/build_unified_ledger.py
+-- Deterministic unified ledger seed generated by `tokenledger ledger build`
 INSERT INTO unified_model_provider_ledger (
   ledger_row_id,
   source_model,
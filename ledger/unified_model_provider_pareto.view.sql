@@ -1,4 +1,4 @@
--- Runtime metrics + Pareto scoring view generated by scripts/refresh_ledger.py
+-- Runtime metrics + Pareto scoring view generated by `tokenledger ledger build`
 -- Seed runtime metrics from ledger/cliproxyapi_runtime_metrics_snapshot.csv as needed.
 
 DROP TABLE IF EXISTS cliproxyapi_runtime_metrics_snapshot;